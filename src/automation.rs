@@ -0,0 +1,146 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Drive the webviews of the app with a WebDriver client, for end-to-end tests.
+//!
+//! The WebDriver server of the engine launches the app with the [`webdriver_capabilities`] of a
+//! new session, and the app lets it control its webviews when automation [`is_requested`]:
+//!
+//! - **Linux:** `WebKitWebDriver` launches the app with the `--automation` argument, and controls
+//!   the webviews built with a [`WebContext`](crate::webview::WebContext) allowing automation,
+//!   see [`WebContext::set_allows_automation`](crate::webview::WebContext::set_allows_automation).
+//! - **Windows:** `msedgedriver` launches the app with the `--remote-debugging-port` browser
+//!   argument in the `WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS` environment variable, which WebView2
+//!   reads by itself.
+//! - **macOS / iOS / Android:** Unsupported, `safaridriver` only drives Safari.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use wry::{automation, webview::WebContext};
+//!
+//! let mut web_context = WebContext::default();
+//! web_context.set_allows_automation(automation::is_requested());
+//! ```
+//!
+//! The test then starts a session with the capabilities of the app, e.g. with a WebDriver client
+//! connected to `WebKitWebDriver --port=4444`:
+//!
+//! ```no_run
+//! let capabilities = wry::automation::webdriver_capabilities("target/debug/app", &[]).unwrap();
+//! ```
+
+use serde_json::{json, Value};
+
+use crate::Result;
+
+#[cfg(not(any(
+  target_os = "windows",
+  target_os = "macos",
+  target_os = "ios",
+  target_os = "android"
+)))]
+const AUTOMATION_ARG: &str = "--automation";
+#[cfg(target_os = "windows")]
+const BROWSER_ARGS_VAR: &str = "WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS";
+
+/// Whether the app was launched by the WebDriver server of the engine.
+pub fn is_requested() -> bool {
+  #[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "android"
+  )))]
+  return std::env::args().any(|arg| arg == AUTOMATION_ARG);
+  #[cfg(target_os = "windows")]
+  return std::env::var(BROWSER_ARGS_VAR).is_ok_and(|args| has_debugging_port(&args));
+  #[cfg(any(target_os = "macos", target_os = "ios", target_os = "android"))]
+  false
+}
+
+/// The capabilities of a WebDriver session running `application` with `args`, for the WebDriver
+/// server of the engine.
+///
+/// ## Platform-specific
+///
+/// - **macOS / iOS / Android:** Unsupported, returns
+///   [`Error::Unsupported`](crate::Error::Unsupported).
+pub fn webdriver_capabilities(application: &str, args: &[&str]) -> Result<Value> {
+  #[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "android"
+  )))]
+  return Ok(webkitgtk_capabilities(application, args));
+  #[cfg(target_os = "windows")]
+  return Ok(webview2_capabilities(application, args));
+  #[cfg(any(target_os = "macos", target_os = "ios", target_os = "android"))]
+  {
+    let _ = (application, args);
+    Err(crate::Error::Unsupported)
+  }
+}
+
+#[cfg_attr(
+  any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "android"
+  ),
+  allow(dead_code)
+)]
+fn webkitgtk_capabilities(application: &str, args: &[&str]) -> Value {
+  json!({
+    "webkitgtk:browserOptions": {
+      "binary": application,
+      "args": args,
+    }
+  })
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn webview2_capabilities(application: &str, args: &[&str]) -> Value {
+  json!({
+    "browserName": "webview2",
+    "ms:edgeOptions": {
+      "binary": application,
+      "args": args,
+      "webviewOptions": {},
+    }
+  })
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn has_debugging_port(browser_args: &str) -> bool {
+  browser_args
+    .split_whitespace()
+    .any(|arg| arg.starts_with("--remote-debugging-port="))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_build_capabilities() {
+    assert_eq!(
+      webkitgtk_capabilities("/usr/bin/app", &["--profile=test"]),
+      json!({ "webkitgtk:browserOptions": { "binary": "/usr/bin/app", "args": ["--profile=test"] } })
+    );
+    let capabilities = webview2_capabilities("C:\\app.exe", &[]);
+    assert_eq!(capabilities["browserName"], "webview2");
+    assert_eq!(capabilities["ms:edgeOptions"]["binary"], "C:\\app.exe");
+  }
+
+  #[test]
+  fn should_detect_debugging_port() {
+    assert!(has_debugging_port(
+      "--disable-features=msWebOOUI --remote-debugging-port=9222"
+    ));
+    assert!(!has_debugging_port("--remote-debugging-pipe"));
+  }
+}
@@ -0,0 +1,603 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Register the app as the handler of a URI scheme, so `myapp://` links open it.
+//!
+//! Custom protocols registered with
+//! [`WebViewBuilder::with_custom_protocol`](crate::webview::WebViewBuilder::with_custom_protocol)
+//! are only known by the webview, while a [`DeepLink`] is a scheme the operating system opens
+//! with the app, e.g. from a link in the browser.
+//!
+//! The operating system launches the app with the URL it opens. [`DeepLink::listen`] delivers
+//! it to the event loop of the first instance of the app, and forwards the URLs of the instances
+//! launched while it runs to it so they can exit.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use wry::{
+//!   application::event_loop::EventLoopBuilder,
+//!   deep_link::DeepLink,
+//! };
+//!
+//! enum UserEvent {
+//!   DeepLink(String),
+//! }
+//!
+//! let event_loop = EventLoopBuilder::<UserEvent>::with_user_event().build();
+//! let deep_link = DeepLink::new("myapp").with_name("My App");
+//! deep_link.register().unwrap();
+//! if !deep_link
+//!   .listen(event_loop.create_proxy(), UserEvent::DeepLink)
+//!   .unwrap()
+//! {
+//!   // The URL was sent to the running instance.
+//!   return;
+//! }
+//! ```
+//!
+//! ## Platform-specific
+//!
+//! - **macOS:** The scheme must be declared in the `CFBundleURLTypes` of the `Info.plist` of the
+//!   app bundle. The URLs are delivered to the running app by the system, as
+//!   [`Event::Opened`](crate::application::event::Event::Opened) events.
+//! - **iOS / Android:** Unsupported, the scheme is declared in the `Info.plist` or the
+//!   `AndroidManifest.xml` of the app.
+
+#[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "android")))]
+use std::io::{BufRead, BufReader, Read};
+
+use crate::{application::event_loop::EventLoopProxy, Error, Result};
+
+/// A URI scheme opened with the app by the operating system.
+///
+/// See the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct DeepLink {
+  scheme: String,
+  name: String,
+}
+
+impl DeepLink {
+  /// Create a deep link for `scheme`, without the `:` separator, e.g. `myapp`.
+  pub fn new(scheme: &str) -> Self {
+    Self {
+      scheme: scheme.to_string(),
+      name: scheme.to_string(),
+    }
+  }
+
+  /// Set the name of the app shown by the operating system for the scheme. Defaults to the
+  /// scheme.
+  pub fn with_name(mut self, name: &str) -> Self {
+    self.name = name.to_string();
+    self
+  }
+
+  /// The scheme of the deep link.
+  pub fn scheme(&self) -> &str {
+    &self.scheme
+  }
+
+  /// Register the current executable as the handler of the scheme, for the current user.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Writes a `<scheme>-handler.desktop` entry in `$XDG_DATA_HOME/applications` and
+  ///   makes it the default handler with `xdg-mime`.
+  /// - **Windows:** Writes the `HKEY_CURRENT_USER\Software\Classes\<scheme>` registry key.
+  /// - **macOS:** Makes the app bundle the default handler of the scheme declared in its
+  ///   `Info.plist`.
+  /// - **iOS / Android:** Unsupported.
+  pub fn register(&self) -> Result<()> {
+    self.validate()?;
+    register(self)
+  }
+
+  /// Remove the registration made by [`Self::register`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS / Android:** Unsupported.
+  pub fn unregister(&self) -> Result<()> {
+    self.validate()?;
+    unregister(self)
+  }
+
+  /// The URL the operating system launched the app with, if any.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS / Android:** Always `None`, see
+  ///   [`Event::Opened`](crate::application::event::Event::Opened).
+  pub fn activation_url(&self) -> Option<String> {
+    if cfg!(any(
+      target_os = "macos",
+      target_os = "ios",
+      target_os = "android"
+    )) {
+      return None;
+    }
+    find_activation_url(&self.scheme, std::env::args().skip(1))
+  }
+
+  /// Deliver the activation URLs to the event loop of the first instance of the app, as the
+  /// events built by `to_event`.
+  ///
+  /// The [`Self::activation_url`] of the current process is sent to the running instance when
+  /// there is one, and `false` is returned so the process can exit. Otherwise the URLs of the
+  /// instances launched later are received until the event loop closes, and `true` is returned.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Listens on a socket in `$XDG_RUNTIME_DIR`, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported) when it isn't set.
+  /// - **macOS / iOS / Android:** Always returns `true`, the system delivers the URLs to the
+  ///   running app.
+  pub fn listen<T, F>(&self, proxy: EventLoopProxy<T>, to_event: F) -> Result<bool>
+  where
+    T: Send + 'static,
+    F: Fn(String) -> T + Send + 'static,
+  {
+    self.validate()?;
+    listen(self, proxy, to_event)
+  }
+
+  /// Schemes are made of a letter followed by letters, digits, `+`, `-` and `.`.
+  fn validate(&self) -> Result<()> {
+    let mut chars = self.scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+      && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    if valid {
+      Ok(())
+    } else {
+      Err(Error::InvalidDeepLinkScheme(self.scheme.clone()))
+    }
+  }
+}
+
+fn find_activation_url(scheme: &str, mut args: impl Iterator<Item = String>) -> Option<String> {
+  args.find(|arg| {
+    arg
+      .split_once(':')
+      .is_some_and(|(prefix, _)| prefix.eq_ignore_ascii_case(scheme))
+  })
+}
+
+/// Sends each line read from an instance launched later to the event loop, returns `false` once
+/// the event loop is closed.
+#[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "android")))]
+fn deliver<T: 'static>(
+  reader: impl Read,
+  proxy: &EventLoopProxy<T>,
+  to_event: &impl Fn(String) -> T,
+) -> bool {
+  for line in BufReader::new(reader).lines() {
+    let Ok(url) = line else {
+      break;
+    };
+    if !url.is_empty() && proxy.send_event(to_event(url)).is_err() {
+      return false;
+    }
+  }
+  true
+}
+
+#[cfg(any(
+  target_os = "linux",
+  target_os = "dragonfly",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd"
+))]
+mod platform {
+  use std::{
+    io::{ErrorKind, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    process::Command,
+  };
+
+  use super::{deliver, DeepLink};
+  use crate::{application::event_loop::EventLoopProxy, Result};
+
+  fn applications_dir() -> Result<PathBuf> {
+    let data_home = match std::env::var_os("XDG_DATA_HOME") {
+      Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+      _ => PathBuf::from(std::env::var_os("HOME").ok_or(crate::Error::Unsupported)?)
+        .join(".local/share"),
+    };
+    Ok(data_home.join("applications"))
+  }
+
+  fn desktop_file_name(deep_link: &DeepLink) -> String {
+    format!("{}-handler.desktop", deep_link.scheme)
+  }
+
+  /// Quotes an argument of the `Exec` key of a desktop entry.
+  fn quote_exec_arg(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+      match c {
+        '"' | '`' | '$' | '\\' => {
+          quoted.push('\\');
+          quoted.push(c);
+        }
+        '%' => quoted.push_str("%%"),
+        _ => quoted.push(c),
+      }
+    }
+    quoted.push('"');
+    quoted
+  }
+
+  pub(super) fn desktop_entry(deep_link: &DeepLink, exe: &str) -> String {
+    format!(
+      "[Desktop Entry]\nType=Application\nName={}\nExec={} %u\nNoDisplay=true\nMimeType=x-scheme-handler/{};\n",
+      deep_link.name.replace('\n', " "),
+      quote_exec_arg(exe),
+      deep_link.scheme
+    )
+  }
+
+  pub(super) fn register(deep_link: &DeepLink) -> Result<()> {
+    let exe = std::env::current_exe()?;
+    let dir = applications_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(
+      dir.join(desktop_file_name(deep_link)),
+      desktop_entry(deep_link, &exe.to_string_lossy()),
+    )?;
+
+    let status = Command::new("xdg-mime")
+      .arg("default")
+      .arg(desktop_file_name(deep_link))
+      .arg(format!("x-scheme-handler/{}", deep_link.scheme))
+      .status()?;
+    if !status.success() {
+      return Err(
+        std::io::Error::other(format!("xdg-mime failed to set the handler: {status}")).into(),
+      );
+    }
+    Ok(())
+  }
+
+  pub(super) fn unregister(deep_link: &DeepLink) -> Result<()> {
+    match std::fs::remove_file(applications_dir()?.join(desktop_file_name(deep_link))) {
+      Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+      _ => Ok(()),
+    }
+  }
+
+  pub(super) fn listen<T, F>(
+    deep_link: &DeepLink,
+    proxy: EventLoopProxy<T>,
+    to_event: F,
+  ) -> Result<bool>
+  where
+    T: Send + 'static,
+    F: Fn(String) -> T + Send + 'static,
+  {
+    // The other users could take or write to a socket in a shared directory like `/tmp`, only the
+    // runtime directory of the user is private.
+    let runtime_dir =
+      PathBuf::from(std::env::var_os("XDG_RUNTIME_DIR").ok_or(crate::Error::Unsupported)?);
+    let path = runtime_dir.join(format!("{}-deep-link.sock", deep_link.scheme));
+
+    let url = deep_link.activation_url();
+    if let Ok(mut stream) = UnixStream::connect(&path) {
+      if let Some(url) = url {
+        writeln!(stream, "{url}")?;
+      }
+      return Ok(false);
+    }
+
+    // Nothing listens on a socket left by an instance which didn't exit cleanly.
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+    if let Some(url) = url {
+      let _ = proxy.send_event(to_event(url));
+    }
+    std::thread::spawn(move || {
+      for stream in listener.incoming().flatten() {
+        if !deliver(stream, &proxy, &to_event) {
+          break;
+        }
+      }
+      let _ = std::fs::remove_file(&path);
+    });
+    Ok(true)
+  }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+  use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    os::windows::io::FromRawHandle,
+  };
+
+  use windows::{
+    core::PCWSTR,
+    Win32::{
+      Foundation::{CloseHandle, ERROR_PIPE_CONNECTED, HANDLE, WIN32_ERROR},
+      Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_INBOUND},
+      System::{
+        Pipes::{
+          ConnectNamedPipe, CreateNamedPipeW, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+        },
+        Registry::{
+          RegCloseKey, RegCreateKeyExW, RegDeleteTreeW, RegSetValueExW, HKEY, HKEY_CURRENT_USER,
+          KEY_WRITE, REG_OPTION_NON_VOLATILE, REG_SZ,
+        },
+      },
+    },
+  };
+
+  use super::{deliver, DeepLink};
+  use crate::{application::event_loop::EventLoopProxy, Result};
+
+  fn encode_wide(string: impl AsRef<std::ffi::OsStr>) -> Vec<u16> {
+    std::os::windows::prelude::OsStrExt::encode_wide(string.as_ref())
+      .chain(std::iter::once(0))
+      .collect()
+  }
+
+  fn check(error: WIN32_ERROR) -> Result<()> {
+    if error.is_ok() {
+      Ok(())
+    } else {
+      Err(std::io::Error::from_raw_os_error(error.0 as i32).into())
+    }
+  }
+
+  fn key_path(deep_link: &DeepLink) -> String {
+    format!("Software\\Classes\\{}", deep_link.scheme)
+  }
+
+  /// Sets the `name` value, or the default value when `None`, of the `path` key.
+  fn set_value(path: &str, name: Option<&str>, value: &str) -> Result<()> {
+    let path = encode_wide(path);
+    let name = name.map(encode_wide);
+    let data = encode_wide(value)
+      .into_iter()
+      .flat_map(u16::to_le_bytes)
+      .collect::<Vec<_>>();
+    unsafe {
+      let mut key = HKEY::default();
+      check(RegCreateKeyExW(
+        HKEY_CURRENT_USER,
+        PCWSTR::from_raw(path.as_ptr()),
+        0,
+        PCWSTR::null(),
+        REG_OPTION_NON_VOLATILE,
+        KEY_WRITE,
+        None,
+        &mut key,
+        None,
+      ))?;
+      let result = check(RegSetValueExW(
+        key,
+        name
+          .as_ref()
+          .map(|name| PCWSTR::from_raw(name.as_ptr()))
+          .unwrap_or_else(PCWSTR::null),
+        0,
+        REG_SZ,
+        Some(&data),
+      ));
+      let _ = RegCloseKey(key);
+      result
+    }
+  }
+
+  pub(super) fn register(deep_link: &DeepLink) -> Result<()> {
+    let exe = std::env::current_exe()?;
+    let path = key_path(deep_link);
+    set_value(&path, None, &format!("URL:{}", deep_link.name))?;
+    set_value(&path, Some("URL Protocol"), "")?;
+    set_value(
+      &format!("{path}\\shell\\open\\command"),
+      None,
+      &format!("\"{}\" \"%1\"", exe.display()),
+    )
+  }
+
+  pub(super) fn unregister(deep_link: &DeepLink) -> Result<()> {
+    let path = encode_wide(key_path(deep_link));
+    match unsafe { RegDeleteTreeW(HKEY_CURRENT_USER, PCWSTR::from_raw(path.as_ptr())) } {
+      windows::Win32::Foundation::ERROR_FILE_NOT_FOUND => Ok(()),
+      error => check(error),
+    }
+  }
+
+  fn create_pipe(name: &[u16], first: bool) -> windows::core::Result<HANDLE> {
+    let mut open_mode = PIPE_ACCESS_INBOUND;
+    if first {
+      open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+    }
+    unsafe {
+      CreateNamedPipeW(
+        PCWSTR::from_raw(name.as_ptr()),
+        open_mode,
+        PIPE_TYPE_BYTE | PIPE_WAIT,
+        PIPE_UNLIMITED_INSTANCES,
+        0,
+        4096,
+        0,
+        None,
+      )
+    }
+  }
+
+  pub(super) fn listen<T, F>(
+    deep_link: &DeepLink,
+    proxy: EventLoopProxy<T>,
+    to_event: F,
+  ) -> Result<bool>
+  where
+    T: Send + 'static,
+    F: Fn(String) -> T + Send + 'static,
+  {
+    let path = format!("\\\\.\\pipe\\{}-deep-link", deep_link.scheme);
+    let name = encode_wide(&path);
+
+    let url = deep_link.activation_url();
+    // Only the first instance of the app can create the first instance of the pipe.
+    let Ok(mut pipe) = create_pipe(&name, true) else {
+      let mut running = OpenOptions::new().write(true).open(&path)?;
+      if let Some(url) = url {
+        writeln!(running, "{url}")?;
+      }
+      return Ok(false);
+    };
+
+    if let Some(url) = url {
+      let _ = proxy.send_event(to_event(url));
+    }
+    std::thread::spawn(move || loop {
+      // A client may connect between the creation of the pipe and this call.
+      let connected = match unsafe { ConnectNamedPipe(pipe, None) } {
+        Ok(()) => true,
+        Err(e) => e.code() == ERROR_PIPE_CONNECTED.to_hresult(),
+      };
+      // Wait for the next instance while reading this one.
+      let next = create_pipe(&name, false);
+      if connected {
+        let open = deliver(
+          unsafe { File::from_raw_handle(pipe.0 as _) },
+          &proxy,
+          &to_event,
+        );
+        if !open {
+          if let Ok(next) = next {
+            let _ = unsafe { CloseHandle(next) };
+          }
+          break;
+        }
+      } else {
+        let _ = unsafe { CloseHandle(pipe) };
+      }
+      match next {
+        Ok(next) => pipe = next,
+        Err(e) => {
+          log::warn!("failed to listen for deep links: {e}");
+          break;
+        }
+      }
+    });
+    Ok(true)
+  }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+  use cocoa::{
+    base::{id, nil},
+    foundation::NSString,
+  };
+
+  use super::DeepLink;
+  use crate::{application::event_loop::EventLoopProxy, Error, Result};
+
+  #[link(name = "CoreServices", kind = "framework")]
+  extern "C" {
+    fn LSSetDefaultHandlerForURLScheme(scheme: id, bundle_id: id) -> i32;
+  }
+
+  pub(super) fn register(deep_link: &DeepLink) -> Result<()> {
+    unsafe {
+      let bundle: id = msg_send![class!(NSBundle), mainBundle];
+      let bundle_id: id = msg_send![bundle, bundleIdentifier];
+      // Launch services only know the executables of an app bundle.
+      if bundle_id == nil {
+        return Err(Error::Unsupported);
+      }
+      let scheme = NSString::alloc(nil).init_str(&deep_link.scheme);
+      let status = LSSetDefaultHandlerForURLScheme(scheme, bundle_id);
+      let _: () = msg_send![scheme, release];
+      if status != 0 {
+        return Err(std::io::Error::from_raw_os_error(status).into());
+      }
+    }
+    Ok(())
+  }
+
+  pub(super) fn unregister(_deep_link: &DeepLink) -> Result<()> {
+    Err(Error::Unsupported)
+  }
+
+  pub(super) fn listen<T, F>(
+    _deep_link: &DeepLink,
+    _proxy: EventLoopProxy<T>,
+    _to_event: F,
+  ) -> Result<bool>
+  where
+    T: Send + 'static,
+    F: Fn(String) -> T + Send + 'static,
+  {
+    Ok(true)
+  }
+}
+
+#[cfg(any(target_os = "ios", target_os = "android"))]
+mod platform {
+  use super::DeepLink;
+  use crate::{application::event_loop::EventLoopProxy, Error, Result};
+
+  pub(super) fn register(_deep_link: &DeepLink) -> Result<()> {
+    Err(Error::Unsupported)
+  }
+
+  pub(super) fn unregister(_deep_link: &DeepLink) -> Result<()> {
+    Err(Error::Unsupported)
+  }
+
+  pub(super) fn listen<T, F>(
+    _deep_link: &DeepLink,
+    _proxy: EventLoopProxy<T>,
+    _to_event: F,
+  ) -> Result<bool>
+  where
+    T: Send + 'static,
+    F: Fn(String) -> T + Send + 'static,
+  {
+    Ok(true)
+  }
+}
+
+use platform::{listen, register, unregister};
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_find_activation_url() {
+    let args = ["--flag", "MyApp://open?id=1", "myapp://other"].map(String::from);
+    assert_eq!(
+      find_activation_url("myapp", args.into_iter()),
+      Some("MyApp://open?id=1".into())
+    );
+    assert_eq!(
+      find_activation_url("myapp", ["C:\\app.exe"].map(String::from).into_iter()),
+      None
+    );
+    assert!(DeepLink::new("my-app+v2.x").validate().is_ok());
+    assert!(DeepLink::new("2app").validate().is_err());
+    assert!(DeepLink::new("my app").validate().is_err());
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn should_quote_desktop_entry() {
+    let entry = platform::desktop_entry(
+      &DeepLink::new("myapp").with_name("My App"),
+      "/opt/my $app/100%",
+    );
+    assert!(entry.contains("\nExec=\"/opt/my \\$app/100%%\" %u\n"));
+    assert!(entry.contains("\nMimeType=x-scheme-handler/myapp;\n"));
+  }
+}
@@ -56,8 +56,9 @@
 //! loading assets. Enabled by default.
 //!  This feature requires either `libayatana-appindicator` or `libappindicator` package installed.
 //!  You can still create those types if you disable it. They just don't create the actual objects.
-//! - `devtools`: Enables devtools on release builds. Devtools are always enabled in debug builds.
-//! On **macOS**, enabling devtools, requires calling private apis so you should not enable this flag in release
+//! - `devtools`: Enables the private devtools apis on **macOS** release builds. They are always enabled in debug builds.
+//! On other platforms, the devtools are controlled at runtime with [`with_devtools`] and [`with_devtools_policy`].
+//! Calling private apis on **macOS** means you should not enable this flag in release
 //! build if your app needs to publish to App Store.
 //! - `transparent`: Transparent background on **macOS** requires calling private functions.
 //! Avoid this in release build if your app needs to publish to App Store.
@@ -66,6 +67,7 @@
 //! libraries and prevent from building documentation on doc.rs fails.
 //! - `linux-headers`: Enables headers support of custom protocol request on Linux. Requires
 //! webkit2gtk v2.36 or above.
+//! - `visual-test`: Enables the [`visual_test`] module to compare snapshots with baseline images.
 //!
 //! [tao]: https://crates.io/crates/tao
 //! [`EventLoop`]: crate::application::event_loop::EventLoop
@@ -73,6 +75,8 @@
 //! [`WebView`]: crate::webview::WebView
 //! [`with_file_drop_handler`]: crate::webview::WebView::with_file_drop_handler
 //! [`with_custom_protocol`]: crate::webview::WebView::with_custom_protocol
+//! [`with_devtools`]: crate::webview::WebViewBuilder::with_devtools
+//! [`with_devtools_policy`]: crate::webview::WebViewBuilder::with_devtools_policy
 
 #![allow(clippy::new_without_default)]
 #![allow(clippy::wrong_self_convention)]
@@ -96,8 +100,13 @@ pub use serde_json::Value;
 use url::ParseError;
 
 pub mod application;
+pub mod automation;
+pub mod deep_link;
+#[cfg(feature = "visual-test")]
+pub mod visual_test;
 pub use http;
 pub mod webview;
+pub use webview::shutdown;
 
 /// Convenient type alias of Result type for wry.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -169,4 +178,39 @@ pub enum Error {
   JniError(#[from] tao::platform::android::ndk_glue::jni::errors::Error),
   #[error("Failed to create proxy endpoint")]
   ProxyEndpointCreationFailed,
+  #[error("No visible element matches the selector: {0}")]
+  ElementNotFound(String),
+  #[error("Failed to capture the webview snapshot")]
+  SnapshotFailed,
+  #[error("The snapshot differs from its baseline: {0}")]
+  SnapshotMismatch(String),
+  #[cfg(feature = "visual-test")]
+  #[error(transparent)]
+  PngDecoding(#[from] png::DecodingError),
+  #[error("Invalid content rule list: {0}")]
+  InvalidContentRuleList(String),
+  #[error("Invalid navigation filter pattern: {0}")]
+  InvalidNavigationPattern(String),
+  #[error("Unsupported on this platform")]
+  Unsupported,
+  #[error("Devtools protocol method failed: {0}")]
+  DevtoolsProtocolError(String),
+  #[error("The devtools are not open in their own window")]
+  DevtoolsNotDetached,
+  #[error("JavaScript exception: {0}")]
+  JavaScriptException(String),
+  #[error("No frame with the id {0}")]
+  FrameNotFound(u32),
+  #[error("Invalid deep link scheme: {0}")]
+  InvalidDeepLinkScheme(String),
+  #[error("The lazy webview failed to be created")]
+  LazyWebViewFailed,
+  #[error("Invalid host mapping: {0}")]
+  InvalidHostMapping(String),
+  #[error("No printer named {0}")]
+  PrinterNotFound(String),
+  #[error("{0} webviews of the current thread are still alive")]
+  WebViewsAlive(usize),
+  #[error("The page navigated away before the result was received")]
+  NavigatedAway,
 }
@@ -0,0 +1,338 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Pixel-test web components rendered by a webview against stored baselines.
+//!
+//! A test loads the page with [`READY_SCRIPT`], which posts [`READY_MESSAGE`] through the IPC
+//! handler once the page is loaded and painted. The event loop then captures the component with
+//! [`VisualTest::capture`], which compares it with the baseline image of the same name.
+//!
+//! The first run, or a run with the `WRY_UPDATE_SNAPSHOTS` environment variable set, stores the
+//! captured images as the baselines. When a capture differs from its baseline, it is written
+//! next to it with the `.actual.png` extension.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use wry::{
+//!   application::{
+//!     event::Event,
+//!     event_loop::{ControlFlow, EventLoopBuilder},
+//!   },
+//!   visual_test::{VisualTest, READY_MESSAGE, READY_SCRIPT},
+//!   webview::WebViewBuilder,
+//! };
+//!
+//! let event_loop = EventLoopBuilder::<()>::with_user_event().build();
+//! let proxy = event_loop.create_proxy();
+//! let webview = WebViewBuilder::new_headless(&event_loop)
+//!   .unwrap()
+//!   .with_initialization_script(READY_SCRIPT)
+//!   .with_ipc_handler(move |_, message| {
+//!     if message == READY_MESSAGE {
+//!       let _ = proxy.send_event(());
+//!     }
+//!   })
+//!   .with_html("<button id=\"button\">Click</button>")
+//!   .unwrap()
+//!   .build()
+//!   .unwrap();
+//!
+//! let visual_test = VisualTest::new("tests/baselines").with_tolerance(8, 0.001);
+//! event_loop.run(move |event, _, control_flow| {
+//!   *control_flow = ControlFlow::Wait;
+//!   if let Event::UserEvent(()) = event {
+//!     visual_test
+//!       .capture(&webview, "button", "#button", |result| {
+//!         result.unwrap();
+//!         std::process::exit(0);
+//!       })
+//!       .unwrap();
+//!   }
+//! });
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+  webview::{SnapshotFormat, WebView},
+  Error, Result,
+};
+
+/// The IPC message posted by [`READY_SCRIPT`].
+pub const READY_MESSAGE: &str = "wry-visual-test:ready";
+
+/// Initialization script posting [`READY_MESSAGE`] through the IPC handler once the page and its
+/// fonts are loaded, and the next frame is painted.
+pub const READY_SCRIPT: &str = r#"window.addEventListener('load', function () {
+  var fonts = document.fonts ? document.fonts.ready : Promise.resolve();
+  fonts.then(function () {
+    requestAnimationFrame(function () {
+      requestAnimationFrame(function () {
+        window.ipc.postMessage('wry-visual-test:ready');
+      });
+    });
+  });
+});"#;
+
+const UPDATE_VAR: &str = "WRY_UPDATE_SNAPSHOTS";
+
+/// The pixels of an image, as 8-bit RGBA.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+  pub width: u32,
+  pub height: u32,
+  pub pixels: Vec<u8>,
+}
+
+impl Image {
+  /// Decode a PNG image, e.g. a snapshot of [`WebView::capture_element`].
+  pub fn decode_png(png: &[u8]) -> Result<Self> {
+    let mut decoder = png::Decoder::new(png);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    buffer.truncate(info.buffer_size());
+    let pixels = match info.color_type {
+      png::ColorType::Rgba => buffer,
+      png::ColorType::Rgb => buffer
+        .chunks_exact(3)
+        .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+        .collect(),
+      png::ColorType::GrayscaleAlpha => buffer
+        .chunks_exact(2)
+        .flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]])
+        .collect(),
+      png::ColorType::Grayscale => buffer.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+      // Expanded to RGB or RGBA by the transformations.
+      png::ColorType::Indexed => return Err(Error::SnapshotFailed),
+    };
+    Ok(Self {
+      width: info.width,
+      height: info.height,
+      pixels,
+    })
+  }
+
+  /// The number of pixels with a channel differing by more than `channel_tolerance` from the same
+  /// pixel of `other`, or `None` if the images don't have the same size.
+  pub fn different_pixels(&self, other: &Image, channel_tolerance: u8) -> Option<usize> {
+    if (self.width, self.height) != (other.width, other.height) {
+      return None;
+    }
+    let different = self
+      .pixels
+      .chunks_exact(4)
+      .zip(other.pixels.chunks_exact(4))
+      .filter(|(a, b)| {
+        a.iter()
+          .zip(b.iter())
+          .any(|(a, b)| a.abs_diff(*b) > channel_tolerance)
+      })
+      .count();
+    Some(different)
+  }
+}
+
+/// Compares the snapshots of a webview with the baseline images of a directory.
+///
+/// See the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct VisualTest {
+  baselines: PathBuf,
+  channel_tolerance: u8,
+  max_different_ratio: f64,
+}
+
+impl VisualTest {
+  /// Compare the snapshots with the PNG images of `baselines`, which must match exactly.
+  pub fn new(baselines: impl Into<PathBuf>) -> Self {
+    Self {
+      baselines: baselines.into(),
+      channel_tolerance: 0,
+      max_different_ratio: 0.,
+    }
+  }
+
+  /// Accept the pixels whose channels differ by up to `channel_tolerance`, and up to a
+  /// `max_different_ratio` of other pixels, e.g. to absorb the antialiasing differences between
+  /// machines.
+  pub fn with_tolerance(mut self, channel_tolerance: u8, max_different_ratio: f64) -> Self {
+    self.channel_tolerance = channel_tolerance;
+    self.max_different_ratio = max_different_ratio;
+    self
+  }
+
+  /// Capture the first element matching `css_selector` as a PNG image and compare it with the
+  /// baseline named `name`. The handler is called on the main thread with
+  /// [`Error::SnapshotMismatch`] if they differ.
+  pub fn capture(
+    &self,
+    webview: &WebView,
+    name: &str,
+    css_selector: &str,
+    handler: impl FnOnce(Result<()>) + 'static,
+  ) -> Result<()> {
+    webview.capture_element(
+      css_selector,
+      SnapshotFormat::Png,
+      self.matcher(name, handler),
+    )
+  }
+
+  /// The handler of [`WebView::capture_element`] comparing the snapshot with the baseline named
+  /// `name`, or passing the error of the capture to `handler`.
+  fn matcher(
+    &self,
+    name: &str,
+    handler: impl FnOnce(Result<()>) + 'static,
+  ) -> impl FnOnce(Result<Vec<u8>>) + 'static {
+    let test = self.clone();
+    let name = name.to_string();
+    move |png| handler(png.and_then(|png| test.assert_matches(&name, &png)))
+  }
+
+  /// Compare a PNG `snapshot` with the baseline named `name`, or store it as the baseline if there
+  /// is none or `WRY_UPDATE_SNAPSHOTS` is set.
+  pub fn assert_matches(&self, name: &str, snapshot: &[u8]) -> Result<()> {
+    let baseline = self.baselines.join(format!("{name}.png"));
+    if std::env::var_os(UPDATE_VAR).is_some() || !baseline.exists() {
+      return write(&baseline, snapshot);
+    }
+
+    let expected = Image::decode_png(&std::fs::read(&baseline)?)?;
+    let actual = Image::decode_png(snapshot)?;
+    let mismatch = match actual.different_pixels(&expected, self.channel_tolerance) {
+      None => Some(format!(
+        "{name} is {}x{} instead of {}x{}",
+        actual.width, actual.height, expected.width, expected.height
+      )),
+      Some(different) => {
+        let total = (actual.width * actual.height).max(1) as usize;
+        (different as f64 / total as f64 > self.max_different_ratio)
+          .then(|| format!("{different} of the {total} pixels of {name} differ"))
+      }
+    };
+    match mismatch {
+      Some(mismatch) => {
+        write(&self.baselines.join(format!("{name}.actual.png")), snapshot)?;
+        Err(Error::SnapshotMismatch(mismatch))
+      }
+      None => Ok(()),
+    }
+  }
+}
+
+fn write(path: &Path, png: &[u8]) -> Result<()> {
+  if let Some(parent) = path.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
+  std::fs::write(path, png)?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    cell::Cell,
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+  };
+
+  use super::*;
+
+  /// A directory of baselines unique to a test, removed even if the test fails.
+  struct Baselines(PathBuf);
+
+  impl Baselines {
+    fn new() -> Self {
+      static COUNT: AtomicUsize = AtomicUsize::new(0);
+      let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.subsec_nanos());
+      Self(std::env::temp_dir().join(format!(
+        "wry-visual-test-{}-{}-{nanos}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+      )))
+    }
+  }
+
+  impl Drop for Baselines {
+    fn drop(&mut self) {
+      let _ = std::fs::remove_dir_all(&self.0);
+    }
+  }
+
+  fn png(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(pixels).unwrap();
+    writer.finish().unwrap();
+    png
+  }
+
+  #[test]
+  fn should_compare_with_tolerance() {
+    let image = Image::decode_png(&png(2, 1, &[0, 0, 0, 255, 255, 255])).unwrap();
+    assert_eq!(image.pixels, [0, 0, 0, 255, 255, 255, 255, 255]);
+
+    let other = Image::decode_png(&png(2, 1, &[4, 0, 0, 255, 200, 255])).unwrap();
+    assert_eq!(image.different_pixels(&other, 0), Some(2));
+    assert_eq!(image.different_pixels(&other, 4), Some(1));
+    assert_eq!(image.different_pixels(&other, 55), Some(0));
+
+    let smaller = Image::decode_png(&png(1, 1, &[0, 0, 0])).unwrap();
+    assert_eq!(image.different_pixels(&smaller, 255), None);
+  }
+
+  #[test]
+  fn should_store_and_compare_baselines() {
+    let baselines = Baselines::new();
+    let dir = &baselines.0;
+    let test = VisualTest::new(dir).with_tolerance(0, 0.5);
+    let baseline = png(2, 1, &[0, 0, 0, 255, 255, 255]);
+
+    test.assert_matches("widget", &baseline).unwrap();
+    assert!(dir.join("widget.png").exists());
+    test
+      .assert_matches("widget", &png(2, 1, &[0, 0, 0, 0, 0, 0]))
+      .unwrap();
+    assert!(matches!(
+      test.assert_matches("widget", &png(2, 1, &[9, 9, 9, 0, 0, 0])),
+      Err(Error::SnapshotMismatch(_))
+    ));
+    assert!(dir.join("widget.actual.png").exists());
+  }
+
+  #[test]
+  fn should_compare_the_captures() {
+    let baselines = Baselines::new();
+    let test = VisualTest::new(&baselines.0);
+    let capture = |png: Result<Vec<u8>>| {
+      let result = Rc::new(Cell::new(None));
+      let handler_result = result.clone();
+      test.matcher("widget", move |r| handler_result.set(Some(r)))(png);
+      result.take().expect("the handler wasn't called")
+    };
+
+    assert!(matches!(
+      capture(Err(Error::SnapshotFailed)),
+      Err(Error::SnapshotFailed)
+    ));
+    assert!(!baselines.0.join("widget.png").exists());
+
+    capture(Ok(png(1, 1, &[0, 0, 0]))).unwrap();
+    assert!(baselines.0.join("widget.png").exists());
+    capture(Ok(png(1, 1, &[0, 0, 0]))).unwrap();
+    assert!(matches!(
+      capture(Ok(png(1, 1, &[9, 9, 9]))),
+      Err(Error::SnapshotMismatch(_))
+    ));
+  }
+}
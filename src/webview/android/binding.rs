@@ -15,11 +15,12 @@ use tao::platform::android::ndk_glue::jni::{
 };
 
 use super::{
-  ASSET_LOADER_DOMAIN, IPC, ON_LOAD_HANDLER, REQUEST_HANDLER, TITLE_CHANGE_HANDLER,
-  URL_LOADING_OVERRIDE, WITH_ASSET_LOADER,
+  ASSET_LOADER_DOMAIN, DOWNLOAD_COMPLETED_HANDLER, DOWNLOAD_STARTED_HANDLER, IPC, ON_LOAD_HANDLER,
+  REQUEST_HANDLER, TITLE_CHANGE_HANDLER, URL_LOADING_OVERRIDE, WITH_ASSET_LOADER,
 };
 
 use crate::webview::PageLoadEvent;
+use std::path::PathBuf;
 
 #[macro_export]
 macro_rules! android_binding {
@@ -89,6 +90,21 @@ macro_rules! android_binding {
       onPageLoaded,
       [JString]
     );
+    android_fn!(
+      $domain,
+      $package,
+      RustDownloadListener,
+      onDownloadStarted,
+      [JString, JString],
+      jstring
+    );
+    android_fn!(
+      $domain,
+      $package,
+      RustDownloadListener,
+      onDownloadCompleted,
+      [JString, JString, jboolean]
+    );
     android_fn!($domain, $package, Ipc, ipc, [JString]);
     android_fn!(
       $domain,
@@ -325,3 +341,54 @@ pub unsafe fn onPageLoaded(mut env: JNIEnv, _: JClass, url: JString) {
     Err(e) => log::warn!("Failed to parse JString: {}", e),
   }
 }
+
+#[allow(non_snake_case)]
+pub unsafe fn onDownloadStarted(
+  mut env: JNIEnv,
+  _: JClass,
+  url: JString,
+  path: JString,
+) -> jstring {
+  let (Ok(url), Ok(path)) = (env.get_string(&url), env.get_string(&path)) else {
+    log::warn!("Failed to parse JString");
+    return JObject::null().as_raw();
+  };
+  let url = url.to_string_lossy().to_string();
+  let mut path = PathBuf::from(path.to_string_lossy().to_string());
+  let allowed = match DOWNLOAD_STARTED_HANDLER.get() {
+    Some(handler) => (handler.handler.borrow_mut())(url, &mut path),
+    None => true,
+  };
+  if !allowed {
+    return JObject::null().as_raw();
+  }
+  match env.new_string(path.to_string_lossy()) {
+    Ok(path) => path.as_raw(),
+    Err(e) => {
+      log::warn!("Failed to create JString: {}", e);
+      JObject::null().as_raw()
+    }
+  }
+}
+
+#[allow(non_snake_case)]
+pub unsafe fn onDownloadCompleted(
+  mut env: JNIEnv,
+  _: JClass,
+  url: JString,
+  path: JString,
+  success: jboolean,
+) {
+  match (env.get_string(&url), env.get_string(&path)) {
+    (Ok(url), Ok(path)) => {
+      if let Some(handler) = DOWNLOAD_COMPLETED_HANDLER.get() {
+        (handler.handler)(
+          url.to_string_lossy().to_string(),
+          Some(PathBuf::from(path.to_string_lossy().to_string())),
+          success != 0,
+        )
+      }
+    }
+    _ => log::warn!("Failed to parse JString"),
+  }
+}
@@ -0,0 +1,87 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+package {{package}}
+
+import android.app.DownloadManager
+import android.content.BroadcastReceiver
+import android.content.Context
+import android.content.Intent
+import android.content.IntentFilter
+import android.net.Uri
+import android.os.Environment
+import android.webkit.CookieManager
+import android.webkit.DownloadListener
+import android.webkit.URLUtil
+import androidx.core.content.ContextCompat
+import java.io.File
+
+class RustDownloadListener(private val context: Context): DownloadListener {
+    // The url and destination of the downloads in progress, by download id.
+    private val downloads = HashMap<Long, Pair<String, String>>()
+
+    private val receiver = object : BroadcastReceiver() {
+        override fun onReceive(context: Context, intent: Intent) {
+            val id = intent.getLongExtra(DownloadManager.EXTRA_DOWNLOAD_ID, -1)
+            val (url, path) = downloads.remove(id) ?: return
+            var success = false
+            downloadManager().query(DownloadManager.Query().setFilterById(id)).use { cursor ->
+                if (cursor.moveToFirst()) {
+                    val status = cursor.getInt(cursor.getColumnIndexOrThrow(DownloadManager.COLUMN_STATUS))
+                    success = status == DownloadManager.STATUS_SUCCESSFUL
+                }
+            }
+            onDownloadCompleted(url, path, success)
+        }
+    }
+
+    init {
+        ContextCompat.registerReceiver(
+            context,
+            receiver,
+            IntentFilter(DownloadManager.ACTION_DOWNLOAD_COMPLETE),
+            ContextCompat.RECEIVER_EXPORTED
+        )
+    }
+
+    override fun onDownloadStart(
+        url: String,
+        userAgent: String,
+        contentDisposition: String,
+        mimetype: String,
+        contentLength: Long
+    ) {
+        val fileName = URLUtil.guessFileName(url, contentDisposition, mimetype)
+        val directory = context.getExternalFilesDir(Environment.DIRECTORY_DOWNLOADS) ?: context.filesDir
+        val path = onDownloadStarted(url, File(directory, fileName).absolutePath) ?: return
+
+        val request = DownloadManager.Request(Uri.parse(url))
+            .setMimeType(mimetype)
+            .addRequestHeader("User-Agent", userAgent)
+            .setNotificationVisibility(DownloadManager.Request.VISIBILITY_VISIBLE_NOTIFY_COMPLETED)
+            .setDestinationUri(Uri.fromFile(File(path)))
+        CookieManager.getInstance().getCookie(url)?.let { request.addRequestHeader("Cookie", it) }
+        try {
+            downloads[downloadManager().enqueue(request)] = Pair(url, path)
+        } catch (ex: Exception) {
+            Logger.error("Unable to start the download of $url: " + ex.message)
+            onDownloadCompleted(url, path, false)
+        }
+    }
+
+    private fun downloadManager(): DownloadManager {
+        return context.getSystemService(Context.DOWNLOAD_SERVICE) as DownloadManager
+    }
+
+    companion object {
+        init {
+            System.loadLibrary("{{library}}")
+        }
+    }
+
+    private external fun onDownloadStarted(url: String, path: String): String?
+    private external fun onDownloadCompleted(url: String, path: String, success: Boolean)
+
+    {{class-extension}}
+}
@@ -51,6 +51,12 @@ class RustWebView(context: Context): WebView(context) {
         }
     }
 
+    fun loadHTMLWithBaseURLMainThread(html: String, baseUrl: String) {
+        post {
+          super.loadDataWithBaseURL(baseUrl, html, "text/html", "utf-8", null)
+        }
+    }
+
     fun clearAllBrowsingData() {
         try {
             super.getContext().deleteDatabase("webviewCache.db")
@@ -73,6 +79,11 @@ class RustWebView(context: Context): WebView(context) {
         settings.userAgentString = ua
     }
 
+    fun appendUserAgent(suffix: String) {
+        val settings = super.getSettings()
+        settings.userAgentString = settings.userAgentString + " " + suffix
+    }
+
     private external fun shouldOverride(url: String): Boolean
 
     {{class-extension}}
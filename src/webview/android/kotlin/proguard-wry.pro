@@ -29,6 +29,6 @@
   void setUserAgent(...);
 }
 
--keep class {{package}}.RustWebChromeClient,{{package}}.RustWebViewClient {
+-keep class {{package}}.RustWebChromeClient,{{package}}.RustWebViewClient,{{package}}.RustDownloadListener {
   public <init>(...);
 }
\ No newline at end of file
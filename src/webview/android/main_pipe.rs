@@ -47,7 +47,7 @@ impl<'a> MainPipe<'a> {
           let CreateWebViewAttributes {
             url,
             html,
-            #[cfg(any(debug_assertions, feature = "devtools"))]
+            html_origin,
             devtools,
             transparent,
             background_color,
@@ -55,6 +55,9 @@ impl<'a> MainPipe<'a> {
             on_webview_created,
             autoplay,
             user_agent,
+            user_agent_suffix,
+            downloads,
+            elastic_overscroll,
             ..
           } = attrs;
           // Create webview
@@ -84,6 +87,15 @@ impl<'a> MainPipe<'a> {
               &[(&user_agent).into()],
             )?;
           }
+          if let Some(suffix) = user_agent_suffix {
+            let suffix = self.env.new_string(suffix)?;
+            self.env.call_method(
+              &webview,
+              "appendUserAgent",
+              "(Ljava/lang/String;)V",
+              &[(&suffix).into()],
+            )?;
+          }
 
           self.env.call_method(
             activity,
@@ -99,12 +111,11 @@ impl<'a> MainPipe<'a> {
             }
           } else if let Some(h) = html {
             if let Ok(html) = self.env.new_string(h) {
-              load_html(&mut self.env, &webview, &html)?;
+              load_html(&mut self.env, &webview, &html, html_origin)?;
             }
           }
 
           // Enable devtools
-          #[cfg(any(debug_assertions, feature = "devtools"))]
           self.env.call_static_method(
             &rust_webview_class,
             "setWebContentsDebuggingEnabled",
@@ -118,6 +129,13 @@ impl<'a> MainPipe<'a> {
             set_background_color(&mut self.env, &webview, color)?;
           }
 
+          if !elastic_overscroll {
+            // View.OVER_SCROLL_NEVER
+            self
+              .env
+              .call_method(&webview, "setOverScrollMode", "(I)V", &[2i32.into()])?;
+          }
+
           // Create and set webview client
           let rust_webview_client_class = find_class(
             &mut self.env,
@@ -136,6 +154,26 @@ impl<'a> MainPipe<'a> {
             &[(&webview_client).into()],
           )?;
 
+          // Download the files with the download manager
+          if downloads {
+            let rust_download_listener_class = find_class(
+              &mut self.env,
+              activity,
+              format!("{}/RustDownloadListener", PACKAGE.get().unwrap()),
+            )?;
+            let download_listener = self.env.new_object(
+              &rust_download_listener_class,
+              "(Landroid/content/Context;)V",
+              &[activity.into()],
+            )?;
+            self.env.call_method(
+              &webview,
+              "setDownloadListener",
+              "(Landroid/webkit/DownloadListener;)V",
+              &[(&download_listener).into()],
+            )?;
+          }
+
           // set webchrome client
           self.env.call_method(
             &webview,
@@ -233,6 +271,28 @@ impl<'a> MainPipe<'a> {
             tx.send(url).unwrap()
           }
         }
+        WebViewMessage::SetVisible(visible) => {
+          if let Some(webview) = &self.webview {
+            // View.VISIBLE or View.GONE.
+            let visibility: i32 = if visible { 0 } else { 8 };
+            self.env.call_method(
+              webview.as_obj(),
+              "setVisibility",
+              "(I)V",
+              &[visibility.into()],
+            )?;
+          }
+        }
+        WebViewMessage::IsVisible(tx) => {
+          if let Some(webview) = &self.webview {
+            let visibility = self
+              .env
+              .call_method(webview.as_obj(), "getVisibility", "()I", &[])
+              .and_then(|v| v.i())
+              .unwrap_or_default();
+            tx.send(visibility == 0).unwrap()
+          }
+        }
         WebViewMessage::Jni(f) => {
           if let Some(w) = &self.webview {
             f(&mut self.env, activity, w.as_obj());
@@ -298,13 +358,24 @@ fn load_html<'a>(
   env: &mut JNIEnv<'a>,
   webview: &JObject<'a>,
   html: &JString<'a>,
+  base_url: Option<String>,
 ) -> Result<(), JniError> {
-  env.call_method(
-    webview,
-    "loadHTMLMainThread",
-    "(Ljava/lang/String;)V",
-    &[html.into()],
-  )?;
+  if let Some(base_url) = base_url {
+    let base_url = env.new_string(base_url)?;
+    env.call_method(
+      webview,
+      "loadHTMLWithBaseURLMainThread",
+      "(Ljava/lang/String;Ljava/lang/String;)V",
+      &[html.into(), (&base_url).into()],
+    )?;
+  } else {
+    env.call_method(
+      webview,
+      "loadHTMLMainThread",
+      "(Ljava/lang/String;)V",
+      &[html.into()],
+    )?;
+  }
   Ok(())
 }
 
@@ -335,6 +406,8 @@ pub(crate) enum WebViewMessage {
   SetBackgroundColor(RGBA),
   GetWebViewVersion(Sender<Result<String, Error>>),
   GetUrl(Sender<String>),
+  SetVisible(bool),
+  IsVisible(Sender<bool>),
   Jni(Box<dyn FnOnce(&mut JNIEnv, &JObject, &JObject) + Send>),
   LoadUrl(String, Option<http::HeaderMap>),
   ClearAllBrowsingData,
@@ -343,7 +416,7 @@ pub(crate) enum WebViewMessage {
 pub(crate) struct CreateWebViewAttributes {
   pub url: Option<String>,
   pub html: Option<String>,
-  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub html_origin: Option<String>,
   pub devtools: bool,
   pub transparent: bool,
   pub background_color: Option<RGBA>,
@@ -351,4 +424,7 @@ pub(crate) struct CreateWebViewAttributes {
   pub autoplay: bool,
   pub on_webview_created: Option<Box<dyn Fn(super::Context) -> Result<(), JniError> + Send>>,
   pub user_agent: Option<String>,
+  pub user_agent_suffix: Option<String>,
+  pub downloads: bool,
+  pub elastic_overscroll: bool,
 }
@@ -2,8 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use super::{PageLoadEvent, WebContext, WebViewAttributes, RGBA};
-use crate::{application::window::Window, webview::RequestAsyncResponder, Result};
+use super::{
+  typed_eval::apply_arguments, AutoplayPolicy, CachePolicy, ClickThroughRegion, DevtoolsPlacement,
+  EditCommand, InitScript, MediaFeatureOverrides, MouseEvent, PageLoadEvent, PrintOptions,
+  ScriptId, SnapshotFormat, TouchEvent, WebArchive, WebContext, WebViewAttributes, RGBA,
+};
+use crate::{
+  application::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    window::Window,
+  },
+  webview::{RequestAsyncResponder, RequestBody},
+  Result,
+};
 use base64::{engine::general_purpose, Engine};
 use crossbeam_channel::*;
 use html5ever::{interface::QualName, namespace_url, ns, tendril::TendrilSink, LocalName};
@@ -14,7 +25,7 @@ use http::{
 use kuchiki::NodeRef;
 use once_cell::sync::OnceCell;
 use sha2::{Digest, Sha256};
-use std::{borrow::Cow, rc::Rc, sync::mpsc::channel};
+use std::{borrow::Cow, cell::RefCell, path::PathBuf, rc::Rc, sync::mpsc::channel};
 use tao::platform::android::ndk_glue::{
   jni::{
     errors::Error as JniError,
@@ -60,6 +71,8 @@ define_static_handlers! {
   TITLE_CHANGE_HANDLER = UnsafeTitleHandler { handler: Box<dyn Fn(&Window, String)>,  window: Rc<Window> };
   URL_LOADING_OVERRIDE = UnsafeUrlLoadingOverride { handler: Box<dyn Fn(String) -> bool> };
   ON_LOAD_HANDLER = UnsafeOnPageLoadHandler { handler: Box<dyn Fn(PageLoadEvent, String)> };
+  DOWNLOAD_STARTED_HANDLER = UnsafeDownloadStartedHandler { handler: RefCell<Box<dyn FnMut(String, &mut PathBuf) -> bool>> };
+  DOWNLOAD_COMPLETED_HANDLER = UnsafeDownloadCompletedHandler { handler: Rc<dyn Fn(String, Option<PathBuf>, bool)> };
 }
 
 pub static WITH_ASSET_LOADER: OnceCell<bool> = OnceCell::new();
@@ -103,34 +116,71 @@ pub unsafe fn setup(mut env: JNIEnv, looper: &ForeignLooper, activity: GlobalRef
     .unwrap();
 }
 
+/// New windows are not supported on Android.
+#[allow(dead_code)]
+pub(crate) struct PlatformOpener;
+
 pub(crate) struct InnerWebView {
   #[allow(unused)]
   pub window: Rc<Window>,
 }
 
+/// The features implemented by this backend, see [`super::Capabilities`].
+pub(crate) const CAPABILITIES: super::Capabilities = super::Capabilities {
+  streaming_protocols: false,
+  protocol_request_body: true,
+  devtools: false,
+  print: false,
+  print_options: false,
+  edit_commands: false,
+  zoom: false,
+  element_capture: false,
+  muting: false,
+  service_worker_management: false,
+  pdf_downloads: true,
+  content_blocking: false,
+  devtools_protocol: false,
+  proxy: false,
+  offscreen: false,
+  find_in_page: false,
+  print_to_pdf: false,
+  selected_text: false,
+  suspend: true,
+};
+
 impl InnerWebView {
   pub fn new(
     window: Rc<Window>,
     attributes: WebViewAttributes,
     pl_attrs: super::PlatformSpecificWebViewAttributes,
     _web_context: Option<&mut WebContext>,
+    _child: bool,
   ) -> Result<Self> {
     let WebViewAttributes {
       url,
       html,
-      initialization_scripts,
+      html_origin,
+      mut initialization_scripts,
       ipc_handler,
-      #[cfg(any(debug_assertions, feature = "devtools"))]
       devtools,
-      custom_protocols,
+      streaming_custom_protocols: custom_protocols,
       background_color,
       transparent,
       headers,
-      autoplay,
+      autoplay_policy,
       user_agent,
+      user_agent_suffix,
+      media_feature_overrides,
+      download_started_handler,
+      download_completed_handler,
+      elastic_overscroll,
       ..
     } = attributes;
 
+    if let Some(overrides) = media_feature_overrides {
+      initialization_scripts.push(overrides.to_script());
+    }
+
     let super::PlatformSpecificWebViewAttributes {
       on_webview_created,
       with_asset_loader,
@@ -140,7 +190,7 @@ impl InnerWebView {
 
     let custom_protocol_scheme = if https_scheme { "https" } else { "http" };
 
-    let url = if let Some(u) = url {
+    let to_android_url = |u: Url| {
       let mut url_string = String::from(u.as_str());
       let name = u.scheme();
       let is_custom_protocol = custom_protocols.iter().any(|(n, _)| n == name);
@@ -150,22 +200,25 @@ impl InnerWebView {
           &format!("{custom_protocol_scheme}://{name}."),
         )
       }
-      Some(url_string)
-    } else {
-      None
+      url_string
     };
+    let url = url.map(to_android_url);
+    let html_origin = html_origin.map(to_android_url);
 
     MainPipe::send(WebViewMessage::CreateWebView(CreateWebViewAttributes {
       url,
       html,
-      #[cfg(any(debug_assertions, feature = "devtools"))]
+      html_origin,
       devtools,
       background_color,
       transparent,
       headers,
       on_webview_created,
-      autoplay,
+      autoplay: matches!(autoplay_policy, Some(AutoplayPolicy::AllowAll)),
       user_agent,
+      user_agent_suffix,
+      downloads: download_started_handler.is_some() || download_completed_handler.is_some(),
+      elastic_overscroll,
     }));
 
     WITH_ASSET_LOADER.get_or_init(move || with_asset_loader);
@@ -240,7 +293,10 @@ impl InnerWebView {
               tx.send(response).unwrap();
             });
 
-          (custom_protocol.1)(request, RequestAsyncResponder { responder });
+          (custom_protocol.1)(
+            request.map(RequestBody::from_vec),
+            RequestAsyncResponder { responder },
+          );
           return Some(rx.recv().unwrap());
         }
         None
@@ -265,11 +321,28 @@ impl InnerWebView {
       ON_LOAD_HANDLER.get_or_init(move || UnsafeOnPageLoadHandler::new(h));
     }
 
+    if let Some(h) = download_started_handler {
+      DOWNLOAD_STARTED_HANDLER
+        .get_or_init(move || UnsafeDownloadStartedHandler::new(RefCell::new(h)));
+    }
+
+    if let Some(h) = download_completed_handler {
+      DOWNLOAD_COMPLETED_HANDLER.get_or_init(move || UnsafeDownloadCompletedHandler::new(h));
+    }
+
     Ok(Self { window })
   }
 
   pub fn print(&self) {}
 
+  pub fn print_with_options(&self, _options: PrintOptions) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn execute_edit_command(&self, _command: EditCommand) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
   pub fn url(&self) -> Url {
     let (tx, rx) = bounded(1);
     MainPipe::send(WebViewMessage::GetUrl(tx));
@@ -282,24 +355,196 @@ impl InnerWebView {
     Ok(())
   }
 
-  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub fn eval_in_world(&self, _world: &str, _js: &str) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn eval_in_world_with_callback(
+    &self,
+    _world: &str,
+    _js: &str,
+    _callback: impl FnOnce(String) + 'static,
+  ) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn add_init_script(&self, _script: &InitScript) -> Result<ScriptId> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn remove_init_script(&self, _id: ScriptId) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn eval_with_args(&self, function: &str, args: &str) -> Result<()> {
+    self.eval(
+      &apply_arguments(function, args),
+      None::<Box<dyn Fn(String) + Send + 'static>>,
+    )
+  }
+
   pub fn open_devtools(&self) {}
 
-  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub fn open_devtools_with_placement(&self, _placement: DevtoolsPlacement) {}
+
   pub fn close_devtools(&self) {}
 
-  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub fn devtools_window_bounds(&self) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    None
+  }
+
+  pub fn set_devtools_window_bounds(
+    &self,
+    _position: PhysicalPosition<i32>,
+    _size: PhysicalSize<u32>,
+  ) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
   pub fn is_devtools_open(&self) -> bool {
     false
   }
 
+  pub fn set_bounds(
+    &self,
+    _position: PhysicalPosition<i32>,
+    _size: PhysicalSize<u32>,
+  ) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn bring_to_front(&self) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn send_to_back(&self) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn set_ignore_cursor_events(&self, _ignore: bool) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn script_evaluator(&self) -> Box<dyn Fn(&str)> {
+    Box::new(|js| MainPipe::send(WebViewMessage::Eval(js.into())))
+  }
+
+  pub fn click_through_setter(&self) -> Option<Box<dyn Fn(&[ClickThroughRegion])>> {
+    None
+  }
+
+  pub fn send_mouse_event(&self, _event: MouseEvent) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn send_touch_event(&self, _event: TouchEvent) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
   pub fn zoom(&self, _scale_factor: f64) {}
 
+  pub fn capture_element(
+    &self,
+    _selector: &str,
+    _format: SnapshotFormat,
+    _handler: impl FnOnce(Result<Vec<u8>>) + 'static,
+  ) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn pause_all_media(&self) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn resume_media(&self) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn process_resident_size(&self) -> Option<u64> {
+    None
+  }
+
+  pub fn suspend(&self) -> Result<()> {
+    MainPipe::send(WebViewMessage::Jni(Box::new(|env, _, webview| {
+      let _ = env.call_method(webview, "onPause", "()V", &[]);
+    })));
+    Ok(())
+  }
+
+  pub fn resume(&self) -> Result<()> {
+    MainPipe::send(WebViewMessage::Jni(Box::new(|env, _, webview| {
+      let _ = env.call_method(webview, "onResume", "()V", &[]);
+    })));
+    Ok(())
+  }
+
+  pub fn discard(&self) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn restore(&self) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn set_muted(&self, _muted: bool) {}
+
+  pub fn is_muted(&self) -> bool {
+    false
+  }
+
+  pub fn set_media_feature_overrides(&self, overrides: MediaFeatureOverrides) -> Result<()> {
+    MainPipe::send(WebViewMessage::Eval(overrides.to_script()));
+    Ok(())
+  }
+
   pub fn set_background_color(&self, background_color: RGBA) -> Result<()> {
     MainPipe::send(WebViewMessage::SetBackgroundColor(background_color));
     Ok(())
   }
 
+  pub fn set_back_forward_gestures(&self, _enabled: bool) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn set_under_page_background_color(&self, _color: RGBA) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn set_transparent(&self, transparent: bool) -> Result<()> {
+    let alpha = if transparent { 0 } else { 255 };
+    MainPipe::send(WebViewMessage::SetBackgroundColor((255, 255, 255, alpha)));
+    Ok(())
+  }
+
+  pub fn focus(&self) -> Result<()> {
+    MainPipe::send(WebViewMessage::Jni(Box::new(|env, _, webview| {
+      let _ = env.call_method(webview, "requestFocus", "()Z", &[]);
+    })));
+    Ok(())
+  }
+
+  pub fn blur(&self) -> Result<()> {
+    MainPipe::send(WebViewMessage::Jni(Box::new(|env, _, webview| {
+      let _ = env.call_method(webview, "clearFocus", "()V", &[]);
+    })));
+    Ok(())
+  }
+
+  pub fn reparent(&self, _window: &Window) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn set_visible(&self, visible: bool) -> Result<()> {
+    MainPipe::send(WebViewMessage::SetVisible(visible));
+    Ok(())
+  }
+
+  pub fn is_visible(&self) -> bool {
+    let (tx, rx) = bounded(1);
+    MainPipe::send(WebViewMessage::IsVisible(tx));
+    rx.recv().unwrap_or(true)
+  }
+
   pub fn load_url(&self, url: &str) {
     MainPipe::send(WebViewMessage::LoadUrl(url.to_string(), None));
   }
@@ -308,10 +553,57 @@ impl InnerWebView {
     MainPipe::send(WebViewMessage::LoadUrl(url.to_string(), Some(headers)));
   }
 
+  pub fn load_archive(&self, archive: WebArchive) -> Result<()> {
+    match archive {
+      WebArchive::File(path) => {
+        let url = Url::from_file_path(path).map_err(|_| crate::Error::Unsupported)?;
+        self.load_url(url.as_str());
+        Ok(())
+      }
+      WebArchive::Bytes(_) => Err(crate::Error::Unsupported),
+    }
+  }
+
+  pub fn load_url_with_cache_policy(&self, url: &str, policy: CachePolicy) {
+    self.load_url_with_headers(url, policy.headers())
+  }
+
+  pub fn reload_ignoring_cache(&self) {
+    self.load_url_with_cache_policy(self.url().as_str(), CachePolicy::Bypass)
+  }
+
   pub fn clear_all_browsing_data(&self) -> Result<()> {
     MainPipe::send(WebViewMessage::ClearAllBrowsingData);
     Ok(())
   }
+
+  pub fn fetch_service_worker_registrations(
+    &self,
+    _handler: impl FnOnce(Result<Vec<String>>) + 'static,
+  ) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn unregister_service_workers(&self, _domain: Option<&str>) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn call_devtools_protocol_method(
+    &self,
+    _method: &str,
+    _params: &str,
+    _handler: impl FnOnce(Result<String>) + 'static,
+  ) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn subscribe_devtools_protocol_event(
+    &self,
+    _event: &str,
+    _handler: impl Fn(String) + 'static,
+  ) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
 }
 
 #[derive(Clone, Copy)]
@@ -328,6 +620,17 @@ impl JniHandle {
   }
 }
 
+// The main looper is run by the Android activity.
+pub fn platform_pump_events() {}
+
+pub fn open_in_browser(_url: &str) -> Result<()> {
+  Err(crate::Error::Unsupported)
+}
+
+pub fn platform_printers() -> Result<Vec<String>> {
+  Err(crate::Error::Unsupported)
+}
+
 pub fn platform_webview_version() -> Result<String> {
   let (tx, rx) = bounded(1);
   MainPipe::send(WebViewMessage::GetWebViewVersion(tx));
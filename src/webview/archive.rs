@@ -0,0 +1,87 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  io,
+  path::{Path, PathBuf},
+  sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A page saved with its subresources, opened with
+/// [`WebView::load_archive`](super::WebView::load_archive).
+///
+/// It is an MHTML document on Windows, Linux and Android, and a `.webarchive` on macOS and iOS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebArchive {
+  /// The archive file.
+  File(PathBuf),
+  /// The content of the archive.
+  Bytes(Vec<u8>),
+}
+
+impl From<PathBuf> for WebArchive {
+  fn from(path: PathBuf) -> Self {
+    Self::File(path)
+  }
+}
+
+impl From<&Path> for WebArchive {
+  fn from(path: &Path) -> Self {
+    Self::File(path.to_path_buf())
+  }
+}
+
+impl From<Vec<u8>> for WebArchive {
+  fn from(bytes: Vec<u8>) -> Self {
+    Self::Bytes(bytes)
+  }
+}
+
+impl WebArchive {
+  /// The content of the archive, read from its file if needed.
+  #[cfg_attr(any(target_os = "windows", target_os = "android"), allow(dead_code))]
+  pub(crate) fn into_bytes(self) -> io::Result<Vec<u8>> {
+    match self {
+      Self::File(path) => std::fs::read(path),
+      Self::Bytes(bytes) => Ok(bytes),
+    }
+  }
+
+  /// The file of the archive, written to the temporary directory if needed, for the engines which
+  /// only open the archives from a file.
+  #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+  pub(crate) fn into_file(self) -> io::Result<PathBuf> {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    match self {
+      Self::File(path) => Ok(path),
+      Self::Bytes(bytes) => {
+        let name = format!(
+          "wry-archive-{}-{}.mht",
+          std::process::id(),
+          NEXT.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, bytes)?;
+        Ok(path)
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_write_archive_to_file() {
+    let content = b"MIME-Version: 1.0\r\n".to_vec();
+    let path = WebArchive::from(content.clone()).into_file().unwrap();
+    assert_eq!(path.extension().unwrap(), "mht");
+    assert_eq!(
+      WebArchive::from(path.as_path()).into_bytes().unwrap(),
+      content
+    );
+    std::fs::remove_file(path).unwrap();
+  }
+}
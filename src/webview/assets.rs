@@ -0,0 +1,213 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  borrow::Cow,
+  collections::hash_map::DefaultHasher,
+  hash::{Hash, Hasher},
+  path::Path,
+};
+
+use http::{
+  header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+  Request, Response, StatusCode,
+};
+
+use super::virtual_host::{mime_type, percent_decode};
+
+/// The assets served by an [`AssetProtocol`], like the files embedded in the binary with
+/// `include_dir!` or `rust-embed`.
+///
+/// Closures taking the path of an asset implement it, e.g.
+/// `|path: &str| Assets::get(path).map(|file| file.data)` for a `rust-embed` folder, or
+/// `|path: &str| DIR.get_file(path).map(|file| file.contents().into())` for `include_dir!`.
+pub trait AssetSource: 'static {
+  /// The content of the asset at `path`, relative to the root of the assets without a leading
+  /// slash, e.g. `index.html` or `assets/app.js`.
+  fn get(&self, path: &str) -> Option<Cow<'static, [u8]>>;
+}
+
+impl<F> AssetSource for F
+where
+  F: Fn(&str) -> Option<Cow<'static, [u8]>> + 'static,
+{
+  fn get(&self, path: &str) -> Option<Cow<'static, [u8]>> {
+    self(path)
+  }
+}
+
+/// Serves an [`AssetSource`] on a custom protocol, with the MIME type of the assets guessed
+/// from their extension, `index.html` for the directories, and an `ETag` so the unchanged
+/// assets are answered with `304 Not Modified`.
+///
+/// Register it with [`WebViewBuilder::with_asset_protocol`](super::WebViewBuilder::with_asset_protocol).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::borrow::Cow;
+/// use wry::webview::AssetProtocol;
+///
+/// let assets = AssetProtocol::new(|path: &str| match path {
+///   "index.html" => Some(Cow::Borrowed(&b"<script src=\"app.js\"></script>"[..])),
+///   "app.js" => Some(Cow::Borrowed(&b"console.log('hello')"[..])),
+///   _ => None,
+/// })
+/// .with_spa_fallback(true);
+/// ```
+pub struct AssetProtocol {
+  source: Box<dyn AssetSource>,
+  spa_fallback: bool,
+}
+
+impl AssetProtocol {
+  pub fn new(source: impl AssetSource) -> Self {
+    Self {
+      source: Box::new(source),
+      spa_fallback: false,
+    }
+  }
+
+  /// Serve `index.html` for the missing paths without an extension, like the routes of a single
+  /// page application handled by its router. Default is `false`, answering `404 Not Found`.
+  pub fn with_spa_fallback(mut self, enabled: bool) -> Self {
+    self.spa_fallback = enabled;
+    self
+  }
+
+  /// The asset at the path of the URL of a request, and its path.
+  fn find(&self, request: &Request<Vec<u8>>) -> Option<(String, Cow<'static, [u8]>)> {
+    let mut path = percent_decode(request.uri().path().trim_start_matches('/'))?;
+    if path.is_empty() || path.ends_with('/') {
+      path.push_str("index.html");
+    }
+    if let Some(content) = self.source.get(&path) {
+      return Some((path, content));
+    }
+    if self.spa_fallback && Path::new(&path).extension().is_none() {
+      let index = "index.html".to_string();
+      return self.source.get(&index).map(|content| (index, content));
+    }
+    None
+  }
+
+  pub(crate) fn respond(&self, request: &Request<Vec<u8>>) -> Response<Cow<'static, [u8]>> {
+    let Some((path, content)) = self.find(request) else {
+      return Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Cow::Borrowed(&[][..]))
+        .unwrap();
+    };
+    let etag = etag(&content);
+    let response = Response::builder()
+      .header(ETAG, &etag)
+      // Revalidated on every load, so an updated app doesn't show stale assets.
+      .header(CACHE_CONTROL, "no-cache");
+    let cached = request
+      .headers()
+      .get_all(IF_NONE_MATCH)
+      .iter()
+      .filter_map(|value| value.to_str().ok())
+      .flat_map(|value| value.split(','))
+      .any(|tag| tag.trim() == etag || tag.trim() == "*");
+    if cached {
+      response
+        .status(StatusCode::NOT_MODIFIED)
+        .body(Cow::Borrowed(&[][..]))
+        .unwrap()
+    } else {
+      response
+        .header(CONTENT_TYPE, mime_type(Path::new(&path)))
+        .body(content)
+        .unwrap()
+    }
+  }
+}
+
+fn etag(content: &[u8]) -> String {
+  let mut hasher = DefaultHasher::new();
+  content.hash(&mut hasher);
+  format!("\"{:016x}\"", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assets() -> AssetProtocol {
+    AssetProtocol::new(|path: &str| match path {
+      "index.html" => Some(Cow::Borrowed(&b"<p>index</p>"[..])),
+      "docs/index.html" => Some(Cow::Borrowed(&b"<p>docs</p>"[..])),
+      "app.js" => Some(Cow::Borrowed(&b"app()"[..])),
+      _ => None,
+    })
+  }
+
+  fn request(uri: &str) -> Request<Vec<u8>> {
+    Request::builder().uri(uri).body(Vec::new()).unwrap()
+  }
+
+  #[test]
+  fn should_serve_assets() {
+    let assets = assets();
+    let response = assets.respond(&request("app://localhost/app.js"));
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "text/javascript");
+    assert_eq!(&response.body()[..], b"app()");
+    assert_eq!(
+      &assets.respond(&request("app://localhost/")).body()[..],
+      b"<p>index</p>"
+    );
+    assert_eq!(
+      &assets
+        .respond(&request("https://app.localhost/docs/"))
+        .body()[..],
+      b"<p>docs</p>"
+    );
+    assert_eq!(
+      assets
+        .respond(&request("app://localhost/settings"))
+        .status(),
+      StatusCode::NOT_FOUND
+    );
+  }
+
+  #[test]
+  fn should_fall_back_to_index_for_routes() {
+    let assets = assets().with_spa_fallback(true);
+    assert_eq!(
+      &assets
+        .respond(&request("app://localhost/settings/profile"))
+        .body()[..],
+      b"<p>index</p>"
+    );
+    assert_eq!(
+      assets
+        .respond(&request("app://localhost/missing.css"))
+        .status(),
+      StatusCode::NOT_FOUND
+    );
+  }
+
+  #[test]
+  fn should_answer_not_modified_for_matching_etag() {
+    let assets = assets();
+    let etag = assets.respond(&request("app://localhost/app.js")).headers()[ETAG].clone();
+    let revalidation = Request::builder()
+      .uri("app://localhost/app.js")
+      .header(IF_NONE_MATCH, etag)
+      .body(Vec::new())
+      .unwrap();
+    let response = assets.respond(&revalidation);
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert!(response.body().is_empty());
+
+    let stale = Request::builder()
+      .uri("app://localhost/app.js")
+      .header(IF_NONE_MATCH, "\"0\"")
+      .body(Vec::new())
+      .unwrap();
+    assert_eq!(assets.respond(&stale).status(), StatusCode::OK);
+  }
+}
@@ -0,0 +1,154 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// How a webview follows the size of its window.
+///
+/// See [`WebViewBuilder::with_auto_resize`](super::WebViewBuilder::with_auto_resize).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AutoResizePolicy {
+  /// Fill the window. Placing the webview with
+  /// [`WebViewBuilder::with_bounds`](super::WebViewBuilder::with_bounds) or
+  /// [`WebView::set_bounds`](super::WebView::set_bounds) makes it [`Self::Fixed`].
+  #[default]
+  FillParent,
+  /// Keep the position and size of the webview.
+  Fixed,
+  /// Keep the distance between the anchored edges of the webview and the same edges of the
+  /// window, stretching the webview between two opposite anchored edges, e.g. left, top and
+  /// bottom for a sidebar. The webview keeps its left and top margins on the axes without anchors.
+  Anchored(Anchors),
+}
+
+/// The edges of a webview kept at the same distance from the edges of its window, see
+/// [`AutoResizePolicy::Anchored`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Anchors {
+  pub left: bool,
+  pub top: bool,
+  pub right: bool,
+  pub bottom: bool,
+}
+
+impl AutoResizePolicy {
+  /// The policy once the webview is placed with bounds.
+  #[cfg_attr(target_os = "android", allow(dead_code))]
+  pub(crate) fn placed(self) -> Self {
+    match self {
+      Self::FillParent => Self::Fixed,
+      policy => policy,
+    }
+  }
+
+  /// The anchored edges, where a fixed webview is anchored at its left and top edges.
+  #[cfg_attr(target_os = "android", allow(dead_code))]
+  pub(crate) fn anchors(self) -> Anchors {
+    match self {
+      Self::FillParent => Anchors {
+        left: true,
+        top: true,
+        right: true,
+        bottom: true,
+      },
+      Self::Fixed => Anchors {
+        left: true,
+        top: true,
+        right: false,
+        bottom: false,
+      },
+      Self::Anchored(anchors) => anchors,
+    }
+  }
+
+  /// The bounds `(x, y, width, height)` of a webview once its parent is resized from `old` to
+  /// `new`, given as `(width, height)`.
+  #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+  pub(crate) fn resize(
+    self,
+    bounds: (i32, i32, i32, i32),
+    old: (i32, i32),
+    new: (i32, i32),
+  ) -> (i32, i32, i32, i32) {
+    if self == Self::FillParent {
+      return (0, 0, new.0, new.1);
+    }
+    let anchors = self.anchors();
+    let (x, width) = anchor_axis(
+      bounds.0,
+      bounds.2,
+      old.0,
+      new.0,
+      anchors.left,
+      anchors.right,
+    );
+    let (y, height) = anchor_axis(
+      bounds.1,
+      bounds.3,
+      old.1,
+      new.1,
+      anchors.top,
+      anchors.bottom,
+    );
+    (x, y, width, height)
+  }
+}
+
+/// The start and length of a webview on one axis once the length of its parent changes from
+/// `old` to `new`.
+fn anchor_axis(
+  start: i32,
+  len: i32,
+  old: i32,
+  new: i32,
+  anchor_start: bool,
+  anchor_end: bool,
+) -> (i32, i32) {
+  let end_margin = old - start - len;
+  match (anchor_start, anchor_end) {
+    (true, true) => (start, (new - start - end_margin).max(0)),
+    (false, true) => (new - end_margin - len, len),
+    _ => (start, len),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_resize_with_policy() {
+    let bounds = (10, 20, 200, 300);
+    let resize = |policy: AutoResizePolicy| policy.resize(bounds, (800, 600), (1000, 500));
+
+    assert_eq!(resize(AutoResizePolicy::FillParent), (0, 0, 1000, 500));
+    assert_eq!(resize(AutoResizePolicy::Fixed), bounds);
+    // A sidebar along the right edge, 10 pixels away from the edges of the window.
+    let sidebar = AutoResizePolicy::Anchored(Anchors {
+      top: true,
+      right: true,
+      bottom: true,
+      ..Default::default()
+    });
+    assert_eq!(
+      sidebar.resize((590, 10, 200, 580), (800, 600), (1000, 500)),
+      (790, 10, 200, 480)
+    );
+    assert_eq!(
+      resize(AutoResizePolicy::Anchored(Anchors::default())),
+      bounds
+    );
+    assert_eq!(
+      AutoResizePolicy::Anchored(Anchors {
+        left: true,
+        right: true,
+        ..Default::default()
+      })
+      .resize(bounds, (800, 600), (100, 600)),
+      (10, 20, 0, 300)
+    );
+    assert_eq!(
+      AutoResizePolicy::FillParent.placed(),
+      AutoResizePolicy::Fixed
+    );
+  }
+}
@@ -0,0 +1,38 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use url::Url;
+
+/// Media autoplay policy of the webview.
+///
+/// See [`WebViewBuilder::with_autoplay_policy`](super::WebViewBuilder::with_autoplay_policy).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutoplayPolicy {
+  /// All media can be played without user interaction.
+  AllowAll,
+  /// Only muted media can be played without user interaction.
+  AllowMuted,
+  /// Media can only be played after a user interaction.
+  RequireUserGesture,
+  /// All media can be played without user interaction on the listed origins
+  /// (e.g. `https://example.com`), the other origins behave like [`Self::RequireUserGesture`].
+  Allowlist(Vec<String>),
+}
+
+impl AutoplayPolicy {
+  /// Whether all media of the page at `url` is allowed to autoplay by the allowlist.
+  #[allow(dead_code)] // only used on Linux, the other engines have no per-origin autoplay policy.
+  pub(crate) fn allowlisted(&self, url: &str) -> bool {
+    let Self::Allowlist(origins) = self else {
+      return false;
+    };
+    let Ok(url) = Url::parse(url) else {
+      return false;
+    };
+    let origin = url.origin().ascii_serialization();
+    origins
+      .iter()
+      .any(|o| o.trim_end_matches('/').eq_ignore_ascii_case(&origin))
+  }
+}
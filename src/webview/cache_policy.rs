@@ -0,0 +1,44 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use http::{
+  header::{CACHE_CONTROL, PRAGMA},
+  HeaderMap, HeaderValue,
+};
+
+/// How a navigation uses the HTTP cache.
+///
+/// See [`WebView::load_url_with_cache_policy`](super::WebView::load_url_with_cache_policy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CachePolicy {
+  /// Use the cached responses, even when they are stale, and only load the missing ones.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows / Linux / Android**: Behaves like a regular navigation, following the HTTP caching
+  ///   headers.
+  UseCache,
+  /// Revalidate the cached responses with the server before using them.
+  Validate,
+  /// Ignore the cache and load everything from the network, like a hard refresh.
+  Bypass,
+}
+
+impl CachePolicy {
+  /// The request headers which make the engines apply the policy to the navigation.
+  pub(crate) fn headers(self) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    match self {
+      Self::UseCache => {}
+      Self::Validate => {
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("max-age=0"));
+      }
+      Self::Bypass => {
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        headers.insert(PRAGMA, HeaderValue::from_static("no-cache"));
+      }
+    }
+    headers
+  }
+}
@@ -0,0 +1,86 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// The features supported by the webview backend of the current platform, so they can be detected
+/// at runtime instead of with `cfg(target_os)`.
+///
+/// A feature is reported as supported when the backend implements it with the enabled cargo
+/// features. The minimum OS or runtime versions listed in the docs of the matching methods still
+/// apply.
+///
+/// See [`WebView::capabilities`](super::WebView::capabilities).
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+  /// Custom protocol responses are sent to the page as they are produced instead of once complete.
+  ///
+  /// No backend supports it yet, the responses are always buffered.
+  pub streaming_protocols: bool,
+  /// Custom protocol handlers receive the body of the requests.
+  ///
+  /// On Linux, this requires the `linux-body` feature flag.
+  pub protocol_request_body: bool,
+  /// [`WebView::open_devtools`](super::WebView::open_devtools) and
+  /// [`WebView::close_devtools`](super::WebView::close_devtools) are available.
+  ///
+  /// On macOS, this requires a debug build or the `devtools` feature flag.
+  pub devtools: bool,
+  /// [`WebView::print`](super::WebView::print).
+  pub print: bool,
+  /// [`WebView::print_with_options`](super::WebView::print_with_options), to print without a
+  /// dialog or with a given printer, and [`printers`](super::printers).
+  pub print_options: bool,
+  /// [`WebView::execute_edit_command`](super::WebView::execute_edit_command).
+  pub edit_commands: bool,
+  /// [`WebView::zoom`](super::WebView::zoom).
+  pub zoom: bool,
+  /// [`WebView::capture_element`](super::WebView::capture_element).
+  pub element_capture: bool,
+  /// [`WebView::set_muted`](super::WebView::set_muted) and
+  /// [`WebView::is_muted`](super::WebView::is_muted).
+  pub muting: bool,
+  /// [`WebView::fetch_service_worker_registrations`](super::WebView::fetch_service_worker_registrations)
+  /// and [`WebView::unregister_service_workers`](super::WebView::unregister_service_workers).
+  pub service_worker_management: bool,
+  /// The PDFs can be handed to the download handler instead of being displayed, see
+  /// [`WebViewBuilder::with_pdf_viewer`](super::WebViewBuilder::with_pdf_viewer).
+  pub pdf_downloads: bool,
+  /// [`WebContext::add_content_rule_list`](super::WebContext::add_content_rule_list).
+  pub content_blocking: bool,
+  /// [`WebView::call_devtools_protocol_method`](super::WebView::call_devtools_protocol_method)
+  /// and [`WebView::subscribe_devtools_protocol_event`](super::WebView::subscribe_devtools_protocol_event).
+  pub devtools_protocol: bool,
+  /// [`WebViewAttributes::proxy_config`](super::WebViewAttributes::proxy_config).
+  ///
+  /// On macOS, this requires the `mac-proxy` feature flag.
+  pub proxy: bool,
+  /// The pages can be rendered without a window, to a buffer.
+  ///
+  /// No backend supports it yet, the system webviews only render in a native view.
+  pub offscreen: bool,
+  /// The text of the page can be searched and the matches highlighted.
+  ///
+  /// No backend supports it yet.
+  pub find_in_page: bool,
+  /// The page can be printed to a PDF file without a dialog.
+  ///
+  /// No backend supports it yet.
+  pub print_to_pdf: bool,
+  /// [`WebView::selected_text`](super::WebView::selected_text).
+  ///
+  /// On macOS and iOS, this requires macOS 11+ / iOS 14+.
+  pub selected_text: bool,
+  /// [`WebView::suspend`](super::WebView::suspend) and
+  /// [`WebView::resume`](super::WebView::resume).
+  ///
+  /// On macOS and iOS, this requires macOS 12+ / iOS 15+.
+  pub suspend: bool,
+}
+
+impl Capabilities {
+  pub(crate) const fn current() -> Self {
+    // Each backend lists its features next to their implementations, with the same `cfg`.
+    super::CAPABILITIES
+  }
+}
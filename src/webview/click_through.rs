@@ -0,0 +1,126 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{cell::RefCell, rc::Rc};
+
+use super::{
+  ipc_router::{IpcRouter, Route},
+  WebViewAttributes,
+};
+
+/// A rectangle of the webview where the mouse events go through it to whatever is underneath, in
+/// CSS pixels relative to its top left corner at the default zoom.
+///
+/// See [`WebView::set_click_through_regions`](super::WebView::set_click_through_regions).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ClickThroughRegion {
+  pub x: f64,
+  pub y: f64,
+  pub width: f64,
+  pub height: f64,
+}
+
+impl ClickThroughRegion {
+  pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+    Self {
+      x,
+      y,
+      width,
+      height,
+    }
+  }
+
+  #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+  pub(crate) fn contains(&self, x: f64, y: f64) -> bool {
+    x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+  }
+
+  /// The smallest rectangle of whole pixels containing the region, as `(x, y, width, height)`.
+  #[cfg_attr(
+    any(target_os = "macos", target_os = "ios", target_os = "android"),
+    allow(dead_code)
+  )]
+  pub(crate) fn to_pixels(self, scale_factor: f64) -> (i32, i32, i32, i32) {
+    let left = (self.x * scale_factor).floor() as i32;
+    let top = (self.y * scale_factor).floor() as i32;
+    let right = ((self.x + self.width) * scale_factor).ceil() as i32;
+    let bottom = ((self.y + self.height) * scale_factor).ceil() as i32;
+    (left, top, right - left, bottom - top)
+  }
+}
+
+/// Prefix of the IPC messages carrying the click-through regions declared by the page.
+pub(crate) const CLICK_THROUGH_MESSAGE_PREFIX: &str = "__WRY_CLICK_THROUGH__:";
+
+/// Defines `window.setIgnoreMouseEvents(regions)`, taking an array of elements or rectangles like
+/// the `DOMRect` of `getBoundingClientRect()`.
+pub(crate) const CLICK_THROUGH_SCRIPT: &str = r#"Object.defineProperty(window, 'setIgnoreMouseEvents', {
+  value: function (regions) {
+    var rects = Array.prototype.map.call(regions || [], function (region) {
+      var rect = region instanceof Element ? region.getBoundingClientRect() : region;
+      return { x: rect.x, y: rect.y, width: rect.width, height: rect.height };
+    });
+    window.ipc.postMessage('__WRY_CLICK_THROUGH__:' + JSON.stringify(rects));
+  }
+});"#;
+
+/// Parse the JSON of a click-through message, `None` if it is malformed.
+pub(crate) fn parse_click_through_message(json: &str) -> Option<Vec<ClickThroughRegion>> {
+  serde_json::from_str(json).ok()
+}
+
+/// Sets the click-through regions of the webview, set once it is built.
+pub(crate) type ClickThroughSetter = Rc<RefCell<Option<Box<dyn Fn(&[ClickThroughRegion])>>>>;
+
+/// The route of the region messages to `setter`.
+pub(crate) fn route(setter: ClickThroughSetter) -> Route {
+  (
+    CLICK_THROUGH_MESSAGE_PREFIX,
+    Box::new(move |_, json| {
+      if let (Some(regions), Some(set)) = (parse_click_through_message(json), &*setter.borrow()) {
+        set(&regions)
+      }
+    }),
+  )
+}
+
+/// Lets the pages declare their click-through regions if `attributes` allows it, returning the
+/// setter to give the regions setter of the webview once built.
+pub(crate) fn install(
+  attributes: &mut WebViewAttributes,
+  router: &mut IpcRouter,
+) -> ClickThroughSetter {
+  let setter = ClickThroughSetter::default();
+  if attributes.click_through {
+    router.add(route(setter.clone()));
+    attributes
+      .initialization_scripts
+      .push(router.sign(CLICK_THROUGH_SCRIPT));
+  }
+  setter
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_click_through_message() {
+    assert_eq!(
+      parse_click_through_message(r#"[{"x":0,"y":10.5,"width":100,"height":20}]"#),
+      Some(vec![ClickThroughRegion::new(0., 10.5, 100., 20.)])
+    );
+    assert_eq!(parse_click_through_message("[]"), Some(vec![]));
+    assert_eq!(parse_click_through_message(r#"[{"x":0}]"#), None);
+  }
+
+  #[test]
+  fn should_cover_the_region_with_pixels() {
+    let region = ClickThroughRegion::new(10.5, 0., 20., 10.25);
+    assert!(region.contains(10.5, 0.));
+    assert!(!region.contains(30.5, 5.));
+    assert_eq!(region.to_pixels(1.), (10, 0, 21, 11));
+    assert_eq!(region.to_pixels(2.), (21, 0, 40, 21));
+  }
+}
@@ -0,0 +1,203 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Intercepts the accesses of the pages to the clipboard with the async Clipboard API, see
+//! [`WebViewBuilder::with_clipboard_access_handler`](super::WebViewBuilder::with_clipboard_access_handler).
+
+use super::{
+  ipc_router::{evaluate, IpcRouter, Route, ScriptEvaluator},
+  WebViewAttributes,
+};
+
+/// An access of a page to the clipboard, with `navigator.clipboard`.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardRequest {
+  /// The URL of the page accessing the clipboard.
+  pub page_url: String,
+  /// What the page does with the clipboard.
+  pub access: ClipboardAccess,
+}
+
+/// What a page does with the clipboard.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "access", content = "text", rename_all = "lowercase")]
+pub enum ClipboardAccess {
+  /// The page reads the clipboard, with `readText()` or `read()`.
+  Read,
+  /// The page writes to the clipboard, with `writeText()` or `write()`, with the text it writes.
+  /// It is `None` for the items written with `write()`, which aren't read before the decision.
+  Write(Option<String>),
+}
+
+/// What to do with a [`ClipboardRequest`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClipboardDecision {
+  /// Access the clipboard.
+  Allow,
+  /// Reject the access with a `NotAllowedError`.
+  Deny,
+  /// Read this text instead of the clipboard, or write it instead of the content of the page.
+  Replace(String),
+}
+
+#[derive(Deserialize)]
+struct Message {
+  id: u64,
+  page_url: String,
+  #[serde(flatten)]
+  access: ClipboardAccess,
+}
+
+/// Prefix of the IPC messages carrying the accesses to the clipboard.
+pub(crate) const CLIPBOARD_MESSAGE_PREFIX: &str = "__WRY_CLIPBOARD__:";
+
+/// Replaces the methods of `navigator.clipboard` to hold the accesses until the application
+/// decides, and calls the native ones if allowed.
+pub(crate) const CLIPBOARD_SCRIPT: &str = r#"(function () {
+  if (window.__WRY_CLIPBOARD__ || !navigator.clipboard) return;
+  var clipboard = navigator.clipboard;
+  var readText = clipboard.readText;
+  var writeText = clipboard.writeText;
+  var read = clipboard.read;
+  var write = clipboard.write;
+  var pending = {};
+  var nextId = 0;
+  var ask = function (access, text) {
+    return new Promise(function (resolve, reject) {
+      var id = nextId++;
+      pending[id] = { resolve: resolve, reject: reject };
+      window.ipc.postMessage('__WRY_CLIPBOARD__:' + JSON.stringify({
+        id: id, page_url: location.href, access: access, text: text
+      }));
+    });
+  };
+  clipboard.readText = function () {
+    return ask('read', null).then(function (text) {
+      return text !== null ? text : readText.call(clipboard);
+    });
+  };
+  clipboard.read = function () {
+    return ask('read', null).then(function (text) {
+      if (text === null) return read.call(clipboard);
+      return [new ClipboardItem({ 'text/plain': new Blob([text], { type: 'text/plain' }) })];
+    });
+  };
+  clipboard.writeText = function (data) {
+    data = String(data);
+    return ask('write', data).then(function (text) {
+      return writeText.call(clipboard, text !== null ? text : data);
+    });
+  };
+  clipboard.write = function (items) {
+    return ask('write', null).then(function (text) {
+      return text !== null ? writeText.call(clipboard, text) : write.call(clipboard, items);
+    });
+  };
+  Object.defineProperty(window, '__WRY_CLIPBOARD__', {
+    value: {
+      decide: function (id, allowed, text) {
+        var request = pending[id];
+        delete pending[id];
+        if (!request) return;
+        if (allowed) {
+          request.resolve(text);
+        } else {
+          request.reject(new DOMException('The clipboard access was denied.', 'NotAllowedError'));
+        }
+      }
+    }
+  });
+})();"#;
+
+/// Parse the JSON of a clipboard message, with the id of the access.
+pub(crate) fn parse_clipboard_message(json: &str) -> Option<(u64, ClipboardRequest)> {
+  let message: Message = serde_json::from_str(json).ok()?;
+  Some((
+    message.id,
+    ClipboardRequest {
+      page_url: message.page_url,
+      access: message.access,
+    },
+  ))
+}
+
+/// Resumes the access `id` of the page with the `decision`.
+pub(crate) fn decide_script(id: u64, decision: &ClipboardDecision) -> String {
+  let (allowed, text) = match decision {
+    ClipboardDecision::Allow => (true, None),
+    ClipboardDecision::Deny => (false, None),
+    ClipboardDecision::Replace(text) => (true, Some(text)),
+  };
+  let text = serde_json::to_string(&text).unwrap();
+  format!("window.__WRY_CLIPBOARD__.decide({id}, {allowed}, {text});")
+}
+
+/// The route of the clipboard messages to `handler`, sending its decision to the page with
+/// `evaluator`.
+pub(crate) fn route(
+  handler: Box<dyn Fn(ClipboardRequest) -> ClipboardDecision>,
+  evaluator: ScriptEvaluator,
+) -> Route {
+  (
+    CLIPBOARD_MESSAGE_PREFIX,
+    Box::new(move |_, json| {
+      if let Some((id, request)) = parse_clipboard_message(json) {
+        evaluate(&evaluator, &decide_script(id, &handler(request)))
+      }
+    }),
+  )
+}
+
+/// Holds the clipboard accesses of the pages for the clipboard access handler of `attributes`,
+/// doing them with `evaluator` once allowed.
+pub(crate) fn install(
+  attributes: &mut WebViewAttributes,
+  router: &mut IpcRouter,
+  evaluator: &ScriptEvaluator,
+) {
+  if let Some(handler) = attributes.clipboard_access_handler.take() {
+    router.add(route(handler, evaluator.clone()));
+    attributes
+      .initialization_scripts
+      .push(router.sign(CLIPBOARD_SCRIPT));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_clipboard_message() {
+    let (id, request) = parse_clipboard_message(
+      r#"{"id":2,"page_url":"wry://localhost/","access":"write","text":"4111 1111"}"#,
+    )
+    .unwrap();
+    assert_eq!(id, 2);
+    assert_eq!(request.page_url, "wry://localhost/");
+    assert_eq!(
+      request.access,
+      ClipboardAccess::Write(Some("4111 1111".to_string()))
+    );
+    let (_, request) = parse_clipboard_message(
+      r#"{"id":3,"page_url":"wry://localhost/","access":"read","text":null}"#,
+    )
+    .unwrap();
+    assert_eq!(request.access, ClipboardAccess::Read);
+    assert_eq!(parse_clipboard_message(r#"{"id":3}"#), None);
+  }
+
+  #[test]
+  fn should_build_decide_script() {
+    assert_eq!(
+      decide_script(2, &ClipboardDecision::Replace("\"quoted\"".to_string())),
+      r#"window.__WRY_CLIPBOARD__.decide(2, true, "\"quoted\"");"#
+    );
+    assert_eq!(
+      decide_script(2, &ClipboardDecision::Deny),
+      "window.__WRY_CLIPBOARD__.decide(2, false, null);"
+    );
+  }
+}
@@ -0,0 +1,153 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::{
+  ipc_router::{IpcRouter, Route},
+  WebViewAttributes,
+};
+use crate::application::{dpi::LogicalPosition, window::Window};
+
+/// A step of the composition of text with an input method (IME) in an editable element of the
+/// page, see [`WebViewBuilder::with_composition_handler`](super::WebViewBuilder::with_composition_handler).
+///
+/// The `candidate_position` is the bottom left corner of the composed text, in CSS pixels relative
+/// to the top left corner of the webview, where the candidate window of the input method goes.
+/// It is `None` when the page doesn't lay out the text yet.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompositionEvent {
+  /// The user started composing text.
+  Start {
+    candidate_position: Option<LogicalPosition<f64>>,
+  },
+  /// The composed text changed.
+  Update {
+    text: String,
+    candidate_position: Option<LogicalPosition<f64>>,
+  },
+  /// The composition ended, inserting `text` in the element, which is empty if it was cancelled.
+  Commit { text: String },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Message {
+  Start {
+    x: Option<f64>,
+    y: Option<f64>,
+  },
+  Update {
+    text: String,
+    x: Option<f64>,
+    y: Option<f64>,
+  },
+  Commit {
+    text: String,
+  },
+}
+
+/// Prefix of the IPC messages carrying the composition events of the page.
+pub(crate) const COMPOSITION_MESSAGE_PREFIX: &str = "__WRY_COMPOSITION__:";
+
+/// Reports the composition events of the editable elements, with the position of the composed
+/// text: the selected range of a content editable element, or the bottom left corner of an input
+/// or text area, whose caret isn't exposed to the page.
+pub(crate) const COMPOSITION_SCRIPT: &str = r#"(function () {
+  var position = function (el) {
+    var rect;
+    var selection = window.getSelection();
+    if (el.isContentEditable && selection && selection.rangeCount > 0) {
+      rect = selection.getRangeAt(0).getBoundingClientRect();
+    }
+    if (!rect || (rect.width === 0 && rect.height === 0)) {
+      if (!(el instanceof Element)) return {};
+      rect = el.getBoundingClientRect();
+    }
+    return { x: rect.left, y: rect.bottom };
+  };
+  var post = function (message) {
+    window.ipc.postMessage('__WRY_COMPOSITION__:' + JSON.stringify(message));
+  };
+  document.addEventListener('compositionstart', function (event) {
+    var p = position(event.target);
+    post({ type: 'start', x: p.x, y: p.y });
+  }, true);
+  document.addEventListener('compositionupdate', function (event) {
+    var p = position(event.target);
+    post({ type: 'update', text: event.data || '', x: p.x, y: p.y });
+  }, true);
+  document.addEventListener('compositionend', function (event) {
+    post({ type: 'commit', text: event.data || '' });
+  }, true);
+})();"#;
+
+fn candidate_position(x: Option<f64>, y: Option<f64>) -> Option<LogicalPosition<f64>> {
+  Some(LogicalPosition::new(x?, y?))
+}
+
+/// Parse the JSON of a composition message, `None` if it is malformed.
+pub(crate) fn parse_composition_message(json: &str) -> Option<CompositionEvent> {
+  let event = match serde_json::from_str(json).ok()? {
+    Message::Start { x, y } => CompositionEvent::Start {
+      candidate_position: candidate_position(x, y),
+    },
+    Message::Update { text, x, y } => CompositionEvent::Update {
+      text,
+      candidate_position: candidate_position(x, y),
+    },
+    Message::Commit { text } => CompositionEvent::Commit { text },
+  };
+  Some(event)
+}
+
+/// The route of the composition messages to `handler`.
+pub(crate) fn route(handler: Box<dyn Fn(&Window, CompositionEvent)>) -> Route {
+  (
+    COMPOSITION_MESSAGE_PREFIX,
+    Box::new(move |window, json| {
+      if let Some(event) = parse_composition_message(json) {
+        handler(window, event)
+      }
+    }),
+  )
+}
+
+/// Reports the compositions of the pages to the composition handler of `attributes`.
+pub(crate) fn install(attributes: &mut WebViewAttributes, router: &mut IpcRouter) {
+  if let Some(handler) = attributes.composition_handler.take() {
+    router.add(route(handler));
+    attributes
+      .initialization_scripts
+      .push(router.sign(COMPOSITION_SCRIPT));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_composition_messages() {
+    assert_eq!(
+      parse_composition_message(r#"{"type":"start","x":10,"y":24.5}"#),
+      Some(CompositionEvent::Start {
+        candidate_position: Some(LogicalPosition::new(10., 24.5))
+      })
+    );
+    assert_eq!(
+      parse_composition_message(r#"{"type":"update","text":"にほ"}"#),
+      Some(CompositionEvent::Update {
+        text: "にほ".into(),
+        candidate_position: None
+      })
+    );
+    assert_eq!(
+      parse_composition_message(r#"{"type":"commit","text":"日本"}"#),
+      Some(CompositionEvent::Commit {
+        text: "日本".into()
+      })
+    );
+    assert_eq!(parse_composition_message(r#"{"type":"end"}"#), None);
+  }
+}
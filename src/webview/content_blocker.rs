@@ -0,0 +1,195 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Content blocker rule lists, written in the
+//! [WebKit content rule list format](https://developer.apple.com/documentation/safariservices/creating_a_content_blocker).
+//!
+//! WebKit compiles the lists natively. WebView2 has no such engine, so the lists are matched against
+//! every resource request instead. This engine only understands the `url-filter`,
+//! `url-filter-is-case-sensitive`, `resource-type`, `if-domain` and `unless-domain` triggers, and the
+//! `block` and `ignore-previous-rules` actions. Rules using other actions are ignored.
+
+use serde::Deserialize;
+
+use crate::Result;
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)] // The rules are only evaluated on Windows.
+pub(crate) struct Rule {
+  trigger: Trigger,
+  action: Action,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[allow(dead_code)]
+struct Trigger {
+  url_filter: String,
+  #[serde(default)]
+  url_filter_is_case_sensitive: bool,
+  #[serde(default)]
+  resource_type: Vec<String>,
+  #[serde(default)]
+  if_domain: Vec<String>,
+  #[serde(default)]
+  unless_domain: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Action {
+  #[serde(rename = "type")]
+  kind: String,
+}
+
+/// Parses a JSON encoded rule list.
+pub(crate) fn parse(rules: &str) -> Result<Vec<Rule>> {
+  Ok(serde_json::from_str(rules)?)
+}
+
+/// Checks that `rules` is a valid rule list, compiling it on engines where this is done by wry.
+pub(crate) fn validate(rules: &str) -> Result<()> {
+  #[cfg(target_os = "windows")]
+  ContentBlocker::compile(rules)?;
+  #[cfg(not(target_os = "windows"))]
+  parse(rules)?;
+  Ok(())
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) use engine::ContentBlocker;
+
+#[cfg(target_os = "windows")]
+mod engine {
+  use regex::{Regex, RegexBuilder};
+
+  use crate::{Error, Result};
+
+  enum Action {
+    Block,
+    IgnorePreviousRules,
+  }
+
+  struct CompiledRule {
+    url_filter: Regex,
+    resource_types: Vec<String>,
+    if_domain: Vec<String>,
+    unless_domain: Vec<String>,
+    action: Action,
+  }
+
+  impl CompiledRule {
+    fn matches(&self, url: &str, resource_types: &[&str], document_host: Option<&str>) -> bool {
+      let document_matches = |domains: &[String]| {
+        document_host.is_some_and(|host| domains.iter().any(|domain| domain_matches(domain, host)))
+      };
+
+      (self.resource_types.is_empty()
+        || self
+          .resource_types
+          .iter()
+          .any(|t| resource_types.contains(&t.as_str())))
+        && (self.if_domain.is_empty() || document_matches(&self.if_domain))
+        && !document_matches(&self.unless_domain)
+        && self.url_filter.is_match(url)
+    }
+  }
+
+  /// `*example.com` matches `example.com` and its subdomains, `example.com` only matches itself.
+  fn domain_matches(domain: &str, host: &str) -> bool {
+    match domain.strip_prefix('*') {
+      Some(domain) => {
+        host.eq_ignore_ascii_case(domain)
+          || host
+            .to_ascii_lowercase()
+            .ends_with(&format!(".{}", domain.to_ascii_lowercase()))
+      }
+      None => host.eq_ignore_ascii_case(domain),
+    }
+  }
+
+  /// The compiled rule lists of a [`WebContext`](crate::webview::WebContext).
+  #[derive(Default)]
+  pub(crate) struct ContentBlocker {
+    lists: Vec<Vec<CompiledRule>>,
+  }
+
+  impl ContentBlocker {
+    pub(crate) fn compile(rules: &str) -> Result<Self> {
+      let mut blocker = Self::default();
+      blocker.add(rules)?;
+      Ok(blocker)
+    }
+
+    pub(crate) fn add(&mut self, rules: &str) -> Result<()> {
+      let mut list = Vec::new();
+      for rule in super::parse(rules)? {
+        let action = match rule.action.kind.as_str() {
+          "block" => Action::Block,
+          "ignore-previous-rules" => Action::IgnorePreviousRules,
+          _ => continue,
+        };
+        let url_filter = RegexBuilder::new(&rule.trigger.url_filter)
+          .case_insensitive(!rule.trigger.url_filter_is_case_sensitive)
+          .build()
+          .map_err(|e| Error::InvalidContentRuleList(e.to_string()))?;
+        list.push(CompiledRule {
+          url_filter,
+          resource_types: rule.trigger.resource_type,
+          if_domain: rule.trigger.if_domain,
+          unless_domain: rule.trigger.unless_domain,
+          action,
+        });
+      }
+      self.lists.push(list);
+      Ok(())
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+      self.lists.is_empty()
+    }
+
+    /// Whether a request to `url` should be blocked.
+    ///
+    /// `resource_types` are the WebKit resource types the request falls in, `document_host` is the
+    /// host of the page making it.
+    pub(crate) fn should_block(
+      &self,
+      url: &str,
+      resource_types: &[&str],
+      document_host: Option<&str>,
+    ) -> bool {
+      self.lists.iter().any(|list| {
+        list
+          .iter()
+          .rev()
+          .find(|rule| rule.matches(url, resource_types, document_host))
+          .is_some_and(|rule| matches!(rule.action, Action::Block))
+      })
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_rule_list() {
+    let rules = parse(
+      r#"[
+        { "trigger": { "url-filter": ".*", "if-domain": ["*example.com"] }, "action": { "type": "block" } },
+        { "trigger": { "url-filter": "ads\\.js$", "resource-type": ["script"], "load-type": ["third-party"] }, "action": { "type": "css-display-none", "selector": ".ad" } }
+      ]"#,
+    )
+    .unwrap();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0].trigger.if_domain, ["*example.com"]);
+    assert_eq!(rules[1].trigger.resource_type, ["script"]);
+    assert_eq!(rules[1].action.kind, "css-display-none");
+
+    assert!(parse(r#"[{ "action": { "type": "block" } }]"#).is_err());
+    assert!(parse("{}").is_err());
+  }
+}
@@ -0,0 +1,309 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Relaxes the CORS checks of the requests made to a custom protocol from other origins, see
+//! [`WebViewBuilder::with_relaxed_cors`](super::WebViewBuilder::with_relaxed_cors).
+
+use std::{borrow::Cow, rc::Rc};
+
+use http::{
+  header::{
+    ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS,
+    ACCESS_CONTROL_REQUEST_METHOD, ORIGIN, VARY,
+  },
+  HeaderMap, HeaderValue, Method, Request, Response, StatusCode,
+};
+
+use super::{RequestAsyncResponder, WebViewAttributes};
+
+type ProtocolHandler<B> = Box<dyn Fn(Request<B>, RequestAsyncResponder)>;
+
+/// Which origins can make requests to a custom protocol, see
+/// [`WebViewBuilder::with_relaxed_cors`](super::WebViewBuilder::with_relaxed_cors).
+///
+/// The pages of the custom protocols of the webview are allowed, and the other origins only when
+/// listed with [`Self::allow_origin`]. The requests with credentials, like cookies, are only
+/// allowed with [`Self::allow_credentials`].
+#[derive(Debug, Clone, Default)]
+pub struct CorsPolicy {
+  origins: Vec<String>,
+  credentials: bool,
+}
+
+impl CorsPolicy {
+  /// A policy allowing the pages of the custom protocols of the webview, without credentials.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Also allow the pages of `origin`, e.g. `https://example.com`.
+  pub fn allow_origin(mut self, origin: impl Into<String>) -> Self {
+    self.origins.push(origin.into());
+    self
+  }
+
+  /// Whether the requests with credentials, like cookies or authorization headers, are allowed.
+  /// Disabled by default.
+  pub fn allow_credentials(mut self, allow: bool) -> Self {
+    self.credentials = allow;
+    self
+  }
+}
+
+/// A [`CorsPolicy`] applied to the custom protocols `protocols` of a webview.
+struct Policy {
+  policy: CorsPolicy,
+  protocols: Rc<Vec<String>>,
+}
+
+impl Policy {
+  fn allows(&self, origin: &str) -> bool {
+    self.policy.origins.iter().any(|allowed| allowed == origin)
+      || self
+        .protocols
+        .iter()
+        .any(|name| is_custom_protocol_origin(name, origin))
+  }
+}
+
+/// Whether `origin` is the origin of the pages of the custom protocol `name`, which are served
+/// from `http(s)://<name>.localhost` on Windows and Android, and from `<name>://<host>` otherwise.
+fn is_custom_protocol_origin(name: &str, origin: &str) -> bool {
+  let Some((scheme, host)) = origin.split_once("://") else {
+    return false;
+  };
+  if cfg!(any(target_os = "windows", target_os = "android")) {
+    matches!(scheme, "http" | "https") && host == format!("{name}.localhost")
+  } else {
+    scheme == name
+  }
+}
+
+/// The headers allowing the request when its origin is allowed by `policy`, whatever its method
+/// and headers. The origin is echoed rather than `*` so the requests with credentials can be
+/// allowed too.
+fn cors_headers<B>(request: &Request<B>, policy: &Policy) -> HeaderMap {
+  let mut headers = HeaderMap::new();
+  let echo = |name| request.headers().get(name).cloned();
+  match echo(ORIGIN) {
+    Some(origin) => {
+      // The response depends on the origin, whether it's allowed or not.
+      headers.insert(VARY, HeaderValue::from_static("Origin"));
+      if !origin.to_str().is_ok_and(|origin| policy.allows(origin)) {
+        return headers;
+      }
+      headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+      if policy.policy.credentials {
+        headers.insert(
+          ACCESS_CONTROL_ALLOW_CREDENTIALS,
+          HeaderValue::from_static("true"),
+        );
+      }
+    }
+    None => {
+      headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+    }
+  }
+  if request.method() == Method::OPTIONS {
+    headers.insert(
+      ACCESS_CONTROL_ALLOW_METHODS,
+      echo(ACCESS_CONTROL_REQUEST_METHOD).unwrap_or(HeaderValue::from_static("*")),
+    );
+    headers.insert(
+      ACCESS_CONTROL_ALLOW_HEADERS,
+      echo(ACCESS_CONTROL_REQUEST_HEADERS).unwrap_or(HeaderValue::from_static("*")),
+    );
+    headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from_static("86400"));
+  }
+  headers
+}
+
+fn is_preflight<B>(request: &Request<B>) -> bool {
+  request.method() == Method::OPTIONS
+    && request
+      .headers()
+      .contains_key(ACCESS_CONTROL_REQUEST_METHOD)
+}
+
+/// Wraps the `handler` of a custom protocol to answer the CORS preflights itself, and add the
+/// CORS headers allowing the origins of `policy` to its responses unless it sets them.
+/// `protocols` are the custom protocols of the webview.
+pub(crate) fn relax<B: 'static>(
+  handler: ProtocolHandler<B>,
+  policy: CorsPolicy,
+  protocols: Rc<Vec<String>>,
+) -> ProtocolHandler<B> {
+  let policy = Policy { policy, protocols };
+  Box::new(move |request, responder| {
+    let headers = cors_headers(&request, &policy);
+    if is_preflight(&request) {
+      let mut response = Response::new(Cow::Borrowed(&[][..]));
+      *response.status_mut() = StatusCode::NO_CONTENT;
+      *response.headers_mut() = headers;
+      return responder.respond(response);
+    }
+    handler(
+      request,
+      RequestAsyncResponder {
+        responder: Box::new(move |mut response| {
+          for (name, value) in &headers {
+            if name == VARY {
+              response.headers_mut().append(name, value.clone());
+            } else if !response.headers().contains_key(name) {
+              response.headers_mut().insert(name, value.clone());
+            }
+          }
+          responder.respond(response)
+        }),
+      },
+    )
+  })
+}
+
+/// Relaxes the CORS checks of the custom protocols of `attributes` listed in its
+/// [`WebViewAttributes::relaxed_cors_protocols`], with their policy.
+pub(crate) fn install(attributes: &mut WebViewAttributes) {
+  if attributes.relaxed_cors_protocols.is_empty() {
+    return;
+  }
+  let relaxed = &attributes.relaxed_cors_protocols;
+  let policy = |name: &String| {
+    relaxed
+      .iter()
+      .find(|(relaxed, _)| relaxed == name)
+      .map(|(_, policy)| policy.clone())
+  };
+  let protocols: Rc<Vec<String>> = Rc::new(
+    (attributes.custom_protocols.iter().map(|(name, _)| name))
+      .chain(
+        attributes
+          .streaming_custom_protocols
+          .iter()
+          .map(|(name, _)| name),
+      )
+      .cloned()
+      .collect(),
+  );
+  attributes.custom_protocols = std::mem::take(&mut attributes.custom_protocols)
+    .into_iter()
+    .map(|(name, handler)| match policy(&name) {
+      Some(policy) => (name, relax(handler, policy, protocols.clone())),
+      None => (name, handler),
+    })
+    .collect();
+  attributes.streaming_custom_protocols =
+    std::mem::take(&mut attributes.streaming_custom_protocols)
+      .into_iter()
+      .map(|(name, handler)| match policy(&name) {
+        Some(policy) => (name, relax(handler, policy, protocols.clone())),
+        None => (name, handler),
+      })
+      .collect();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::{cell::RefCell, rc::Rc};
+
+  fn respond(
+    handler: &ProtocolHandler<Vec<u8>>,
+    request: Request<Vec<u8>>,
+  ) -> Response<Cow<'static, [u8]>> {
+    let response = Rc::new(RefCell::new(None));
+    let response_ = response.clone();
+    handler(
+      request,
+      RequestAsyncResponder {
+        responder: Box::new(move |r| *response_.borrow_mut() = Some(r)),
+      },
+    );
+    let response = response.borrow_mut().take().unwrap();
+    response
+  }
+
+  fn handler(policy: CorsPolicy) -> ProtocolHandler<Vec<u8>> {
+    relax(
+      Box::new(|_, responder| {
+        responder.respond(
+          Response::builder()
+            .header(VARY, "Accept")
+            .body(b"ok".to_vec())
+            .unwrap(),
+        )
+      }),
+      policy,
+      Rc::new(vec!["app".to_string(), "api".to_string()]),
+    )
+  }
+
+  fn request_from(origin: &str) -> Request<Vec<u8>> {
+    Request::builder()
+      .uri("api://localhost/items")
+      .header(ORIGIN, origin)
+      .body(Vec::new())
+      .unwrap()
+  }
+
+  #[test]
+  fn should_answer_preflights() {
+    let preflight = Request::builder()
+      .method(Method::OPTIONS)
+      .uri("api://localhost/items")
+      .header(ORIGIN, "https://example.com")
+      .header(ACCESS_CONTROL_REQUEST_METHOD, "PUT")
+      .header(ACCESS_CONTROL_REQUEST_HEADERS, "content-type")
+      .body(Vec::new())
+      .unwrap();
+    let policy = CorsPolicy::new().allow_origin("https://example.com");
+    let response = respond(&handler(policy), preflight);
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let headers = response.headers();
+    assert_eq!(headers[ACCESS_CONTROL_ALLOW_ORIGIN], "https://example.com");
+    assert!(!headers.contains_key(ACCESS_CONTROL_ALLOW_CREDENTIALS));
+    assert_eq!(headers[ACCESS_CONTROL_ALLOW_METHODS], "PUT");
+    assert_eq!(headers[ACCESS_CONTROL_ALLOW_HEADERS], "content-type");
+  }
+
+  #[test]
+  fn should_add_cors_headers() {
+    let request = Request::builder()
+      .uri("api://localhost/items")
+      .body(Vec::new())
+      .unwrap();
+    let response = respond(&handler(CorsPolicy::new()), request);
+    assert_eq!(response.body().as_ref(), b"ok");
+    assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    assert_eq!(response.headers()[VARY], "Accept");
+  }
+
+  #[test]
+  fn should_only_allow_the_origins_of_the_policy() {
+    let app = if cfg!(any(target_os = "windows", target_os = "android")) {
+      "http://app.localhost"
+    } else {
+      "app://localhost"
+    };
+    let response = respond(&handler(CorsPolicy::new()), request_from(app));
+    assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], app);
+    assert!(!response
+      .headers()
+      .contains_key(ACCESS_CONTROL_ALLOW_CREDENTIALS));
+
+    let response = respond(
+      &handler(CorsPolicy::new()),
+      request_from("https://example.com"),
+    );
+    assert!(!response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+    assert_eq!(
+      response.headers().get_all(VARY).iter().collect::<Vec<_>>(),
+      ["Accept", "Origin"]
+    );
+
+    let policy = CorsPolicy::new().allow_credentials(true);
+    let response = respond(&handler(policy), request_from(app));
+    assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+  }
+}
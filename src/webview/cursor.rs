@@ -0,0 +1,170 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::{
+  ipc_router::{IpcRouter, Route},
+  WebViewAttributes,
+};
+use crate::application::window::Window;
+
+/// The mouse cursor the page wants to show, named after the values of the CSS
+/// [`cursor`](https://developer.mozilla.org/en-US/docs/Web/CSS/cursor) property.
+///
+/// See [`WebViewBuilder::with_cursor_changed_handler`](super::WebViewBuilder::with_cursor_changed_handler).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PageCursor {
+  Default,
+  /// No cursor should be drawn.
+  None,
+  ContextMenu,
+  Help,
+  Pointer,
+  Progress,
+  Wait,
+  Cell,
+  Crosshair,
+  Text,
+  VerticalText,
+  Alias,
+  Copy,
+  Move,
+  NoDrop,
+  NotAllowed,
+  Grab,
+  Grabbing,
+  AllScroll,
+  ColResize,
+  RowResize,
+  NResize,
+  EResize,
+  SResize,
+  WResize,
+  NeResize,
+  NwResize,
+  SeResize,
+  SwResize,
+  EwResize,
+  NsResize,
+  NeswResize,
+  NwseResize,
+  ZoomIn,
+  ZoomOut,
+  /// Any other value: an image cursor like `url(cursor.png) 4 4, pointer`, or the value of a
+  /// `data-wry-cursor` attribute which isn't a CSS keyword.
+  Custom(String),
+}
+
+impl PageCursor {
+  pub(crate) fn from_css(value: &str) -> Self {
+    let value = value.trim();
+    let keyword = value.to_ascii_lowercase();
+    match keyword.strip_prefix("-webkit-").unwrap_or(&keyword) {
+      "auto" | "default" => Self::Default,
+      "none" => Self::None,
+      "context-menu" => Self::ContextMenu,
+      "help" => Self::Help,
+      "pointer" | "hand" => Self::Pointer,
+      "progress" => Self::Progress,
+      "wait" => Self::Wait,
+      "cell" => Self::Cell,
+      "crosshair" => Self::Crosshair,
+      "text" => Self::Text,
+      "vertical-text" => Self::VerticalText,
+      "alias" => Self::Alias,
+      "copy" => Self::Copy,
+      "move" => Self::Move,
+      "no-drop" => Self::NoDrop,
+      "not-allowed" => Self::NotAllowed,
+      "grab" => Self::Grab,
+      "grabbing" => Self::Grabbing,
+      "all-scroll" => Self::AllScroll,
+      "col-resize" => Self::ColResize,
+      "row-resize" => Self::RowResize,
+      "n-resize" => Self::NResize,
+      "e-resize" => Self::EResize,
+      "s-resize" => Self::SResize,
+      "w-resize" => Self::WResize,
+      "ne-resize" => Self::NeResize,
+      "nw-resize" => Self::NwResize,
+      "se-resize" => Self::SeResize,
+      "sw-resize" => Self::SwResize,
+      "ew-resize" => Self::EwResize,
+      "ns-resize" => Self::NsResize,
+      "nesw-resize" => Self::NeswResize,
+      "nwse-resize" => Self::NwseResize,
+      "zoom-in" => Self::ZoomIn,
+      "zoom-out" => Self::ZoomOut,
+      _ => Self::Custom(value.to_string()),
+    }
+  }
+}
+
+/// Prefix of the IPC messages carrying the cursor of the page.
+pub(crate) const CURSOR_MESSAGE_PREFIX: &str = "__WRY_CURSOR__:";
+
+/// Reports the cursor under the mouse whenever it changes.
+///
+/// `auto` is resolved to `text` over editable elements, and the `data-wry-cursor` attribute of the
+/// closest element which has one takes precedence over the CSS `cursor` property.
+pub(crate) const CURSOR_SCRIPT: &str = r#"(function () {
+  var current;
+  var editable = function (el) {
+    if (el.isContentEditable || el.tagName === 'TEXTAREA') return true;
+    return el.tagName === 'INPUT' && !/^(button|submit|reset|checkbox|radio|range|color|file|image|hidden)$/.test(el.type);
+  };
+  var update = function (event) {
+    var el = event.target;
+    if (!(el instanceof Element)) return;
+    var hatch = el.closest('[data-wry-cursor]');
+    var cursor = hatch ? hatch.getAttribute('data-wry-cursor') : getComputedStyle(el).cursor;
+    if (cursor === 'auto') cursor = editable(el) ? 'text' : 'default';
+    if (cursor === current) return;
+    current = cursor;
+    window.ipc.postMessage('__WRY_CURSOR__:' + cursor);
+  };
+  document.addEventListener('mouseover', update, true);
+  document.addEventListener('mousemove', update, true);
+})();"#;
+
+/// The route of the cursor messages to `handler`.
+pub(crate) fn route(handler: Box<dyn Fn(&Window, PageCursor)>) -> Route {
+  (
+    CURSOR_MESSAGE_PREFIX,
+    Box::new(move |window, cursor| handler(window, PageCursor::from_css(cursor))),
+  )
+}
+
+/// Reports the cursor changes of the pages to the cursor changed handler of `attributes`.
+pub(crate) fn install(attributes: &mut WebViewAttributes, router: &mut IpcRouter) {
+  if let Some(handler) = attributes.cursor_changed_handler.take() {
+    router.add(route(handler));
+    attributes
+      .initialization_scripts
+      .push(router.sign(CURSOR_SCRIPT));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_css_cursor() {
+    assert_eq!(PageCursor::from_css("pointer"), PageCursor::Pointer);
+    assert_eq!(
+      PageCursor::from_css(" NWSE-Resize "),
+      PageCursor::NwseResize
+    );
+    assert_eq!(PageCursor::from_css("-webkit-grab"), PageCursor::Grab);
+    assert_eq!(PageCursor::from_css("none"), PageCursor::None);
+    assert_eq!(
+      PageCursor::from_css("url(\"sword.png\") 2 2, pointer"),
+      PageCursor::Custom("url(\"sword.png\") 2 2, pointer".into())
+    );
+    assert_eq!(
+      PageCursor::from_css("Sword"),
+      PageCursor::Custom("Sword".into())
+    );
+  }
+}
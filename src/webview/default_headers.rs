@@ -0,0 +1,67 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use http::HeaderMap;
+
+/// The headers of a navigation with the default headers of the webview, see
+/// [`WebViewBuilder::with_default_headers`](super::WebViewBuilder::with_default_headers). The
+/// headers of the navigation replace the default ones with the same name.
+pub(crate) fn merge(defaults: &HeaderMap, headers: HeaderMap) -> HeaderMap {
+  let mut merged = defaults.clone();
+  let mut name = None;
+  for (next, value) in headers {
+    // The first value of each header comes with its name, followed by its other values.
+    if let Some(next) = next {
+      merged.remove(&next);
+      name = Some(next);
+    }
+    if let Some(name) = &name {
+      merged.append(name.clone(), value);
+    }
+  }
+  merged
+}
+
+/// The values of each header, joined like in an HTTP request.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn joined(headers: &HeaderMap) -> Vec<(String, String)> {
+  headers
+    .keys()
+    .map(|name| {
+      let values: Vec<_> = headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+      (name.to_string(), values.join(", "))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use http::{header::AUTHORIZATION, HeaderValue};
+
+  #[test]
+  fn should_merge_headers() {
+    let mut defaults = HeaderMap::new();
+    defaults.insert(AUTHORIZATION, HeaderValue::from_static("Bearer token"));
+    defaults.insert("x-tenant-id", HeaderValue::from_static("acme"));
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-tenant-id", HeaderValue::from_static("globex"));
+    headers.append("x-tenant-id", HeaderValue::from_static("initech"));
+    let merged = merge(&defaults, headers);
+
+    assert_eq!(merged[AUTHORIZATION], "Bearer token");
+    assert_eq!(
+      joined(&merged),
+      vec![
+        ("authorization".to_string(), "Bearer token".to_string()),
+        ("x-tenant-id".to_string(), "globex, initech".to_string()),
+      ]
+    );
+  }
+}
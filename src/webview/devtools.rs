@@ -0,0 +1,59 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// Decides at runtime whether the web inspector is available, so a single binary can ship with
+/// devtools access controlled by its configuration.
+///
+/// See [`WebViewBuilder::with_devtools_policy`](super::WebViewBuilder::with_devtools_policy).
+pub enum DevtoolsPolicy {
+  /// The devtools are always available.
+  Always,
+  /// The devtools are never available.
+  Never,
+  /// The devtools are available when the given environment variable is set to a value other than
+  /// an empty string, `0` or `false`.
+  EnvVar(String),
+  /// The devtools are available if the closure, called by the host when the webview is built,
+  /// returns `true`.
+  Approval(Box<dyn Fn() -> bool>),
+}
+
+impl DevtoolsPolicy {
+  /// Whether this policy currently allows the devtools.
+  pub fn is_allowed(&self) -> bool {
+    match self {
+      Self::Always => true,
+      Self::Never => false,
+      Self::EnvVar(name) => std::env::var(name)
+        .map(|v| !matches!(v.trim().to_ascii_lowercase().as_str(), "" | "0" | "false"))
+        .unwrap_or(false),
+      Self::Approval(approve) => approve(),
+    }
+  }
+}
+
+/// Where [`WebView::open_devtools_with_placement`](super::WebView::open_devtools_with_placement)
+/// opens the web inspector.
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DevtoolsPlacement {
+  /// Where the engine opened it last, or its default placement.
+  #[default]
+  Default,
+  /// In its own window.
+  Detached,
+  /// Docked in the window of the webview, on the side last chosen in the inspector.
+  Docked,
+}
+
+impl std::fmt::Debug for DevtoolsPolicy {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Always => f.write_str("Always"),
+      Self::Never => f.write_str("Never"),
+      Self::EnvVar(name) => f.debug_tuple("EnvVar").field(name).finish(),
+      Self::Approval(_) => f.write_str("Approval(..)"),
+    }
+  }
+}
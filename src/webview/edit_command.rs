@@ -0,0 +1,21 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// An editing command run by the engine on the focused content of the page, see
+/// [`WebView::execute_edit_command`](super::WebView::execute_edit_command).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EditCommand {
+  /// Copy the selection to the clipboard.
+  Copy,
+  /// Copy the selection to the clipboard and delete it.
+  Cut,
+  /// Replace the selection with the content of the clipboard.
+  Paste,
+  /// Select all the content of the focused field, or of the page.
+  SelectAll,
+  /// Undo the last edit.
+  Undo,
+  /// Redo the last undone edit.
+  Redo,
+}
@@ -0,0 +1,157 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::{
+  ipc_router::{IpcRouter, Route},
+  WebViewAttributes,
+};
+use crate::application::dpi::{LogicalPosition, LogicalSize};
+
+/// A change of the focus of the editable elements of the page, see
+/// [`WebViewBuilder::with_editable_focus_handler`](super::WebViewBuilder::with_editable_focus_handler).
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditableFocusEvent {
+  /// An editable element got the focus.
+  Focused(EditableElement),
+  /// The focused editable element lost the focus, and no other one got it.
+  Blurred,
+}
+
+/// An editable element of the page.
+///
+/// The `position` is its top left corner in CSS pixels relative to the top left corner of the
+/// webview, when it got the focus.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditableElement {
+  /// The `type` of an input, e.g. `email` or `number`, or `textarea`, or `contenteditable` for
+  /// the other editable elements.
+  pub input_type: String,
+  /// The `inputmode` attribute of the element, e.g. `numeric`, if it sets one.
+  pub input_mode: Option<String>,
+  pub position: LogicalPosition<f64>,
+  pub size: LogicalSize<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Message {
+  Focused {
+    input_type: String,
+    input_mode: Option<String>,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+  },
+  Blurred,
+}
+
+/// Prefix of the IPC messages carrying the focus changes of the editable elements.
+pub(crate) const EDITABLE_FOCUS_MESSAGE_PREFIX: &str = "__WRY_EDITABLE_FOCUS__:";
+
+/// Reports the editable elements of the top frame getting the focus, and losing it once the focus
+/// doesn't move to another one, so moving between the fields of a form doesn't report a blur.
+pub(crate) const EDITABLE_FOCUS_SCRIPT: &str = r#"(function () {
+  if (window !== window.top) return;
+  var notText = ['button', 'checkbox', 'color', 'file', 'hidden', 'image', 'radio', 'range', 'reset', 'submit'];
+  var inputType = function (el) {
+    if (!el || !el.tagName) return null;
+    if (el.tagName === 'INPUT') return notText.indexOf(el.type) === -1 ? el.type : null;
+    if (el.tagName === 'TEXTAREA') return 'textarea';
+    return el.isContentEditable ? 'contenteditable' : null;
+  };
+  var post = function (message) {
+    window.ipc.postMessage('__WRY_EDITABLE_FOCUS__:' + JSON.stringify(message));
+  };
+  var focused = false;
+  document.addEventListener('focusin', function (event) {
+    var el = event.target;
+    var type = inputType(el);
+    if (type === null || el.readOnly || el.disabled) return;
+    var rect = el.getBoundingClientRect();
+    focused = true;
+    post({
+      type: 'focused', input_type: type, input_mode: el.getAttribute('inputmode'),
+      x: rect.left, y: rect.top, width: rect.width, height: rect.height
+    });
+  }, true);
+  document.addEventListener('focusout', function () {
+    setTimeout(function () {
+      var el = document.activeElement;
+      if (!focused || (inputType(el) !== null && !el.readOnly && !el.disabled)) return;
+      focused = false;
+      post({ type: 'blurred' });
+    });
+  }, true);
+})();"#;
+
+/// Parse the JSON of an editable focus message, `None` if it is malformed.
+pub(crate) fn parse_editable_focus_message(json: &str) -> Option<EditableFocusEvent> {
+  let event = match serde_json::from_str(json).ok()? {
+    Message::Focused {
+      input_type,
+      input_mode,
+      x,
+      y,
+      width,
+      height,
+    } => EditableFocusEvent::Focused(EditableElement {
+      input_type,
+      input_mode,
+      position: LogicalPosition::new(x, y),
+      size: LogicalSize::new(width, height),
+    }),
+    Message::Blurred => EditableFocusEvent::Blurred,
+  };
+  Some(event)
+}
+
+/// The route of the focus messages to `handler`.
+pub(crate) fn route(handler: Box<dyn Fn(EditableFocusEvent)>) -> Route {
+  (
+    EDITABLE_FOCUS_MESSAGE_PREFIX,
+    Box::new(move |_, json| {
+      if let Some(event) = parse_editable_focus_message(json) {
+        handler(event)
+      }
+    }),
+  )
+}
+
+/// Reports the focus of the editable elements to the editable focus handler of `attributes`.
+pub(crate) fn install(attributes: &mut WebViewAttributes, router: &mut IpcRouter) {
+  if let Some(handler) = attributes.editable_focus_handler.take() {
+    router.add(route(handler));
+    attributes
+      .initialization_scripts
+      .push(router.sign(EDITABLE_FOCUS_SCRIPT));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_editable_focus_messages() {
+    assert_eq!(
+      parse_editable_focus_message(
+        r#"{"type":"focused","input_type":"number","input_mode":"numeric","x":16,"y":420.5,"width":280,"height":32}"#
+      ),
+      Some(EditableFocusEvent::Focused(EditableElement {
+        input_type: "number".into(),
+        input_mode: Some("numeric".into()),
+        position: LogicalPosition::new(16., 420.5),
+        size: LogicalSize::new(280., 32.),
+      }))
+    );
+    assert_eq!(
+      parse_editable_focus_message(r#"{"type":"blurred"}"#),
+      Some(EditableFocusEvent::Blurred)
+    );
+    assert_eq!(parse_editable_focus_message(r#"{"type":"focused"}"#), None);
+  }
+}
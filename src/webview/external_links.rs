@@ -0,0 +1,62 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use url::Url;
+
+/// Decides which new windows are opened in the system browser instead of the webview.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExternalLinks {
+  internal_hosts: Vec<String>,
+}
+
+impl ExternalLinks {
+  pub(crate) fn new(internal_hosts: Vec<String>) -> Self {
+    Self { internal_hosts }
+  }
+
+  /// Http and https URLs are external unless their host is one of the internal hosts, where
+  /// `*.example.com` matches `example.com` and its subdomains.
+  pub(crate) fn is_external(&self, url: &str) -> bool {
+    let Ok(url) = Url::parse(url) else {
+      return false;
+    };
+    let Some(host) = url.host_str() else {
+      return false;
+    };
+    matches!(url.scheme(), "http" | "https")
+      && !self
+        .internal_hosts
+        .iter()
+        .any(|internal| host_matches(internal, host))
+  }
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+  match pattern.strip_prefix("*.") {
+    Some(domain) => {
+      host.eq_ignore_ascii_case(domain)
+        || host
+          .to_ascii_lowercase()
+          .ends_with(&format!(".{}", domain.to_ascii_lowercase()))
+    }
+    None => host.eq_ignore_ascii_case(pattern),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_detect_external_links() {
+    let links = ExternalLinks::new(vec!["app.example.com".into(), "*.auth.example".into()]);
+    assert!(links.is_external("https://github.com/tauri-apps/wry"));
+    assert!(links.is_external("http://example.com/"));
+    assert!(!links.is_external("https://APP.example.com/settings"));
+    assert!(!links.is_external("https://auth.example/login"));
+    assert!(!links.is_external("https://eu.auth.example/login"));
+    assert!(!links.is_external("wry://localhost/popup.html"));
+    assert!(!links.is_external("about:blank"));
+  }
+}
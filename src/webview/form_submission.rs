@@ -0,0 +1,179 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Intercepts the POST form submissions of the pages, see
+//! [`WebViewBuilder::with_form_submission_handler`](super::WebViewBuilder::with_form_submission_handler).
+
+use super::{
+  ipc_router::{evaluate, IpcRouter, Route, ScriptEvaluator},
+  WebViewAttributes,
+};
+
+/// A POST form submitted by a page.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FormData {
+  /// The URL of the page submitting the form.
+  pub page_url: String,
+  /// The URL the form is submitted to.
+  pub url: String,
+  /// The fields of the form in their order, with the name of the files of the file inputs.
+  pub fields: Vec<(String, String)>,
+}
+
+impl FormData {
+  /// The value of the first field named `name`.
+  pub fn field(&self, name: &str) -> Option<&str> {
+    self
+      .fields
+      .iter()
+      .find(|(field, _)| field == name)
+      .map(|(_, value)| value.as_str())
+  }
+}
+
+/// What to do with a [`FormData`] submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FormSubmissionDecision {
+  /// Submit the form to its URL.
+  Submit,
+  /// Don't submit the form, it was handled by the application.
+  Consume,
+}
+
+#[derive(Deserialize)]
+struct Message {
+  id: u64,
+  #[serde(flatten)]
+  form: FormData,
+}
+
+/// Prefix of the IPC messages carrying the submitted forms.
+pub(crate) const FORM_SUBMISSION_MESSAGE_PREFIX: &str = "__WRY_FORM__:";
+
+/// Holds the POST submissions of the forms until the application decides, including the ones of
+/// `form.submit()` which doesn't fire the `submit` event, and submits them natively if allowed.
+pub(crate) const FORM_SUBMISSION_SCRIPT: &str = r#"(function () {
+  if (window.__WRY_FORM__) return;
+  var submit = HTMLFormElement.prototype.submit;
+  var pending = {};
+  var nextId = 0;
+  var intercept = function (form, submitter) {
+    var method = (submitter && submitter.hasAttribute('formmethod') ? submitter.formMethod : form.method) || '';
+    if (method.toLowerCase() !== 'post') return false;
+    var url = submitter && submitter.hasAttribute('formaction') ? submitter.formAction : form.action;
+    var fields = [];
+    new FormData(form, submitter || undefined).forEach(function (value, name) {
+      fields.push([name, typeof value === 'string' ? value : value.name]);
+    });
+    var id = nextId++;
+    pending[id] = { form: form, submitter: submitter };
+    window.ipc.postMessage('__WRY_FORM__:' + JSON.stringify({
+      id: id, page_url: location.href, url: url, fields: fields
+    }));
+    return true;
+  };
+  document.addEventListener('submit', function (event) {
+    if (event.defaultPrevented) return;
+    if (intercept(event.target, event.submitter)) event.preventDefault();
+  });
+  HTMLFormElement.prototype.submit = function () {
+    if (!intercept(this, null)) submit.call(this);
+  };
+  Object.defineProperty(window, '__WRY_FORM__', {
+    value: {
+      decide: function (id, allowed) {
+        var submission = pending[id];
+        delete pending[id];
+        if (!submission || !allowed) return;
+        var form = submission.form;
+        var submitter = submission.submitter;
+        // The native submission only has the value of the submitter when it submits the form.
+        var input;
+        if (submitter && submitter.name) {
+          input = document.createElement('input');
+          input.type = 'hidden';
+          input.name = submitter.name;
+          input.value = submitter.value;
+          form.appendChild(input);
+        }
+        var action = form.getAttribute('action');
+        if (submitter && submitter.hasAttribute('formaction')) form.action = submitter.formAction;
+        submit.call(form);
+        if (action === null) form.removeAttribute('action'); else form.setAttribute('action', action);
+        if (input) form.removeChild(input);
+      }
+    }
+  });
+})();"#;
+
+/// Parse the JSON of a form submission message, with the id of the submission.
+pub(crate) fn parse_form_submission_message(json: &str) -> Option<(u64, FormData)> {
+  let message: Message = serde_json::from_str(json).ok()?;
+  Some((message.id, message.form))
+}
+
+/// Submits the form `id` natively, or drops it.
+pub(crate) fn decide_script(id: u64, decision: FormSubmissionDecision) -> String {
+  let allowed = decision == FormSubmissionDecision::Submit;
+  format!("window.__WRY_FORM__.decide({id}, {allowed});")
+}
+
+/// The route of the form submission messages to `handler`, sending its decision to the page with
+/// `evaluator`.
+pub(crate) fn route(
+  handler: Box<dyn Fn(FormData) -> FormSubmissionDecision>,
+  evaluator: ScriptEvaluator,
+) -> Route {
+  (
+    FORM_SUBMISSION_MESSAGE_PREFIX,
+    Box::new(move |_, json| {
+      if let Some((id, form)) = parse_form_submission_message(json) {
+        evaluate(&evaluator, &decide_script(id, handler(form)))
+      }
+    }),
+  )
+}
+
+/// Holds the form submissions of the pages for the form submission handler of `attributes`,
+/// submitting them with `evaluator` once allowed.
+pub(crate) fn install(
+  attributes: &mut WebViewAttributes,
+  router: &mut IpcRouter,
+  evaluator: &ScriptEvaluator,
+) {
+  if let Some(handler) = attributes.form_submission_handler.take() {
+    router.add(route(handler, evaluator.clone()));
+    attributes
+      .initialization_scripts
+      .push(router.sign(FORM_SUBMISSION_SCRIPT));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_form_submission_message() {
+    let (id, form) = parse_form_submission_message(
+      r#"{"id":4,"page_url":"wry://localhost/login","url":"https://example.com/session","fields":[["user","ada"],["password","hunter2"],["user","again"]]}"#,
+    )
+    .unwrap();
+    assert_eq!(id, 4);
+    assert_eq!(form.url, "https://example.com/session");
+    assert_eq!(form.field("user"), Some("ada"));
+    assert_eq!(form.field("password"), Some("hunter2"));
+    assert_eq!(form.field("remember"), None);
+    assert_eq!(parse_form_submission_message(r#"{"id":4}"#), None);
+  }
+
+  #[test]
+  fn should_build_decide_script() {
+    assert_eq!(
+      decide_script(4, FormSubmissionDecision::Consume),
+      "window.__WRY_FORM__.decide(4, false);"
+    );
+  }
+}
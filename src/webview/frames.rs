@@ -0,0 +1,71 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Lists the frames of the page and evaluates scripts in them, with scripts on the engines which
+//! don't expose their frames.
+
+/// A frame of the main document, see [`WebView::frames`](super::WebView::frames).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FrameInfo {
+  /// The id to pass to [`WebView::evaluate_script_in_frame`](super::WebView::evaluate_script_in_frame).
+  pub id: u32,
+  /// The `name` attribute of the frame.
+  pub name: String,
+}
+
+/// Evaluates to the [`FrameInfo`]s of the frames of the document, their id being their index.
+#[cfg(not(target_os = "windows"))]
+pub(crate) const FRAMES_EXPRESSION: &str = "Array.prototype.map.call(document.querySelectorAll('iframe, frame'), function (frame, index) { return { id: index, name: frame.name }; })";
+
+/// Evaluates `js` in the frame listed with `id` by [`FRAMES_EXPRESSION`].
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn eval_in_frame_script(id: u32, js: &str) -> String {
+  format!(
+    r#"(function () {{
+  var frame = document.querySelectorAll('iframe, frame')[{}];
+  if (frame && frame.contentWindow) frame.contentWindow.eval({});
+}})();"#,
+    id,
+    serde_json::Value::from(js)
+  )
+}
+
+#[cfg(all(test, not(target_os = "windows")))]
+mod tests {
+  use super::*;
+  use crate::webview::typed_eval;
+
+  #[test]
+  fn should_parse_the_frames_of_the_document() {
+    assert!(typed_eval::wrap_expression(FRAMES_EXPRESSION).contains(FRAMES_EXPRESSION));
+    // The value the wrapped expression evaluates to with a named frame and an anonymous one.
+    let frames: Vec<FrameInfo> =
+      typed_eval::parse_result(r#"{"ok":[{"id":0,"name":"checkout"},{"id":1,"name":""}]}"#)
+        .unwrap();
+    assert_eq!(
+      frames,
+      vec![
+        FrameInfo {
+          id: 0,
+          name: "checkout".into()
+        },
+        FrameInfo {
+          id: 1,
+          name: String::new()
+        },
+      ]
+    );
+    let frames: Vec<FrameInfo> = typed_eval::parse_result(r#"{"ok":[]}"#).unwrap();
+    assert!(frames.is_empty());
+    assert!(typed_eval::parse_result::<Vec<FrameInfo>>(r#"{"ok":[{"id":-1,"name":""}]}"#).is_err());
+  }
+
+  #[test]
+  fn should_evaluate_in_the_listed_frame() {
+    let script = eval_in_frame_script(2, r#"alert("it's done")"#);
+    assert!(script.contains("document.querySelectorAll('iframe, frame')[2];"));
+    // The script is passed as a string literal, not spliced into the wrapper.
+    assert!(script.contains(r#"frame.contentWindow.eval("alert(\"it's done\")");"#));
+  }
+}
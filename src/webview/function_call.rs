@@ -0,0 +1,191 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Calls global javascript functions and sends their awaited result back through the IPC, since
+//! evaluating a script doesn't wait for promises on every engine.
+
+use std::{
+  cell::{Cell, RefCell},
+  collections::HashMap,
+  rc::Rc,
+};
+
+use super::{
+  ipc_router::{IpcRouter, Route},
+  PageLoadEvent, WebViewAttributes,
+};
+use crate::{Error, Result};
+
+type CallHandler = Box<dyn FnOnce(Result<&str>)>;
+
+/// Prefix of the IPC messages carrying the result of a call, followed by `<id>:<outcome>`.
+pub(crate) const CALL_MESSAGE_PREFIX: &str = "__WRY_CALL__:";
+
+/// The handlers of the calls waiting for their result.
+pub(crate) struct PendingCalls {
+  /// [`CALL_MESSAGE_PREFIX`] followed by the nonce of the webview.
+  prefix: String,
+  next_id: Cell<u32>,
+  handlers: RefCell<HashMap<u32, CallHandler>>,
+}
+
+impl PendingCalls {
+  pub(crate) fn new(prefix: String) -> Self {
+    Self {
+      prefix,
+      next_id: Cell::new(0),
+      handlers: RefCell::default(),
+    }
+  }
+
+  /// Stores `handler` until the outcome of the call is received, or the page navigates away,
+  /// returning the id of the call.
+  pub(crate) fn register(&self, handler: CallHandler) -> u32 {
+    let id = self.next_id.get();
+    self.next_id.set(id.wrapping_add(1));
+    self.handlers.borrow_mut().insert(id, handler);
+    id
+  }
+
+  /// Drops the handler of a call whose script couldn't be evaluated.
+  pub(crate) fn cancel(&self, id: u32) {
+    self.handlers.borrow_mut().remove(&id);
+  }
+
+  /// Fails the pending calls with [`Error::NavigatedAway`], since the page of their scripts is
+  /// gone and won't send their outcome.
+  pub(crate) fn fail_all(&self) {
+    let handlers = std::mem::take(&mut *self.handlers.borrow_mut());
+    for (_, handler) in handlers {
+      handler(Err(Error::NavigatedAway));
+    }
+  }
+
+  /// Calls the handler of a call result message, `<id>:<outcome>` after the prefix.
+  pub(crate) fn resolve(&self, reply: &str) {
+    if let Some((id, outcome)) = reply.split_once(':') {
+      let handler = id
+        .parse()
+        .ok()
+        .and_then(|id| self.handlers.borrow_mut().remove(&id));
+      if let Some(handler) = handler {
+        handler(Ok(outcome));
+      }
+    }
+  }
+
+  /// Calls the function at `path`, e.g. `app.handleEvent`, with `this` bound to its parent object
+  /// and the serialized `args`, and posts `{ ok: value }` or `{ error: message }` once it resolves.
+  pub(crate) fn call_script(&self, id: u32, path: &str, args: &str) -> String {
+    let prefix = &self.prefix;
+    // Serializing the path as a string keeps it from being evaluated as code.
+    let path = serde_json::Value::from(path);
+    format!(
+      r#"(function () {{
+  var reply = function (outcome) {{
+    var json;
+    try {{
+      json = JSON.stringify(outcome);
+    }} catch (e) {{
+      json = JSON.stringify({{ error: String(e) }});
+    }}
+    window.ipc.postMessage('{prefix}{id}:' + json);
+  }};
+  try {{
+    var owner = window;
+    var target = window;
+    {path}.split('.').forEach(function (key) {{
+      owner = target;
+      target = target == null ? undefined : target[key];
+    }});
+    if (typeof target !== 'function') throw new TypeError({path} + ' is not a function');
+    Promise.resolve(target.apply(owner, {args})).then(
+      function (value) {{ reply({{ ok: value === undefined ? null : value }}); }},
+      function (e) {{ reply({{ error: String(e) }}); }}
+    );
+  }} catch (e) {{
+    reply({{ error: String(e) }});
+  }}
+}})();"#,
+    )
+  }
+}
+
+/// The route of the call result messages to `calls`.
+pub(crate) fn route(calls: Rc<PendingCalls>) -> Route {
+  (
+    CALL_MESSAGE_PREFIX,
+    Box::new(move |_, reply| calls.resolve(reply)),
+  )
+}
+
+/// Routes the call results to the returned calls, which fail once the page navigates away or
+/// reloads, since they are never answered then.
+pub(crate) fn install(
+  attributes: &mut WebViewAttributes,
+  router: &mut IpcRouter,
+) -> Rc<PendingCalls> {
+  let calls = Rc::new(PendingCalls::new(router.tag(CALL_MESSAGE_PREFIX)));
+  router.add(route(calls.clone()));
+  // Android keeps the page load handler of the first webview which has one, so it's only wrapped.
+  if cfg!(not(target_os = "android")) || attributes.on_page_load_handler.is_some() {
+    let pending = calls.clone();
+    let handler = attributes.on_page_load_handler.take();
+    attributes.on_page_load_handler = Some(Box::new(move |event, url| {
+      if let PageLoadEvent::Started = event {
+        pending.fail_all();
+      }
+      if let Some(handler) = &handler {
+        handler(event, url)
+      }
+    }));
+  }
+  calls
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::rc::Rc;
+
+  #[test]
+  fn should_resolve_calls() {
+    let calls = PendingCalls::new(CALL_MESSAGE_PREFIX.to_string());
+    let outcome = Rc::new(RefCell::new(None));
+    let outcome_ = outcome.clone();
+    let id = calls.register(Box::new(move |json| {
+      *outcome_.borrow_mut() = Some(json.unwrap().to_string())
+    }));
+    let other = calls.register(Box::new(|_| panic!("wrong call resolved")));
+    assert_ne!(id, other);
+
+    calls.resolve("hello");
+    assert_eq!(*outcome.borrow(), None);
+    calls.resolve(&format!("{id}:{{\"ok\":1}}"));
+    assert_eq!(outcome.borrow().as_deref(), Some("{\"ok\":1}"));
+    // Each call is resolved once.
+    calls.resolve(&format!("{id}:{{}}"));
+    assert_eq!(outcome.borrow().as_deref(), Some("{\"ok\":1}"));
+  }
+
+  #[test]
+  fn should_fail_pending_calls() {
+    let calls = PendingCalls::new(CALL_MESSAGE_PREFIX.to_string());
+    let failed = Rc::new(Cell::new(0));
+    for _ in 0..2 {
+      let failed = failed.clone();
+      calls.register(Box::new(move |outcome| {
+        assert!(matches!(outcome, Err(Error::NavigatedAway)));
+        failed.set(failed.get() + 1);
+      }));
+    }
+    let cancelled = calls.register(Box::new(|_| panic!("cancelled call resolved")));
+    calls.cancel(cancelled);
+
+    calls.fail_all();
+    assert_eq!(failed.get(), 2);
+    calls.fail_all();
+    assert_eq!(failed.get(), 2);
+  }
+}
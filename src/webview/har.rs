@@ -0,0 +1,193 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::ResourceLoadInfo;
+
+use std::{
+  cell::RefCell,
+  rc::Rc,
+  time::{Duration, SystemTime},
+};
+use url::Url;
+
+/// The network activity captured by [`WebContext::start_har_capture`](super::WebContext::start_har_capture),
+/// which [`Har::to_json`] exports in the [HAR format](http://www.softwareishard.com/blog/har-12-spec/).
+///
+/// The entries come from the resource timing of the pages, which doesn't expose the requests
+/// themselves: they are exported as `GET` requests without headers nor cookies, and the timings
+/// are only split into the wait for the first byte and the download of the response.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Har {
+  pub entries: Vec<ResourceLoadInfo>,
+}
+
+impl Har {
+  /// The HAR 1.2 JSON document of the capture.
+  pub fn to_json(&self) -> String {
+    self.to_value().to_string()
+  }
+
+  fn to_value(&self) -> serde_json::Value {
+    serde_json::json!({
+      "log": {
+        "version": "1.2",
+        "creator": { "name": "wry", "version": env!("CARGO_PKG_VERSION") },
+        "entries": self.entries.iter().map(entry).collect::<Vec<_>>(),
+      }
+    })
+  }
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+  duration.as_micros() as f64 / 1000.
+}
+
+fn entry(info: &ResourceLoadInfo) -> serde_json::Value {
+  let query_string: Vec<_> = Url::parse(&info.url)
+    .map(|url| {
+      url
+        .query_pairs()
+        .map(|(name, value)| serde_json::json!({ "name": name, "value": value }))
+        .collect()
+    })
+    .unwrap_or_default();
+  let time = milliseconds(info.duration);
+  let wait = info.time_to_first_byte.map(milliseconds).unwrap_or(time);
+  serde_json::json!({
+    "startedDateTime": format_date_time(info.started_at),
+    "time": time,
+    "request": {
+      "method": "GET",
+      "url": info.url,
+      "httpVersion": "",
+      "cookies": [],
+      "headers": [],
+      "queryString": query_string,
+      "headersSize": -1,
+      "bodySize": -1,
+    },
+    "response": {
+      "status": info.status.unwrap_or(0),
+      "statusText": "",
+      "httpVersion": "",
+      "cookies": [],
+      "headers": [],
+      "content": {
+        "size": info.transfer_size.unwrap_or(0),
+        "mimeType": info.mime_type.as_deref().unwrap_or(""),
+      },
+      "redirectURL": "",
+      "headersSize": -1,
+      "bodySize": info.transfer_size.map_or(-1, |size| size as i64),
+    },
+    "cache": {},
+    "timings": { "send": 0, "wait": wait, "receive": (time - wait).max(0.) },
+    "_initiator": info.initiator_type,
+  })
+}
+
+/// Formats `time` like `2023-11-14T22:13:20.012Z`.
+fn format_date_time(time: SystemTime) -> String {
+  let since_epoch = time
+    .duration_since(SystemTime::UNIX_EPOCH)
+    .unwrap_or_default();
+  let seconds = since_epoch.as_secs();
+  let (days, seconds_of_day) = ((seconds / 86400) as i64, seconds % 86400);
+  // The civil date of a day since the epoch, from http://howardhinnant.github.io/date_algorithms.html.
+  let z = days + 719468;
+  let era = z.div_euclid(146097);
+  let day_of_era = z.rem_euclid(146097);
+  let year_of_era =
+    (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let mp = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = year_of_era + era * 400 + i64::from(month <= 2);
+  format!(
+    "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+    seconds_of_day / 3600,
+    seconds_of_day / 60 % 60,
+    seconds_of_day % 60,
+    since_epoch.subsec_millis()
+  )
+}
+
+/// Collects the resources loaded by the webviews of a context while a capture is running.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct HarRecorder(Rc<RefCell<Option<Vec<ResourceLoadInfo>>>>);
+
+impl HarRecorder {
+  pub(crate) fn start(&self) {
+    self.0.replace(Some(Vec::new()));
+  }
+
+  pub(crate) fn stop(&self) -> Har {
+    Har {
+      entries: self.0.take().unwrap_or_default(),
+    }
+  }
+
+  pub(crate) fn is_capturing(&self) -> bool {
+    self.0.borrow().is_some()
+  }
+
+  pub(crate) fn record(&self, info: &ResourceLoadInfo) {
+    let mut entries = self.0.borrow_mut();
+    let Some(entries) = entries.as_mut() else {
+      return;
+    };
+    entries.push(info.clone());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_format_date_time() {
+    assert_eq!(
+      format_date_time(SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_012)),
+      "2023-11-14T22:13:20.012Z"
+    );
+    assert_eq!(
+      format_date_time(SystemTime::UNIX_EPOCH + Duration::from_secs(951_782_400)),
+      "2000-02-29T00:00:00.000Z"
+    );
+  }
+
+  #[test]
+  fn should_record_while_capturing() {
+    let info = ResourceLoadInfo {
+      url: "https://example.com/api?page=2".into(),
+      status: Some(404),
+      mime_type: Some("application/json".into()),
+      initiator_type: "fetch".into(),
+      start: Duration::from_millis(100),
+      started_at: SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_100),
+      duration: Duration::from_millis(30),
+      time_to_first_byte: Some(Duration::from_millis(20)),
+      transfer_size: None,
+    };
+    let recorder = HarRecorder::default();
+    recorder.record(&info);
+    assert!(!recorder.is_capturing());
+    recorder.start();
+    assert!(recorder.is_capturing());
+    recorder.record(&info);
+    let har = recorder.stop();
+    assert_eq!(har.entries.len(), 1);
+    assert_eq!(recorder.stop().entries.len(), 0);
+
+    let log = &har.to_value()["log"];
+    let entry = &log["entries"][0];
+    assert_eq!(log["version"], "1.2");
+    assert_eq!(entry["startedDateTime"], "2023-11-14T22:13:20.100Z");
+    assert_eq!(entry["request"]["queryString"][0]["value"], "2");
+    assert_eq!(entry["response"]["status"], 404);
+    assert_eq!(entry["response"]["bodySize"], -1);
+    assert_eq!(entry["timings"]["receive"], 10.);
+  }
+}
@@ -0,0 +1,142 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Routes the IPC messages of the scripts injected by wry to their handlers, and the other ones to
+//! the IPC handler of the application.
+//!
+//! The pages can post any message with `window.ipc.postMessage`, so the injected scripts follow
+//! the prefix of their messages with a nonce generated for each webview, and the messages with a
+//! prefix but without the nonce are dropped. The scripts only see the nonce in their own closure
+//! and post through the frozen `window.ipc`, so neither the page nor its frames can read it.
+
+use std::{
+  cell::RefCell,
+  collections::hash_map::RandomState,
+  hash::{BuildHasher, Hasher},
+  rc::Rc,
+};
+
+use crate::application::window::Window;
+
+/// The prefix of the messages of an injected script, and the handler called with the rest of the
+/// messages.
+pub(crate) type Route = (&'static str, Box<dyn Fn(&Window, &str)>);
+
+/// Evaluates a script in the webview, set once it is built.
+pub(crate) type ScriptEvaluator = Rc<RefCell<Option<Box<dyn Fn(&str)>>>>;
+
+/// Evaluates `js` with `evaluator` if the webview is built.
+pub(crate) fn evaluate(evaluator: &ScriptEvaluator, js: &str) {
+  if let Some(evaluate) = &*evaluator.borrow() {
+    evaluate(js)
+  }
+}
+
+pub(crate) struct IpcRouter {
+  nonce: String,
+  routes: Vec<Route>,
+}
+
+impl IpcRouter {
+  pub(crate) fn new() -> Self {
+    // The keys of `RandomState` are seeded by the OS, there is no need for another dependency.
+    let mut nonce = String::with_capacity(32);
+    for _ in 0..2 {
+      let mut hasher = RandomState::new().build_hasher();
+      hasher.write_usize(nonce.len());
+      nonce.push_str(&format!("{:016x}", hasher.finish()));
+    }
+    Self {
+      nonce,
+      routes: Vec::new(),
+    }
+  }
+
+  /// The prefix followed by the nonce, for the scripts evaluated after the webview is built.
+  pub(crate) fn tag(&self, prefix: &str) -> String {
+    format!("{prefix}{}:", self.nonce)
+  }
+
+  /// Call the handler of `route` with the messages posted with its prefix and the nonce.
+  pub(crate) fn add(&mut self, route: Route) {
+    self.routes.push(route);
+  }
+
+  /// `script` with the nonce after the prefixes of the routes, to inject it in the webview.
+  pub(crate) fn sign(&self, script: &str) -> String {
+    self
+      .routes
+      .iter()
+      .fold(script.to_string(), |script, (prefix, _)| {
+        script.replace(prefix, &self.tag(prefix))
+      })
+  }
+
+  /// The index of the route matching the prefix of `message`, and the rest of the message if it
+  /// carries the nonce.
+  fn find<'a>(&self, message: &'a str) -> Option<(usize, Option<&'a str>)> {
+    self.routes.iter().enumerate().find_map(|(i, (prefix, _))| {
+      let rest = message.strip_prefix(prefix)?;
+      let rest = rest
+        .strip_prefix(self.nonce.as_str())
+        .and_then(|rest| rest.strip_prefix(':'));
+      Some((i, rest))
+    })
+  }
+
+  /// The IPC handler of the webview, sending the messages which don't match any route to
+  /// `fallback`.
+  pub(crate) fn into_ipc_handler(
+    self,
+    fallback: impl Fn(&Window, String) + 'static,
+  ) -> Box<dyn Fn(&Window, String)> {
+    Box::new(move |window, message| match self.find(&message) {
+      Some((i, Some(rest))) => (self.routes[i].1)(window, rest),
+      Some((i, None)) => log::warn!(
+        "dropped a {} message without the nonce of the webview",
+        self.routes[i].0
+      ),
+      None => fallback(window, message),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn router() -> IpcRouter {
+    let mut router = IpcRouter::new();
+    router.add(("__WRY_A__:", Box::new(|_, _| {})));
+    router.add(("__WRY_B__:", Box::new(|_, _| {})));
+    router
+  }
+
+  #[test]
+  fn should_sign_the_scripts_of_the_routes() {
+    let router = router();
+    assert_eq!(router.nonce.len(), 32);
+    assert_ne!(router.nonce, IpcRouter::new().nonce);
+
+    let script = router.sign("post('__WRY_B__:' + x); post('__WRY_C__:')");
+    assert_eq!(
+      script,
+      format!(
+        "post('{}' + x); post('__WRY_C__:')",
+        router.tag("__WRY_B__:")
+      )
+    );
+  }
+
+  #[test]
+  fn should_only_route_the_messages_with_the_nonce() {
+    let router = router();
+    let signed = format!("{}payload", router.tag("__WRY_B__:"));
+    assert_eq!(router.find(&signed), Some((1, Some("payload"))));
+    assert_eq!(router.find("__WRY_B__:payload"), Some((1, None)));
+    assert_eq!(router.find("__WRY_B__:0123:payload"), Some((1, None)));
+    assert_eq!(router.find("__WRY_C__:payload"), None);
+    assert_eq!(router.find("hello"), None);
+  }
+}
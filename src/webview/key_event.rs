@@ -0,0 +1,214 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::WebViewAttributes;
+
+/// A key press received by the webview, see
+/// [`WebViewBuilder::with_key_event_handler`](super::WebViewBuilder::with_key_event_handler).
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyEvent {
+  /// The key, named like the
+  /// [`key`](https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/key) of the DOM
+  /// keyboard events: the character of the key with the shift modifier applied, e.g. `k` or `K`,
+  /// or its name, e.g. `Enter`, `ArrowUp` or `F5`. `Unidentified` for the other keys.
+  pub key: String,
+  /// The modifiers held while the key was pressed.
+  pub modifiers: KeyModifiers,
+}
+
+/// The modifier keys held during a [`KeyEvent`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyModifiers {
+  pub shift: bool,
+  pub control: bool,
+  /// The Alt key, or the Option key on macOS.
+  pub alt: bool,
+  /// The Command key on macOS, the Windows key on Windows, the Super key on Linux.
+  pub meta: bool,
+}
+
+/// What to do with a [`KeyEvent`] once the key event handler saw it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyEventResponse {
+  /// The app handled the key, the page and the engine don't receive it.
+  Handled,
+  /// Dispatch the key to the page as usual.
+  Forward,
+}
+
+/// A built-in accelerator of the engine, which can be disabled with
+/// [`WebViewBuilder::with_default_shortcuts_disabled`](super::WebViewBuilder::with_default_shortcuts_disabled).
+///
+/// The shortcuts use Command instead of Ctrl on macOS.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shortcut {
+  /// Print the page with `Ctrl+P`.
+  Print,
+  /// Find in the page with `Ctrl+F`, `Ctrl+G` and `F3`.
+  Find,
+  /// Reload the page with `F5`, `Ctrl+R` and `Ctrl+Shift+R`.
+  Reload,
+  /// Zoom the page with `Ctrl+=`, `Ctrl++`, `Ctrl+-` and `Ctrl+0`.
+  Zoom,
+  /// Go back and forward in the history with `Alt+ArrowLeft` and `Alt+ArrowRight`, or `Cmd+[` and
+  /// `Cmd+]` on macOS.
+  Navigation,
+  /// Save the page with `Ctrl+S`.
+  Save,
+  /// Open the devtools with `F12`, `Ctrl+Shift+I` and `Ctrl+Shift+J`, or `Cmd+Option+I` on macOS.
+  Devtools,
+}
+
+impl Shortcut {
+  /// Whether `event` triggers this shortcut, with `Cmd` instead of `Ctrl` if `mac` is set.
+  pub(crate) fn matches(self, event: &KeyEvent, mac: bool) -> bool {
+    let KeyModifiers {
+      shift,
+      control,
+      alt,
+      meta,
+    } = event.modifiers;
+    let primary = if mac {
+      meta && !control
+    } else {
+      control && !meta
+    };
+    let key = event.key.to_ascii_lowercase();
+    let key = key.as_str();
+    match self {
+      Shortcut::Print => primary && !alt && key == "p",
+      Shortcut::Find => (primary && !alt && matches!(key, "f" | "g")) || key == "f3",
+      Shortcut::Reload => (primary && !alt && key == "r") || key == "f5",
+      Shortcut::Zoom => primary && !alt && matches!(key, "=" | "+" | "-" | "_" | "0"),
+      Shortcut::Navigation if mac => primary && !alt && !shift && matches!(key, "[" | "]"),
+      Shortcut::Navigation => alt && !control && !meta && matches!(key, "arrowleft" | "arrowright"),
+      Shortcut::Save => primary && !alt && key == "s",
+      Shortcut::Devtools if mac => primary && alt && key == "i",
+      Shortcut::Devtools => (primary && shift && matches!(key, "i" | "j")) || key == "f12",
+    }
+  }
+}
+
+/// The DOM name of a Windows virtual key code.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn key_from_virtual_key(virtual_key: u32, shift: bool) -> String {
+  let named = match virtual_key {
+    0x08 => "Backspace",
+    0x09 => "Tab",
+    0x0D => "Enter",
+    0x1B => "Escape",
+    0x20 => " ",
+    0x21 => "PageUp",
+    0x22 => "PageDown",
+    0x23 => "End",
+    0x24 => "Home",
+    0x25 => "ArrowLeft",
+    0x26 => "ArrowUp",
+    0x27 => "ArrowRight",
+    0x28 => "ArrowDown",
+    0x2D => "Insert",
+    0x2E => "Delete",
+    // VK_NUMPAD0 to VK_NUMPAD9.
+    0x60..=0x69 => return char::from(virtual_key as u8 - 0x30).to_string(),
+    0x6B => "+",
+    0x6D => "-",
+    // VK_OEM_PLUS and VK_OEM_MINUS, named after the US layout.
+    0xBB if shift => "+",
+    0xBB => "=",
+    0xBD if shift => "_",
+    0xBD => "-",
+    // VK_0 to VK_9.
+    0x30..=0x39 => return char::from(virtual_key as u8).to_string(),
+    // VK_A to VK_Z.
+    0x41..=0x5A if shift => return char::from(virtual_key as u8).to_string(),
+    0x41..=0x5A => {
+      return char::from(virtual_key as u8)
+        .to_ascii_lowercase()
+        .to_string()
+    }
+    // VK_F1 to VK_F24.
+    0x70..=0x87 => return format!("F{}", virtual_key - 0x6F),
+    _ => "Unidentified",
+  };
+  named.to_string()
+}
+
+/// Swallows the disabled shortcuts of `attributes` in its key event handler, unless the handler
+/// handles them itself.
+pub(crate) fn install(attributes: &mut WebViewAttributes) {
+  let disabled_shortcuts = std::mem::take(&mut attributes.disabled_shortcuts);
+  if disabled_shortcuts.is_empty() {
+    return;
+  }
+  let handler = attributes.key_event_handler.take();
+  attributes.key_event_handler = Some(Box::new(move |event| {
+    let disabled = disabled_shortcuts
+      .iter()
+      .any(|shortcut| shortcut.matches(&event, cfg!(target_os = "macos")));
+    match &handler {
+      Some(handler) if handler(event) == KeyEventResponse::Handled => KeyEventResponse::Handled,
+      _ if disabled => KeyEventResponse::Handled,
+      _ => KeyEventResponse::Forward,
+    }
+  }));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_name_virtual_keys() {
+    assert_eq!(key_from_virtual_key(0x4B, false), "k");
+    assert_eq!(key_from_virtual_key(0x4B, true), "K");
+    assert_eq!(key_from_virtual_key(0x31, false), "1");
+    assert_eq!(key_from_virtual_key(0x74, false), "F5");
+    assert_eq!(key_from_virtual_key(0x26, false), "ArrowUp");
+    assert_eq!(key_from_virtual_key(0x62, false), "2");
+    assert_eq!(key_from_virtual_key(0xBB, true), "+");
+    assert_eq!(key_from_virtual_key(0xFF, false), "Unidentified");
+  }
+
+  fn key(key: &str, modifiers: KeyModifiers) -> KeyEvent {
+    KeyEvent {
+      key: key.to_string(),
+      modifiers,
+    }
+  }
+
+  #[test]
+  fn should_match_shortcuts() {
+    let ctrl = KeyModifiers {
+      control: true,
+      ..Default::default()
+    };
+    let cmd = KeyModifiers {
+      meta: true,
+      ..Default::default()
+    };
+    let ctrl_shift = KeyModifiers {
+      shift: true,
+      ..ctrl
+    };
+
+    assert!(Shortcut::Print.matches(&key("p", ctrl), false));
+    assert!(!Shortcut::Print.matches(&key("p", ctrl), true));
+    assert!(Shortcut::Print.matches(&key("p", cmd), true));
+    assert!(!Shortcut::Print.matches(&key("p", Default::default()), false));
+    assert!(Shortcut::Reload.matches(&key("R", ctrl_shift), false));
+    assert!(Shortcut::Reload.matches(&key("F5", Default::default()), false));
+    assert!(Shortcut::Zoom.matches(&key("+", ctrl_shift), false));
+    assert!(!Shortcut::Zoom.matches(&key("1", ctrl), false));
+    assert!(Shortcut::Devtools.matches(&key("I", ctrl_shift), false));
+    assert!(!Shortcut::Devtools.matches(&key("i", ctrl), false));
+    let alt = KeyModifiers {
+      alt: true,
+      ..Default::default()
+    };
+    assert!(Shortcut::Navigation.matches(&key("ArrowLeft", alt), false));
+    assert!(Shortcut::Navigation.matches(&key("[", cmd), true));
+  }
+}
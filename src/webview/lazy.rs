@@ -0,0 +1,83 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::{WebView, WebViewBuilder};
+use crate::{application::window::Window, Error, Result};
+
+/// A webview whose native view is only created when first needed, see
+/// [`WebViewBuilder::build_lazy`].
+pub struct LazyWebView<'a> {
+  state: State<'a>,
+}
+
+enum State<'a> {
+  Pending(Box<WebViewBuilder<'a>>),
+  Built(Box<WebView>),
+  /// The build failed, the error was returned to the caller which triggered it.
+  Failed,
+}
+
+impl<'a> LazyWebView<'a> {
+  pub(crate) fn new(builder: WebViewBuilder<'a>) -> Self {
+    Self {
+      state: State::Pending(Box::new(builder)),
+    }
+  }
+
+  /// The window of the webview.
+  pub fn window(&self) -> Option<&Window> {
+    match &self.state {
+      State::Pending(builder) => Some(&builder.window),
+      State::Built(webview) => Some(webview.window()),
+      State::Failed => None,
+    }
+  }
+
+  /// Whether the native view has been created.
+  pub fn is_initialized(&self) -> bool {
+    matches!(self.state, State::Built(_))
+  }
+
+  /// The webview, created first if needed.
+  ///
+  /// Returns [`Error::LazyWebViewFailed`] once the creation has failed.
+  pub fn webview(&mut self) -> Result<&WebView> {
+    self.state = match std::mem::replace(&mut self.state, State::Failed) {
+      State::Pending(builder) => State::Built(Box::new(builder.build()?)),
+      state => state,
+    };
+    match &self.state {
+      State::Built(webview) => Ok(webview),
+      _ => Err(Error::LazyWebViewFailed),
+    }
+  }
+
+  /// Navigate to `url`, creating the webview with it as its first page if needed.
+  pub fn load_url(&mut self, url: &str) -> Result<()> {
+    if let State::Pending(builder) = &mut self.state {
+      builder.webview.url = Some(url.parse()?);
+      builder.webview.headers = None;
+      builder.webview.html = None;
+      self.webview()?;
+    } else {
+      self.webview()?.load_url(url);
+    }
+    Ok(())
+  }
+
+  /// Show the window, creating the webview first if needed.
+  pub fn show(&mut self) -> Result<()> {
+    self.webview()?.window().set_visible(true);
+    Ok(())
+  }
+
+  /// Take the webview, created first if needed.
+  pub fn into_webview(mut self) -> Result<WebView> {
+    self.webview()?;
+    match self.state {
+      State::Built(webview) => Ok(*webview),
+      _ => Err(Error::LazyWebViewFailed),
+    }
+  }
+}
@@ -0,0 +1,196 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Counts the live webviews and engine processes, so [`super::shutdown`] can wait for them, and
+//! releases the engine resources shared by the webviews of a context with the last of them.
+
+use std::{
+  cell::Cell,
+  sync::{Condvar, Mutex},
+  time::Duration,
+};
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios"))]
+use std::{cell::RefCell, rc::Rc};
+
+static INSTANCES: (Mutex<usize>, Condvar) = (Mutex::new(0), Condvar::new());
+
+thread_local! {
+  /// The live webviews of the current thread, which can't be dropped while it waits for them.
+  static THREAD_WEBVIEWS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Keeps a webview, or an engine process, counted until dropped.
+pub(crate) struct InstanceGuard {
+  webview: bool,
+}
+
+impl InstanceGuard {
+  /// Counts a webview, which is dropped on the thread it is created on.
+  pub(crate) fn webview() -> Self {
+    THREAD_WEBVIEWS.with(|count| count.set(count.get() + 1));
+    Self::acquire(true)
+  }
+
+  /// Counts an engine process until it exits.
+  #[cfg(target_os = "windows")]
+  pub(crate) fn process() -> Self {
+    Self::acquire(false)
+  }
+
+  fn acquire(webview: bool) -> Self {
+    *INSTANCES.0.lock().unwrap() += 1;
+    Self { webview }
+  }
+}
+
+impl Drop for InstanceGuard {
+  fn drop(&mut self) {
+    if self.webview {
+      THREAD_WEBVIEWS.with(|count| count.set(count.get() - 1));
+    }
+    *INSTANCES.0.lock().unwrap() -= 1;
+    INSTANCES.1.notify_all();
+  }
+}
+
+pub(crate) fn instance_count() -> usize {
+  *INSTANCES.0.lock().unwrap()
+}
+
+/// The number of live webviews of the current thread.
+pub(crate) fn thread_webview_count() -> usize {
+  THREAD_WEBVIEWS.with(Cell::get)
+}
+
+/// Blocks until no instance is left, calling `pump` regularly so the engine events releasing them
+/// can be delivered when called on the main thread.
+pub(crate) fn wait_until_released(mut pump: impl FnMut()) {
+  let mut count = INSTANCES.0.lock().unwrap();
+  while *count > 0 {
+    drop(count);
+    pump();
+    count = INSTANCES.0.lock().unwrap();
+    if *count > 0 {
+      count = INSTANCES
+        .1
+        .wait_timeout(count, Duration::from_millis(10))
+        .unwrap()
+        .0;
+    }
+  }
+}
+
+/// An engine resource shared by the webviews of a context, e.g. its WebView2 environment, created
+/// with the first webview and released with the last one, so the context doesn't keep the engine
+/// processes alive between create/destroy cycles.
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios"))]
+#[derive(Debug)]
+pub(crate) struct Shared<T>(Rc<SharedSlot<T>>);
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios"))]
+#[derive(Debug)]
+struct SharedSlot<T> {
+  value: RefCell<Option<T>>,
+  users: Cell<usize>,
+}
+
+/// Keeps a [`Shared`] resource alive until dropped.
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios"))]
+pub(crate) struct SharedGuard<T>(Rc<SharedSlot<T>>);
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios"))]
+impl<T: Clone> Shared<T> {
+  pub(crate) fn new() -> Self {
+    Self(Rc::new(SharedSlot {
+      value: RefCell::new(None),
+      users: Cell::new(0),
+    }))
+  }
+
+  /// The resource, created by `init` if no webview uses it, and a guard to keep with the webview.
+  pub(crate) fn try_acquire<E>(
+    &self,
+    init: impl FnOnce() -> std::result::Result<T, E>,
+  ) -> std::result::Result<(T, SharedGuard<T>), E> {
+    let value = self.0.value.borrow().clone();
+    let value = match value {
+      Some(value) => value,
+      None => {
+        let value = init()?;
+        self.0.value.replace(Some(value.clone()));
+        value
+      }
+    };
+    self.0.users.set(self.0.users.get() + 1);
+    Ok((value, SharedGuard(self.0.clone())))
+  }
+
+  /// Same as [`Self::try_acquire`], with an infallible `init`.
+  #[cfg(any(target_os = "macos", target_os = "ios"))]
+  pub(crate) fn acquire(&self, init: impl FnOnce() -> T) -> (T, SharedGuard<T>) {
+    match self.try_acquire(|| Ok::<_, std::convert::Infallible>(init())) {
+      Ok(acquired) => acquired,
+      Err(e) => match e {},
+    }
+  }
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios"))]
+impl<T> Drop for SharedGuard<T> {
+  fn drop(&mut self) {
+    let users = self.0.users.get() - 1;
+    self.0.users.set(users);
+    if users == 0 {
+      self.0.value.take();
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_wait_for_instances() {
+    let guard = InstanceGuard::webview();
+    assert_eq!(thread_webview_count(), 1);
+    assert!(instance_count() >= 1);
+    let mut guard = Some(guard);
+    let mut pumped = 0;
+    wait_until_released(|| {
+      pumped += 1;
+      if pumped == 3 {
+        guard.take();
+      }
+    });
+    assert_eq!(pumped, 3);
+    assert_eq!(instance_count(), 0);
+    assert_eq!(thread_webview_count(), 0);
+  }
+
+  #[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios"))]
+  #[test]
+  fn should_release_the_shared_resource_with_the_last_guard() {
+    let shared = Shared::new();
+    let mut created = 0;
+    let mut acquire = || {
+      shared
+        .try_acquire(|| {
+          created += 1;
+          Ok::<_, ()>(Rc::new(created))
+        })
+        .unwrap()
+    };
+    let (first, first_guard) = acquire();
+    let (second, second_guard) = acquire();
+    assert!(Rc::ptr_eq(&first, &second));
+    drop(first_guard);
+    drop(second_guard);
+    assert_eq!(Rc::strong_count(&first), 2);
+
+    let (third, _guard) = acquire();
+    assert_eq!(*third, 2);
+    assert_eq!(Rc::strong_count(&first), 2);
+  }
+}
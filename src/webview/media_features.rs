@@ -0,0 +1,94 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// Value of the [`prefers-reduced-motion`](https://developer.mozilla.org/en-US/docs/Web/CSS/@media/prefers-reduced-motion) media feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReducedMotion {
+  /// The user has made no preference known.
+  NoPreference,
+  /// The user wants the amount of non-essential motion to be minimized.
+  Reduce,
+}
+
+/// Value of the [`prefers-contrast`](https://developer.mozilla.org/en-US/docs/Web/CSS/@media/prefers-contrast) media feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Contrast {
+  /// The user has made no preference known.
+  NoPreference,
+  /// The user prefers a higher level of contrast.
+  More,
+  /// The user prefers a lower level of contrast.
+  Less,
+  /// The user has set a specific set of colors which are neither high nor low contrast.
+  Custom,
+}
+
+/// Overrides of the user preference media features seen by the page.
+///
+/// Fields set to `None` keep following the operating system settings.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MediaFeatureOverrides {
+  /// Forced value of `prefers-reduced-motion`.
+  pub reduced_motion: Option<ReducedMotion>,
+  /// Forced value of `prefers-contrast`.
+  pub contrast: Option<Contrast>,
+}
+
+impl MediaFeatureOverrides {
+  /// The media feature names paired with their forced values.
+  pub(crate) fn features(&self) -> [(&'static str, Option<&'static str>); 2] {
+    [
+      (
+        "prefers-reduced-motion",
+        self.reduced_motion.map(|v| match v {
+          ReducedMotion::NoPreference => "no-preference",
+          ReducedMotion::Reduce => "reduce",
+        }),
+      ),
+      (
+        "prefers-contrast",
+        self.contrast.map(|v| match v {
+          Contrast::NoPreference => "no-preference",
+          Contrast::More => "more",
+          Contrast::Less => "less",
+          Contrast::Custom => "custom",
+        }),
+      ),
+    ]
+  }
+
+  /// Script patching `window.matchMedia` for engines that can't override media features natively.
+  ///
+  /// Running it again replaces the previously applied overrides.
+  #[allow(dead_code)] // not used on Windows, the overrides go through the devtools protocol there.
+  pub(crate) fn to_script(self) -> String {
+    let overrides: serde_json::Map<String, serde_json::Value> = self
+      .features()
+      .iter()
+      .filter_map(|(name, value)| value.map(|v| (name.to_string(), v.into())))
+      .collect();
+
+    format!(
+      r#"(function () {{
+  var first = !window.__WRY_MEDIA_FEATURES__;
+  window.__WRY_MEDIA_FEATURES__ = {};
+  if (!first) return;
+  var matchMedia = window.matchMedia.bind(window);
+  window.matchMedia = function (query) {{
+    var overrides = window.__WRY_MEDIA_FEATURES__;
+    for (var name in overrides) {{
+      var m = new RegExp('^\\s*\\(\\s*' + name + '\\s*(?::\\s*([a-z-]+)\\s*)?\\)\\s*$').exec(query);
+      if (m) {{
+        var matches = m[1] === undefined ? overrides[name] !== 'no-preference' : m[1] === overrides[name];
+        var noop = function () {{}};
+        return {{ matches: matches, media: query, onchange: null, addListener: noop, removeListener: noop, addEventListener: noop, removeEventListener: noop, dispatchEvent: function () {{ return false; }} }};
+      }}
+    }}
+    return matchMedia(query);
+  }};
+}})();"#,
+      serde_json::Value::Object(overrides)
+    )
+  }
+}
@@ -0,0 +1,21 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// The memory used by a webview, see [`WebView::memory_usage`](super::WebView::memory_usage).
+///
+/// The values are `None` when the engine doesn't report them.
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryStats {
+  /// The resident set size of the web content process, in bytes.
+  ///
+  /// The process can be shared with other webviews, e.g. those showing the same site.
+  pub process_resident_size: Option<u64>,
+  /// The size of the JavaScript heap used by the page, in bytes.
+  pub js_heap_size: Option<u64>,
+}
+
+/// Evaluates to the used JavaScript heap size, which only Chromium exposes.
+pub(crate) const JS_HEAP_SIZE_EXPRESSION: &str =
+  "performance.memory ? performance.memory.usedJSHeapSize : null";
@@ -4,8 +4,68 @@
 
 //! [`WebView`] struct and associated types.
 
+mod archive;
+#[cfg(feature = "protocol")]
+mod assets;
+mod auto_resize;
+mod autoplay;
+mod cache_policy;
+mod capabilities;
+mod click_through;
+mod clipboard;
+mod composition;
+mod content_blocker;
+mod cors;
+mod cursor;
+mod default_headers;
+mod devtools;
+mod edit_command;
+mod editable_focus;
+mod external_links;
+mod form_submission;
+mod frames;
+mod function_call;
+mod har;
+mod ipc_router;
+mod key_event;
+mod lazy;
+mod lifecycle;
+mod media_features;
+mod memory;
+mod mouse_event;
+mod navigation_filter;
+mod navigation_metrics;
+mod network_conditions;
+mod new_window;
+mod overscroll;
+#[cfg(not(target_os = "windows"))]
+mod page_load;
+mod print;
+mod privacy_signals;
+#[cfg(feature = "protocol")]
+mod protocol_bridge;
+#[cfg(feature = "protocol")]
+mod protocol_logger;
+#[cfg(feature = "protocol")]
+mod protocol_router;
 mod proxy;
+mod raw_window;
+mod referrer_policy;
+mod request_body;
+mod resource_load;
+mod scroll;
+mod selection;
+mod service_worker;
+mod snapshot;
+mod spellcheck;
+mod text_input;
+mod touch_event;
+mod typed_eval;
+mod user_script;
+mod virtual_host;
 mod web_context;
+mod webrtc;
+mod websocket;
 
 pub use web_context::WebContext;
 
@@ -44,19 +104,71 @@ pub(crate) mod webview2;
 #[cfg(target_os = "windows")]
 use self::webview2::*;
 use crate::{application::dpi::PhysicalPosition, Result};
+use serde::de::DeserializeOwned;
 #[cfg(target_os = "windows")]
 use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Controller;
 #[cfg(target_os = "windows")]
 use windows::{Win32::Foundation::HWND, Win32::UI::WindowsAndMessaging::DestroyWindow};
 
-use std::{borrow::Cow, path::PathBuf, rc::Rc};
-
+use std::{
+  borrow::Cow,
+  cell::{Cell, RefCell},
+  path::PathBuf,
+  rc::Rc,
+};
+
+pub use archive::WebArchive;
+#[cfg(feature = "protocol")]
+pub use assets::{AssetProtocol, AssetSource};
+pub use auto_resize::{Anchors, AutoResizePolicy};
+pub use autoplay::AutoplayPolicy;
+pub use cache_policy::CachePolicy;
+pub use capabilities::Capabilities;
+pub use click_through::ClickThroughRegion;
+pub use clipboard::{ClipboardAccess, ClipboardDecision, ClipboardRequest};
+pub use composition::CompositionEvent;
+pub use cors::CorsPolicy;
+pub use cursor::PageCursor;
+pub use devtools::{DevtoolsPlacement, DevtoolsPolicy};
+pub use edit_command::EditCommand;
+pub use editable_focus::{EditableElement, EditableFocusEvent};
+pub use form_submission::{FormData, FormSubmissionDecision};
+pub use frames::FrameInfo;
+pub use har::Har;
+pub use key_event::{KeyEvent, KeyEventResponse, KeyModifiers, Shortcut};
+pub use lazy::LazyWebView;
+pub use media_features::{Contrast, MediaFeatureOverrides, ReducedMotion};
+pub use memory::MemoryStats;
+pub use mouse_event::MouseEvent;
+pub use navigation_filter::NavigationFilter;
+pub use navigation_metrics::NavigationMetrics;
+pub use new_window::{NewWindowOpener, NewWindowRequest, NewWindowResponse};
+pub use print::{PrintDecision, PrintOptions};
+#[cfg(feature = "protocol")]
+pub use protocol_logger::{ProtocolLogEntry, ProtocolLogger};
+#[cfg(feature = "protocol")]
+pub use protocol_router::CustomProtocolRouter;
 pub use proxy::{ProxyConfig, ProxyEndpoint};
+pub use referrer_policy::ReferrerPolicy;
+pub use request_body::RequestBody;
+pub use resource_load::ResourceLoadInfo;
+pub use scroll::ScrollEvent;
+pub use snapshot::SnapshotFormat;
+pub use text_input::TextInputSettings;
+pub use touch_event::{TouchEvent, TouchPoint};
 pub use url::Url;
+pub use user_script::{InitScript, InjectionTime, ScriptId};
+pub use virtual_host::virtual_host_origin;
+pub use webrtc::WebRtcPolicy;
+pub use websocket::{WebSocketAction, WebSocketMessage, WebSocketProxyEvent, WebSocketRequest};
 
 #[cfg(target_os = "windows")]
 use crate::application::platform::windows::WindowExtWindows;
-use crate::application::{dpi::PhysicalSize, window::Window};
+use crate::application::{
+  dpi::PhysicalSize,
+  event_loop::EventLoopWindowTarget,
+  window::{Window, WindowBuilder},
+};
 
 use http::{Request, Response as HttpResponse};
 
@@ -83,6 +195,15 @@ impl RequestAsyncResponder {
 pub struct WebViewAttributes {
   /// Whether the WebView should have a custom user-agent.
   pub user_agent: Option<String>,
+  /// Append an identification to the user-agent, e.g. `MyApp/1.2`, instead of replacing it.
+  ///
+  /// It is separated from the user-agent by a space, and is appended to the platform default
+  /// user-agent, or to [`Self::user_agent`] if set.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Sets the `applicationNameForUserAgent` of the webview configuration.
+  pub user_agent_suffix: Option<String>,
   /// Whether the WebView window should be visible.
   pub visible: bool,
   /// Whether the WebView should be transparent.
@@ -102,10 +223,42 @@ pub struct WebViewAttributes {
   ///   - On Windows 7, transparency is not supported and the alpha value will be ignored.
   ///   - On Windows higher than 7: translucent colors are not supported so any alpha value other than `0` will be replaced by `255`
   pub background_color: Option<RGBA>,
+  /// The color of the area revealed when the page is scrolled past its edges, e.g. by the
+  /// rubber-band effect of the trackpads. It follows the background of the page by default.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Requires macOS 12+ / iOS 15+.
+  /// - **Windows / Linux / Android**: Unsupported, the pages aren't scrolled past their edges.
+  pub under_page_background_color: Option<RGBA>,
+  /// Whether the page bounces when it is scrolled past its edges. Default is `true`.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: Disabled with the `overscroll-behavior` CSS property of the page, set by an
+  ///   initialization script.
+  /// - **iOS**: Also sets the `bounces` property of the scroll view.
+  /// - **Android**: Also sets the `overScrollMode` of the webview.
+  pub elastic_overscroll: bool,
+  /// Whether the scrolling with the mouse wheel and the keyboard is animated, `None` keeping the
+  /// default of the engine.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: Sets the `--enable-smooth-scrolling` or `--disable-smooth-scrolling` browser
+  ///   argument, ignored on the webviews sharing the environment of a previous one.
+  /// - **macOS / iOS / Android**: Unsupported, the scrolling follows the system.
+  pub smooth_scrolling: Option<bool>,
   /// Whether load the provided URL to [`WebView`].
   pub url: Option<Url>,
   /// Headers used when loading the requested `url`.
   pub headers: Option<http::HeaderMap>,
+  /// Headers added to all the requests of the webview, see
+  /// [`WebViewBuilder::with_default_headers`].
+  pub default_headers: Option<http::HeaderMap>,
+  /// Whether the `DNT` and `Sec-GPC` privacy signals are sent, see
+  /// [`WebViewBuilder::with_privacy_signals`].
+  pub privacy_signals: bool,
   /// Whether page zooming by hotkeys is enabled
   ///
   /// ## Platform-specific
@@ -117,12 +270,22 @@ pub struct WebViewAttributes {
   ///
   /// # Warning
   ///
-  /// The Page loaded from html string will have `null` origin.
+  /// The Page loaded from html string will have `null` origin, unless an origin is given with
+  /// [`WebViewBuilder::with_html_origin`].
   ///
   /// ## PLatform-specific:
   ///
   /// - **Windows:** the string can not be larger than 2 MB (2 * 1024 * 1024 bytes) in total size
   pub html: Option<String>,
+
+  /// The URL the page loaded from the HTML string is associated with, e.g. `https://app.example/`
+  /// or `wry://localhost/` for a custom protocol. The page gets the origin of this URL, so it
+  /// can use `localStorage` and cookies, and its relative URLs are resolved against it.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows:** The navigation to this URL is intercepted to answer with the HTML string.
+  pub html_origin: Option<Url>,
   /// Initialize javascript code when loading new pages. When webview load a new page, this
   /// initialization code will be executed. It is guaranteed that code is executed before
   /// `window.onload`.
@@ -132,6 +295,26 @@ pub struct WebViewAttributes {
   /// - **Android:** The Android WebView does not provide an API for initialization scripts,
   /// so we prepend them to each HTML head. They are only implemented on custom protocol URLs.
   pub initialization_scripts: Vec<String>,
+
+  /// Initialization scripts with injection options, see [`InitScript`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / Android:** The options are applied in javascript, the scripts are added to
+  ///   [`Self::initialization_scripts`] and check the frame they run in, or wait for the
+  ///   `DOMContentLoaded` event.
+  pub init_scripts: Vec<InitScript>,
+
+  /// Initialization scripts run in isolated JavaScript worlds, as `(world, script)` pairs, so the
+  /// page scripts can't observe or tamper with the objects they define. Each world has its own
+  /// globals and shares the DOM of the page, and `window.ipc` is available in it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS:** Requires macOS 11+ / iOS 14+.
+  /// - **Windows / Android:** Unsupported, building the webview returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported).
+  pub isolated_initialization_scripts: Vec<(String, String)>,
   /// Register custom file loading protocols with pairs of scheme uri string and a handling
   /// closure.
   ///
@@ -155,6 +338,18 @@ pub struct WebViewAttributes {
   ///
   /// [bug]: https://bugs.webkit.org/show_bug.cgi?id=229034
   pub custom_protocols: Vec<(String, Box<dyn Fn(Request<Vec<u8>>, RequestAsyncResponder)>)>,
+
+  /// Register custom protocols whose handlers read the request bodies as they are uploaded, see
+  /// [`WebViewBuilder::with_streaming_custom_protocol`].
+  pub streaming_custom_protocols: Vec<(
+    String,
+    Box<dyn Fn(Request<RequestBody>, RequestAsyncResponder)>,
+  )>,
+
+  /// Log the requests served by the custom protocols, see [`ProtocolLogger`].
+  #[cfg(feature = "protocol")]
+  pub protocol_logger: Option<ProtocolLogger>,
+
   /// Set the IPC handler to receive the message from Javascript on webview to host Rust code.
   /// The message sent from webview should call `window.ipc.postMessage("insert_message_here");`.
   ///
@@ -184,6 +379,12 @@ pub struct WebViewAttributes {
   /// second is a mutable `PathBuf` reference that (possibly) represents where the file will be downloaded to. The latter
   /// parameter can be used to set the download location by assigning a new path to it - the assigned path _must_ be
   /// absolute. The closure returns a `bool` to allow or deny the download.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android**: The file is downloaded by the system download manager, to the `Download`
+  ///   directory of the app external files by default. The path must be a location the download
+  ///   manager can write to.
   pub download_started_handler: Option<Box<dyn FnMut(String, &mut PathBuf) -> bool>>,
 
   /// Sets a download completion handler to manage downloads that have finished.
@@ -201,18 +402,68 @@ pub struct WebViewAttributes {
   /// limitations.
   pub download_completed_handler: Option<Rc<dyn Fn(String, Option<PathBuf>, bool) + 'static>>,
 
+  /// Whether the PDFs are displayed by the viewer of the engine. Otherwise they are downloaded,
+  /// see [`WebViewBuilder::with_pdf_viewer`].
+  pub pdf_viewer: bool,
+
   /// Set a new window handler to decide if incoming url is allowed to open in a new window.
   ///
   /// The closure take a `String` parameter as url and return `bool` to determine the url. True is
   /// allow to navigate and false is not.
   pub new_window_req_handler: Option<Box<dyn Fn(String) -> bool>>,
 
+  /// Set a handler deciding how to open the new windows requested by the page, see
+  /// [`NewWindowResponse`]. It is called once [`Self::new_window_req_handler`] allows the request.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android:** Unsupported, new windows are never opened.
+  pub new_window_handler: Option<Box<dyn Fn(NewWindowRequest) -> NewWindowResponse>>,
+
+  /// Build the webview of a new window requested by another webview, see [`NewWindowOpener`].
+  /// The requested page is loaded instead of [`Self::url`] or [`Self::html`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS:** The webview shares the custom protocols and the website data store of its
+  ///   opener, which must outlive it.
+  /// - **Android:** Unsupported.
+  pub new_window_opener: Option<NewWindowOpener>,
+
+  /// Open the new windows requested for external links in the system browser, i.e. the http and
+  /// https URLs whose host isn't one of the [`Self::internal_link_hosts`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android:** Unsupported, the new windows are denied.
+  pub external_links_in_browser: bool,
+
+  /// The hosts of the links which stay in the webview when [`Self::external_links_in_browser`] is
+  /// enabled, where `*.example.com` matches `example.com` and its subdomains.
+  pub internal_link_hosts: Vec<String>,
+
+  /// Restrict the URLs the webview may navigate to, or open in a new window.
+  ///
+  /// The filter is checked before the navigation and new window handlers, which are not called for
+  /// the denied URLs.
+  pub navigation_filter: Option<NavigationFilter>,
+
   /// Enables clipboard access for the page rendered on **Linux** and **Windows**.
   ///
   /// macOS doesn't provide such method and is always enabled by default. But you still need to add menu
   /// item accelerators to use shortcuts.
   pub clipboard: bool,
 
+  /// Whether the spelling of the editable content is checked, the default of the engine if
+  /// `None`. See [`WebViewBuilder::with_spellcheck`].
+  pub spellcheck: Option<bool>,
+
+  /// The languages of the spell checker, see [`WebViewBuilder::with_spellcheck_languages`].
+  pub spellcheck_languages: Vec<String>,
+
+  /// The automatic changes of the typed text, see [`WebViewBuilder::with_text_input_settings`].
+  pub text_input_settings: TextInputSettings,
+
   /// Enable web inspector which is usually called dev tool.
   ///
   /// Note this only enables dev tool to the webview. To open it, you can call
@@ -220,16 +471,43 @@ pub struct WebViewAttributes {
   ///
   /// ## Platform-specific
   ///
-  /// - macOS: This will call private functions on **macOS**. It's still enabled if set in **debug** build on mac,
-  /// but requires `devtools` feature flag to actually enable it in **release** build.
+  /// - macOS: Uses the public `inspectable` property on macOS 13.3+. Older macOS versions and the
+  ///   [`WebView::open_devtools`] family of methods call private functions, which are only
+  ///   enabled in **debug** build or with the `devtools` feature flag.
   /// - Android: Open `chrome://inspect/#devices` in Chrome to get the devtools window. Wry's `WebView` devtools API isn't supported on Android.
   /// - iOS: Open Safari > Develop > [Your Device Name] > [Your WebView] to get the devtools window.
   pub devtools: bool,
-  /// Whether clicking an inactive window also clicks through to the webview. Default is `false`.
+
+  /// A runtime policy deciding whether the web inspector is available. When set, it's resolved
+  /// when the webview is built and overrides [`Self::devtools`].
+  pub devtools_policy: Option<DevtoolsPolicy>,
+
+  /// Let external debuggers, like the browser devtools or Playwright, attach to the webview through
+  /// the remote inspector of the engine listening on `port`. This also enables the devtools.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: Passes `--remote-debugging-port` to the browser process, open
+  ///   `http://localhost:<port>` or `edge://inspect` to attach. Ignored when
+  ///   [`WebViewBuilderExtWindows::with_additional_browser_args`] is used, and only applied by
+  ///   the first webview of the browser process.
+  /// - **Linux**: Starts the WebKit inspector HTTP server on `127.0.0.1:<port>` through the
+  ///   `WEBKIT_INSPECTOR_HTTP_SERVER` environment variable, which is only read when the first web
+  ///   context of the process is created, and requires WebKitGTK 2.32+.
+  /// - **macOS / iOS**: The port is ignored, attach from Safari > Develop.
+  /// - **Android**: The port is ignored, attach from `chrome://inspect/#devices`.
+  pub remote_debugging_port: Option<u16>,
+
+  /// Whether clicking an inactive window also clicks through to the webview. Default is `false`
+  /// on macOS and `true` on the other platforms, which always delivered that click.
+  ///
+  /// When disabled, the click which activates the window is not delivered to the page.
   ///
   /// ## Platform-specific
   ///
-  /// This configuration only impacts macOS.
+  /// - **Linux**: Best effort, depends on the window manager activating the window after the
+  ///   click is received.
+  /// - **Android / iOS:** Unsupported.
   pub accept_first_mouse: bool,
 
   /// Indicates whether horizontal swipe gestures trigger backward and forward page navigation.
@@ -239,9 +517,25 @@ pub struct WebViewAttributes {
   /// - **Android / iOS:** Unsupported.
   pub back_forward_navigation_gestures: bool,
 
+  /// Whether pressing a link with force touch or a long press shows a preview of its destination.
+  /// Default is `true`.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows / Linux / Android:** Unsupported.
+  pub allows_link_preview: bool,
+
   /// Set a handler closure to process the change of the webview's document title.
   pub document_title_changed_handler: Option<Box<dyn Fn(&Window, String)>>,
 
+  /// Set a handler closure called when the page asks to close its window with `window.close()`,
+  /// or when its opener closes it.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android:** Unsupported.
+  pub close_requested_handler: Option<Box<dyn Fn(&Window)>>,
+
   /// Run the WebView with incognito mode. Note that WebContext will be ingored if incognito is
   /// enabled.
   ///
@@ -253,6 +547,16 @@ pub struct WebViewAttributes {
   /// Whether all media can be played without user interaction.
   pub autoplay: bool,
 
+  /// The media autoplay policy. When set, it overrides [`Self::autoplay`].
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows / macOS / iOS / Android:** [`AutoplayPolicy::Allowlist`] is not supported per
+  ///   origin and behaves like [`AutoplayPolicy::RequireUserGesture`].
+  /// - **Windows:** Sets the `--autoplay-policy` browser argument.
+  /// - **Android:** [`AutoplayPolicy::AllowMuted`] behaves like [`AutoplayPolicy::RequireUserGesture`].
+  pub autoplay_policy: Option<AutoplayPolicy>,
+
   /// Set a handler closure to process page load events.
   pub on_page_load_handler: Option<Box<dyn Fn(PageLoadEvent, String)>>,
 
@@ -266,44 +570,284 @@ pub struct WebViewAttributes {
   ///
   /// ## Platform-specific:
   ///
-  /// - **macOS / Android / iOS:** Unsupported.
+  /// - **Android / iOS:** Unsupported.
   pub focused: bool,
+
+  /// A handler called with `true` when the webview gains the keyboard focus, and `false` when it
+  /// loses it.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android / iOS:** Unsupported.
+  pub focus_changed_handler: Option<Box<dyn Fn(bool)>>,
+
+  /// A handler called with the key presses received by the webview before the page, which doesn't
+  /// receive those it returns [`KeyEventResponse::Handled`] for.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows:** Only called with the accelerator keys: the keys pressed with Ctrl or Alt, and
+  ///   the function keys. The layout isn't applied to the keys, `Ctrl+Shift+1` is reported as `1`.
+  /// - **Android / iOS:** Unsupported.
+  pub key_event_handler: Option<Box<dyn Fn(KeyEvent) -> KeyEventResponse>>,
+
+  /// The built-in accelerators of the engine which are disabled, neither the engine nor the page
+  /// receive their keys. The [`key_event_handler`](Self::key_event_handler) still sees them.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android / iOS:** Unsupported.
+  pub disabled_shortcuts: Vec<Shortcut>,
+
+  /// The position and size of a webview built with [`WebViewBuilder::new_as_child`], relative to
+  /// the top left corner of the client area of the window. It fills the window by default.
+  pub bounds: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+
+  /// How a webview built with [`WebViewBuilder::new_as_child`] follows the size of its window.
+  /// It fills the window until it is placed with [`Self::bounds`] or [`WebView::set_bounds`], and
+  /// then keeps its position and size by default.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android:** Unsupported.
+  pub auto_resize: AutoResizePolicy,
+
+  /// Force the values of the `prefers-reduced-motion` and `prefers-contrast` media features seen
+  /// by the page, regardless of the operating system accessibility settings.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / Linux / iOS / Android:** The engine has no native override, so only
+  ///   `window.matchMedia` reflects the forced values, set by an
+  ///   [initialization script](WebViewBuilder::with_initialization_script), CSS `@media` rules
+  ///   still follow the system.
+  pub media_feature_overrides: Option<MediaFeatureOverrides>,
+
+  /// Set a handler closure to process the change of the webview's audible state.
+  ///
+  /// The closure takes a `bool` which is `true` when the page starts playing audio and `false`
+  /// when it stops. Muting the webview doesn't change the reported state.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Uses private APIs.
+  /// - **Android**: Unsupported.
+  pub audio_state_handler: Option<Box<dyn Fn(bool)>>,
+
+  /// Restrict WebRTC, e.g. to disable it entirely or to prevent it from leaking the local IP
+  /// addresses.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux**: WebRTC is disabled by default, setting a policy with [`WebRtcPolicy::enabled`]
+  ///   enables it.
+  /// - **Windows**: Relay only also sets the `--force-webrtc-ip-handling-policy` browser argument.
+  /// - **macOS / iOS / Android**: The restrictions are only enforced by an
+  ///   [initialization script](WebViewBuilder::with_initialization_script).
+  pub webrtc_policy: Option<WebRtcPolicy>,
+
+  /// Set a handler closure to process the changes of the mouse cursor requested by the page,
+  /// for hosts which draw their own cursor over the webview.
+  ///
+  /// The cursor is computed from the CSS `cursor` property of the element under the mouse. An
+  /// element can override it for itself and its children with a `data-wry-cursor` attribute, e.g.
+  /// `<canvas data-wry-cursor="sword">`, whose value is reported as is.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The cursor is tracked by an [initialization script](WebViewBuilder::with_initialization_script), so only mouse
+  ///   moves update it.
+  pub cursor_changed_handler: Option<Box<dyn Fn(&Window, PageCursor)>>,
+
+  /// Set a handler closure to process the composition of text with an input method in the
+  /// editable elements of the page, for hosts which draw their own windows. The position of the
+  /// composed text lets them move the candidate window next to it with
+  /// [`Window::set_ime_position`], after adding the position of the webview in the window.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The composition is tracked by an [initialization script](WebViewBuilder::with_initialization_script).
+  pub composition_handler: Option<Box<dyn Fn(&Window, CompositionEvent)>>,
+
+  /// A handler called when an editable element of the page gets or loses the focus, see
+  /// [`WebViewBuilder::with_editable_focus_handler`].
+  pub editable_focus_handler: Option<Box<dyn Fn(EditableFocusEvent)>>,
+
+  /// Whether the page can declare the regions of the webview where the mouse events go through
+  /// it, by calling `window.setIgnoreMouseEvents(regions)` with an array of elements or
+  /// rectangles, see [`WebView::set_click_through_regions`].
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows:** Only supported by the webviews built with [`WebViewBuilder::new_as_child`].
+  /// - **iOS / Android:** Unsupported.
+  pub click_through: bool,
+
+  /// A handler called with the changes of the scroll position of the page, e.g. to collapse a
+  /// native title bar while the page scrolls down.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The position is reported by an [initialization script](WebViewBuilder::with_initialization_script) once per
+  ///   frame while the page scrolls, and when a page is shown.
+  pub scroll_handler: Option<Box<dyn Fn(ScrollEvent)>>,
+
+  /// Whether the page reports its scroll position for [`WebView::scroll_position`] without a
+  /// [`WebViewAttributes::scroll_handler`]. Defaults to `false`.
+  pub scroll_tracking: bool,
+
+  /// A handler called with the text selected in the page when it changes, `None` once nothing is
+  /// selected.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The selection is reported by an [initialization script](WebViewBuilder::with_initialization_script) once per
+  ///   frame while it changes, and when a page is shown.
+  pub selection_changed_handler: Option<Box<dyn Fn(Option<String>)>>,
+
+  /// A handler called with each subresource loaded by the page, with its status, MIME type and
+  /// timings, e.g. to build a network panel or to detect the failing third-party assets.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The resources are reported by an [initialization script](WebViewBuilder::with_initialization_script) from the
+  ///   resource timing entries of the top frame. The status and the MIME type are only exposed by
+  ///   recent engines.
+  pub resource_load_handler: Option<Box<dyn Fn(ResourceLoadInfo)>>,
+
+  /// Decides what to do with the WebSocket connections opened by the pages, see
+  /// [`WebViewBuilder::with_websocket_handler`].
+  pub websocket_handler: Option<Box<dyn Fn(WebSocketRequest) -> WebSocketAction>>,
+
+  /// Decides whether the POST forms of the pages are submitted, see
+  /// [`WebViewBuilder::with_form_submission_handler`].
+  pub form_submission_handler: Option<Box<dyn Fn(FormData) -> FormSubmissionDecision>>,
+
+  /// Decides whether the pages access the clipboard with the async Clipboard API, see
+  /// [`WebViewBuilder::with_clipboard_access_handler`].
+  pub clipboard_access_handler: Option<Box<dyn Fn(ClipboardRequest) -> ClipboardDecision>>,
+
+  /// Decides what to do when the pages call `window.print()`, see
+  /// [`WebViewBuilder::with_print_handler`].
+  pub print_handler: Option<Box<dyn Fn(String) -> PrintDecision>>,
+
+  /// The local folders served on virtual hosts, as `(host, folder)` pairs, so the pages of a
+  /// bundled folder get their own origin, listed by [`virtual_host_origin`], instead of a `file://`
+  /// one.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: Uses `SetVirtualHostNameToFolderMapping`, the pages are served on
+  ///   `https://<host>/`.
+  /// - **Linux / macOS / iOS**: Served by the `wry-host` custom protocol, on `wry-host://<host>/`,
+  ///   which can't be registered with [`WebViewBuilder::with_custom_protocol`] then.
+  /// - **Android**: Unsupported, the mappings are ignored.
+  pub virtual_host_mappings: Vec<(String, PathBuf)>,
+
+  /// The custom protocols whose requests from other origins are allowed, with the origins they
+  /// allow, see [`WebViewBuilder::with_relaxed_cors`].
+  pub relaxed_cors_protocols: Vec<(String, CorsPolicy)>,
+
+  /// Which referrer is sent with the requests of the pages, see
+  /// [`WebViewBuilder::with_referrer_policy`].
+  pub referrer_policy: Option<ReferrerPolicy>,
 }
 
 impl Default for WebViewAttributes {
   fn default() -> Self {
     Self {
       user_agent: None,
+      user_agent_suffix: None,
       visible: true,
       transparent: false,
       background_color: None,
+      under_page_background_color: None,
+      elastic_overscroll: true,
+      smooth_scrolling: None,
       url: None,
       headers: None,
+      default_headers: None,
+      privacy_signals: false,
       html: None,
+      html_origin: None,
       initialization_scripts: vec![],
+      init_scripts: vec![],
+      isolated_initialization_scripts: vec![],
       custom_protocols: vec![],
+      streaming_custom_protocols: vec![],
+      #[cfg(feature = "protocol")]
+      protocol_logger: None,
       ipc_handler: None,
       file_drop_handler: None,
       navigation_handler: None,
       download_started_handler: None,
       download_completed_handler: None,
+      pdf_viewer: true,
       new_window_req_handler: None,
+      new_window_handler: None,
+      new_window_opener: None,
+      external_links_in_browser: false,
+      internal_link_hosts: vec![],
+      navigation_filter: None,
       clipboard: false,
+      spellcheck: None,
+      spellcheck_languages: Vec::new(),
+      text_input_settings: TextInputSettings::default(),
       #[cfg(debug_assertions)]
       devtools: true,
       #[cfg(not(debug_assertions))]
       devtools: false,
+      devtools_policy: None,
+      remote_debugging_port: None,
       zoom_hotkeys_enabled: false,
-      accept_first_mouse: false,
+      accept_first_mouse: cfg!(not(target_os = "macos")),
       back_forward_navigation_gestures: false,
+      allows_link_preview: true,
       document_title_changed_handler: None,
+      close_requested_handler: None,
       incognito: false,
       autoplay: true,
+      autoplay_policy: None,
       on_page_load_handler: None,
       proxy_config: None,
       focused: true,
+      focus_changed_handler: None,
+      key_event_handler: None,
+      disabled_shortcuts: Vec::new(),
+      bounds: None,
+      auto_resize: AutoResizePolicy::default(),
+      media_feature_overrides: None,
+      audio_state_handler: None,
+      webrtc_policy: None,
+      cursor_changed_handler: None,
+      composition_handler: None,
+      editable_focus_handler: None,
+      click_through: false,
+      scroll_handler: None,
+      scroll_tracking: false,
+      selection_changed_handler: None,
+      resource_load_handler: None,
+      websocket_handler: None,
+      form_submission_handler: None,
+      clipboard_access_handler: None,
+      print_handler: None,
+      virtual_host_mappings: Vec::new(),
+      relaxed_cors_protocols: Vec::new(),
+      referrer_policy: None,
+    }
+  }
+}
+
+/// The distinct worlds of the isolated initialization scripts.
+#[allow(dead_code)] // Isolated worlds are unsupported on Windows and Android.
+pub(crate) fn isolated_worlds(scripts: &[(String, String)]) -> Vec<String> {
+  let mut worlds: Vec<String> = Vec::new();
+  for (world, _) in scripts {
+    if !worlds.contains(world) {
+      worlds.push(world.clone());
     }
   }
+  worlds
 }
 
 #[cfg(windows)]
@@ -331,10 +875,13 @@ impl Default for PlatformSpecificWebViewAttributes {
   target_os = "freebsd",
   target_os = "netbsd",
   target_os = "openbsd",
-  target_os = "macos",
-  target_os = "ios",
 ))]
 #[derive(Default)]
+pub(crate) struct PlatformSpecificWebViewAttributes {
+  container: Option<gtk::Container>,
+}
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[derive(Default)]
 pub(crate) struct PlatformSpecificWebViewAttributes;
 
 #[cfg(target_os = "android")]
@@ -362,6 +909,11 @@ pub type RGBA = (u8, u8, u8, u8);
 pub enum PageLoadEvent {
   /// Indicates that the content of the page has started loading
   Started,
+  /// Indicates that the document of the page was parsed, before its stylesheets, images and
+  /// subframes finished loading.
+  ///
+  /// - **Android:** Only reported for the pages loaded with a custom protocol.
+  DomContentLoaded,
   /// Indicates that the page content has finished loading
   Finished,
 }
@@ -375,23 +927,136 @@ pub struct WebViewBuilder<'a> {
   pub webview: WebViewAttributes,
   platform_specific: PlatformSpecificWebViewAttributes,
   web_context: Option<&'a mut WebContext>,
-  window: Window,
+  window: Rc<Window>,
+  child: bool,
 }
 
 impl<'a> WebViewBuilder<'a> {
   /// Create [`WebViewBuilder`] from provided [`Window`].
   pub fn new(window: Window) -> Result<Self> {
+    Ok(Self::with_window(Rc::new(window), false))
+  }
+
+  /// Create [`WebViewBuilder`] for a webview in a hidden window of 800x600 logical pixels, e.g. to
+  /// load pages, evaluate scripts and take snapshots in integration tests. See [`WebView::window`]
+  /// to resize it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** A display server is still required, e.g. `Xvfb` on CI.
+  /// - **Windows:** WebView2 may throttle the timers and animations of the pages in a hidden window.
+  pub fn new_headless<T: 'static>(event_loop: &EventLoopWindowTarget<T>) -> Result<Self> {
+    let window = WindowBuilder::new()
+      .with_visible(false)
+      .with_decorations(false)
+      .with_inner_size(crate::application::dpi::LogicalSize::new(800, 600))
+      .build(event_loop)?;
+    Ok(Self::with_window(Rc::new(window), false))
+  }
+
+  /// Create [`WebViewBuilder`] for a webview embedded in a window of another toolkit, e.g. a
+  /// window of winit, egui or a game engine, from its raw handle.
+  ///
+  /// The webview fills a borderless child window of `parent`, of the size of its client area when
+  /// built. Resize it with [`WebView::window`] to follow `parent`. Dropping the webview closes the
+  /// child window, not `parent`.
+  ///
+  /// The webview is built by the same backend as the webviews of [`Self::new`], so all the
+  /// attributes of the builder apply, e.g. the custom protocols, the IPC handler, the download
+  /// and navigation handlers. To add a webview to a window of its own webview instead, see
+  /// [`Self::new_as_child`].
+  ///
+  /// # Examples
+  ///
+  /// ```no_run
+  /// use wry::{
+  ///   application::{event_loop::EventLoop, window::WindowBuilder},
+  ///   webview::WebViewBuilder,
+  /// };
+  ///
+  /// let event_loop = EventLoop::new();
+  /// // Any window with a raw handle, usually the one of another toolkit.
+  /// let parent = WindowBuilder::new().build(&event_loop).unwrap();
+  /// let webview = WebViewBuilder::new_in_raw_window(&event_loop, &parent)
+  ///   .unwrap()
+  ///   .with_custom_protocol("app".into(), |_request| {
+  ///     let html = r#"<button onclick="window.ipc.postMessage('hi')">Hi</button>"#;
+  ///     http::Response::builder()
+  ///       .body(html.as_bytes().to_vec().into())
+  ///       .unwrap()
+  ///   })
+  ///   .with_url("app://localhost/")
+  ///   .unwrap()
+  ///   .with_ipc_handler(|_window, message| println!("{message}"))
+  ///   .with_navigation_handler(|url| url.starts_with("app:"))
+  ///   .with_download_started_handler(|_url, _path| false)
+  ///   .build()
+  ///   .unwrap();
+  /// ```
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The child window has the `WS_CHILD` style, it is confined to the client area of
+  ///   `parent`. The WebView2 controller is created for the child window, like for the windows of
+  ///   [`Self::new`].
+  /// - **macOS:** The child window is attached above the window of `parent` and moves with it.
+  /// - **Linux / iOS / Android:** Unsupported, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported). On Linux, see
+  ///   `WebViewBuilderExtUnix::build_in_container` for the gtk containers.
+  pub fn new_in_raw_window<T: 'static>(
+    event_loop: &EventLoopWindowTarget<T>,
+    parent: &impl raw_window_handle::HasRawWindowHandle,
+  ) -> Result<Self> {
+    let window = raw_window::build_child_window(event_loop, parent)?;
+    Ok(Self::with_window(Rc::new(window), false))
+  }
+
+  /// Create [`WebViewBuilder`] for another webview in the window of `parent`, e.g. to show an
+  /// editor and its preview side by side.
+  ///
+  /// The webview is placed above the other webviews of the window, at the
+  /// [`Self::with_bounds`]. See [`WebView::set_bounds`], [`WebView::bring_to_front`] and
+  /// [`WebView::send_to_back`] to lay it out later. Dropping it removes it from the window, while
+  /// dropping the webview built with [`Self::new`] closes the window.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The WebView2 controller is hosted in a child window of the window, with all
+  ///   the attributes of the builder like the webviews of [`Self::new`].
+  /// - **Linux:** The child webviews are stacked above the webview built with [`Self::new`].
+  /// - **Android:** Unsupported, building the webview fails with
+  ///   [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn new_as_child(parent: &WebView) -> Result<Self> {
+    Ok(Self::with_window(parent.window.clone(), true))
+  }
+
+  fn with_window(window: Rc<Window>, child: bool) -> Self {
     let webview = WebViewAttributes::default();
     let web_context = None;
     #[allow(clippy::default_constructed_unit_structs)]
     let platform_specific = PlatformSpecificWebViewAttributes::default();
 
-    Ok(Self {
+    Self {
       webview,
       web_context,
       window,
+      child,
       platform_specific,
-    })
+    }
+  }
+
+  /// Set the position and size of a webview built with [`Self::new_as_child`], relative to the
+  /// top left corner of the client area of the window. It fills the window by default.
+  pub fn with_bounds(mut self, position: PhysicalPosition<i32>, size: PhysicalSize<u32>) -> Self {
+    self.webview.bounds = Some((position, size));
+    self
+  }
+
+  /// Set how a webview built with [`Self::new_as_child`] follows the size of its window, e.g.
+  /// [`AutoResizePolicy::Anchored`] to keep a sidebar along the right edge of the window.
+  pub fn with_auto_resize(mut self, policy: AutoResizePolicy) -> Self {
+    self.webview.auto_resize = policy;
+    self
   }
 
   /// Indicates whether horizontal swipe gestures trigger backward and forward page navigation.
@@ -404,6 +1069,17 @@ impl<'a> WebViewBuilder<'a> {
     self
   }
 
+  /// Sets whether pressing a link with force touch or a long press shows a preview of its
+  /// destination, which can be confusing in app-like interfaces. Default is `true`.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows / Linux / Android:** Unsupported.
+  pub fn with_allows_link_preview(mut self, allows: bool) -> Self {
+    self.webview.allows_link_preview = allows;
+    self
+  }
+
   /// Sets whether the WebView should be transparent.
   ///
   /// ## Platform-specific:
@@ -429,6 +1105,46 @@ impl<'a> WebViewBuilder<'a> {
     self
   }
 
+  /// Set the color of the area revealed when the page is scrolled past its edges, e.g. to match a
+  /// dark theme instead of flashing white during the rubber-band effect.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Requires macOS 12+ / iOS 15+.
+  /// - **Windows / Linux / Android**: Unsupported, the pages aren't scrolled past their edges.
+  pub fn with_under_page_background_color(mut self, color: RGBA) -> Self {
+    self.webview.under_page_background_color = Some(color);
+    self
+  }
+
+  /// Set whether the page bounces when it is scrolled past its edges, e.g. to disable the
+  /// rubber-band effect in kiosk and app-like interfaces. Default is `true`.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: Disabled with the `overscroll-behavior` CSS property of the page, set by an
+  ///   initialization script.
+  /// - **iOS**: Also sets the `bounces` property of the scroll view.
+  /// - **Android**: Also sets the `overScrollMode` of the webview, since initialization scripts
+  ///   only run on custom protocol URLs.
+  pub fn with_elastic_overscroll(mut self, elastic: bool) -> Self {
+    self.webview.elastic_overscroll = elastic;
+    self
+  }
+
+  /// Set whether the scrolling with the mouse wheel and the keyboard is animated, e.g. to match the
+  /// scrolling of the rest of the app. The engine default is kept if not set.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: Sets the `--enable-smooth-scrolling` or `--disable-smooth-scrolling` browser
+  ///   argument, ignored on the webviews sharing the environment of a previous one.
+  /// - **macOS / iOS / Android**: Unsupported, the scrolling follows the system.
+  pub fn with_smooth_scrolling(mut self, smooth: bool) -> Self {
+    self.webview.smooth_scrolling = Some(smooth);
+    self
+  }
+
   /// Sets whether the WebView should be transparent.
   pub fn with_visible(mut self, visible: bool) -> Self {
     self.webview.visible = visible;
@@ -441,10 +1157,28 @@ impl<'a> WebViewBuilder<'a> {
     self
   }
 
+  /// Sets the media autoplay policy. It overrides [`Self::with_autoplay`].
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows / macOS / iOS / Android:** [`AutoplayPolicy::Allowlist`] is not supported per
+  ///   origin and behaves like [`AutoplayPolicy::RequireUserGesture`].
+  /// - **Windows:** Sets the `--autoplay-policy` browser argument.
+  /// - **Android:** [`AutoplayPolicy::AllowMuted`] behaves like [`AutoplayPolicy::RequireUserGesture`].
+  pub fn with_autoplay_policy(mut self, policy: AutoplayPolicy) -> Self {
+    self.webview.autoplay_policy = Some(policy);
+    self
+  }
+
   /// Initialize javascript code when loading new pages. When webview load a new page, this
   /// initialization code will be executed. It is guaranteed that code is executed before
   /// `window.onload`.
   ///
+  /// The features of the builder tracking or intercepting the pages, like
+  /// [`Self::with_scroll_handler`], are implemented with initialization scripts too, reporting
+  /// through the IPC channel, and share their platform limits: on Android, they only apply to the
+  /// pages of the custom protocols.
+  ///
   /// ## Platform-specific
   ///
   /// - **Android:** The Android WebView does not provide an API for initialization scripts,
@@ -456,6 +1190,52 @@ impl<'a> WebViewBuilder<'a> {
     self
   }
 
+  /// Initialize javascript code when loading new pages, running it at the given `time`, e.g.
+  /// [`InjectionTime::DocumentEnd`] for scripts needing the DOM of the page.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android:** Same as [`Self::with_initialization_script`], only implemented on custom
+  ///   protocol URLs.
+  pub fn with_initialization_script_at(self, js: &str, time: InjectionTime) -> Self {
+    self.with_init_script(InitScript::new(js).with_injection_time(time))
+  }
+
+  /// Initialize javascript code when loading new pages, with the injection options of `script`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The script is injected in every frame and checks if it runs in the main
+  ///   frame when [`InitScript::for_main_frame_only`] is set.
+  /// - **Android:** Same as [`Self::with_initialization_script`], only implemented on custom
+  ///   protocol URLs.
+  pub fn with_init_script(mut self, script: InitScript) -> Self {
+    if !script.js.is_empty() {
+      self.webview.init_scripts.push(script);
+    }
+    self
+  }
+
+  /// Initialize javascript code in the isolated JavaScript `world` when loading new pages, so the
+  /// page scripts can't observe or tamper with the objects it defines. Each world has its own
+  /// globals and shares the DOM of the page, and `window.ipc` is available in it. Use
+  /// [`WebView::evaluate_script_in_world`] to evaluate scripts in the same world later on.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS:** Requires macOS 11+ / iOS 14+.
+  /// - **Windows / Android:** Unsupported, building the webview returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn with_isolated_initialization_script(mut self, world: &str, js: &str) -> Self {
+    if !js.is_empty() {
+      self
+        .webview
+        .isolated_initialization_scripts
+        .push((world.to_string(), js.to_string()));
+    }
+    self
+  }
+
   /// Register custom file loading protocols with pairs of scheme uri string and a handling
   /// closure.
   ///
@@ -537,16 +1317,147 @@ impl<'a> WebViewBuilder<'a> {
     self
   }
 
-  /// Set the IPC handler to receive the message from Javascript on webview to host Rust code.
-  /// The message sent from webview should call `window.ipc.postMessage("insert_message_here");`.
-  pub fn with_ipc_handler<F>(mut self, handler: F) -> Self
+  /// Same as [`Self::with_asynchronous_custom_protocol`], but the body of the request is read by
+  /// the handler as it is uploaded, instead of being read in memory before calling it, e.g. to
+  /// write large uploads to a file. The handler is called on the main thread, move the
+  /// [`RequestBody`] to another thread to read it there without blocking the main thread.
+  ///
+  /// The requests of these protocols aren't reported to the [`ProtocolLogger`].
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux**: The body is only available with the `linux-body` feature flag, and is empty
+  ///   otherwise. Its chunks are read by the main loop, which runs while the main thread waits for
+  ///   them if the body is read there.
+  /// - **Android**: The body is always empty, the Android webview doesn't expose it.
+  #[cfg(feature = "protocol")]
+  pub fn with_streaming_custom_protocol<F>(mut self, name: String, handler: F) -> Self
   where
-    F: Fn(&Window, String) + 'static,
+    F: Fn(Request<RequestBody>, RequestAsyncResponder) + 'static,
   {
-    self.webview.ipc_handler = Some(Box::new(handler));
     self
-  }
-
+      .webview
+      .streaming_custom_protocols
+      .push((name, Box::new(handler)));
+    self
+  }
+
+  /// Register a custom protocol whose requests are dispatched to different handlers based on the
+  /// host of their URL, see [`CustomProtocolRouter`].
+  ///
+  /// The same origin rules as [`Self::with_custom_protocol`] apply, each host being its own origin.
+  #[cfg(feature = "protocol")]
+  pub fn with_custom_protocol_router(self, name: String, router: CustomProtocolRouter) -> Self {
+    let scheme = name.clone();
+    self.with_asynchronous_custom_protocol(name, move |request, responder| {
+      router.dispatch(&scheme, request, responder)
+    })
+  }
+
+  /// Allow the pages of the origins of `policy`, like the other custom protocols of the webview,
+  /// to `fetch` the custom protocol `name`: its CORS preflight requests are answered, and the CORS
+  /// headers allowing the requests of these origins are added to its responses, unless its
+  /// handler sets them. See [`CorsPolicy`].
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux**: The scheme is also registered as CORS enabled, since WebKitGTK blocks the
+  ///   requests between custom schemes otherwise.
+  #[cfg(feature = "protocol")]
+  pub fn with_relaxed_cors(mut self, name: &str, policy: CorsPolicy) -> Self {
+    self
+      .webview
+      .relaxed_cors_protocols
+      .push((name.to_string(), policy));
+    self
+  }
+
+  /// Set which referrer is sent with the requests of the pages, e.g. so the third-party content
+  /// they embed doesn't see the URLs of the custom protocols.
+  ///
+  /// The `Referrer-Policy` header is added to the responses of the custom protocols, unless their
+  /// handler sets it, and a page can still set another policy on its elements.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: The referrer of all the requests is replaced by the engine.
+  /// - **macOS / iOS / Linux / Android**: Only applied to the pages of the custom protocols, the
+  ///   other pages follow their own policy.
+  pub fn with_referrer_policy(mut self, policy: ReferrerPolicy) -> Self {
+    self.webview.referrer_policy = Some(policy);
+    self
+  }
+
+  /// Register a custom protocol serving embedded assets, see [`AssetProtocol`].
+  ///
+  /// The same origin rules as [`Self::with_custom_protocol`] apply.
+  #[cfg(feature = "protocol")]
+  pub fn with_asset_protocol(self, name: String, assets: AssetProtocol) -> Self {
+    self.with_custom_protocol(name, move |request| assets.respond(&request))
+  }
+
+  /// Register a custom protocol whose requests are served by another process listening on a
+  /// local IPC endpoint, so helper processes can serve content without opening TCP ports.
+  ///
+  /// `path` is a unix domain socket path on Linux, macOS, iOS and Android, and a named pipe path
+  /// (`\\.\pipe\<name>`) on Windows.
+  ///
+  /// Each request is sent on a new connection as an HTTP/1.1 message with an absolute-form target,
+  /// e.g. `GET wry://localhost/index.html HTTP/1.1`, a `Content-Length` header and a
+  /// `Connection: close` header. The endpoint must reply with an HTTP/1.1 response, either with a
+  /// `Content-Length` header, chunked, or by closing the connection after the body. Connection
+  /// errors resolve the request with `502 Bad Gateway`, and an endpoint silent for 30 seconds with
+  /// `504 Gateway Timeout`.
+  #[cfg(feature = "protocol")]
+  pub fn with_local_socket_protocol(self, name: String, path: impl Into<PathBuf>) -> Self {
+    let path = path.into();
+    self.with_asynchronous_custom_protocol(name, move |request, responder| {
+      let path = path.clone();
+      std::thread::spawn(move || responder.respond(protocol_bridge::forward(&path, request)));
+    })
+  }
+
+  /// Register a custom protocol proxying its requests to the development server listening on
+  /// `http://localhost:<port>`, so the development builds keep the origin of the custom protocol,
+  /// with its storage and content security policy, instead of the server's.
+  ///
+  /// The path and query of the URLs are forwarded, e.g. `wry://localhost/src/main.ts` is fetched
+  /// from `http://localhost:<port>/src/main.ts`. The WebSockets opened by the page on its own host,
+  /// like the hot module replacement ones, connect to `ws://localhost:<port>` instead, since the
+  /// custom protocols can't carry them. Connection errors resolve the requests with
+  /// `502 Bad Gateway`.
+  #[cfg(feature = "protocol")]
+  pub fn with_dev_server_proxy(self, name: String, port: u16) -> Self {
+    self
+      .with_initialization_script(&protocol_bridge::dev_server_websocket_script(port))
+      .with_asynchronous_custom_protocol(name, move |request, responder| {
+        std::thread::spawn(move || {
+          responder.respond(protocol_bridge::forward_to_dev_server(port, request))
+        });
+      })
+  }
+
+  /// Log the method, URL, status, duration and body sizes of the requests served by the custom
+  /// protocols, with the sensitive headers redacted. See [`ProtocolLogger`].
+  ///
+  /// This applies to all the custom protocols of the webview, whether they are registered before or
+  /// after this call.
+  #[cfg(feature = "protocol")]
+  pub fn with_protocol_logger(mut self, logger: ProtocolLogger) -> Self {
+    self.webview.protocol_logger = Some(logger);
+    self
+  }
+
+  /// Set the IPC handler to receive the message from Javascript on webview to host Rust code.
+  /// The message sent from webview should call `window.ipc.postMessage("insert_message_here");`.
+  pub fn with_ipc_handler<F>(mut self, handler: F) -> Self
+  where
+    F: Fn(&Window, String) + 'static,
+  {
+    self.webview.ipc_handler = Some(Box::new(handler));
+    self
+  }
+
   /// Set a handler closure to process incoming [`FileDropEvent`] of the webview.
   ///
   /// # Blocking OS Default Behavior
@@ -571,6 +1482,33 @@ impl<'a> WebViewBuilder<'a> {
     Ok(self)
   }
 
+  /// Add `headers` to all the requests of the webview, e.g. an authentication token or a tenant
+  /// id, replacing the headers with the same name set by the page.
+  ///
+  /// The headers of [`Self::with_url_and_headers`] and [`WebView::load_url_with_headers`] replace
+  /// the default ones with the same name.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS / Linux / Android**: The requests aren't intercepted, the headers are only
+  ///   added to the navigations started with [`WebView::load_url`] and
+  ///   [`WebView::load_url_with_headers`], and to the initial one.
+  pub fn with_default_headers(mut self, headers: http::HeaderMap) -> Self {
+    self.webview.default_headers = Some(headers);
+    self
+  }
+
+  /// Tell the websites that the user opts out of being tracked and of the sale of their data, with
+  /// the `DNT: 1` and `Sec-GPC: 1` headers and the `navigator.doNotTrack` and
+  /// `navigator.globalPrivacyControl` properties.
+  ///
+  /// The headers are sent like the [default headers](Self::with_default_headers), and replace
+  /// them.
+  pub fn with_privacy_signals(mut self, enabled: bool) -> Self {
+    self.webview.privacy_signals = enabled;
+    self
+  }
+
   /// Load the provided URL when the builder calling [`WebViewBuilder::build`] to create the
   /// [`WebView`]. The provided URL must be valid.
   pub fn with_url(mut self, url: &str) -> Result<Self> {
@@ -584,7 +1522,8 @@ impl<'a> WebViewBuilder<'a> {
   ///
   /// # Warning
   ///
-  /// The Page loaded from html string will have `null` origin.
+  /// The Page loaded from html string will have `null` origin, unless an origin is given with
+  /// [`WebViewBuilder::with_html_origin`].
   ///
   /// ## PLatform-specific:
   ///
@@ -594,6 +1533,18 @@ impl<'a> WebViewBuilder<'a> {
     Ok(self)
   }
 
+  /// The URL the page loaded from the HTML string is associated with, e.g. `https://app.example/`
+  /// or `wry://localhost/` for a custom protocol. The page gets the origin of this URL, so it
+  /// can use `localStorage` and cookies, and its relative URLs are resolved against it.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows:** The navigation to this URL is intercepted to answer with the HTML string.
+  pub fn with_html_origin(mut self, origin: &str) -> Result<Self> {
+    self.webview.html_origin = Some(Url::parse(origin)?);
+    Ok(self)
+  }
+
   /// Set the web context that can share with multiple [`WebView`]s.
   pub fn with_web_context(mut self, web_context: &'a mut WebContext) -> Self {
     self.web_context = Some(web_context);
@@ -606,6 +1557,19 @@ impl<'a> WebViewBuilder<'a> {
     self
   }
 
+  /// Append an identification to the user-agent, e.g. `MyApp/1.2`, instead of replacing it.
+  ///
+  /// It is separated from the user-agent by a space, and is appended to the platform default
+  /// user-agent, or to the one given to [`Self::with_user_agent`] if set.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Sets the `applicationNameForUserAgent` of the webview configuration.
+  pub fn with_user_agent_suffix(mut self, suffix: &str) -> Self {
+    self.webview.user_agent_suffix = Some(suffix.to_string());
+    self
+  }
+
   /// Enable or disable web inspector which is usually called dev tool.
   ///
   /// Note this only enables dev tool to the webview. To open it, you can call
@@ -613,8 +1577,9 @@ impl<'a> WebViewBuilder<'a> {
   ///
   /// ## Platform-specific
   ///
-  /// - macOS: This will call private functions on **macOS**. It's still enabled if set in **debug** build on mac,
-  /// but requires `devtools` feature flag to actually enable it in **release** build.
+  /// - macOS: Uses the public `inspectable` property on macOS 13.3+. Older macOS versions and the
+  ///   [`WebView::open_devtools`] family of methods call private functions, which are only
+  ///   enabled in **debug** build or with the `devtools` feature flag.
   /// - Android: Open `chrome://inspect/#devices` in Chrome to get the devtools window. Wry's `WebView` devtools API isn't supported on Android.
   /// - iOS: Open Safari > Develop > [Your Device Name] > [Your WebView] to get the devtools window.
   pub fn with_devtools(mut self, devtools: bool) -> Self {
@@ -622,6 +1587,35 @@ impl<'a> WebViewBuilder<'a> {
     self
   }
 
+  /// Decide at runtime whether the web inspector is available, e.g. from an environment variable
+  /// or a host approval callback. The policy is resolved when the webview is built and overrides
+  /// [`Self::with_devtools`].
+  ///
+  /// See [`Self::with_devtools`] for the platform-specific notes.
+  pub fn with_devtools_policy(mut self, policy: DevtoolsPolicy) -> Self {
+    self.webview.devtools_policy = Some(policy);
+    self
+  }
+
+  /// Let external debuggers, like the browser devtools or Playwright, attach to the webview through
+  /// the remote inspector of the engine listening on `port`. This also enables the devtools.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: Passes `--remote-debugging-port` to the browser process, open
+  ///   `http://localhost:<port>` or `edge://inspect` to attach. Ignored when
+  ///   [`WebViewBuilderExtWindows::with_additional_browser_args`] is used, and only applied by
+  ///   the first webview of the browser process.
+  /// - **Linux**: Starts the WebKit inspector HTTP server on `127.0.0.1:<port>` through the
+  ///   `WEBKIT_INSPECTOR_HTTP_SERVER` environment variable, which is only read when the first web
+  ///   context of the process is created, and requires WebKitGTK 2.32+.
+  /// - **macOS / iOS**: The port is ignored, attach from Safari > Develop.
+  /// - **Android**: The port is ignored, attach from `chrome://inspect/#devices`.
+  pub fn with_remote_debugging_port(mut self, port: u16) -> Self {
+    self.webview.remote_debugging_port = Some(port);
+    self
+  }
+
   /// Whether page zooming by hotkeys or gestures is enabled
   ///
   /// ## Platform-specific
@@ -647,6 +1641,12 @@ impl<'a> WebViewBuilder<'a> {
   /// second is a mutable `PathBuf` reference that (possibly) represents where the file will be downloaded to. The latter
   /// parameter can be used to set the download location by assigning a new path to it - the assigned path _must_ be
   /// absolute. The closure returns a `bool` to allow or deny the download.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android**: The file is downloaded by the system download manager, to the `Download`
+  ///   directory of the app external files by default. The path must be a location the download
+  ///   manager can write to.
   pub fn with_download_started_handler(
     mut self,
     started_handler: impl FnMut(String, &mut PathBuf) -> bool + 'static,
@@ -676,6 +1676,21 @@ impl<'a> WebViewBuilder<'a> {
     self
   }
 
+  /// Sets whether the PDFs are displayed by the viewer of the engine, enabled by default.
+  ///
+  /// When disabled, the PDFs the pages navigate to are downloaded instead, and handed to
+  /// [`Self::with_download_started_handler`] so the application can open them in its own viewer.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Requires macOS 11.3+ or iOS 14.5+.
+  /// - **Windows**: Unsupported, the PDFs are always displayed.
+  /// - **Android**: The PDFs are always downloaded, the engine has no PDF viewer.
+  pub fn with_pdf_viewer(mut self, enabled: bool) -> Self {
+    self.webview.pdf_viewer = enabled;
+    self
+  }
+
   /// Enables clipboard access for the page rendered on **Linux** and **Windows**.
   ///
   /// macOS doesn't provide such method and is always enabled by default. But you still need to add menu
@@ -685,6 +1700,59 @@ impl<'a> WebViewBuilder<'a> {
     self
   }
 
+  /// Sets whether the spelling of the text fields and the editable content of the pages is
+  /// checked, instead of the default of the engine, e.g. for a preference of an editor.
+  ///
+  /// The elements setting their `spellcheck` attribute, and their children, keep it.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The attribute of the root element of the pages is set by an
+  ///   [initialization script](Self::with_initialization_script).
+  /// - **Linux**: Enables the spell checker of the [`WebContext`], for all its webviews.
+  /// - **macOS**: Sets "Check Spelling While Typing" of the Edit menu, for all the webviews of the
+  ///   app.
+  pub fn with_spellcheck(mut self, enabled: bool) -> Self {
+    self.webview.spellcheck = Some(enabled);
+    self
+  }
+
+  /// Sets the languages of the spell checker, e.g. `["en_US", "fr_FR"]`.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux**: Sets the languages of the spell checker of the [`WebContext`], for all its
+  ///   webviews, with the dictionaries installed for Enchant.
+  /// - **macOS / Windows / iOS / Android**: Unsupported, the languages of the system are used.
+  pub fn with_spellcheck_languages(mut self, languages: &[&str]) -> Self {
+    self.webview.spellcheck_languages = languages
+      .iter()
+      .map(|language| language.to_string())
+      .collect();
+    self
+  }
+
+  /// Sets the automatic corrections, capitalization and substitutions of the text typed in the
+  /// pages, e.g. to disable the smart quotes which break the code typed in an embedded editor.
+  ///
+  /// The autocorrection and the capitalization set the `autocorrect` and `autocapitalize`
+  /// attributes of the focused elements which don't set their own.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS**: The autocorrection and the substitutions set the items of the "Substitutions"
+  ///   and "Spelling and Grammar" menus, for all the webviews of the app. The capitalization is
+  ///   unsupported.
+  /// - **iOS**: The smart quotes, dashes and text replacements are unsupported.
+  /// - **Windows / Linux**: Only the capitalization of the virtual keyboards is applied, the
+  ///   engines don't change the typed text.
+  /// - **Android**: Only the capitalization of the keyboard is applied, by an
+  ///   [initialization script](Self::with_initialization_script).
+  pub fn with_text_input_settings(mut self, settings: TextInputSettings) -> Self {
+    self.webview.text_input_settings = settings;
+    self
+  }
+
   /// Set a new window request handler to decide if incoming url is allowed to be opened.
   ///
   /// The closure takes a `String` parameter as url and return `bool` to determine if the url can be
@@ -698,11 +1766,110 @@ impl<'a> WebViewBuilder<'a> {
     self
   }
 
-  /// Sets whether clicking an inactive window also clicks through to the webview. Default is `false`.
+  /// Set a handler deciding how to open the new windows requested by the page, e.g. to load
+  /// `window.open` popups in webviews managed by the application.
+  ///
+  /// To open the page in a new webview, build it with [`NewWindowRequest::opener`] and respond with
+  /// [`NewWindowResponse::Create`]:
+  ///
+  /// ```no_run
+  /// # use std::{cell::RefCell, rc::Rc};
+  /// # use wry::{application::window::Window, webview::{NewWindowResponse, WebView, WebViewBuilder}};
+  /// # fn create_window() -> Window { unimplemented!() }
+  /// let popups: Rc<RefCell<Vec<WebView>>> = Default::default();
+  /// # let window = create_window();
+  /// let _webview = WebViewBuilder::new(window)
+  ///   .unwrap()
+  ///   .with_new_window_handler(move |request| {
+  ///     let popup = WebViewBuilder::new(create_window())
+  ///       .unwrap()
+  ///       .with_new_window_opener(request.opener())
+  ///       .build();
+  ///     match popup {
+  ///       Ok(popup) => {
+  ///         popups.borrow_mut().push(popup);
+  ///         NewWindowResponse::Create
+  ///       }
+  ///       Err(_) => NewWindowResponse::Deny,
+  ///     }
+  ///   })
+  ///   .build();
+  /// ```
+  ///
+  /// The handler is called once [`Self::with_new_window_req_handler`] allows the request.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android:** Unsupported, new windows are never opened.
+  pub fn with_new_window_handler(
+    mut self,
+    handler: impl Fn(NewWindowRequest) -> NewWindowResponse + 'static,
+  ) -> Self {
+    self.webview.new_window_handler = Some(Box::new(handler));
+    self
+  }
+
+  /// Build the webview of a new window requested by another webview, with the opener of the
+  /// [`NewWindowRequest`]. The requested page is loaded instead of the URL or HTML of the builder.
+  ///
+  /// The webview must be built in the new window handler, before it responds with
+  /// [`NewWindowResponse::Create`]. It stays related to its opener: the `window.open` call of the
+  /// page returns a proxy to it, so `window.opener.postMessage` and `close()` work as in a browser.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The webview shares the web process and the web context of its opener.
+  /// - **macOS / iOS:** The webview shares the custom protocols and the website data store of its
+  ///   opener, which must outlive it.
+  /// - **Windows:** The webview shares the environment of its opener and its incognito mode.
+  /// - **Android:** Unsupported.
+  pub fn with_new_window_opener(mut self, opener: NewWindowOpener) -> Self {
+    self.webview.new_window_opener = Some(opener);
+    self
+  }
+
+  /// Open the new windows requested for external links, e.g. `target="_blank"` links to other
+  /// websites, in the system browser instead of denying them. Links are external when they have
+  /// an http or https URL whose host isn't allowed with [`Self::with_internal_link_hosts`].
+  ///
+  /// The new window handlers are called for the other links, and external links are still denied
+  /// by [`Self::with_new_window_req_handler`] and [`Self::with_navigation_filter`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android:** Unsupported, the new windows are denied.
+  pub fn with_external_links_in_browser(mut self, enabled: bool) -> Self {
+    self.webview.external_links_in_browser = enabled;
+    self
+  }
+
+  /// Set the hosts of the links which stay in the webview when
+  /// [`Self::with_external_links_in_browser`] is enabled, e.g. `app.example.com`, or
+  /// `*.example.com` for `example.com` and its subdomains.
+  pub fn with_internal_link_hosts(mut self, hosts: &[&str]) -> Self {
+    self.webview.internal_link_hosts = hosts.iter().map(|host| host.to_string()).collect();
+    self
+  }
+
+  /// Restrict the URLs the webview may navigate to, or open in a new window.
+  ///
+  /// The filter is checked before the navigation and new window handlers, which are not called for
+  /// the denied URLs.
+  pub fn with_navigation_filter(mut self, filter: NavigationFilter) -> Self {
+    self.webview.navigation_filter = Some(filter);
+    self
+  }
+
+  /// Sets whether clicking an inactive window also clicks through to the webview. Default is
+  /// `false` on macOS and `true` on the other platforms, which always delivered that click.
+  ///
+  /// When disabled, the click which activates the window is not delivered to the page.
   ///
   /// ## Platform-specific
   ///
-  /// This configuration only impacts macOS.
+  /// - **Linux**: Best effort, depends on the window manager activating the window after the
+  ///   click is received.
+  /// - **Android / iOS:** Unsupported.
   pub fn with_accept_first_mouse(mut self, accept_first_mouse: bool) -> Self {
     self.webview.accept_first_mouse = accept_first_mouse;
     self
@@ -717,6 +1884,18 @@ impl<'a> WebViewBuilder<'a> {
     self
   }
 
+  /// Set a handler closure called when the page asks to close its window with `window.close()`,
+  /// or when its opener closes it, e.g. to drop the webview of a [`NewWindowRequest`] once an
+  /// authentication popup is done. The engines only allow it in windows opened by a script.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android:** Unsupported.
+  pub fn with_close_requested_handler(mut self, callback: impl Fn(&Window) + 'static) -> Self {
+    self.webview.close_requested_handler = Some(Box::new(callback));
+    self
+  }
+
   /// Run the WebView with incognito mode. Note that WebContext will be ingored if incognito is
   /// enabled.
   ///
@@ -753,87 +1932,571 @@ impl<'a> WebViewBuilder<'a> {
   ///
   /// ## Platform-specific:
   ///
-  /// - **macOS / Android / iOS:** Unsupported.
+  /// - **Android / iOS:** Unsupported.
   pub fn with_focused(mut self, focused: bool) -> Self {
     self.webview.focused = focused;
     self
   }
 
-  /// Consume the builder and create the [`WebView`].
+  /// Set a handler called with `true` when the webview gains the keyboard focus, and `false` when
+  /// it loses it, e.g. to keep the focus of the native widgets of the window in sync.
   ///
-  /// Platform-specific behavior:
+  /// ## Platform-specific:
   ///
-  /// - **Unix:** This method must be called in a gtk thread. Usually this means it should be
-  /// called in the same thread with the [`EventLoop`] you create.
+  /// - **Android / iOS:** Unsupported.
+  pub fn with_focus_changed_handler(mut self, handler: impl Fn(bool) + 'static) -> Self {
+    self.webview.focus_changed_handler = Some(Box::new(handler));
+    self
+  }
+
+  /// Force the values of the `prefers-reduced-motion` and `prefers-contrast` media features seen
+  /// by the page, regardless of the operating system accessibility settings.
   ///
-  /// [`EventLoop`]: crate::application::event_loop::EventLoop
-  pub fn build(self) -> Result<WebView> {
-    let window = Rc::new(self.window);
-    let webview = InnerWebView::new(
-      window.clone(),
-      self.webview,
-      self.platform_specific,
-      self.web_context,
-    )?;
-    Ok(WebView { window, webview })
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / Linux / iOS / Android:** The engine has no native override, so only
+  ///   `window.matchMedia` reflects the forced values, set by an
+  ///   [initialization script](Self::with_initialization_script), CSS `@media` rules still follow
+  ///   the system.
+  pub fn with_media_feature_overrides(mut self, overrides: MediaFeatureOverrides) -> Self {
+    self.webview.media_feature_overrides = Some(overrides);
+    self
   }
-}
 
-#[cfg(windows)]
-pub trait WebViewBuilderExtWindows {
-  /// Pass additional args to Webview2 upon creating the webview.
+  /// Set a handler called with the key presses received by the webview before the page, e.g. for
+  /// the global shortcuts of the app to work while a text field of the page is focused. The page
+  /// doesn't receive the keys the handler returns [`KeyEventResponse::Handled`] for.
   ///
-  /// ## Warning
+  /// ## Platform-specific:
   ///
-  /// By default wry passes `--disable-features=msWebOOUI,msPdfOOUI,msSmartScreenProtection`
-  /// `--autoplay-policy=no-user-gesture-required` if autoplay is enabled
-  /// and `--proxy-server=<scheme>://<host>:<port>` if a proxy is set.
-  /// so if you use this method, you have to add these arguments yourself if you want to keep the same behavior.
-  fn with_additional_browser_args<S: Into<String>>(self, additional_args: S) -> Self;
+  /// - **Windows:** Only called with the accelerator keys: the keys pressed with Ctrl or Alt, and
+  ///   the function keys. The layout isn't applied to the keys, `Ctrl+Shift+1` is reported as `1`.
+  /// - **Android / iOS:** Unsupported.
+  pub fn with_key_event_handler(
+    mut self,
+    handler: impl Fn(KeyEvent) -> KeyEventResponse + 'static,
+  ) -> Self {
+    self.webview.key_event_handler = Some(Box::new(handler));
+    self
+  }
 
-  /// Determines whether browser-specific accelerator keys are enabled. When this setting is set to
-  /// `false`, it disables all accelerator keys that access features specific to a web browser.
-  /// The default value is `true`. See the following link to know more details.
+  /// Disable built-in accelerators of the engine, e.g. for the app to own `Ctrl+P` or `F5`. Neither
+  /// the engine nor the page receive their keys, the
+  /// [key event handler](Self::with_key_event_handler) still sees them and decides what they do.
   ///
-  /// https://learn.microsoft.com/en-us/microsoft-edge/webview2/reference/winrt/microsoft_web_webview2_core/corewebview2settings#arebrowseracceleratorkeysenabled
-  fn with_browser_accelerator_keys(self, enabled: bool) -> Self;
+  /// ## Platform-specific:
+  ///
+  /// - **Android / iOS:** Unsupported.
+  pub fn with_default_shortcuts_disabled(mut self, shortcuts: &[Shortcut]) -> Self {
+    self.webview.disabled_shortcuts = shortcuts.to_vec();
+    self
+  }
 
-  /// Specifies the theme of webview2. This affects things like `prefers-color-scheme`.
+  /// Set a handler closure to process the change of the webview's audible state.
   ///
-  /// Defaults to [`Theme::Auto`] which will follow the OS defaults.
-  fn with_theme(self, theme: Theme) -> Self;
+  /// The closure takes a `bool` which is `true` when the page starts playing audio and `false`
+  /// when it stops. Muting the webview doesn't change the reported state.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Uses private APIs.
+  /// - **Android**: Unsupported.
+  pub fn with_audio_state_handler(mut self, handler: impl Fn(bool) + 'static) -> Self {
+    self.webview.audio_state_handler = Some(Box::new(handler));
+    self
+  }
 
-  /// Determines whether the custom protocols should use `https://<scheme>.localhost` instead of the default `http://<scheme>.localhost`.
+  /// Restrict WebRTC, e.g. to disable it entirely or to prevent it from leaking the local IP
+  /// addresses.
   ///
-  /// Using a `http` scheme will allow mixed content when trying to fetch `http` endpoints
-  /// and is therefore less secure but will match the behavior of the `<scheme>://localhost` protocols used on macOS and Linux.
+  /// ## Platform-specific:
   ///
-  /// The default value is `false`.
-  fn with_https_scheme(self, enabled: bool) -> Self;
-}
+  /// - **Linux**: WebRTC is disabled by default, setting a policy with [`WebRtcPolicy::enabled`]
+  ///   enables it.
+  /// - **Windows**: Relay only also sets the `--force-webrtc-ip-handling-policy` browser argument.
+  /// - **macOS / iOS / Android**: The restrictions are only enforced by an
+  ///   [initialization script](Self::with_initialization_script).
+  pub fn with_webrtc_policy(mut self, policy: WebRtcPolicy) -> Self {
+    self.webview.webrtc_policy = Some(policy);
+    self
+  }
 
-#[cfg(windows)]
-impl WebViewBuilderExtWindows for WebViewBuilder<'_> {
-  fn with_additional_browser_args<S: Into<String>>(mut self, additional_args: S) -> Self {
-    self.platform_specific.additional_browser_args = Some(additional_args.into());
+  /// Set a handler closure to process the changes of the mouse cursor requested by the page,
+  /// for hosts which draw their own cursor over the webview.
+  ///
+  /// The cursor is computed from the CSS `cursor` property of the element under the mouse. An
+  /// element can override it for itself and its children with a `data-wry-cursor` attribute, e.g.
+  /// `<canvas data-wry-cursor="sword">`, whose value is reported as is.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The cursor is tracked by an [initialization script](Self::with_initialization_script), so only mouse moves
+  ///   update it.
+  pub fn with_cursor_changed_handler(
+    mut self,
+    handler: impl Fn(&Window, PageCursor) + 'static,
+  ) -> Self {
+    self.webview.cursor_changed_handler = Some(Box::new(handler));
     self
   }
 
-  fn with_browser_accelerator_keys(mut self, enabled: bool) -> Self {
-    self.platform_specific.browser_accelerator_keys = enabled;
+  /// Set a handler closure to process the composition of text with an input method in the
+  /// editable elements of the page, for hosts which draw their own windows. The position of the
+  /// composed text lets them move the candidate window next to it with
+  /// [`Window::set_ime_position`], after adding the position of the webview in the window.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The composition is tracked by an [initialization script](Self::with_initialization_script).
+  pub fn with_composition_handler(
+    mut self,
+    handler: impl Fn(&Window, CompositionEvent) + 'static,
+  ) -> Self {
+    self.webview.composition_handler = Some(Box::new(handler));
     self
   }
 
-  fn with_theme(mut self, theme: Theme) -> Self {
-    self.platform_specific.theme = Some(theme);
+  /// Set a handler called when an editable element of the page gets the focus, with its input
+  /// type and its bounds, and when the focus leaves the editable elements, e.g. to show a custom
+  /// on-screen keyboard or to resize the window above it on touch devices.
+  ///
+  /// Moving the focus between editable elements only reports the newly focused one.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The focus is tracked by an [initialization script](Self::with_initialization_script). Only the elements of
+  ///   the top frame are reported.
+  pub fn with_editable_focus_handler(
+    mut self,
+    handler: impl Fn(EditableFocusEvent) + 'static,
+  ) -> Self {
+    self.webview.editable_focus_handler = Some(Box::new(handler));
     self
   }
 
-  fn with_https_scheme(mut self, enabled: bool) -> Self {
-    self.platform_specific.https_scheme = enabled;
+  /// Let the page declare the regions of the webview where the mouse events go through it, by
+  /// calling `window.setIgnoreMouseEvents(regions)` with an array of elements or rectangles, e.g.
+  /// for a transparent overlay to only catch the clicks on its controls. An empty array catches
+  /// all the mouse events again. See [`WebView::set_click_through_regions`].
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows:** Only supported by the webviews built with [`WebViewBuilder::new_as_child`].
+  /// - **iOS / Android:** Unsupported.
+  pub fn with_click_through(mut self, enabled: bool) -> Self {
+    self.webview.click_through = enabled;
     self
   }
-}
+
+  /// Set a handler called with the changes of the scroll position of the page, e.g. to collapse a
+  /// native title bar while the page scrolls down and expand it when it scrolls up.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The position is reported by an [initialization script](Self::with_initialization_script) once per frame
+  ///   while the page scrolls, and when a page is shown.
+  pub fn with_scroll_handler(mut self, handler: impl Fn(ScrollEvent) + 'static) -> Self {
+    self.webview.scroll_handler = Some(Box::new(handler));
+    self
+  }
+
+  /// Whether the page reports its scroll position for [`WebView::scroll_position`], which is
+  /// always the case with [`WebViewBuilder::with_scroll_handler`]. Defaults to `false`, the page
+  /// doesn't run the script tracking its scroll position.
+  pub fn with_scroll_tracking(mut self, enabled: bool) -> Self {
+    self.webview.scroll_tracking = enabled;
+    self
+  }
+
+  /// Set a handler called with the text selected in the page when it changes, `None` once nothing
+  /// is selected, e.g. to enable a native "look up" action.
+  ///
+  /// The selections of the text fields are included, except the password fields, and the ones of
+  /// the focused same-origin frames. Use [`WebView::selected_text`] to read the selection without
+  /// relying on the page.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The selection is reported by an [initialization script](Self::with_initialization_script) once per frame
+  ///   while it changes, and when a page is shown.
+  pub fn with_selection_changed_handler(
+    mut self,
+    handler: impl Fn(Option<String>) + 'static,
+  ) -> Self {
+    self.webview.selection_changed_handler = Some(Box::new(handler));
+    self
+  }
+
+  /// Set a handler called with each subresource loaded by the page, with its status, MIME type and
+  /// timings, e.g. to build a network panel or to detect the failing third-party assets.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The resources are reported by an [initialization script](Self::with_initialization_script) from the resource
+  ///   timing entries of the top frame. The status and the MIME type are only exposed by recent
+  ///   engines.
+  pub fn with_resource_load_handler(
+    mut self,
+    handler: impl Fn(ResourceLoadInfo) + 'static,
+  ) -> Self {
+    self.webview.resource_load_handler = Some(Box::new(handler));
+    self
+  }
+
+  /// Set a handler deciding what to do with each WebSocket connection opened by the pages, since
+  /// they aren't seen by the custom protocols nor the request interception: connect to its URL or
+  /// to another one, e.g. with an authentication token, fail it, or proxy it through the
+  /// application. See [`WebSocketAction`].
+  ///
+  /// The connections are intercepted by an [initialization script](Self::with_initialization_script) replacing the
+  /// `WebSocket` constructor of the pages, the native one connecting once the handler has decided.
+  /// The headers of the connection can't be changed by the pages, a token has to go in its URL or
+  /// a proxied connection.
+  pub fn with_websocket_handler(
+    mut self,
+    handler: impl Fn(WebSocketRequest) -> WebSocketAction + 'static,
+  ) -> Self {
+    self.webview.websocket_handler = Some(Box::new(handler));
+    self
+  }
+
+  /// Set a handler called with the POST forms submitted by the pages, before they are sent,
+  /// deciding whether they are submitted or consumed by the application, e.g. to handle a login
+  /// form with the keychain of the system rather than the network.
+  ///
+  /// The submissions are held by an [initialization script](Self::with_initialization_script), and submitted natively once
+  /// allowed. The forms cancelling their `submit` event, usually submitted by a script, aren't
+  /// seen.
+  pub fn with_form_submission_handler(
+    mut self,
+    handler: impl Fn(FormData) -> FormSubmissionDecision + 'static,
+  ) -> Self {
+    self.webview.form_submission_handler = Some(Box::new(handler));
+    self
+  }
+
+  /// Set a handler called when the pages read or write the clipboard with `navigator.clipboard`,
+  /// deciding whether the access is allowed, denied, or done with another text, e.g. to strip
+  /// the tracking parameters of the copied links or to keep secrets off the clipboard.
+  ///
+  /// The accesses are held by an [initialization script](Self::with_initialization_script), and done natively once allowed.
+  /// The copy and paste of the user, and `document.execCommand`, aren't seen.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The native access happens after the handler has decided, which the engines may
+  ///   reject when the page is no longer handling a user gesture, like without the handler.
+  pub fn with_clipboard_access_handler(
+    mut self,
+    handler: impl Fn(ClipboardRequest) -> ClipboardDecision + 'static,
+  ) -> Self {
+    self.webview.clipboard_access_handler = Some(Box::new(handler));
+    self
+  }
+
+  /// Set a handler called with the URL of the page when it calls `window.print()`, deciding
+  /// whether the engine shows its print dialog or the application handled it, e.g. with
+  /// [`WebView::print_with_options`] or its own dialog.
+  ///
+  /// `window.print()` returns immediately to the page, before the handler has decided.
+  /// [`WebView::print`] still shows the print dialog of the engine.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: Only the calls of the top frame are seen, by an [initialization script](Self::with_initialization_script).
+  /// - **macOS**: WebKit doesn't print the pages calling `window.print()`, like without the
+  ///   handler.
+  pub fn with_print_handler(mut self, handler: impl Fn(String) -> PrintDecision + 'static) -> Self {
+    self.webview.print_handler = Some(Box::new(handler));
+    self
+  }
+
+  /// Serve the files of `folder` on the virtual `host`, e.g. `app.local`, so the pages of a
+  /// bundled folder get their own origin, listed by [`virtual_host_origin`], instead of a `file://`
+  /// one. Mapping several hosts serves several folders.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: Uses `SetVirtualHostNameToFolderMapping`, the pages are served on
+  ///   `https://<host>/`.
+  /// - **Linux / macOS / iOS**: Served by the `wry-host` custom protocol, on `wry-host://<host>/`,
+  ///   which can't be registered with [`WebViewBuilder::with_custom_protocol`] then.
+  /// - **Android**: Unsupported, the mappings are ignored.
+  pub fn with_virtual_host_mapping(mut self, host: &str, folder: impl Into<PathBuf>) -> Self {
+    self
+      .webview
+      .virtual_host_mappings
+      .push((host.to_string(), folder.into()));
+    self
+  }
+
+  /// Consume the builder into a [`LazyWebView`], which only creates the [`WebView`] on its first
+  /// navigation, when its window is shown or when it is accessed. Apps creating many windows up
+  /// front don't pay for the engine startup of the webviews which are never opened.
+  ///
+  /// The builder is validated when the webview is created, which can fail like
+  /// [`WebViewBuilder::build`].
+  pub fn build_lazy(self) -> LazyWebView<'a> {
+    LazyWebView::new(self)
+  }
+
+  /// Consume the builder and create the [`WebView`].
+  ///
+  /// Platform-specific behavior:
+  ///
+  /// - **Unix:** This method must be called in a gtk thread. Usually this means it should be
+  /// called in the same thread with the [`EventLoop`] you create.
+  ///
+  /// [`EventLoop`]: crate::application::event_loop::EventLoop
+  pub fn build(mut self) -> Result<WebView> {
+    if cfg!(any(target_os = "windows", target_os = "android"))
+      && !self.webview.isolated_initialization_scripts.is_empty()
+    {
+      return Err(crate::Error::Unsupported);
+    }
+    if cfg!(any(target_os = "windows", target_os = "android")) {
+      let scripts = std::mem::take(&mut self.webview.init_scripts);
+      self
+        .webview
+        .initialization_scripts
+        .extend(scripts.iter().map(InitScript::to_portable_script));
+    }
+    if let Some(policy) = self.webview.devtools_policy.take() {
+      self.webview.devtools = policy.is_allowed();
+    }
+    if self.webview.remote_debugging_port.is_some() {
+      self.webview.devtools = true;
+    }
+    if self.webview.autoplay_policy.is_none() && self.webview.autoplay {
+      self.webview.autoplay_policy = Some(AutoplayPolicy::AllowAll);
+    }
+    if let Some(js) = self.webview.webrtc_policy.and_then(WebRtcPolicy::to_script) {
+      // Run before the user scripts so they can't grab the unrestricted APIs.
+      self.webview.initialization_scripts.insert(0, js);
+    }
+    privacy_signals::install(&mut self.webview);
+    if !self.webview.elastic_overscroll {
+      self
+        .webview
+        .initialization_scripts
+        .push(overscroll::DISABLE_OVERSCROLL_SCRIPT.to_string());
+    }
+    if matches!(&self.web_context, Some(context) if !context.service_workers_enabled()) {
+      self.webview.initialization_scripts.insert(
+        0,
+        service_worker::DISABLE_SERVICE_WORKERS_SCRIPT.to_string(),
+      );
+    }
+    navigation_filter::install(&mut self.webview);
+    key_event::install(&mut self.webview);
+    new_window::install(&mut self.webview);
+    // The messages of the injected scripts are routed to their handlers, the other ones to the
+    // IPC handler of the app.
+    let mut router = ipc_router::IpcRouter::new();
+    cursor::install(&mut self.webview, &mut router);
+    composition::install(&mut self.webview, &mut router);
+    editable_focus::install(&mut self.webview, &mut router);
+    let script_evaluator = ipc_router::ScriptEvaluator::default();
+    websocket::install(&mut self.webview, &mut router, &script_evaluator);
+    form_submission::install(&mut self.webview, &mut router, &script_evaluator);
+    clipboard::install(&mut self.webview, &mut router, &script_evaluator);
+    print::install(&mut self.webview, &mut router, &script_evaluator);
+    let click_through_setter = click_through::install(&mut self.webview, &mut router);
+    #[cfg(not(target_os = "windows"))]
+    page_load::install(&mut self.webview, &mut router);
+    let scroll_position = scroll::install(&mut self.webview, &mut router);
+    if let Some(enabled) = self.webview.spellcheck {
+      self
+        .webview
+        .initialization_scripts
+        .push(spellcheck::spellcheck_script(enabled));
+    }
+    if let Some(script) = text_input::text_input_script(&self.webview.text_input_settings) {
+      self.webview.initialization_scripts.push(script);
+    }
+    selection::install(&mut self.webview, &mut router);
+    // The webviews of a context built during a HAR capture report their resources for it.
+    let har_recorder = self
+      .web_context
+      .as_deref()
+      .map(WebContext::har_recorder)
+      .filter(har::HarRecorder::is_capturing);
+    resource_load::install(&mut self.webview, &mut router, har_recorder);
+    let calls = function_call::install(&mut self.webview, &mut router);
+    // Dispatch to a handler which can be replaced with `WebView::set_ipc_handler`, cloned out of
+    // the cell so it can replace itself.
+    let ipc_handler: Rc<RefCell<Option<Rc<dyn Fn(&Window, String)>>>> =
+      Rc::new(RefCell::new(self.webview.ipc_handler.take().map(Rc::from)));
+    {
+      let ipc_handler = ipc_handler.clone();
+      self.webview.ipc_handler = Some(router.into_ipc_handler(move |window, message| {
+        let handler = ipc_handler.borrow().clone();
+        if let Some(handler) = handler {
+          handler(window, message)
+        }
+      }));
+    }
+    if cfg!(not(any(target_os = "windows", target_os = "android")))
+      && !self.webview.virtual_host_mappings.is_empty()
+    {
+      let mappings = std::mem::take(&mut self.webview.virtual_host_mappings);
+      self.webview.custom_protocols.push((
+        virtual_host::VIRTUAL_HOST_SCHEME.to_string(),
+        Box::new(virtual_host::protocol_handler(mappings)),
+      ));
+    }
+    cors::install(&mut self.webview);
+    #[cfg(feature = "protocol")]
+    if let Some(logger) = self.webview.protocol_logger.take() {
+      let logger = std::sync::Arc::new(logger);
+      self.webview.custom_protocols = std::mem::take(&mut self.webview.custom_protocols)
+        .into_iter()
+        .map(|(name, handler)| (name, logger.wrap(handler)))
+        .collect();
+    }
+    // The backends serve all the custom protocols as streaming ones.
+    let custom_protocols = std::mem::take(&mut self.webview.custom_protocols);
+    self.webview.streaming_custom_protocols.extend(
+      custom_protocols
+        .into_iter()
+        .map(|(name, handler)| (name, request_body::buffered(handler))),
+    );
+    if let Some(policy) = self.webview.referrer_policy {
+      self.webview.streaming_custom_protocols =
+        std::mem::take(&mut self.webview.streaming_custom_protocols)
+          .into_iter()
+          .map(|(name, handler)| (name, referrer_policy::apply(policy, handler)))
+          .collect();
+    }
+    let default_headers = self.webview.default_headers.clone().unwrap_or_default();
+    if self.webview.url.is_some() && !default_headers.is_empty() {
+      let headers = self.webview.headers.take().unwrap_or_default();
+      self.webview.headers = Some(default_headers::merge(&default_headers, headers));
+    }
+    if self.child && cfg!(target_os = "android") {
+      return Err(crate::Error::Unsupported);
+    }
+    let window = self.window;
+    let background_color = self.webview.background_color;
+    let webview = InnerWebView::new(
+      window.clone(),
+      self.webview,
+      self.platform_specific,
+      self.web_context,
+      self.child,
+    )?;
+    *click_through_setter.borrow_mut() = webview.click_through_setter();
+    *script_evaluator.borrow_mut() = Some(webview.script_evaluator());
+    Ok(WebView {
+      window,
+      webview,
+      child: self.child,
+      calls,
+      ipc_handler,
+      scroll_position,
+      suspended: Cell::new(None),
+      background_color: Cell::new(background_color),
+      default_headers,
+      #[cfg(not(target_os = "windows"))]
+      network_conditions_script: Cell::new(None),
+      _instance: lifecycle::InstanceGuard::webview(),
+    })
+  }
+}
+
+#[cfg(windows)]
+pub trait WebViewBuilderExtWindows {
+  /// Pass additional args to Webview2 upon creating the webview.
+  ///
+  /// ## Warning
+  ///
+  /// By default wry passes `--disable-features=msWebOOUI,msPdfOOUI,msSmartScreenProtection`
+  /// `--autoplay-policy=<policy>` depending on the autoplay policy,
+  /// `--force-webrtc-ip-handling-policy=disable_non_proxied_udp` if the WebRTC policy is relay only
+  /// and `--proxy-server=<scheme>://<host>:<port>` if a proxy is set.
+  /// so if you use this method, you have to add these arguments yourself if you want to keep the same behavior.
+  fn with_additional_browser_args<S: Into<String>>(self, additional_args: S) -> Self;
+
+  /// Determines whether browser-specific accelerator keys are enabled. When this setting is set to
+  /// `false`, it disables all accelerator keys that access features specific to a web browser.
+  /// The default value is `true`. See the following link to know more details.
+  ///
+  /// https://learn.microsoft.com/en-us/microsoft-edge/webview2/reference/winrt/microsoft_web_webview2_core/corewebview2settings#arebrowseracceleratorkeysenabled
+  fn with_browser_accelerator_keys(self, enabled: bool) -> Self;
+
+  /// Specifies the theme of webview2. This affects things like `prefers-color-scheme`.
+  ///
+  /// Defaults to [`Theme::Auto`] which will follow the OS defaults.
+  fn with_theme(self, theme: Theme) -> Self;
+
+  /// Determines whether the custom protocols should use `https://<scheme>.localhost` instead of the default `http://<scheme>.localhost`.
+  ///
+  /// Using a `http` scheme will allow mixed content when trying to fetch `http` endpoints
+  /// and is therefore less secure but will match the behavior of the `<scheme>://localhost` protocols used on macOS and Linux.
+  ///
+  /// The default value is `false`.
+  fn with_https_scheme(self, enabled: bool) -> Self;
+}
+
+#[cfg(windows)]
+impl WebViewBuilderExtWindows for WebViewBuilder<'_> {
+  fn with_additional_browser_args<S: Into<String>>(mut self, additional_args: S) -> Self {
+    self.platform_specific.additional_browser_args = Some(additional_args.into());
+    self
+  }
+
+  fn with_browser_accelerator_keys(mut self, enabled: bool) -> Self {
+    self.platform_specific.browser_accelerator_keys = enabled;
+    self
+  }
+
+  fn with_theme(mut self, theme: Theme) -> Self {
+    self.platform_specific.theme = Some(theme);
+    self
+  }
+
+  fn with_https_scheme(mut self, enabled: bool) -> Self {
+    self.platform_specific.https_scheme = enabled;
+    self
+  }
+}
+
+#[cfg(any(
+  target_os = "linux",
+  target_os = "dragonfly",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd",
+))]
+pub trait WebViewBuilderExtUnix {
+  /// Build the webview in `container` instead of the window of the builder, e.g. a `gtk::Box` of
+  /// a gtk-rs application. A `gtk::Box` packs the webview so it fills the free space.
+  ///
+  /// The window of the builder is still the one passed to the handlers and returned by
+  /// [`WebView::window`], but dropping the webview only removes it from the container. The
+  /// container can belong to another window.
+  fn build_in_container<C: gtk::glib::IsA<gtk::Container>>(self, container: &C) -> Result<WebView>;
+}
+
+#[cfg(any(
+  target_os = "linux",
+  target_os = "dragonfly",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd",
+))]
+impl WebViewBuilderExtUnix for WebViewBuilder<'_> {
+  fn build_in_container<C: gtk::glib::IsA<gtk::Container>>(
+    mut self,
+    container: &C,
+  ) -> Result<WebView> {
+    use gtk::glib::Cast;
+    self.platform_specific.container = Some(container.clone().upcast());
+    // Like a child webview, the webview doesn't own the window.
+    self.child = true;
+    self.build()
+  }
+}
 
 #[cfg(target_os = "android")]
 pub trait WebViewBuilderExtAndroid {
@@ -910,9 +2573,33 @@ impl WebViewBuilderExtAndroid for WebViewBuilder<'_> {
 /// scripts for those who prefer to control fine grained window creation and event handling.
 /// [`WebView`] presents the actual WebView window and let you still able to perform actions
 /// during event handling to it. [`WebView`] also contains the associate [`Window`] with it.
+///
+/// The system webviews only render into their native view, there is no offscreen mode rendering
+/// to a texture or a buffer, see [`Capabilities::offscreen`]. Use
+/// [`WebView::capture_element`] to get an image of the page instead.
 pub struct WebView {
   window: Rc<Window>,
   webview: InnerWebView,
+  calls: Rc<function_call::PendingCalls>,
+  ipc_handler: Rc<RefCell<Option<Rc<dyn Fn(&Window, String)>>>>,
+  // The last scroll position reported by the page.
+  scroll_position: Rc<Cell<(f64, f64)>>,
+  #[cfg_attr(
+    any(target_os = "macos", target_os = "ios", target_os = "android"),
+    allow(dead_code)
+  )]
+  child: bool,
+  // Whether the webview was visible before `suspend`, while suspended.
+  suspended: Cell<Option<bool>>,
+  // The background color restored by `set_transparent(false)`.
+  background_color: Cell<Option<RGBA>>,
+  // Added to the navigations started by the application.
+  default_headers: http::HeaderMap,
+  // The initialization script emulating the network conditions on the next navigations.
+  #[cfg(not(target_os = "windows"))]
+  network_conditions_script: Cell<Option<ScriptId>>,
+  // Dropped last, once the engine resources of the webview are released.
+  _instance: lifecycle::InstanceGuard,
 }
 
 // Signal the Window to drop on Linux and Windows. On mac, we need to handle several unsafe code
@@ -929,7 +2616,12 @@ impl Drop for WebView {
     unsafe {
       use crate::application::platform::unix::WindowExtUnix;
       use gtk::prelude::WidgetExtManual;
-      self.window().gtk_window().destroy();
+      // The webview may have been moved to another window with `reparent`.
+      self.webview.webview.destroy();
+      // The webviews built as children only remove their own view from the window.
+      if !self.child {
+        self.window().gtk_window().destroy();
+      }
     }
   }
 }
@@ -937,6 +2629,10 @@ impl Drop for WebView {
 #[cfg(target_os = "windows")]
 impl Drop for WebView {
   fn drop(&mut self) {
+    // The webviews built as children only remove their own view from the window.
+    if self.child {
+      return;
+    }
     unsafe {
       let _ = DestroyWindow(HWND(self.window.hwnd() as _));
     }
@@ -969,6 +2665,14 @@ impl WebView {
     self.webview.url()
   }
 
+  /// Replace the IPC handler set with [`WebViewBuilder::with_ipc_handler`], or remove it with
+  /// `None` so the messages are ignored.
+  ///
+  /// The previous handler is dropped once it returns, so a handler can replace itself.
+  pub fn set_ipc_handler(&self, handler: Option<Box<dyn Fn(&Window, String)>>) {
+    *self.ipc_handler.borrow_mut() = handler.map(Rc::from);
+  }
+
   /// Evaluate and run javascript code. Must be called on the same thread who created the
   /// [`WebView`]. Use [`EventLoopProxy`] and a custom event to send scripts from other threads.
   ///
@@ -980,6 +2684,48 @@ impl WebView {
       .eval(js, None::<Box<dyn Fn(String) + Send + 'static>>)
   }
 
+  /// The scroll position of the page, in CSS pixels from its top left corner, e.g. to restore it
+  /// with [`WebView::scroll_to`] in a webview recreated later.
+  ///
+  /// The page reports its position through the IPC channel once per frame while it scrolls, this
+  /// returns the last one without waiting for the page. It is `(0, 0)` before the first report,
+  /// and if the webview was built without [`WebViewBuilder::with_scroll_tracking`] or
+  /// [`WebViewBuilder::with_scroll_handler`], whose platform limits apply.
+  pub fn scroll_position(&self) -> (f64, f64) {
+    self.scroll_position.get()
+  }
+
+  /// Scroll the page to `(x, y)` in CSS pixels from its top left corner, smoothly if `animated`,
+  /// e.g. `scroll_to(0., 0., true)` for a native "scroll to top" gesture.
+  pub fn scroll_to(&self, x: f64, y: f64, animated: bool) -> Result<()> {
+    self.evaluate_script(&scroll::scroll_to_script(x, y, animated))
+  }
+
+  /// The text selected in the page, `None` if nothing is selected, e.g. for a native "define word"
+  /// or "share selection" action.
+  ///
+  /// The selection is read by the engine in an isolated world when this is called, so the page
+  /// can't hide or replace it, and it doesn't depend on the initialization scripts. The handler is
+  /// called with the selection of the focused text field, except the password fields, or of the
+  /// focused same-origin frame, or else of the document.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Requires macOS 11+ / iOS 14+, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported) otherwise.
+  /// - **Windows / Android**: Unsupported, the engines have no isolated worlds to read it from.
+  ///   Returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn selected_text(
+    &self,
+    handler: impl FnOnce(Result<Option<String>>) + 'static,
+  ) -> Result<()> {
+    self.webview.eval_in_world_with_callback(
+      selection::SELECTION_WORLD,
+      &typed_eval::wrap_expression(selection::SELECTED_TEXT_EXPRESSION),
+      move |json| handler(selection::parse_selected_text(&json)),
+    )
+  }
+
   /// Evaluate and run javascript code with callback function. The evaluation result will be
   /// serialized into a JSON string and passed to the callback function. Must be called on the
   /// same thread who created the [`WebView`]. Use [`EventLoopProxy`] and a custom event to
@@ -998,38 +2744,280 @@ impl WebView {
     self.webview.eval(js, Some(callback))
   }
 
+  /// Evaluate javascript code in the isolated JavaScript `world`, see
+  /// [`WebViewBuilder::with_isolated_initialization_script`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS:** Requires macOS 11+ / iOS 14+, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported) on older versions.
+  /// - **Windows / Android:** Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn evaluate_script_in_world(&self, world: &str, js: &str) -> Result<()> {
+    self.webview.eval_in_world(world, js)
+  }
+
+  /// Add an initialization script after the webview is built, see
+  /// [`WebViewBuilder::with_initialization_script`].
+  ///
+  /// The script runs on the next navigations, not on the current page. The returned [`ScriptId`]
+  /// removes it with [`WebView::remove_init_script`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android:** Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn add_init_script(&self, js: &str) -> Result<ScriptId> {
+    self.add_init_script_at(js, InjectionTime::DocumentStart)
+  }
+
+  /// Add an initialization script running at the given `time`, see
+  /// [`WebView::add_init_script`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android:** Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn add_init_script_at(&self, js: &str, time: InjectionTime) -> Result<ScriptId> {
+    self.add_init_script_with(InitScript::new(js).with_injection_time(time))
+  }
+
+  /// Add an initialization script with the injection options of `script`, see
+  /// [`WebView::add_init_script`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The script is injected in every frame and checks if it runs in the main
+  ///   frame when [`InitScript::for_main_frame_only`] is set.
+  /// - **Android:** Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn add_init_script_with(&self, script: InitScript) -> Result<ScriptId> {
+    self.webview.add_init_script(&script)
+  }
+
+  /// Remove an initialization script added with [`WebView::add_init_script`], so it doesn't run on
+  /// the next navigations. Removing a script twice does nothing.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android:** Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn remove_init_script(&self, id: ScriptId) -> Result<()> {
+    self.webview.remove_init_script(id)
+  }
+
+  /// Call a javascript function with serialized arguments, instead of formatting them into the
+  /// script, e.g. `webview.evaluate_script_with_args("(name) => greet(name)", &["O'Brien"])`.
+  ///
+  /// `function` must be an expression evaluating to a function, which is called with `args` once
+  /// they are serialized to JSON.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS:** Uses `callAsyncJavaScript:arguments:` on macOS 11+ / iOS 14+.
+  pub fn evaluate_script_with_args<A: serde::Serialize>(
+    &self,
+    function: &str,
+    args: &[A],
+  ) -> Result<()> {
+    self
+      .webview
+      .eval_with_args(function, &typed_eval::serialize_arguments(args)?)
+  }
+
+  /// Call the global javascript function at `path`, e.g. `app.handleEvent`, with serialized
+  /// arguments, and pass its result to the handler once it resolves if it's a promise.
+  ///
+  /// The function is called with `this` bound to its parent object. The handler is called on the
+  /// main thread with [`Error::JavaScriptException`](crate::Error::JavaScriptException) if the
+  /// function isn't found, throws or rejects, and with
+  /// [`Error::NavigatedAway`](crate::Error::NavigatedAway) if the page navigates away or reloads
+  /// before the result is received. It isn't called if the call can't be evaluated, which is
+  /// returned as an error instead.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android**: The calls only fail on navigation with a page load handler, see
+  ///   [`WebViewBuilder::with_on_page_load_handler`].
+  pub fn call_function<T: DeserializeOwned, A: serde::Serialize>(
+    &self,
+    path: &str,
+    args: &[A],
+    handler: impl FnOnce(Result<T>) + 'static,
+  ) -> Result<()> {
+    let args = typed_eval::serialize_arguments(args)?;
+    let id = self.calls.register(Box::new(move |outcome| {
+      handler(outcome.and_then(typed_eval::parse_result))
+    }));
+    let evaluated = self.evaluate_script(&self.calls.call_script(id, path, &args));
+    if evaluated.is_err() {
+      self.calls.cancel(id);
+    }
+    evaluated
+  }
+
+  /// List the frames of the main document, to script them with
+  /// [`WebView::evaluate_script_in_frame`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The frames are tracked as they are created, their ids stay valid until they are
+  ///   removed.
+  /// - **Linux / macOS / iOS:** The ids are the index of the frames in the document, which change
+  ///   when frames are added or removed before them.
+  /// - **Android:** Not implemented yet, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn frames(
+    &self,
+    handler: impl FnOnce(Result<Vec<FrameInfo>>) + Send + 'static,
+  ) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+      handler(Ok(self.webview.frames()));
+      Ok(())
+    }
+    #[cfg(not(target_os = "windows"))]
+    self.evaluate_script_typed(frames::FRAMES_EXPRESSION, handler)
+  }
+
+  /// Evaluate javascript code in a frame listed by [`WebView::frames`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Returns [`Error::FrameNotFound`](crate::Error::FrameNotFound) if the frame
+  ///   was removed. Requires WebView2 Runtime 1.0.1072.54 or newer.
+  /// - **Linux / macOS / iOS / Android:** The script is evaluated by the main document, so the frame
+  ///   must have the same origin. Missing frames are ignored.
+  pub fn evaluate_script_in_frame(&self, frame_id: u32, js: &str) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    return self.webview.eval_in_frame(frame_id, js);
+    #[cfg(not(target_os = "windows"))]
+    self.evaluate_script(&frames::eval_in_frame_script(frame_id, js))
+  }
+
+  /// Evaluate a javascript expression and pass its value, deserialized into `T`, to the handler.
+  /// Same as [`WebView::evaluate_script_with_callback`], but exceptions thrown by the expression
+  /// are reported as [`Error::JavaScriptException`](crate::Error::JavaScriptException).
+  ///
+  /// `expression` must be a single expression; wrap statements in a function, e.g.
+  /// `(() => { const a = 1; return a + 1; })()`. `undefined` is deserialized as `null`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android:** Not implemented yet, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn evaluate_script_typed<T: DeserializeOwned>(
+    &self,
+    expression: &str,
+    handler: impl FnOnce(Result<T>) + Send + 'static,
+  ) -> Result<()> {
+    if cfg!(target_os = "android") {
+      return Err(crate::Error::Unsupported);
+    }
+    let handler = std::sync::Mutex::new(Some(handler));
+    self.webview.eval(
+      &typed_eval::wrap_expression(expression),
+      Some(move |json: String| {
+        if let Some(handler) = handler.lock().unwrap().take() {
+          handler(typed_eval::parse_result(&json))
+        }
+      }),
+    )
+  }
+
+  /// The features supported by the webview backend of the current platform.
+  pub fn capabilities(&self) -> Capabilities {
+    Capabilities::current()
+  }
+
   /// Launch print modal for the webview content.
   pub fn print(&self) -> Result<()> {
     self.webview.print();
     Ok(())
   }
 
+  /// Run an editing `command` of the engine on the focused content of the page, like its keyboard
+  /// shortcut, e.g. for the items of a native Edit menu.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: Runs the command of a key event sent with the DevTools protocol.
+  /// - **Android**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn execute_edit_command(&self, command: EditCommand) -> Result<()> {
+    self.webview.execute_edit_command(command)
+  }
+
+  /// Print the webview content on the printer and with the dialog of the `options`, e.g. to print
+  /// receipts on a given printer without asking the user.
+  ///
+  /// The printer isn't looked up among the [`printers`] beforehand, since listing them can wait for
+  /// the network printers.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS**: Returns [`Error::PrinterNotFound`](crate::Error::PrinterNotFound) if the printer
+  ///   isn't known.
+  /// - **Linux / Windows**: An unknown printer fails the print once it starts, which is logged.
+  /// - **Windows**: The printer is only selected when printing silently, the print dialog
+  ///   starts with the default printer.
+  /// - **iOS / Android**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn print_with_options(&self, options: PrintOptions) -> Result<()> {
+    self.webview.print_with_options(options)
+  }
+
   /// Open the web inspector which is usually called dev tool.
   ///
   /// ## Platform-specific
   ///
   /// - **Android / iOS:** Not supported.
-  #[cfg(any(debug_assertions, feature = "devtools"))]
   pub fn open_devtools(&self) {
     self.webview.open_devtools();
   }
 
+  /// Open the web inspector in the given placement, so it doesn't cover the UI of the app.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Always opened in its own window.
+  /// - **Android / iOS:** Not supported.
+  pub fn open_devtools_with_placement(&self, placement: DevtoolsPlacement) {
+    self.webview.open_devtools_with_placement(placement);
+  }
+
   /// Close the web inspector which is usually called dev tool.
   ///
   /// ## Platform-specific
   ///
   /// - **Windows / Android / iOS:** Not supported.
-  #[cfg(any(debug_assertions, feature = "devtools"))]
   pub fn close_devtools(&self) {
     self.webview.close_devtools();
   }
 
+  /// Gets the position and size of the web inspector window, or `None` if it isn't open in its
+  /// own window.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / Android / iOS:** Not supported, returns `None`.
+  pub fn devtools_window_bounds(&self) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    self.webview.devtools_window_bounds()
+  }
+
+  /// Moves and resizes the web inspector window.
+  ///
+  /// Returns [`Error::DevtoolsNotDetached`](crate::Error::DevtoolsNotDetached) if it isn't open in
+  /// its own window.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / Android / iOS:** Not supported, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn set_devtools_window_bounds(
+    &self,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+  ) -> Result<()> {
+    self.webview.set_devtools_window_bounds(position, size)
+  }
+
   /// Gets the devtool window's current visibility state.
   ///
   /// ## Platform-specific
   ///
   /// - **Windows / Android / iOS:** Not supported.
-  #[cfg(any(debug_assertions, feature = "devtools"))]
   pub fn is_devtools_open(&self) -> bool {
     self.webview.is_devtools_open()
   }
@@ -1068,6 +3056,151 @@ impl WebView {
     self.window.inner_size()
   }
 
+  /// Moves and resizes the webview inside its window, e.g. to lay it out with the other views of
+  /// the window. The position is relative to the top left corner of the client area of the window,
+  /// and the webview then follows the size of the window as set with
+  /// [`WebViewBuilder::with_auto_resize`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / Linux:** Only supported by the webviews built with
+  ///   [`WebViewBuilder::new_as_child`].
+  /// - **Android:** Not supported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn set_bounds(&self, position: PhysicalPosition<i32>, size: PhysicalSize<u32>) -> Result<()> {
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    {
+      let scale_factor = self.window.scale_factor();
+      self.webview.set_bounds(position, size, scale_factor)
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    self.webview.set_bounds(position, size)
+  }
+
+  /// Moves the keyboard focus to the webview.
+  pub fn focus(&self) -> Result<()> {
+    self.webview.focus()
+  }
+
+  /// Removes the keyboard focus from the webview, if it has it, so the key events go to the
+  /// window.
+  pub fn blur(&self) -> Result<()> {
+    self.webview.blur()
+  }
+
+  /// Moves the webview to `window` with its page, e.g. to pop a tab out into its own window
+  /// without reloading it. It fills the window, unless it was laid out with [`Self::set_bounds`].
+  ///
+  /// `window` must outlive the webview, which keeps its original window: [`Self::window`] and the
+  /// handlers taking a window still get it, and dropping the webview closes it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only supported by the webviews built with [`WebViewBuilder::new_as_child`],
+  ///   which keep their bounds.
+  /// - **Android:** Not supported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn reparent(&self, window: &Window) -> Result<()> {
+    self.webview.reparent(window)
+  }
+
+  /// Shows or hides the webview, e.g. to switch between several webviews of a window without
+  /// reloading them. A hidden webview keeps its page running.
+  pub fn set_visible(&self, visible: bool) -> Result<()> {
+    self.webview.set_visible(visible)
+  }
+
+  /// Whether the webview is shown, see [`Self::set_visible`].
+  pub fn is_visible(&self) -> bool {
+    self.webview.is_visible()
+  }
+
+  /// Places the webview above the other webviews of its window.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / Linux:** Only supported by the webviews built with
+  ///   [`WebViewBuilder::new_as_child`].
+  /// - **Android:** Not supported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn bring_to_front(&self) -> Result<()> {
+    self.webview.bring_to_front()
+  }
+
+  /// Places the webview below the other webviews of its window.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only supported by the webviews built with [`WebViewBuilder::new_as_child`].
+  /// - **Linux:** Only supported by the webviews built with [`WebViewBuilder::new_as_child`],
+  ///   which are kept above the webview built with [`WebViewBuilder::new`].
+  /// - **Android:** Not supported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn send_to_back(&self) -> Result<()> {
+    self.webview.send_to_back()
+  }
+
+  /// Lets the mouse events go through the webview to the views below it, e.g. to draw an HTML
+  /// interface over a scene rendered with the GPU in the same window: build a transparent child
+  /// webview with [`WebViewBuilder::new_as_child`] and [`WebViewBuilder::with_transparent`] above
+  /// the view rendering the scene, and ignore the cursor events while the page reports, through
+  /// the IPC handler, that the pointer is over its transparent parts.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only supported by the webviews built with [`WebViewBuilder::new_as_child`],
+  ///   the events go to the parent window.
+  /// - **Android:** Not supported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn set_ignore_cursor_events(&self, ignore: bool) -> Result<()> {
+    self.webview.set_ignore_cursor_events(ignore)
+  }
+
+  /// Lets the mouse events over `regions` go through the webview to whatever is underneath, e.g.
+  /// over the transparent parts of a HUD-style overlay, while the rest of the webview still
+  /// receives them. An empty slice makes the whole webview receive them again. The page can also
+  /// declare its regions when built with [`WebViewBuilder::with_click_through`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only supported by the webviews built with [`WebViewBuilder::new_as_child`],
+  ///   whose window is clipped to the other regions, and whose events go to the parent window.
+  /// - **Linux:** Replaces the input shape set by [`WebView::set_ignore_cursor_events`].
+  /// - **iOS / Android:** Not supported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn set_click_through_regions(&self, regions: &[ClickThroughRegion]) -> Result<()> {
+    let set = self
+      .webview
+      .click_through_setter()
+      .ok_or(crate::Error::Unsupported)?;
+    set(regions);
+    Ok(())
+  }
+
+  /// Send a synthetic mouse input to the page without moving the real pointer, e.g. for automated
+  /// demos or accessibility tools. The page receives it like the inputs of a real mouse.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Requires the webview to be realized, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported) before.
+  /// - **macOS:** Only supports the left and right buttons, and requires the webview to be in a
+  ///   window. Returns [`Error::Unsupported`](crate::Error::Unsupported) otherwise.
+  /// - **Windows:** Dispatched with the devtools protocol.
+  /// - **iOS / Android:** Not supported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn send_mouse_event(&self, event: MouseEvent) -> Result<()> {
+    self.webview.send_mouse_event(event)
+  }
+
+  /// Send a synthetic touch input to the page, e.g. for integration tests of touch interactions
+  /// on machines without a touch screen. [`TouchEvent::tap`], [`TouchEvent::swipe`] and
+  /// [`TouchEvent::pinch`] build the events of common sequences.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Requires the webview to be realized, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported) before.
+  /// - **Windows:** Dispatched with the devtools protocol.
+  /// - **macOS / iOS / Android:** Not supported, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn send_touch_event(&self, event: TouchEvent) -> Result<()> {
+    self.webview.send_touch_event(event)
+  }
+
   /// Set the webview zoom level
   ///
   /// ## Platform-specific:
@@ -1090,17 +3223,420 @@ impl WebView {
   ///   - On Windows 7, transparency is not supported and the alpha value will be ignored.
   ///   - On Windows higher than 7: translucent colors are not supported so any alpha value other than `0` will be replaced by `255`
   pub fn set_background_color(&self, background_color: RGBA) -> Result<()> {
-    self.webview.set_background_color(background_color)
+    self.webview.set_background_color(background_color)?;
+    self.background_color.set(Some(background_color));
+    Ok(())
+  }
+
+  /// Enable or disable the horizontal swipe gestures navigating backward and forward, see
+  /// [`WebViewBuilder::with_back_forward_navigation_gestures`], e.g. while a drawing tool is
+  /// active.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android / iOS:** Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn set_back_forward_gestures(&self, enabled: bool) -> Result<()> {
+    self.webview.set_back_forward_gestures(enabled)
+  }
+
+  /// Set the color of the area revealed when the page is scrolled past its edges, see
+  /// [`WebViewBuilder::with_under_page_background_color`].
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Requires macOS 12+ / iOS 15+, does nothing on older versions.
+  /// - **Windows / Linux / Android**: Unsupported, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn set_under_page_background_color(&self, color: RGBA) -> Result<()> {
+    self.webview.set_under_page_background_color(color)
+  }
+
+  /// Switch the webview between a transparent background, like with
+  /// [`WebViewBuilder::with_transparent`], and an opaque one, using the background color given to
+  /// the builder or to [`WebView::set_background_color`].
+  ///
+  /// The window must be transparent for the content below the webview to show through.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS**: Requires the `transparent` feature, uses private APIs. Returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported) without it.
+  /// - **Windows**: Unsupported on Windows 7, the background stays opaque.
+  pub fn set_transparent(&self, transparent: bool) -> Result<()> {
+    self.webview.set_transparent(transparent)?;
+    match self.background_color.get() {
+      Some(background_color) if !transparent => self.webview.set_background_color(background_color),
+      _ => Ok(()),
+    }
+  }
+
+  /// Force the values of the accessibility media features seen by the page.
+  ///
+  /// See [`WebViewBuilder::with_media_feature_overrides`] for the platform-specific notes.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS / Android:** The new values only apply to the current document,
+  ///   subsequent navigations use the values set on the builder.
+  pub fn set_media_feature_overrides(&self, overrides: MediaFeatureOverrides) -> Result<()> {
+    self.webview.set_media_feature_overrides(overrides)
+  }
+
+  /// Capture an image of the first element matching the `css_selector`, e.g. to export a chart.
+  ///
+  /// The element is scrolled into view and the part of it inside the viewport is captured. The
+  /// `handler` is called on the main thread with the encoded image, or with
+  /// [`Error::ElementNotFound`](crate::Error::ElementNotFound) if no visible element matches.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android / iOS**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn capture_element(
+    &self,
+    css_selector: &str,
+    format: SnapshotFormat,
+    handler: impl FnOnce(Result<Vec<u8>>) + 'static,
+  ) -> Result<()> {
+    self.webview.capture_element(css_selector, format, handler)
+  }
+
+  /// Pause all the audio and video playing in the webview, e.g. when the view is hidden or
+  /// backgrounded.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Requires macOS 12+ / iOS 15+, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported) otherwise. Media playback stays
+  ///   suspended, even if started by the page, until [`WebView::resume_media`] is called.
+  /// - **Windows / Linux**: The engines can't pause the media, the webview is muted instead until
+  ///   [`WebView::resume_media`] is called, which restores the state of [`WebView::set_muted`].
+  ///   On Windows, this requires WebView2 Runtime 95.0.1020.44 or newer, and returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported) otherwise.
+  /// - **Android**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn pause_all_media(&self) -> Result<()> {
+    self.webview.pause_all_media()
+  }
+
+  /// Resume the media paused by [`WebView::pause_all_media`].
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Requires macOS 12+ / iOS 15+, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported) otherwise.
+  /// - **Android**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn resume_media(&self) -> Result<()> {
+    self.webview.resume_media()
+  }
+
+  /// Measure the memory used by the webview, e.g. to show it per tab or to pick the webviews to
+  /// discard under memory pressure.
+  ///
+  /// The `handler` is called on the main thread with the measured [`MemoryStats`].
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS**: Uses private APIs to find the web content process. WebKit doesn't expose the
+  ///   JavaScript heap size.
+  /// - **Linux / iOS**: The stats are empty, WebKit doesn't expose the web content process nor the
+  ///   JavaScript heap size.
+  /// - **Windows**: The process size isn't reported, WebView2 doesn't expose the renderer process
+  ///   of a webview.
+  /// - **Android**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn memory_usage(&self, handler: impl FnOnce(MemoryStats) + Send + 'static) -> Result<()> {
+    let process_resident_size = self.webview.process_resident_size();
+    self.evaluate_script_typed(
+      memory::JS_HEAP_SIZE_EXPRESSION,
+      move |js_heap_size: Result<Option<u64>>| {
+        handler(MemoryStats {
+          process_resident_size,
+          js_heap_size: js_heap_size.ok().flatten(),
+        })
+      },
+    )
+  }
+
+  /// Send `message` to the page on the WebSocket connection `id`, proxied with
+  /// [`WebSocketAction::Proxy`]. It is ignored once the connection is closed.
+  pub fn send_websocket_message(&self, id: u64, message: &WebSocketMessage) -> Result<()> {
+    self.evaluate_script(&websocket::send_message_script(id, message))
+  }
+
+  /// Close the WebSocket connection `id`, proxied with [`WebSocketAction::Proxy`], with the close
+  /// `code` and `reason` given to the page.
+  pub fn close_websocket(&self, id: u64, code: u16, reason: &str) -> Result<()> {
+    self.evaluate_script(&websocket::close_script(id, code, reason))
+  }
+
+  /// Measure the timings of the navigation to the current page, like the DNS lookup, the time to
+  /// first byte or when the DOM was complete, e.g. for the performance telemetry of the embedded
+  /// content.
+  ///
+  /// The `handler` is called on the main thread with the [`NavigationMetrics`] read from the page,
+  /// `None` if the page has none, e.g. for `about:blank` or while it is being replaced.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn navigation_metrics(
+    &self,
+    handler: impl FnOnce(Option<NavigationMetrics>) + Send + 'static,
+  ) -> Result<()> {
+    self.evaluate_script_typed(
+      navigation_metrics::NAVIGATION_TIMING_EXPRESSION,
+      move |entry: Result<serde_json::Value>| {
+        handler(
+          entry
+            .ok()
+            .and_then(navigation_metrics::parse_navigation_timing),
+        )
+      },
+    )
+  }
+
+  /// Emulate a slow or offline network, e.g. to test the offline states of the app from its
+  /// integration tests. The requests are delayed by `latency`, the transfers are limited to
+  /// `throughput` bytes per second, and fail while `offline`. The default conditions, without
+  /// latency nor throughput limit and online, stop the emulation.
+  ///
+  /// The conditions apply to the current page and the next navigations.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: Emulated by the devtools protocol, for all the requests of the webview.
+  /// - **Linux / macOS / iOS**: Emulated by wrapping the `fetch` and `XMLHttpRequest` functions of
+  ///   the pages, so the documents and the subresources loaded by their elements aren't affected,
+  ///   and the throughput only limits the `fetch` responses. `navigator.onLine` and the `online`
+  ///   and `offline` events follow the emulated state.
+  /// - **Android**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn set_network_conditions(
+    &self,
+    latency: std::time::Duration,
+    throughput: Option<u64>,
+    offline: bool,
+  ) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    return self
+      .webview
+      .set_network_conditions(latency, throughput, offline);
+    #[cfg(not(target_os = "windows"))]
+    {
+      if cfg!(target_os = "android") {
+        return Err(crate::Error::Unsupported);
+      }
+      let script = network_conditions::network_conditions_script(latency, throughput, offline);
+      if let Some(id) = self.network_conditions_script.take() {
+        self.remove_init_script(id)?;
+      }
+      if !network_conditions::is_default(latency, throughput, offline) {
+        self
+          .network_conditions_script
+          .set(Some(self.add_init_script(&script)?));
+      }
+      self.evaluate_script(&script)
+    }
+  }
+
+  /// Terminate the web content process of the webview to free its memory, like the discarded tabs
+  /// of the browsers. The navigation history is kept, and the page is loaded again by
+  /// [`WebView::restore`].
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux**: The process is shared by the webviews of a
+  ///   [`WebContext`](crate::webview::WebContext) using a single secondary process.
+  /// - **macOS / iOS**: Uses private APIs.
+  /// - **Windows / Android**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  ///   On Windows, [`WebView::suspend`] keeps the page but lets the system reclaim the memory of
+  ///   its renderer process.
+  pub fn discard(&self) -> Result<()> {
+    self.webview.discard()
+  }
+
+  /// Load the page of a webview discarded by [`WebView::discard`] again. Does nothing if the
+  /// webview isn't discarded.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows / Android**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn restore(&self) -> Result<()> {
+    self.webview.restore()
+  }
+
+  /// Pause the timers, media and rendering of the webview, e.g. for the webviews of the hidden tabs
+  /// of a tab strip, until [`WebView::resume`] is called.
+  ///
+  /// The webview is hidden while suspended, and shown again by [`WebView::resume`] if it was
+  /// visible. See [`Capabilities::suspend`](crate::webview::Capabilities::suspend).
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: The renderer process is suspended, stopping its timers, media and rendering.
+  /// - **macOS / iOS**: The media is suspended, the media paused with [`WebView::pause_all_media`]
+  ///   staying paused. WebKit throttles the timers and skips the rendering of the hidden webviews,
+  ///   but doesn't stop them. Requires macOS 12+ / iOS 15+.
+  /// - **Android**: Pauses the webview with `WebView.onPause`.
+  /// - **Linux**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn suspend(&self) -> Result<()> {
+    if self.suspended.get().is_some() {
+      return Ok(());
+    }
+    let visible = self.is_visible();
+    // WebView2 only suspends the hidden webviews.
+    self.set_visible(false)?;
+    if let Err(error) = self.webview.suspend() {
+      self.set_visible(visible)?;
+      return Err(error);
+    }
+    self.suspended.set(Some(visible));
+    Ok(())
+  }
+
+  /// Resume a webview suspended by [`WebView::suspend`], showing it again if it was visible.
+  pub fn resume(&self) -> Result<()> {
+    let Some(visible) = self.suspended.take() else {
+      return Ok(());
+    };
+    self.webview.resume()?;
+    self.set_visible(visible)
+  }
+
+  /// Mute or unmute all the audio played by the webview.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Uses private APIs.
+  /// - **Windows**: Requires WebView2 Runtime 95.0.1020.44 or newer.
+  /// - **Windows / Linux**: While the media is paused by [`WebView::pause_all_media`], the webview
+  ///   stays muted and the state is applied by [`WebView::resume_media`].
+  /// - **Android**: Unsupported.
+  pub fn set_muted(&self, muted: bool) {
+    self.webview.set_muted(muted)
+  }
+
+  /// Whether the webview audio is muted.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Uses private APIs.
+  /// - **Windows**: Requires WebView2 Runtime 95.0.1020.44 or newer.
+  /// - **Android**: Unsupported, always returns `false`.
+  pub fn is_muted(&self) -> bool {
+    self.webview.is_muted()
+  }
+
+  /// List the domains which have registered service workers in the data store of the webview.
+  ///
+  /// The `handler` is called on the main thread with the host names of the registrations.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: Only the registrations of the origin of the current page are listed.
+  /// - **Android**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn fetch_service_worker_registrations(
+    &self,
+    handler: impl FnOnce(Result<Vec<String>>) + 'static,
+  ) -> Result<()> {
+    self.webview.fetch_service_worker_registrations(handler)
+  }
+
+  /// Unregister the service workers of `domain`, or all of them if `None`, and remove their
+  /// caches.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn unregister_service_workers(&self, domain: Option<&str>) -> Result<()> {
+    self.webview.unregister_service_workers(domain)
+  }
+
+  /// Call a [Chrome DevTools Protocol](https://chromedevtools.github.io/devtools-protocol/) method,
+  /// e.g. `Network.enable`, with its JSON encoded `params`.
+  ///
+  /// The `handler` is called on the main thread with the JSON encoded result of the method, or
+  /// [`Error::DevtoolsProtocolError`](crate::Error::DevtoolsProtocolError) with the error returned
+  /// by the browser.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux / macOS / iOS / Android**: Unsupported, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported). WebKit only exposes its inspector
+  ///   protocol to remote inspectors.
+  pub fn call_devtools_protocol_method(
+    &self,
+    method: &str,
+    params: &str,
+    handler: impl FnOnce(Result<String>) + 'static,
+  ) -> Result<()> {
+    self
+      .webview
+      .call_devtools_protocol_method(method, params, handler)
+  }
+
+  /// Call `handler` on the main thread with the JSON encoded parameters of each Chrome DevTools
+  /// Protocol `event`, e.g. `Network.requestWillBeSent`.
+  ///
+  /// Most events are only sent once their domain is enabled with
+  /// [`WebView::call_devtools_protocol_method`], e.g. with `Network.enable`.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux / macOS / iOS / Android**: Unsupported, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn subscribe_devtools_protocol_event(
+    &self,
+    event: &str,
+    handler: impl Fn(String) + 'static,
+  ) -> Result<()> {
+    self
+      .webview
+      .subscribe_devtools_protocol_event(event, handler)
   }
 
   /// Navigate to the specified url
   pub fn load_url(&self, url: &str) {
-    self.webview.load_url(url)
+    if self.default_headers.is_empty() {
+      self.webview.load_url(url)
+    } else {
+      self
+        .webview
+        .load_url_with_headers(url, self.default_headers.clone())
+    }
   }
 
-  /// Navigate to the specified url using the specified headers
+  /// Navigate to the specified url using the specified headers, which replace the
+  /// [default headers](WebViewBuilder::with_default_headers) with the same name.
   pub fn load_url_with_headers(&self, url: &str, headers: http::HeaderMap) {
-    self.webview.load_url_with_headers(url, headers)
+    self
+      .webview
+      .load_url_with_headers(url, default_headers::merge(&self.default_headers, headers))
+  }
+
+  /// Open a page saved with its subresources, e.g. to reopen a captured page without the
+  /// network. See [`WebArchive`] for the format of each platform.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: An archive given as bytes is written to the temporary directory, which isn't
+  ///   cleaned up, since WebView2 only opens the MHTML files.
+  /// - **Android**: Only [`WebArchive::File`] is supported, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported) otherwise.
+  pub fn load_archive(&self, archive: impl Into<WebArchive>) -> Result<()> {
+    self.webview.load_archive(archive.into())
+  }
+
+  /// Navigate to the specified url using the specified cache policy.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows / Linux / Android**: The policy is applied with the `Cache-Control` and `Pragma`
+  ///   request headers, [`CachePolicy::UseCache`] behaves like [`WebView::load_url`].
+  pub fn load_url_with_cache_policy(&self, url: &str, policy: CachePolicy) {
+    self.webview.load_url_with_cache_policy(url, policy)
+  }
+
+  /// Reload the current page from the network, ignoring the cached resources.
+  pub fn reload_ignoring_cache(&self) {
+    self.webview.reload_ignoring_cache()
   }
 
   /// Clear all browsing data
@@ -1134,6 +3670,56 @@ pub fn webview_version() -> Result<String> {
   platform_webview_version()
 }
 
+/// The names of the printers available to [`WebView::print_with_options`].
+///
+/// ## Platform-specific:
+///
+/// - **Linux**: Waits until the print backends of GTK have listed their printers.
+/// - **iOS / Android**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+pub fn printers() -> Result<Vec<String>> {
+  platform_printers()
+}
+
+/// The number of live webviews, and of engine processes which have not exited yet.
+pub fn webview_instance_count() -> usize {
+  lifecycle::instance_count()
+}
+
+/// Blocks until all the webviews are dropped and their engine processes have exited, so that
+/// long-running processes can release the engine resources between create/destroy cycles.
+///
+/// The engine resources shared by the webviews of a [`WebContext`], the WebView2 environment and
+/// the `WKProcessPool`, are released with the last of its webviews, and created again with the
+/// next one.
+///
+/// When called on the main thread, the platform events are processed while waiting, as the
+/// engines release their resources through them. The webviews of the other threads are waited
+/// for, but the ones of the current thread must be dropped before, since they can't be dropped
+/// while it waits.
+///
+/// # Errors
+///
+/// Returns [`Error::WebViewsAlive`](crate::Error::WebViewsAlive) without waiting if webviews of
+/// the current thread are alive.
+///
+/// ## Platform-specific:
+///
+/// - **Windows**: Also waits for the WebView2 browser processes to exit, on WebView2 Runtime
+///   versions supporting `BrowserProcessExited`.
+/// - **Linux / macOS / iOS / Android**: The web processes are terminated by the engine once their
+///   webviews are dropped, only the webviews are waited for. GTK and the Objective-C classes of
+///   wry are initialized once per process and reused by the next webviews.
+/// - **Linux**: The WebKitGTK context, and its network process, are released with the
+///   [`WebContext`].
+pub fn shutdown() -> Result<()> {
+  let alive = lifecycle::thread_webview_count();
+  if alive > 0 {
+    return Err(crate::Error::WebViewsAlive(alive));
+  }
+  lifecycle::wait_until_released(platform_pump_events);
+  Ok(())
+}
+
 /// The [memory usage target level][1]. There are two levels 'Low' and 'Normal' and the default
 /// level is 'Normal'. When the application is going inactive, setting the level to 'Low' can
 /// significantly reduce the application's memory consumption.
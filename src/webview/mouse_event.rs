@@ -0,0 +1,135 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::application::{dpi::LogicalPosition, event::MouseButton};
+
+/// A synthetic mouse input sent to the page with
+/// [`WebView::send_mouse_event`](super::WebView::send_mouse_event).
+///
+/// The positions are in CSS pixels relative to the top left corner of the webview, at the default
+/// zoom. The buttons other than left, middle and right are numbered like on X11, where `8` is
+/// back and `9` is forward.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MouseEvent {
+  /// The pointer moved to `position`.
+  Move { position: LogicalPosition<f64> },
+  /// A button was pressed at `position`.
+  Down {
+    position: LogicalPosition<f64>,
+    button: MouseButton,
+  },
+  /// A button was released at `position`.
+  Up {
+    position: LogicalPosition<f64>,
+    button: MouseButton,
+  },
+  /// The wheel scrolled the content under `position` by `delta_x` and `delta_y` CSS pixels,
+  /// downwards and to the right for positive deltas.
+  Wheel {
+    position: LogicalPosition<f64>,
+    delta_x: f64,
+    delta_y: f64,
+  },
+}
+
+impl MouseEvent {
+  #[cfg_attr(any(target_os = "ios", target_os = "android"), allow(dead_code))]
+  pub(crate) fn position(&self) -> LogicalPosition<f64> {
+    match *self {
+      MouseEvent::Move { position }
+      | MouseEvent::Down { position, .. }
+      | MouseEvent::Up { position, .. }
+      | MouseEvent::Wheel { position, .. } => position,
+    }
+  }
+}
+
+/// The X11 number of a mouse button, `1` for the left button.
+#[cfg_attr(
+  not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "windows"
+  )),
+  allow(dead_code)
+)]
+pub(crate) fn button_number(button: MouseButton) -> u32 {
+  match button {
+    MouseButton::Left => 1,
+    MouseButton::Middle => 2,
+    MouseButton::Right => 3,
+    MouseButton::Other(number) => number as u32,
+    _ => 0,
+  }
+}
+
+/// The parameters of the
+/// [`Input.dispatchMouseEvent`](https://chromedevtools.github.io/devtools-protocol/tot/Input/#method-dispatchMouseEvent)
+/// devtools protocol method dispatching `event`.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn devtools_params(event: &MouseEvent) -> serde_json::Value {
+  let LogicalPosition { x, y } = event.position();
+  let button = |button| match button_number(button) {
+    1 => "left",
+    2 => "middle",
+    3 => "right",
+    8 => "back",
+    9 => "forward",
+    _ => "none",
+  };
+  match *event {
+    MouseEvent::Move { .. } => serde_json::json!({ "type": "mouseMoved", "x": x, "y": y }),
+    MouseEvent::Down { button: b, .. } => serde_json::json!({
+      "type": "mousePressed", "x": x, "y": y, "button": button(b), "clickCount": 1
+    }),
+    MouseEvent::Up { button: b, .. } => serde_json::json!({
+      "type": "mouseReleased", "x": x, "y": y, "button": button(b), "clickCount": 1
+    }),
+    MouseEvent::Wheel {
+      delta_x, delta_y, ..
+    } => serde_json::json!({
+      "type": "mouseWheel", "x": x, "y": y, "deltaX": delta_x, "deltaY": delta_y
+    }),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_build_devtools_params() {
+    let position = LogicalPosition::new(10., 20.5);
+    assert_eq!(
+      devtools_params(&MouseEvent::Move { position }),
+      serde_json::json!({ "type": "mouseMoved", "x": 10., "y": 20.5 })
+    );
+    assert_eq!(
+      devtools_params(&MouseEvent::Down {
+        position,
+        button: MouseButton::Right
+      }),
+      serde_json::json!({ "type": "mousePressed", "button": "right", "clickCount": 1, "x": 10., "y": 20.5 })
+    );
+    assert_eq!(
+      devtools_params(&MouseEvent::Up {
+        position,
+        button: MouseButton::Other(8)
+      })["button"],
+      "back"
+    );
+    assert_eq!(
+      devtools_params(&MouseEvent::Wheel {
+        position,
+        delta_x: 0.,
+        delta_y: 120.
+      })["deltaY"],
+      120.
+    );
+  }
+}
@@ -0,0 +1,184 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::rc::Rc;
+
+use regex::Regex;
+
+use super::WebViewAttributes;
+use crate::{Error, Result};
+
+#[derive(Debug, Clone)]
+enum Pattern {
+  Glob(String),
+  Regex(Regex),
+}
+
+impl Pattern {
+  fn matches(&self, url: &str) -> bool {
+    match self {
+      Self::Glob(glob) => glob_matches(glob.as_bytes(), url.as_bytes()),
+      Self::Regex(regex) => regex.is_match(url),
+    }
+  }
+}
+
+/// `*` matches any sequence of characters, `?` matches exactly one, the whole input must match.
+fn glob_matches(glob: &[u8], input: &[u8]) -> bool {
+  let (mut g, mut i) = (0, 0);
+  // Position of the last `*` in the glob, and of the input it was matched against.
+  let mut backtrack = None;
+  while i < input.len() {
+    match glob.get(g) {
+      Some(b'*') => {
+        backtrack = Some((g, i));
+        g += 1;
+      }
+      Some(&c) if c == b'?' || c == input[i] => {
+        g += 1;
+        i += 1;
+      }
+      _ => match backtrack {
+        Some((star, matched)) => {
+          g = star + 1;
+          i = matched + 1;
+          backtrack = Some((star, matched + 1));
+        }
+        None => return false,
+      },
+    }
+  }
+  glob[g..].iter().all(|&c| c == b'*')
+}
+
+/// A declarative allowlist and blocklist of the URLs a webview may navigate to.
+///
+/// A URL is allowed when it matches none of the denied patterns and, if any allowed pattern is
+/// set, at least one of them. Glob patterns must match the whole URL, e.g. `https://example.com/*`,
+/// while regular expressions match any part of it unless anchored, e.g.
+/// `^https://([a-z]+\.)?example\.com/`.
+///
+/// See [`WebViewBuilder::with_navigation_filter`](super::WebViewBuilder::with_navigation_filter).
+///
+/// # Examples
+///
+/// ```
+/// use wry::webview::NavigationFilter;
+///
+/// let filter = NavigationFilter::new()
+///   .allow_glob("https://kiosk.example.com/*")
+///   .deny_glob("https://kiosk.example.com/admin/*");
+///
+/// assert!(filter.is_allowed("https://kiosk.example.com/index.html"));
+/// assert!(!filter.is_allowed("https://kiosk.example.com/admin/"));
+/// assert!(!filter.is_allowed("https://example.com/"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct NavigationFilter {
+  allowed: Vec<Pattern>,
+  denied: Vec<Pattern>,
+}
+
+impl NavigationFilter {
+  /// Create a filter allowing every URL.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Allow the URLs matching a glob pattern, where `*` matches any sequence of characters and `?`
+  /// a single character.
+  pub fn allow_glob(mut self, pattern: &str) -> Self {
+    self.allowed.push(Pattern::Glob(pattern.to_string()));
+    self
+  }
+
+  /// Allow the URLs matching a regular expression.
+  pub fn allow_regex(mut self, pattern: &str) -> Result<Self> {
+    self.allowed.push(Pattern::Regex(regex(pattern)?));
+    Ok(self)
+  }
+
+  /// Deny the URLs matching a glob pattern, where `*` matches any sequence of characters and `?`
+  /// a single character.
+  pub fn deny_glob(mut self, pattern: &str) -> Self {
+    self.denied.push(Pattern::Glob(pattern.to_string()));
+    self
+  }
+
+  /// Deny the URLs matching a regular expression.
+  pub fn deny_regex(mut self, pattern: &str) -> Result<Self> {
+    self.denied.push(Pattern::Regex(regex(pattern)?));
+    Ok(self)
+  }
+
+  /// Whether the filter allows navigating to `url`.
+  pub fn is_allowed(&self, url: &str) -> bool {
+    !self.denied.iter().any(|p| p.matches(url))
+      && (self.allowed.is_empty() || self.allowed.iter().any(|p| p.matches(url)))
+  }
+}
+
+fn regex(pattern: &str) -> Result<Regex> {
+  Regex::new(pattern).map_err(|e| Error::InvalidNavigationPattern(e.to_string()))
+}
+
+/// Gates the navigation and new window handlers of `attributes` with its navigation filter, the
+/// URLs it blocks never reaching them.
+pub(crate) fn install(attributes: &mut WebViewAttributes) {
+  let Some(filter) = attributes.navigation_filter.take() else {
+    return;
+  };
+  let filter = Rc::new(filter);
+  let guard = |handler: Option<Box<dyn Fn(String) -> bool>>| -> Box<dyn Fn(String) -> bool> {
+    let filter = filter.clone();
+    Box::new(move |url| {
+      filter.is_allowed(&url)
+        && match &handler {
+          Some(handler) => handler(url),
+          None => true,
+        }
+    })
+  };
+  attributes.navigation_handler = Some(guard(attributes.navigation_handler.take()));
+  attributes.new_window_req_handler = Some(guard(attributes.new_window_req_handler.take()));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_match_glob() {
+    assert!(glob_matches(
+      b"https://*.example.com/*",
+      b"https://a.b.example.com/x"
+    ));
+    assert!(glob_matches(
+      b"http?://example.com/",
+      b"https://example.com/"
+    ));
+    assert!(glob_matches(b"*", b""));
+    assert!(!glob_matches(
+      b"https://*.example.com/*",
+      b"https://example.com/"
+    ));
+    assert!(!glob_matches(
+      b"https://example.com",
+      b"https://example.com/"
+    ));
+  }
+
+  #[test]
+  fn should_filter_urls() {
+    let filter = NavigationFilter::new()
+      .allow_regex(r"^https://([a-z]+\.)?example\.com/")
+      .unwrap()
+      .deny_glob("*/private/*");
+    assert!(filter.is_allowed("https://www.example.com/"));
+    assert!(!filter.is_allowed("https://www.example.com/private/page"));
+    assert!(!filter.is_allowed("https://example.org/"));
+    assert!(NavigationFilter::new().is_allowed("https://example.org/"));
+    assert!(NavigationFilter::new().allow_regex("(").is_err());
+  }
+}
@@ -0,0 +1,121 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::time::Duration;
+
+/// The timings of the navigation to the current page, see
+/// [`WebView::navigation_metrics`](super::WebView::navigation_metrics).
+///
+/// The durations of the phases come from the [navigation timing](https://developer.mozilla.org/docs/Web/API/PerformanceNavigationTiming)
+/// entry of the page, and the milestones are relative to the start of the navigation. The
+/// milestones are `None` until the page reaches them.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavigationMetrics {
+  pub url: String,
+  /// The time spent resolving the domain name, zero when it was cached.
+  pub dns_lookup: Duration,
+  /// The time spent connecting to the server, including the TLS handshake.
+  pub connect: Duration,
+  /// The time spent on the TLS handshake, `None` for insecure connections.
+  pub tls_handshake: Option<Duration>,
+  /// When the first byte of the response was received.
+  pub time_to_first_byte: Duration,
+  /// The time spent receiving the response, after its first byte.
+  pub download: Duration,
+  pub dom_interactive: Option<Duration>,
+  pub dom_content_loaded: Option<Duration>,
+  pub dom_complete: Option<Duration>,
+  pub load: Option<Duration>,
+}
+
+// The timestamps of a `PerformanceNavigationTiming`, in milliseconds. The unreached ones are `0`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+  name: String,
+  domain_lookup_start: f64,
+  domain_lookup_end: f64,
+  connect_start: f64,
+  connect_end: f64,
+  #[serde(default)]
+  secure_connection_start: f64,
+  response_start: f64,
+  response_end: f64,
+  dom_interactive: f64,
+  dom_content_loaded_event_end: f64,
+  dom_complete: f64,
+  load_event_end: f64,
+}
+
+/// Evaluates to the navigation timing entry of the page, `null` if there is none, e.g. for
+/// `about:blank`.
+pub(crate) const NAVIGATION_TIMING_EXPRESSION: &str = "(function () { \
+  var entry = performance.getEntriesByType && performance.getEntriesByType('navigation')[0]; \
+  return entry ? entry.toJSON() : null; \
+})()";
+
+fn duration(milliseconds: f64) -> Duration {
+  Duration::try_from_secs_f64(milliseconds / 1000.).unwrap_or_default()
+}
+
+fn between(start: f64, end: f64) -> Duration {
+  duration(end - start)
+}
+
+fn milestone(timestamp: f64) -> Option<Duration> {
+  (timestamp > 0.).then(|| duration(timestamp))
+}
+
+/// Parse the JSON value of [`NAVIGATION_TIMING_EXPRESSION`], `None` if it is `null` or malformed.
+pub(crate) fn parse_navigation_timing(json: serde_json::Value) -> Option<NavigationMetrics> {
+  let entry: Entry = serde_json::from_value(json).ok()?;
+  Some(NavigationMetrics {
+    url: entry.name,
+    dns_lookup: between(entry.domain_lookup_start, entry.domain_lookup_end),
+    connect: between(entry.connect_start, entry.connect_end),
+    tls_handshake: (entry.secure_connection_start > 0.)
+      .then(|| between(entry.secure_connection_start, entry.connect_end)),
+    time_to_first_byte: duration(entry.response_start),
+    download: between(entry.response_start, entry.response_end),
+    dom_interactive: milestone(entry.dom_interactive),
+    dom_content_loaded: milestone(entry.dom_content_loaded_event_end),
+    dom_complete: milestone(entry.dom_complete),
+    load: milestone(entry.load_event_end),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_navigation_timing() {
+    let metrics = parse_navigation_timing(serde_json::json!({
+      "name": "https://example.com/",
+      "entryType": "navigation",
+      "domainLookupStart": 2,
+      "domainLookupEnd": 12,
+      "connectStart": 12,
+      "connectEnd": 42,
+      "secureConnectionStart": 20,
+      "responseStart": 80.5,
+      "responseEnd": 90.5,
+      "domInteractive": 120,
+      "domContentLoadedEventEnd": 130,
+      "domComplete": 0,
+      "loadEventEnd": 0
+    }))
+    .unwrap();
+    assert_eq!(metrics.dns_lookup, Duration::from_millis(10));
+    assert_eq!(metrics.connect, Duration::from_millis(30));
+    assert_eq!(metrics.tls_handshake, Some(Duration::from_millis(22)));
+    assert_eq!(metrics.time_to_first_byte, Duration::from_micros(80500));
+    assert_eq!(metrics.download, Duration::from_millis(10));
+    assert_eq!(metrics.dom_content_loaded, Some(Duration::from_millis(130)));
+    assert_eq!(metrics.dom_complete, None);
+
+    assert_eq!(parse_navigation_timing(serde_json::Value::Null), None);
+  }
+}
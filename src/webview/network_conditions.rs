@@ -0,0 +1,133 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Emulates slow networks and offline states, see
+//! [`WebView::set_network_conditions`](super::WebView::set_network_conditions).
+
+use std::time::Duration;
+
+/// Whether the conditions don't change the network, so they don't need to be emulated.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub(crate) fn is_default(latency: Duration, throughput: Option<u64>, offline: bool) -> bool {
+  latency.is_zero() && throughput.is_none() && !offline
+}
+
+/// The parameters of the
+/// [`Network.emulateNetworkConditions`](https://chromedevtools.github.io/devtools-protocol/tot/Network/#method-emulateNetworkConditions)
+/// devtools protocol method, where `-1` disables the throttling.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn devtools_params(
+  latency: Duration,
+  throughput: Option<u64>,
+  offline: bool,
+) -> serde_json::Value {
+  let throughput = throughput.map_or(-1., |throughput| throughput as f64);
+  serde_json::json!({
+    "offline": offline,
+    "latency": latency.as_secs_f64() * 1000.,
+    "downloadThroughput": throughput,
+    "uploadThroughput": throughput,
+  })
+}
+
+/// Wraps `fetch` and `XMLHttpRequest` once per page to delay the requests by the latency, the
+/// `fetch` responses by their size over the throughput, and make them fail while offline, then
+/// updates the conditions and fires the `online` or `offline` event when they change.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+const NETWORK_CONDITIONS_SCRIPT: &str = r#"(function (conditions) {
+  var network = window.__WRY_NETWORK__;
+  if (!network) {
+    network = { fetch: window.fetch, send: XMLHttpRequest.prototype.send };
+    Object.defineProperty(window, '__WRY_NETWORK__', { value: network });
+    var delay = function (ms) {
+      return new Promise(function (resolve) { setTimeout(resolve, ms); });
+    };
+    window.fetch = function () {
+      var args = arguments;
+      var c = network.conditions;
+      if (c.offline) return Promise.reject(new TypeError('Failed to fetch'));
+      return delay(c.latency).then(function () {
+        return network.fetch.apply(window, args);
+      }).then(function (response) {
+        if (!c.throughput) return response;
+        return response.arrayBuffer().then(function (body) {
+          return delay(body.byteLength / c.throughput * 1000).then(function () {
+            return new Response(body, {
+              status: response.status,
+              statusText: response.statusText,
+              headers: response.headers
+            });
+          });
+        });
+      });
+    };
+    XMLHttpRequest.prototype.send = function () {
+      var xhr = this, args = arguments;
+      if (network.conditions.offline) {
+        setTimeout(function () {
+          xhr.dispatchEvent(new ProgressEvent('error'));
+          xhr.dispatchEvent(new ProgressEvent('loadend'));
+        });
+        return;
+      }
+      setTimeout(function () { network.send.apply(xhr, args); }, network.conditions.latency);
+    };
+    Object.defineProperty(navigator, 'onLine', {
+      configurable: true,
+      get: function () { return !network.conditions.offline; }
+    });
+  }
+  var wasOffline = network.conditions ? network.conditions.offline : false;
+  network.conditions = conditions;
+  if (wasOffline !== conditions.offline) {
+    window.dispatchEvent(new Event(conditions.offline ? 'offline' : 'online'));
+  }
+})"#;
+
+/// Applies the conditions to the page, `throughput` in bytes per second.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub(crate) fn network_conditions_script(
+  latency: Duration,
+  throughput: Option<u64>,
+  offline: bool,
+) -> String {
+  let conditions = serde_json::json!({
+    "latency": latency.as_secs_f64() * 1000.,
+    "throughput": throughput,
+    "offline": offline,
+  });
+  format!("{NETWORK_CONDITIONS_SCRIPT}({conditions});")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_build_devtools_params() {
+    assert_eq!(
+      devtools_params(Duration::from_millis(150), Some(50_000), false),
+      serde_json::json!({
+        "offline": false,
+        "latency": 150.,
+        "downloadThroughput": 50_000.,
+        "uploadThroughput": 50_000.,
+      })
+    );
+    assert_eq!(
+      devtools_params(Duration::ZERO, None, true)["downloadThroughput"],
+      -1.
+    );
+  }
+
+  #[test]
+  fn should_build_network_conditions_script() {
+    assert!(
+      network_conditions_script(Duration::from_millis(20), None, true)
+        .ends_with(r#"({"latency":20.0,"offline":true,"throughput":null});"#)
+    );
+    assert!(is_default(Duration::ZERO, None, false));
+    assert!(!is_default(Duration::ZERO, Some(1), false));
+  }
+}
@@ -0,0 +1,85 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::rc::Rc;
+
+use super::{external_links::ExternalLinks, open_in_browser, PlatformOpener, WebViewAttributes};
+
+/// A request of the page to open a new window, e.g. from `window.open` or a `target="_blank"` link.
+///
+/// See [`WebViewBuilder::with_new_window_handler`](super::WebViewBuilder::with_new_window_handler).
+pub struct NewWindowRequest {
+  pub(crate) url: String,
+  pub(crate) opener: NewWindowOpener,
+}
+
+impl NewWindowRequest {
+  /// The URL to open.
+  pub fn url(&self) -> &str {
+    &self.url
+  }
+
+  /// The opener to build the webview of the new window with, before responding with
+  /// [`NewWindowResponse::Create`].
+  pub fn opener(&self) -> NewWindowOpener {
+    self.opener.clone()
+  }
+}
+
+/// How to handle a [`NewWindowRequest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NewWindowResponse {
+  /// Let the engine handle the request. WebView2 opens the page in a popup window it manages,
+  /// WebKit ignores the request.
+  Allow,
+  /// Don't open the page.
+  Deny,
+  /// Load the page in the webview requesting the new window instead.
+  Navigate,
+  /// Load the page in the webview built with the [`NewWindowRequest::opener`], see
+  /// [`WebViewBuilder::with_new_window_opener`](super::WebViewBuilder::with_new_window_opener).
+  /// The request is denied if no webview was built with it.
+  Create,
+}
+
+/// Relates the webview of a new window to the webview which requested it, so the engine can load
+/// the requested page in it.
+///
+/// See [`WebViewBuilder::with_new_window_opener`](super::WebViewBuilder::with_new_window_opener).
+#[derive(Clone)]
+pub struct NewWindowOpener(pub(crate) Rc<PlatformOpener>);
+
+/// Merges the new window handlers of `attributes` into the one the backends call: the allow or
+/// deny handler first, then the external links opened in the browser, then the new window
+/// handler.
+pub(crate) fn install(attributes: &mut WebViewAttributes) {
+  let new_window_req_handler = attributes.new_window_req_handler.take();
+  let new_window_handler = attributes.new_window_handler.take();
+  let external_links = attributes
+    .external_links_in_browser
+    .then(|| ExternalLinks::new(std::mem::take(&mut attributes.internal_link_hosts)));
+  if new_window_req_handler.is_none() && new_window_handler.is_none() && external_links.is_none() {
+    return;
+  }
+  attributes.new_window_handler = Some(Box::new(move |request| {
+    if let Some(allow) = &new_window_req_handler {
+      if !allow(request.url.clone()) {
+        return NewWindowResponse::Deny;
+      }
+    }
+    if external_links
+      .as_ref()
+      .is_some_and(|links| links.is_external(&request.url))
+    {
+      if let Err(e) = open_in_browser(&request.url) {
+        log::warn!("failed to open {} in the browser: {}", request.url, e);
+      }
+      return NewWindowResponse::Deny;
+    }
+    match &new_window_handler {
+      Some(handler) => handler(request),
+      None => NewWindowResponse::Allow,
+    }
+  }));
+}
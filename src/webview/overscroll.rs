@@ -0,0 +1,15 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// Disables the elastic overscroll of the page with the `overscroll-behavior` CSS property, see
+/// [`WebViewBuilder::with_elastic_overscroll`](super::WebViewBuilder::with_elastic_overscroll).
+pub(crate) const DISABLE_OVERSCROLL_SCRIPT: &str = r#"(function () {
+  var disable = function () {
+    var style = document.createElement('style');
+    style.textContent = 'html, body { overscroll-behavior: none !important; }';
+    document.documentElement.appendChild(style);
+  };
+  if (document.documentElement) disable();
+  else document.addEventListener('readystatechange', disable, { once: true });
+})();"#;
@@ -0,0 +1,51 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Reports the `DOMContentLoaded` event of the page on the engines which don't expose it.
+
+use std::rc::Rc;
+
+use super::{
+  ipc_router::{IpcRouter, Route},
+  PageLoadEvent, WebViewAttributes,
+};
+
+/// Prefix of the IPC messages reporting that the DOM of the top frame was parsed, followed by
+/// its url.
+pub(crate) const DOM_CONTENT_LOADED_MESSAGE_PREFIX: &str = "__WRY_DOM_CONTENT_LOADED__:";
+
+/// Posts the url of the top frame once its DOM is parsed, or right away if the script runs after.
+pub(crate) const DOM_CONTENT_LOADED_SCRIPT: &str = r#"(function () {
+  if (window !== window.top) return;
+  var report = function () {
+    window.ipc.postMessage('__WRY_DOM_CONTENT_LOADED__:' + window.location.href);
+  };
+  if (document.readyState === 'loading') {
+    document.addEventListener('DOMContentLoaded', report, { once: true });
+  } else {
+    report();
+  }
+})();"#;
+
+/// The route of the `DOMContentLoaded` messages to `handler`.
+pub(crate) fn route(handler: Rc<dyn Fn(PageLoadEvent, String)>) -> Route {
+  (
+    DOM_CONTENT_LOADED_MESSAGE_PREFIX,
+    Box::new(move |_, url| handler(PageLoadEvent::DomContentLoaded, url.to_string())),
+  )
+}
+
+/// Reports the `DOMContentLoaded` events to the page load handler of `attributes`, which the
+/// backend keeps reporting the other events to.
+pub(crate) fn install(attributes: &mut WebViewAttributes, router: &mut IpcRouter) {
+  if let Some(handler) = attributes.on_page_load_handler.take() {
+    let handler: Rc<dyn Fn(PageLoadEvent, String)> = Rc::from(handler);
+    let handler_ = handler.clone();
+    attributes.on_page_load_handler = Some(Box::new(move |event, url| handler_(event, url)));
+    router.add(route(handler));
+    attributes
+      .initialization_scripts
+      .push(router.sign(DOM_CONTENT_LOADED_SCRIPT));
+  }
+}
@@ -0,0 +1,85 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::{
+  ipc_router::{evaluate, IpcRouter, Route, ScriptEvaluator},
+  WebViewAttributes,
+};
+
+/// How the content of the webview is printed, see
+/// [`WebView::print_with_options`](super::WebView::print_with_options).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct PrintOptions {
+  /// The name of the printer, one of [`printers`](super::printers), or the default printer of the
+  /// system if `None`.
+  pub printer: Option<String>,
+  /// Whether the content is printed without showing the print dialog, e.g. for receipts and
+  /// labels.
+  pub silent: bool,
+}
+
+/// What to do when a page calls `window.print()`, see
+/// [`WebViewBuilder::with_print_handler`](super::WebViewBuilder::with_print_handler).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrintDecision {
+  /// Show the print dialog of the engine.
+  Print,
+  /// Don't print, the application handled it.
+  Handled,
+}
+
+/// Prefix of the IPC messages of the calls to `window.print()`, followed by the URL of the page.
+pub(crate) const PRINT_MESSAGE_PREFIX: &str = "__WRY_PRINT__:";
+
+/// Replaces `window.print()` of the top frame to let the application decide, keeping the native
+/// one to print once allowed.
+pub(crate) const PRINT_SCRIPT: &str = r#"(function () {
+  if (window !== window.top || window.__WRY_PRINT__) return;
+  var print = window.print;
+  window.print = function () {
+    window.ipc.postMessage('__WRY_PRINT__:' + location.href);
+  };
+  Object.defineProperty(window, '__WRY_PRINT__', {
+    value: {
+      print: function () {
+        print.call(window);
+      }
+    }
+  });
+})();"#;
+
+/// Shows the print dialog of the engine, bypassing the print handler.
+pub(crate) const NATIVE_PRINT_SCRIPT: &str =
+  "window.__WRY_PRINT__ ? window.__WRY_PRINT__.print() : window.print()";
+
+/// The route of the `window.print()` messages to `handler`, printing with `evaluator` if it lets
+/// the engine print.
+pub(crate) fn route(
+  handler: Box<dyn Fn(String) -> PrintDecision>,
+  evaluator: ScriptEvaluator,
+) -> Route {
+  (
+    PRINT_MESSAGE_PREFIX,
+    Box::new(move |_, url| {
+      if handler(url.to_string()) == PrintDecision::Print {
+        evaluate(&evaluator, NATIVE_PRINT_SCRIPT)
+      }
+    }),
+  )
+}
+
+/// Sends the `window.print()` calls of the pages to the print handler of `attributes`, printing
+/// with `evaluator` once allowed.
+pub(crate) fn install(
+  attributes: &mut WebViewAttributes,
+  router: &mut IpcRouter,
+  evaluator: &ScriptEvaluator,
+) {
+  if let Some(handler) = attributes.print_handler.take() {
+    router.add(route(handler, evaluator.clone()));
+    attributes
+      .initialization_scripts
+      .push(router.sign(PRINT_SCRIPT));
+  }
+}
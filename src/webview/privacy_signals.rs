@@ -0,0 +1,45 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Tells the websites that the user opts out of being tracked and of the sale of their data, see
+//! [`WebViewBuilder::with_privacy_signals`](super::WebViewBuilder::with_privacy_signals).
+
+use http::{HeaderMap, HeaderValue};
+
+use super::{default_headers, WebViewAttributes};
+
+/// The `DNT` and `Sec-GPC` headers sent with the requests.
+pub(crate) fn headers() -> HeaderMap {
+  let mut headers = HeaderMap::new();
+  headers.insert("dnt", HeaderValue::from_static("1"));
+  headers.insert("sec-gpc", HeaderValue::from_static("1"));
+  headers
+}
+
+/// Exposes the signals to the scripts of the pages, like the browsers sending the headers.
+pub(crate) const PRIVACY_SIGNALS_SCRIPT: &str = r#"(function () {
+  var define = function (name, value) {
+    try {
+      Object.defineProperty(Navigator.prototype, name, {
+        get: function () { return value; },
+        configurable: true
+      });
+    } catch (e) {}
+  };
+  define('globalPrivacyControl', true);
+  define('doNotTrack', '1');
+})();"#;
+
+/// Sends the signals with the requests and exposes them to the pages if `attributes` enables
+/// them.
+pub(crate) fn install(attributes: &mut WebViewAttributes) {
+  if !attributes.privacy_signals {
+    return;
+  }
+  let headers = attributes.default_headers.take().unwrap_or_default();
+  attributes.default_headers = Some(default_headers::merge(&headers, self::headers()));
+  attributes
+    .initialization_scripts
+    .insert(0, PRIVACY_SIGNALS_SCRIPT.to_string());
+}
@@ -0,0 +1,465 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Forwards custom protocol requests to another process over a local IPC transport.
+//!
+//! Each request opens a new connection to the endpoint and is written as a plain HTTP/1.1 message
+//! with an absolute-form request target (e.g. `GET wry://localhost/index.html HTTP/1.1`), a
+//! `Content-Length` header and a `Connection: close` header. The endpoint must answer with an
+//! HTTP/1.1 response which either has a `Content-Length` header, is chunked, or is terminated by
+//! closing the connection. The request fails if the endpoint sends nothing for [`READ_TIMEOUT`].
+//!
+//! The development servers are reached the same way over TCP, with origin-form request targets.
+
+use std::{
+  io::{self, BufRead, BufReader, Read, Write},
+  net::TcpStream,
+  path::Path,
+  time::Duration,
+};
+
+use http::{
+  header::{
+    HeaderName, HeaderValue, ACCEPT_ENCODING, CONNECTION, CONTENT_LENGTH, HOST, TRANSFER_ENCODING,
+  },
+  Method, Request, Response, StatusCode,
+};
+
+/// How long the endpoint can stay silent while it's expected to send its response, so one which
+/// keeps the connection open can't hang the request.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sends `request` to the endpoint at `path` and waits for its response.
+///
+/// Transport or framing errors are turned into a `502 Bad Gateway` response, and timeouts into a
+/// `504 Gateway Timeout` response.
+pub(crate) fn forward(path: &Path, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+  exchange(|| connect(path), &request, READ_TIMEOUT).unwrap_or_else(|e| {
+    log::warn!(
+      "failed to forward `{}` to `{}`: {e}",
+      request.uri(),
+      path.display()
+    );
+    gateway_error(e)
+  })
+}
+
+/// Sends `request` to the development server listening on `port` of the local host, for the path
+/// and query of its URL, and waits for its response.
+///
+/// Transport or framing errors are turned into a `502 Bad Gateway` response, and timeouts into a
+/// `504 Gateway Timeout` response.
+pub(crate) fn forward_to_dev_server(port: u16, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+  let request = dev_server_request(port, request);
+  let connect = || TcpStream::connect(("localhost", port));
+  exchange(connect, &request, READ_TIMEOUT).unwrap_or_else(|e| {
+    log::warn!(
+      "failed to forward `{}` to the development server on port {port}: {e}",
+      request.uri(),
+    );
+    gateway_error(e)
+  })
+}
+
+/// The request to a development server, without the headers of the custom protocol connection,
+/// nor compression which the webview wouldn't decode.
+fn dev_server_request(port: u16, request: Request<Vec<u8>>) -> Request<Vec<u8>> {
+  let (mut parts, body) = request.into_parts();
+  parts.uri = parts
+    .uri
+    .path_and_query()
+    .map_or("/", |path| path.as_str())
+    .parse()
+    .unwrap_or_default();
+  for name in [HOST, ACCEPT_ENCODING] {
+    parts.headers.remove(name);
+  }
+  parts.headers.insert(
+    HOST,
+    HeaderValue::from_str(&format!("localhost:{port}")).unwrap(),
+  );
+  Request::from_parts(parts, body)
+}
+
+fn exchange<S: Connection>(
+  connect: impl FnOnce() -> io::Result<S>,
+  request: &Request<Vec<u8>>,
+  timeout: Duration,
+) -> io::Result<Response<Vec<u8>>> {
+  let mut stream = connect()?;
+  stream.set_read_timeout(timeout)?;
+  write_request(&mut stream, request)?;
+  stream.flush()?;
+  read_response(&mut BufReader::new(stream), request.method())
+}
+
+fn gateway_error(error: io::Error) -> Response<Vec<u8>> {
+  let status = match error.kind() {
+    // Reading a socket past its timeout fails with `WouldBlock` on Unix.
+    io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => StatusCode::GATEWAY_TIMEOUT,
+    _ => StatusCode::BAD_GATEWAY,
+  };
+  let mut response = Response::new(error.to_string().into_bytes());
+  *response.status_mut() = status;
+  response
+}
+
+/// Rewrites the WebSocket URLs of the page's own host to the development server on `port`, for
+/// its hot module replacement, since custom protocols can't carry WebSockets.
+pub(crate) fn dev_server_websocket_script(port: u16) -> String {
+  format!(
+    r#"(function () {{
+  var NativeWebSocket = window.WebSocket;
+  if (!NativeWebSocket) return;
+  var DevServerWebSocket = function (url, protocols) {{
+    var target = new URL(url, window.location.href);
+    if (target.host === window.location.host) {{
+      url = 'ws://localhost:{port}' + target.pathname + target.search;
+    }}
+    return protocols === undefined
+      ? new NativeWebSocket(url)
+      : new NativeWebSocket(url, protocols);
+  }};
+  DevServerWebSocket.prototype = NativeWebSocket.prototype;
+  ['CONNECTING', 'OPEN', 'CLOSING', 'CLOSED'].forEach(function (state) {{
+    DevServerWebSocket[state] = NativeWebSocket[state];
+  }});
+  window.WebSocket = DevServerWebSocket;
+}})();"#
+  )
+}
+
+/// A connection to an endpoint, whose reads fail once it stays silent for a timeout.
+trait Connection: Read + Write {
+  fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()>;
+}
+
+impl Connection for TcpStream {
+  fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+    TcpStream::set_read_timeout(self, Some(timeout))
+  }
+}
+
+#[cfg(unix)]
+impl Connection for std::os::unix::net::UnixStream {
+  fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+    std::os::unix::net::UnixStream::set_read_timeout(self, Some(timeout))
+  }
+}
+
+#[cfg(unix)]
+fn connect(path: &Path) -> io::Result<std::os::unix::net::UnixStream> {
+  std::os::unix::net::UnixStream::connect(path)
+}
+
+#[cfg(windows)]
+fn connect(path: &Path) -> io::Result<Pipe> {
+  // Named pipes (`\\.\pipe\<name>`) are opened like regular files.
+  let file = std::fs::OpenOptions::new()
+    .read(true)
+    .write(true)
+    .open(path)?;
+  Ok(Pipe {
+    file,
+    timeout: READ_TIMEOUT,
+  })
+}
+
+/// A named pipe, which has no read timeout of its own in blocking mode, so its reads wait for
+/// data to be available before reading it.
+#[cfg(windows)]
+struct Pipe {
+  file: std::fs::File,
+  timeout: Duration,
+}
+
+#[cfg(windows)]
+impl Read for Pipe {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::{Foundation::HANDLE, System::Pipes::PeekNamedPipe};
+
+    let handle = HANDLE(self.file.as_raw_handle() as isize);
+    let deadline = std::time::Instant::now() + self.timeout;
+    loop {
+      let mut available = 0;
+      // Fails once the endpoint closed the pipe, for the read to report it.
+      let peeked = unsafe { PeekNamedPipe(handle, None, 0, None, Some(&mut available), None) };
+      if peeked.is_err() || available > 0 {
+        return self.file.read(buf);
+      }
+      if std::time::Instant::now() >= deadline {
+        return Err(io::ErrorKind::TimedOut.into());
+      }
+      std::thread::sleep(Duration::from_millis(10));
+    }
+  }
+}
+
+#[cfg(windows)]
+impl Write for Pipe {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    self.file.write(buf)
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    self.file.flush()
+  }
+}
+
+#[cfg(windows)]
+impl Connection for Pipe {
+  fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+    self.timeout = timeout;
+    Ok(())
+  }
+}
+
+fn write_request(writer: &mut impl Write, request: &Request<Vec<u8>>) -> io::Result<()> {
+  write!(
+    writer,
+    "{} {} HTTP/1.1\r\n",
+    request.method(),
+    request.uri()
+  )?;
+  for (name, value) in request.headers() {
+    if name == CONTENT_LENGTH || name == CONNECTION {
+      continue;
+    }
+    writer.write_all(name.as_str().as_bytes())?;
+    writer.write_all(b": ")?;
+    writer.write_all(value.as_bytes())?;
+    writer.write_all(b"\r\n")?;
+  }
+  // A single exchange per connection, so the end of the response is known even without framing.
+  writer.write_all(b"connection: close\r\n")?;
+  write!(writer, "content-length: {}\r\n\r\n", request.body().len())?;
+  writer.write_all(request.body())
+}
+
+fn read_response(reader: &mut impl BufRead, method: &Method) -> io::Result<Response<Vec<u8>>> {
+  let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+  let status_line = read_line(reader)?;
+  let mut parts = status_line.splitn(3, ' ');
+  if !parts.next().unwrap_or_default().starts_with("HTTP/") {
+    return Err(invalid("malformed status line"));
+  }
+  let status = parts
+    .next()
+    .and_then(|code| StatusCode::from_bytes(code.as_bytes()).ok())
+    .ok_or_else(|| invalid("malformed status code"))?;
+
+  let mut response = Response::new(Vec::new());
+  *response.status_mut() = status;
+
+  loop {
+    let line = read_line(reader)?;
+    if line.is_empty() {
+      break;
+    }
+    let (name, value) = line
+      .split_once(':')
+      .ok_or_else(|| invalid("malformed header"))?;
+    let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| invalid("bad header"))?;
+    let value = HeaderValue::from_str(value.trim()).map_err(|_| invalid("bad header"))?;
+    response.headers_mut().append(name, value);
+  }
+
+  // These responses have no body, whatever their headers say.
+  if method == Method::HEAD
+    || status.is_informational()
+    || status == StatusCode::NO_CONTENT
+    || status == StatusCode::NOT_MODIFIED
+  {
+    return Ok(response);
+  }
+
+  let content_length = response
+    .headers()
+    .get(CONTENT_LENGTH)
+    .map(|v| {
+      v.to_str()
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .ok_or_else(|| invalid("bad content-length"))
+    })
+    .transpose()?;
+
+  if response
+    .headers()
+    .get(TRANSFER_ENCODING)
+    .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"chunked"))
+  {
+    response.headers_mut().remove(TRANSFER_ENCODING);
+    read_chunks(reader, response.body_mut())?;
+    return Ok(response);
+  }
+
+  let body = response.body_mut();
+  match content_length {
+    Some(len) => {
+      reader.take(len).read_to_end(body)?;
+      if (body.len() as u64) < len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+      }
+    }
+    None => {
+      reader.read_to_end(body)?;
+    }
+  }
+
+  Ok(response)
+}
+
+fn read_chunks(reader: &mut impl BufRead, body: &mut Vec<u8>) -> io::Result<()> {
+  loop {
+    let line = read_line(reader)?;
+    // The size can be followed by chunk extensions.
+    let size = line.split(';').next().unwrap_or_default().trim();
+    let size = u64::from_str_radix(size, 16)
+      .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bad chunk size"))?;
+    if size == 0 {
+      // Skip the trailers.
+      while !read_line(reader)?.is_empty() {}
+      return Ok(());
+    }
+    let start = body.len();
+    reader.take(size).read_to_end(body)?;
+    if ((body.len() - start) as u64) < size {
+      return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    read_line(reader)?;
+  }
+}
+
+fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+  let mut line = String::new();
+  if reader.read_line(&mut line)? == 0 {
+    return Err(io::ErrorKind::UnexpectedEof.into());
+  }
+  Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_encode_request() {
+    let request = Request::builder()
+      .method("POST")
+      .uri("wry://localhost/api")
+      .header("x-test", "1")
+      .header("connection", "keep-alive")
+      .body(b"hello".to_vec())
+      .unwrap();
+    let mut buffer = Vec::new();
+    write_request(&mut buffer, &request).unwrap();
+    assert_eq!(
+      String::from_utf8(buffer).unwrap(),
+      "POST wry://localhost/api HTTP/1.1\r\nx-test: 1\r\nconnection: close\r\ncontent-length: 5\r\n\r\nhello"
+    );
+  }
+
+  #[test]
+  fn should_decode_response() {
+    let raw =
+      b"HTTP/1.1 404 Not Found\r\ncontent-type: text/plain\r\ncontent-length: 4\r\n\r\nnopeextra";
+    let response = read_response(&mut &raw[..], &Method::GET).unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()["content-type"], "text/plain");
+    assert_eq!(response.body(), b"nope");
+
+    let raw = b"HTTP/1.1 200 OK\r\n\r\nuntil eof";
+    assert_eq!(
+      read_response(&mut &raw[..], &Method::GET).unwrap().body(),
+      b"until eof"
+    );
+  }
+
+  #[test]
+  fn should_decode_chunked_response() {
+    let raw = b"HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n4\r\nhell\r\n1;ext=1\r\no\r\n0\r\n\r\n";
+    let response = read_response(&mut &raw[..], &Method::GET).unwrap();
+    assert_eq!(response.body(), b"hello");
+    assert!(response.headers().get(TRANSFER_ENCODING).is_none());
+  }
+
+  #[test]
+  fn should_not_read_the_body_of_bodyless_responses() {
+    for raw in [
+      &b"HTTP/1.1 204 No Content\r\n\r\nnext"[..],
+      b"HTTP/1.1 304 Not Modified\r\ncontent-length: 4\r\n\r\n",
+      b"HTTP/1.1 103 Early Hints\r\n\r\n",
+    ] {
+      assert!(read_response(&mut &raw[..], &Method::GET)
+        .unwrap()
+        .body()
+        .is_empty());
+    }
+    let raw = b"HTTP/1.1 200 OK\r\ncontent-length: 4\r\n\r\n";
+    assert!(read_response(&mut &raw[..], &Method::HEAD)
+      .unwrap()
+      .body()
+      .is_empty());
+  }
+
+  #[test]
+  fn should_time_out_when_the_endpoint_keeps_the_connection_open() {
+    let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+      let (mut stream, _) = listener.accept().unwrap();
+      stream.write_all(b"HTTP/1.1 200 OK\r\n\r\npartial").unwrap();
+      // Keeps the connection open until the client gives up.
+      let _ = stream.read(&mut [0; 1024]);
+      let _ = stream.read(&mut [0; 1024]);
+    });
+
+    let request = Request::new(Vec::new());
+    let connect = || TcpStream::connect(("localhost", port));
+    let error = exchange(connect, &request, Duration::from_millis(100)).unwrap_err();
+    assert_eq!(gateway_error(error).status(), StatusCode::GATEWAY_TIMEOUT);
+    server.join().unwrap();
+  }
+
+  #[test]
+  fn should_forward_to_dev_server() {
+    let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+      let (stream, _) = listener.accept().unwrap();
+      let mut reader = BufReader::new(stream);
+      let mut head = Vec::new();
+      loop {
+        let line = read_line(&mut reader).unwrap();
+        if line.is_empty() {
+          break;
+        }
+        head.push(line);
+      }
+      reader
+        .get_mut()
+        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok")
+        .unwrap();
+      head
+    });
+
+    let request = Request::builder()
+      .uri("wry://localhost/src/main.ts?t=1")
+      .header("accept-encoding", "gzip")
+      .body(Vec::new())
+      .unwrap();
+    let response = forward_to_dev_server(port, request);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.body(), b"ok");
+
+    let head = server.join().unwrap();
+    assert_eq!(head[0], "GET /src/main.ts?t=1 HTTP/1.1");
+    assert!(head.contains(&format!("host: localhost:{port}")));
+    assert!(head.contains(&"connection: close".to_string()));
+    assert!(!head.iter().any(|line| line.starts_with("accept-encoding")));
+  }
+}
@@ -0,0 +1,200 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  sync::Arc,
+  time::{Duration, Instant},
+};
+
+use http::{
+  header::{AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, SET_COOKIE},
+  HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode,
+};
+
+use super::RequestAsyncResponder;
+
+type ProtocolHandler = Box<dyn Fn(Request<Vec<u8>>, RequestAsyncResponder)>;
+
+/// A request handled by a custom protocol, as reported by a [`ProtocolLogger`].
+#[derive(Debug, Clone)]
+pub struct ProtocolLogEntry {
+  pub method: Method,
+  pub uri: String,
+  /// The request headers, with the sensitive values redacted.
+  pub request_headers: HeaderMap,
+  /// Size of the request body, in bytes.
+  pub request_size: usize,
+  pub status: StatusCode,
+  /// The response headers, with the sensitive values redacted.
+  pub response_headers: HeaderMap,
+  /// Size of the response body, in bytes.
+  pub response_size: usize,
+  /// Time between the start of the request and its response.
+  pub duration: Duration,
+}
+
+/// Logs the requests and responses of the custom protocols of a webview.
+///
+/// By default the entries are emitted to the [`log`](https://docs.rs/log) crate with the
+/// `wry::protocol` target, and the `Authorization`, `Proxy-Authorization`, `Cookie` and
+/// `Set-Cookie` headers are redacted.
+///
+/// See [`WebViewBuilder::with_protocol_logger`](super::WebViewBuilder::with_protocol_logger).
+pub struct ProtocolLogger {
+  redact: Box<dyn Fn(&HeaderName) -> bool + Send + Sync>,
+  sink: Option<Box<dyn Fn(&ProtocolLogEntry) + Send + Sync>>,
+}
+
+impl Default for ProtocolLogger {
+  fn default() -> Self {
+    Self {
+      redact: Box::new(|name| {
+        [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE, SET_COOKIE].contains(name)
+      }),
+      sink: None,
+    }
+  }
+}
+
+impl ProtocolLogger {
+  /// Create a logger emitting to the `log` crate.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Set which headers have their value replaced by `[redacted]`, replacing the default list.
+  pub fn with_redaction<F>(mut self, redact: F) -> Self
+  where
+    F: Fn(&HeaderName) -> bool + Send + Sync + 'static,
+  {
+    self.redact = Box::new(redact);
+    self
+  }
+
+  /// Send the entries to `sink` instead of the `log` crate.
+  ///
+  /// It may be called from any thread, depending on where the protocol handlers respond.
+  pub fn with_sink<F>(mut self, sink: F) -> Self
+  where
+    F: Fn(&ProtocolLogEntry) + Send + Sync + 'static,
+  {
+    self.sink = Some(Box::new(sink));
+    self
+  }
+
+  fn redacted(&self, headers: &HeaderMap) -> HeaderMap {
+    let mut headers = headers.clone();
+    for (name, value) in headers.iter_mut() {
+      if (self.redact)(name) {
+        *value = HeaderValue::from_static("[redacted]");
+      }
+    }
+    headers
+  }
+
+  fn emit(&self, entry: &ProtocolLogEntry) {
+    match &self.sink {
+      Some(sink) => sink(entry),
+      None => {
+        log::info!(
+          target: "wry::protocol",
+          "{} {} {} in {:?} ({} bytes sent, {} bytes received)",
+          entry.method,
+          entry.uri,
+          entry.status,
+          entry.duration,
+          entry.request_size,
+          entry.response_size
+        );
+        log::debug!(
+          target: "wry::protocol",
+          "{} {} request headers: {:?}, response headers: {:?}",
+          entry.method,
+          entry.uri,
+          entry.request_headers,
+          entry.response_headers
+        );
+      }
+    }
+  }
+
+  /// Wraps a protocol handler to log its requests.
+  pub(crate) fn wrap(self: &Arc<Self>, handler: ProtocolHandler) -> ProtocolHandler {
+    let logger = self.clone();
+    Box::new(move |request, responder| {
+      let started = Instant::now();
+      let method = request.method().clone();
+      let uri = request.uri().to_string();
+      let request_headers = logger.redacted(request.headers());
+      let request_size = request.body().len();
+
+      let logger = logger.clone();
+      let respond = responder.responder;
+      let responder = RequestAsyncResponder {
+        responder: Box::new(move |response| {
+          logger.emit(&ProtocolLogEntry {
+            method,
+            uri,
+            request_headers,
+            request_size,
+            status: response.status(),
+            response_headers: logger.redacted(response.headers()),
+            response_size: response.body().len(),
+            duration: started.elapsed(),
+          });
+          respond(response)
+        }),
+      };
+      handler(request, responder)
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::Mutex;
+
+  #[test]
+  fn should_log_redacted_entry() {
+    let entries = Arc::new(Mutex::new(Vec::new()));
+    let sink = entries.clone();
+    let logger = Arc::new(
+      ProtocolLogger::new().with_sink(move |entry| sink.lock().unwrap().push(entry.clone())),
+    );
+
+    let handler = logger.wrap(Box::new(|_request, responder| {
+      responder.respond(
+        http::Response::builder()
+          .status(StatusCode::CREATED)
+          .header(SET_COOKIE, "session=secret")
+          .body(b"done".to_vec())
+          .unwrap(),
+      )
+    }));
+    let request = Request::builder()
+      .method(Method::POST)
+      .uri("wry://localhost/api")
+      .header(AUTHORIZATION, "Bearer secret")
+      .header("x-trace", "1")
+      .body(b"hello".to_vec())
+      .unwrap();
+    handler(
+      request,
+      RequestAsyncResponder {
+        responder: Box::new(|_| {}),
+      },
+    );
+
+    let entries = entries.lock().unwrap();
+    let entry = &entries[0];
+    assert_eq!(entry.method, Method::POST);
+    assert_eq!(entry.uri, "wry://localhost/api");
+    assert_eq!(entry.status, StatusCode::CREATED);
+    assert_eq!((entry.request_size, entry.response_size), (5, 4));
+    assert_eq!(entry.request_headers[AUTHORIZATION], "[redacted]");
+    assert_eq!(entry.request_headers["x-trace"], "1");
+    assert_eq!(entry.response_headers[SET_COOKIE], "[redacted]");
+  }
+}
@@ -0,0 +1,135 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{borrow::Cow, collections::HashMap};
+
+use http::{Request, Response as HttpResponse, StatusCode};
+
+use super::RequestAsyncResponder;
+
+type RouteHandler = Box<dyn Fn(Request<Vec<u8>>, RequestAsyncResponder)>;
+
+/// Dispatches the requests of a single custom protocol to different handlers based on the host
+/// component of their URL, e.g. `app://settings/...` and `app://docs/...`.
+///
+/// Register it with [`WebViewBuilder::with_custom_protocol_router`](super::WebViewBuilder::with_custom_protocol_router).
+///
+/// # Examples
+///
+/// ```no_run
+/// use wry::webview::CustomProtocolRouter;
+///
+/// let router = CustomProtocolRouter::new()
+///   .with_route("settings", |_request| {
+///     http::Response::builder()
+///       .body(b"settings".to_vec().into())
+///       .unwrap()
+///   })
+///   .with_route("docs", |_request| {
+///     http::Response::builder()
+///       .body(b"docs".to_vec().into())
+///       .unwrap()
+///   });
+/// ```
+#[derive(Default)]
+pub struct CustomProtocolRouter {
+  routes: HashMap<String, RouteHandler>,
+  fallback: Option<RouteHandler>,
+}
+
+impl CustomProtocolRouter {
+  /// Create a router without any route.
+  ///
+  /// Requests which don't match a route are answered with `404 Not Found`.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Mount a handler on the given host. Hosts are matched case-insensitively.
+  ///
+  /// Registering the same host twice replaces the previous handler.
+  pub fn with_route<F>(self, host: &str, handler: F) -> Self
+  where
+    F: Fn(Request<Vec<u8>>) -> HttpResponse<Cow<'static, [u8]>> + 'static,
+  {
+    self.with_asynchronous_route(host, move |request, responder| {
+      responder.respond(handler(request))
+    })
+  }
+
+  /// Same as [`Self::with_route`] but with an asynchronous responder.
+  pub fn with_asynchronous_route<F>(mut self, host: &str, handler: F) -> Self
+  where
+    F: Fn(Request<Vec<u8>>, RequestAsyncResponder) + 'static,
+  {
+    self
+      .routes
+      .insert(host.to_ascii_lowercase(), Box::new(handler));
+    self
+  }
+
+  /// Set the handler of the requests which don't match any route.
+  pub fn with_fallback<F>(mut self, handler: F) -> Self
+  where
+    F: Fn(Request<Vec<u8>>, RequestAsyncResponder) + 'static,
+  {
+    self.fallback = Some(Box::new(handler));
+    self
+  }
+
+  /// Finds the handler of a request made to the `scheme` protocol.
+  fn resolve(&self, scheme: &str, request: &Request<Vec<u8>>) -> Option<&RouteHandler> {
+    let host = request
+      .uri()
+      .host()
+      .unwrap_or_default()
+      .to_ascii_lowercase();
+    // Android serves custom protocols as `http(s)://<scheme>.<host>/`.
+    let host = host
+      .strip_prefix(scheme)
+      .and_then(|h| h.strip_prefix('.'))
+      .filter(|_| request.uri().scheme_str() != Some(scheme))
+      .unwrap_or(&host);
+    self.routes.get(host).or(self.fallback.as_ref())
+  }
+
+  pub(crate) fn dispatch(
+    &self,
+    scheme: &str,
+    request: Request<Vec<u8>>,
+    responder: RequestAsyncResponder,
+  ) {
+    match self.resolve(scheme, &request) {
+      Some(handler) => handler(request, responder),
+      None => responder.respond(
+        HttpResponse::builder()
+          .status(StatusCode::NOT_FOUND)
+          .body(Vec::new())
+          .unwrap(),
+      ),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_resolve_route_by_host() {
+    let router = CustomProtocolRouter::new()
+      .with_asynchronous_route("Settings", |_, _| {})
+      .with_asynchronous_route("docs", |_, _| {});
+    let request = |uri: &str| Request::builder().uri(uri).body(Vec::new()).unwrap();
+
+    let settings = router.routes.get("settings").unwrap() as *const _;
+    let docs = router.routes.get("docs").unwrap() as *const _;
+    let resolve = |uri: &str| router.resolve("app", &request(uri)).map(|h| h as *const _);
+
+    assert_eq!(resolve("app://settings/index.html"), Some(settings));
+    assert_eq!(resolve("app://DOCS/"), Some(docs));
+    assert_eq!(resolve("https://app.docs/page"), Some(docs));
+    assert_eq!(resolve("app://unknown/"), None);
+  }
+}
@@ -0,0 +1,75 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The child windows hosting the webviews embedded in the windows of other toolkits, see
+//! [`WebViewBuilder::new_in_raw_window`](super::WebViewBuilder::new_in_raw_window).
+
+use raw_window_handle::HasRawWindowHandle;
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+use raw_window_handle::RawWindowHandle;
+
+use crate::{
+  application::{
+    event_loop::EventLoopWindowTarget,
+    window::{Window, WindowBuilder},
+  },
+  Error, Result,
+};
+
+/// Build a borderless window inside `parent`, of the size of its client area.
+pub(crate) fn build_child_window<T: 'static>(
+  event_loop: &EventLoopWindowTarget<T>,
+  parent: &impl HasRawWindowHandle,
+) -> Result<Window> {
+  let builder = WindowBuilder::new().with_decorations(false);
+  match parent.raw_window_handle() {
+    #[cfg(target_os = "windows")]
+    RawWindowHandle::Win32(handle) => {
+      use crate::application::{dpi::PhysicalSize, platform::windows::WindowBuilderExtWindows};
+      use windows::Win32::{
+        Foundation::{HWND, RECT},
+        UI::WindowsAndMessaging::GetClientRect,
+      };
+
+      let mut rect = RECT::default();
+      unsafe { GetClientRect(HWND(handle.hwnd as _), &mut rect) }
+        .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))?;
+      let size = PhysicalSize::new(
+        (rect.right - rect.left) as u32,
+        (rect.bottom - rect.top) as u32,
+      );
+      Ok(
+        builder
+          .with_inner_size(size)
+          .with_parent_window(handle.hwnd as _)
+          .build(event_loop)?,
+      )
+    }
+    #[cfg(target_os = "macos")]
+    RawWindowHandle::AppKit(handle) => {
+      use crate::application::platform::macos::{WindowBuilderExtMacOS, WindowExtMacOS};
+      use cocoa::{
+        base::{id, YES},
+        foundation::NSRect,
+      };
+
+      let window = builder
+        .with_parent_window(handle.ns_window)
+        .build(event_loop)?;
+      unsafe {
+        // The child windows are laid out in the coordinates of the screen, like their parent.
+        let parent = handle.ns_window as id;
+        let frame: NSRect = msg_send![parent, frame];
+        let content: NSRect = msg_send![parent, contentRectForFrameRect: frame];
+        let child = window.ns_window() as id;
+        let () = msg_send![child, setFrame: content display: YES];
+      }
+      Ok(window)
+    }
+    _ => {
+      let _ = (event_loop, builder);
+      Err(Error::Unsupported)
+    }
+  }
+}
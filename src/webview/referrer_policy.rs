@@ -0,0 +1,183 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use http::{header::REFERRER_POLICY, HeaderValue, Request};
+use url::Url;
+
+use super::RequestAsyncResponder;
+
+/// Which referrer is sent with the requests of the pages, like the
+/// [`Referrer-Policy`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Referrer-Policy)
+/// header, see [`WebViewBuilder::with_referrer_policy`](super::WebViewBuilder::with_referrer_policy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferrerPolicy {
+  /// Never send a referrer.
+  NoReferrer,
+  /// Send the whole URL, unless going from HTTPS to HTTP.
+  NoReferrerWhenDowngrade,
+  /// Only send the origin of the page.
+  Origin,
+  /// Send the whole URL to the same origin, and only the origin to the other ones.
+  OriginWhenCrossOrigin,
+  /// Send the whole URL to the same origin, and nothing to the other ones.
+  SameOrigin,
+  /// Only send the origin of the page, unless going from HTTPS to HTTP.
+  StrictOrigin,
+  /// Send the whole URL to the same origin, only the origin to the other ones, and nothing when
+  /// going from HTTPS to HTTP. This is the default of the engines.
+  StrictOriginWhenCrossOrigin,
+  /// Always send the whole URL.
+  UnsafeUrl,
+}
+
+impl ReferrerPolicy {
+  /// The value of the `Referrer-Policy` header.
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::NoReferrer => "no-referrer",
+      Self::NoReferrerWhenDowngrade => "no-referrer-when-downgrade",
+      Self::Origin => "origin",
+      Self::OriginWhenCrossOrigin => "origin-when-cross-origin",
+      Self::SameOrigin => "same-origin",
+      Self::StrictOrigin => "strict-origin",
+      Self::StrictOriginWhenCrossOrigin => "strict-origin-when-cross-origin",
+      Self::UnsafeUrl => "unsafe-url",
+    }
+  }
+
+  /// The referrer sent by `referrer` when requesting `destination`, `None` if none is sent.
+  #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+  pub(crate) fn referrer(self, referrer: &Url, destination: &Url) -> Option<String> {
+    let mut url = referrer.clone();
+    // These are never part of a referrer.
+    url.set_fragment(None);
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    let full = Some(url.to_string());
+    // The origins of the non special schemes are opaque, they aren't sent on their own.
+    let origin = referrer.origin();
+    let origin = origin
+      .is_tuple()
+      .then(|| format!("{}/", origin.ascii_serialization()));
+
+    let same_origin = referrer.origin() == destination.origin();
+    let downgrade = is_trustworthy(referrer) && !is_trustworthy(destination);
+    match self {
+      Self::NoReferrer => None,
+      Self::NoReferrerWhenDowngrade if downgrade => None,
+      Self::NoReferrerWhenDowngrade => full,
+      Self::Origin => origin,
+      Self::OriginWhenCrossOrigin if same_origin => full,
+      Self::OriginWhenCrossOrigin => origin,
+      Self::SameOrigin if same_origin => full,
+      Self::SameOrigin => None,
+      Self::StrictOrigin if downgrade => None,
+      Self::StrictOrigin => origin,
+      Self::StrictOriginWhenCrossOrigin if same_origin => full,
+      Self::StrictOriginWhenCrossOrigin if downgrade => None,
+      Self::StrictOriginWhenCrossOrigin => origin,
+      Self::UnsafeUrl => full,
+    }
+  }
+}
+
+// The custom protocols are served by the application, they are as trustworthy as HTTPS.
+fn is_trustworthy(url: &Url) -> bool {
+  !matches!(url.scheme(), "http" | "ws")
+    || matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"))
+}
+
+type ProtocolHandler<B> = Box<dyn Fn(Request<B>, RequestAsyncResponder)>;
+
+/// Wraps the `handler` of a custom protocol to add the `Referrer-Policy` header to its responses,
+/// unless it sets it.
+pub(crate) fn apply<B: 'static>(
+  policy: ReferrerPolicy,
+  handler: ProtocolHandler<B>,
+) -> ProtocolHandler<B> {
+  Box::new(move |request, responder| {
+    handler(
+      request,
+      RequestAsyncResponder {
+        responder: Box::new(move |mut response| {
+          response
+            .headers_mut()
+            .entry(REFERRER_POLICY)
+            .or_insert(HeaderValue::from_static(policy.as_str()));
+          responder.respond(response)
+        }),
+      },
+    )
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::borrow::Cow;
+
+  fn referrer(policy: ReferrerPolicy, destination: &str) -> Option<String> {
+    let referrer = Url::parse("https://app.localhost/settings?tab=2#token").unwrap();
+    policy.referrer(&referrer, &Url::parse(destination).unwrap())
+  }
+
+  #[test]
+  fn should_compute_referrer() {
+    let full = Some("https://app.localhost/settings?tab=2".to_string());
+    let origin = Some("https://app.localhost/".to_string());
+    let same = "https://app.localhost/api";
+    let cross = "https://cdn.example.com/lib.js";
+    let downgrade = "http://cdn.example.com/lib.js";
+
+    assert_eq!(referrer(ReferrerPolicy::NoReferrer, same), None);
+    assert_eq!(referrer(ReferrerPolicy::UnsafeUrl, downgrade), full);
+    assert_eq!(referrer(ReferrerPolicy::Origin, same), origin);
+    assert_eq!(referrer(ReferrerPolicy::SameOrigin, same), full);
+    assert_eq!(referrer(ReferrerPolicy::SameOrigin, cross), None);
+    assert_eq!(referrer(ReferrerPolicy::StrictOrigin, downgrade), None);
+    let strict = ReferrerPolicy::StrictOriginWhenCrossOrigin;
+    assert_eq!(referrer(strict, same), full);
+    assert_eq!(referrer(strict, cross), origin);
+    assert_eq!(referrer(strict, downgrade), None);
+    assert_eq!(
+      referrer(
+        ReferrerPolicy::NoReferrerWhenDowngrade,
+        "http://localhost:8080/"
+      ),
+      full
+    );
+  }
+
+  #[test]
+  fn should_not_send_opaque_origins() {
+    let referrer = Url::parse("wry://localhost/index.html").unwrap();
+    let destination = Url::parse("https://example.com/").unwrap();
+    assert_eq!(
+      ReferrerPolicy::StrictOriginWhenCrossOrigin.referrer(&referrer, &destination),
+      None
+    );
+    assert_eq!(
+      ReferrerPolicy::UnsafeUrl.referrer(&referrer, &destination),
+      Some("wry://localhost/index.html".to_string())
+    );
+  }
+
+  #[test]
+  fn should_add_referrer_policy_header() {
+    let handler = apply::<Vec<u8>>(
+      ReferrerPolicy::NoReferrer,
+      Box::new(|_, responder| responder.respond(http::Response::new(Cow::Borrowed(&[][..])))),
+    );
+    let response = std::rc::Rc::new(std::cell::RefCell::new(None));
+    let response_ = response.clone();
+    handler(
+      Request::new(Vec::new()),
+      RequestAsyncResponder {
+        responder: Box::new(move |r| *response_.borrow_mut() = Some(r)),
+      },
+    );
+    let response = response.borrow_mut().take().unwrap();
+    assert_eq!(response.headers()[REFERRER_POLICY], "no-referrer");
+  }
+}
@@ -0,0 +1,91 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  fmt,
+  io::{self, Read},
+};
+
+use http::Request;
+
+use super::RequestAsyncResponder;
+
+/// The body of a request to a streaming custom protocol, see
+/// [`WebViewBuilder::with_streaming_custom_protocol`](super::WebViewBuilder::with_streaming_custom_protocol).
+///
+/// It is read from the engine in chunks as the handler reads it, so large uploads aren't held in
+/// memory. It can be moved to another thread to be read there, which keeps the main thread
+/// responsive during large uploads.
+pub struct RequestBody(Box<dyn Read + Send>);
+
+impl RequestBody {
+  pub(crate) fn new(reader: impl Read + Send + 'static) -> Self {
+    Self(Box::new(reader))
+  }
+
+  #[cfg(any(target_os = "macos", target_os = "ios", target_os = "android"))]
+  pub(crate) fn from_vec(body: Vec<u8>) -> Self {
+    Self::new(io::Cursor::new(body))
+  }
+
+  #[cfg(not(target_os = "android"))]
+  pub(crate) fn empty() -> Self {
+    Self::new(io::empty())
+  }
+}
+
+impl Read for RequestBody {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    self.0.read(buf)
+  }
+}
+
+impl fmt::Debug for RequestBody {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("RequestBody").finish_non_exhaustive()
+  }
+}
+
+pub(crate) type ProtocolHandler = Box<dyn Fn(Request<Vec<u8>>, RequestAsyncResponder)>;
+pub(crate) type StreamingProtocolHandler = Box<dyn Fn(Request<RequestBody>, RequestAsyncResponder)>;
+
+/// Adapts the handler of a custom protocol taking the whole body, which is read before calling it,
+/// on the thread of the engine.
+pub(crate) fn buffered(handler: ProtocolHandler) -> StreamingProtocolHandler {
+  Box::new(move |request, responder| {
+    let (parts, mut body) = request.into_parts();
+    let mut buffer = Vec::new();
+    if let Err(e) = body.read_to_end(&mut buffer) {
+      log::warn!("failed to read the body of `{}`: {e}", parts.uri);
+    }
+    handler(Request::from_parts(parts, buffer), responder)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::{cell::RefCell, rc::Rc};
+
+  #[test]
+  fn should_buffer_the_body() {
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let received_ = received.clone();
+    let handler = buffered(Box::new(move |request, _| {
+      *received_.borrow_mut() = request.into_body()
+    }));
+    let request = Request::builder()
+      .method("POST")
+      .uri("wry://localhost/upload")
+      .body(RequestBody::new(&b"chunked upload"[..]))
+      .unwrap();
+    handler(
+      request,
+      RequestAsyncResponder {
+        responder: Box::new(|_| {}),
+      },
+    );
+    assert_eq!(&received.borrow()[..], b"chunked upload");
+  }
+}
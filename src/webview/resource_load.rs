@@ -0,0 +1,173 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::{
+  har::HarRecorder,
+  ipc_router::{IpcRouter, Route},
+  WebViewAttributes,
+};
+
+use std::time::{Duration, SystemTime};
+
+/// A subresource loaded by the page, see
+/// [`WebViewBuilder::with_resource_load_handler`](super::WebViewBuilder::with_resource_load_handler).
+///
+/// The timings come from the [resource timing](https://developer.mozilla.org/docs/Web/API/PerformanceResourceTiming)
+/// entries of the page. `start` is relative to the start of the navigation of the page, and
+/// `started_at` is the same instant on the system clock.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceLoadInfo {
+  pub url: String,
+  /// The HTTP status of the response, `None` if the load failed, or wasn't exposed by the
+  /// engine or the server.
+  pub status: Option<u16>,
+  /// The MIME type of the response, `None` if it wasn't exposed by the engine.
+  pub mime_type: Option<String>,
+  /// What loaded the resource, like `script`, `img`, `css` or `fetch`.
+  pub initiator_type: String,
+  pub start: Duration,
+  pub started_at: SystemTime,
+  pub duration: Duration,
+  /// The time from `start` to the first byte of the response, `None` if it wasn't exposed, e.g.
+  /// for a cross-origin resource without a `Timing-Allow-Origin` header.
+  pub time_to_first_byte: Option<Duration>,
+  /// The size of the response including its headers, `None` if it wasn't exposed, e.g. for a
+  /// cross-origin resource without a `Timing-Allow-Origin` header.
+  pub transfer_size: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct Message {
+  url: String,
+  status: u16,
+  mime: String,
+  initiator: String,
+  origin: f64,
+  start: f64,
+  response: f64,
+  duration: f64,
+  size: u64,
+}
+
+/// Prefix of the IPC messages carrying the resources loaded by the page.
+pub(crate) const RESOURCE_LOAD_MESSAGE_PREFIX: &str = "__WRY_RESOURCE_LOAD__:";
+
+/// Reports the resource timing entries of the top frame, including the ones buffered before the
+/// script runs. `responseStatus` and `contentType` are missing on the older engines.
+pub(crate) const RESOURCE_LOAD_SCRIPT: &str = r#"(function () {
+  if (window !== window.top || typeof PerformanceObserver === 'undefined') return;
+  new PerformanceObserver(function (list) {
+    list.getEntries().forEach(function (entry) {
+      window.ipc.postMessage('__WRY_RESOURCE_LOAD__:' + JSON.stringify({
+        url: entry.name,
+        status: entry.responseStatus || 0,
+        mime: entry.contentType || '',
+        initiator: entry.initiatorType,
+        origin: performance.timeOrigin,
+        start: entry.startTime,
+        response: entry.responseStart,
+        duration: entry.duration,
+        size: entry.transferSize || 0
+      }));
+    });
+  }).observe({ type: 'resource', buffered: true });
+})();"#;
+
+// The timings are in milliseconds, which the engines coarsen to a few microseconds anyway. The
+// negative ones are clamped to zero by the cast.
+fn duration(milliseconds: f64) -> Duration {
+  Duration::from_micros((milliseconds * 1e3).round() as u64)
+}
+
+/// Parse the JSON of a resource load message, `None` if it is malformed.
+pub(crate) fn parse_resource_load_message(json: &str) -> Option<ResourceLoadInfo> {
+  let message: Message = serde_json::from_str(json).ok()?;
+  Some(ResourceLoadInfo {
+    url: message.url,
+    status: Some(message.status).filter(|status| *status != 0),
+    mime_type: Some(message.mime).filter(|mime| !mime.is_empty()),
+    initiator_type: message.initiator,
+    start: duration(message.start),
+    started_at: SystemTime::UNIX_EPOCH + duration(message.origin + message.start),
+    duration: duration(message.duration),
+    time_to_first_byte: (message.response > 0.).then(|| duration(message.response - message.start)),
+    transfer_size: Some(message.size).filter(|size| *size != 0),
+  })
+}
+
+/// The route of the resource messages to `handler` and to the HAR captures of `har_recorder`.
+pub(crate) fn route(
+  handler: Option<Box<dyn Fn(ResourceLoadInfo)>>,
+  har_recorder: Option<HarRecorder>,
+) -> Route {
+  (
+    RESOURCE_LOAD_MESSAGE_PREFIX,
+    Box::new(move |_, json| {
+      let recording = har_recorder.as_ref().is_some_and(HarRecorder::is_capturing);
+      if handler.is_none() && !recording {
+        return;
+      }
+      if let Some(info) = parse_resource_load_message(json) {
+        if let Some(har_recorder) = &har_recorder {
+          har_recorder.record(&info);
+        }
+        if let Some(handler) = &handler {
+          handler(info)
+        }
+      }
+    }),
+  )
+}
+
+/// Reports the resources loaded by the pages to the resource load handler of `attributes` and to
+/// the HAR captures of `har_recorder`.
+pub(crate) fn install(
+  attributes: &mut WebViewAttributes,
+  router: &mut IpcRouter,
+  har_recorder: Option<HarRecorder>,
+) {
+  let handler = attributes.resource_load_handler.take();
+  if handler.is_some() || har_recorder.is_some() {
+    router.add(route(handler, har_recorder));
+    attributes
+      .initialization_scripts
+      .push(router.sign(RESOURCE_LOAD_SCRIPT));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_resource_load_message() {
+    let info = parse_resource_load_message(
+      r#"{"url":"https://example.com/app.js","status":200,"mime":"text/javascript","initiator":"script","origin":1700000000000,"start":12.5,"response":32.5,"duration":40,"size":1024}"#,
+    )
+    .unwrap();
+    assert_eq!(info.status, Some(200));
+    assert_eq!(info.mime_type.as_deref(), Some("text/javascript"));
+    assert_eq!(info.start, Duration::from_micros(12500));
+    assert_eq!(
+      info.started_at,
+      SystemTime::UNIX_EPOCH + Duration::from_micros(1_700_000_000_012_500)
+    );
+    assert_eq!(info.duration, Duration::from_millis(40));
+    assert_eq!(info.time_to_first_byte, Some(Duration::from_millis(20)));
+    assert_eq!(info.transfer_size, Some(1024));
+
+    let info = parse_resource_load_message(
+      r#"{"url":"https://cdn.example.com/a.png","status":0,"mime":"","initiator":"img","origin":0,"start":-1,"response":0,"duration":3,"size":0}"#,
+    )
+    .unwrap();
+    assert_eq!(info.status, None);
+    assert_eq!(info.mime_type, None);
+    assert_eq!(info.start, Duration::ZERO);
+    assert_eq!(info.time_to_first_byte, None);
+    assert_eq!(info.transfer_size, None);
+
+    assert_eq!(parse_resource_load_message(r#"{"url":"a"}"#), None);
+  }
+}
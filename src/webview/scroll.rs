@@ -0,0 +1,142 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Mirrors the scroll position of the page, so it can be read without evaluating a script.
+
+use std::{cell::Cell, rc::Rc};
+
+use super::{
+  ipc_router::{IpcRouter, Route},
+  WebViewAttributes,
+};
+
+/// A change of the scroll position of the page, see
+/// [`WebViewBuilder::with_scroll_handler`](super::WebViewBuilder::with_scroll_handler).
+///
+/// The offsets are in CSS pixels from the top left corner of the page, and the deltas are relative
+/// to the previous event, positive when scrolling down and to the right.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollEvent {
+  pub x: f64,
+  pub y: f64,
+  pub delta_x: f64,
+  pub delta_y: f64,
+}
+
+impl ScrollEvent {
+  pub(crate) fn new((x, y): (f64, f64), (previous_x, previous_y): (f64, f64)) -> Self {
+    Self {
+      x,
+      y,
+      delta_x: x - previous_x,
+      delta_y: y - previous_y,
+    }
+  }
+}
+
+/// Prefix of the IPC messages carrying the scroll position of the page, followed by `<x>,<y>`.
+pub(crate) const SCROLL_MESSAGE_PREFIX: &str = "__WRY_SCROLL__:";
+
+/// Reports the scroll position of the top frame once per frame while it scrolls, and when a page
+/// is shown, so the position of the previous page isn't kept.
+pub(crate) const SCROLL_SCRIPT: &str = r#"(function () {
+  if (window !== window.top) return;
+  var pending = false;
+  var report = function () {
+    pending = false;
+    window.ipc.postMessage('__WRY_SCROLL__:' + window.scrollX + ',' + window.scrollY);
+  };
+  window.addEventListener('scroll', function () {
+    if (pending) return;
+    pending = true;
+    requestAnimationFrame(report);
+  }, { passive: true });
+  window.addEventListener('pageshow', report);
+})();"#;
+
+/// Parse the `<x>,<y>` of a scroll message.
+pub(crate) fn parse_scroll_message(message: &str) -> Option<(f64, f64)> {
+  let (x, y) = message.split_once(',')?;
+  Some((x.parse().ok()?, y.parse().ok()?))
+}
+
+/// Scrolls the top frame to `(x, y)`, smoothly if `animated`.
+pub(crate) fn scroll_to_script(x: f64, y: f64, animated: bool) -> String {
+  let behavior = if animated { "smooth" } else { "instant" };
+  let (x, y) = (finite(x), finite(y));
+  format!("window.scrollTo({{ left: {x}, top: {y}, behavior: '{behavior}' }});")
+}
+
+// `NaN` and the infinites aren't formatted as javascript numbers.
+fn finite(value: f64) -> f64 {
+  if value.is_finite() {
+    value
+  } else {
+    0.
+  }
+}
+
+/// The route of the scroll messages, storing the scroll position in `position` and calling
+/// `handler`.
+pub(crate) fn route(
+  position: Rc<Cell<(f64, f64)>>,
+  handler: Option<Box<dyn Fn(ScrollEvent)>>,
+) -> Route {
+  (
+    SCROLL_MESSAGE_PREFIX,
+    Box::new(move |_, message| {
+      if let Some(current) = parse_scroll_message(message) {
+        let previous = position.replace(current);
+        if let Some(handler) = &handler {
+          handler(ScrollEvent::new(current, previous));
+        }
+      }
+    }),
+  )
+}
+
+/// Reports the scroll position of the pages to the scroll handler of `attributes`, or only tracks
+/// it with [`WebViewAttributes::scroll_tracking`], returning the tracked position.
+pub(crate) fn install(
+  attributes: &mut WebViewAttributes,
+  router: &mut IpcRouter,
+) -> Rc<Cell<(f64, f64)>> {
+  let position = Rc::new(Cell::new((0., 0.)));
+  if attributes.scroll_handler.is_some() || attributes.scroll_tracking {
+    router.add(route(position.clone(), attributes.scroll_handler.take()));
+    attributes
+      .initialization_scripts
+      .push(router.sign(SCROLL_SCRIPT));
+  }
+  position
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_scroll_message() {
+    assert_eq!(parse_scroll_message("0,120.5"), Some((0., 120.5)));
+    assert_eq!(parse_scroll_message("0"), None);
+    assert_eq!(parse_scroll_message("a,b"), None);
+  }
+
+  #[test]
+  fn should_compute_scroll_delta() {
+    let event = ScrollEvent::new((0., 80.), (0., 120.));
+    assert_eq!(event.y, 80.);
+    assert_eq!(event.delta_y, -40.);
+    assert_eq!(event.delta_x, 0.);
+  }
+
+  #[test]
+  fn should_build_scroll_to_script() {
+    assert_eq!(
+      scroll_to_script(0., 240., true),
+      "window.scrollTo({ left: 0, top: 240, behavior: 'smooth' });"
+    );
+  }
+}
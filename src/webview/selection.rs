@@ -0,0 +1,135 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Reads the text selected in the page from an isolated world, and reports its changes.
+
+use super::{
+  ipc_router::{IpcRouter, Route},
+  WebViewAttributes,
+};
+use crate::Result;
+
+/// Prefix of the IPC messages carrying the text selected in the page, followed by the text.
+pub(crate) const SELECTION_MESSAGE_PREFIX: &str = "__WRY_SELECTION__:";
+
+/// The isolated world [`SELECTED_TEXT_EXPRESSION`] is evaluated in, so the page can't replace the
+/// functions it calls.
+pub(crate) const SELECTION_WORLD: &str = "wry-selection";
+
+/// A function returning the selected text, including the selection of the focused text field
+/// except the password fields, and the selection of the focused same-origin frames.
+macro_rules! selected_text_function {
+  () => {
+    r#"function () {
+    var selectionOf = function (window) {
+      var field = window.document.activeElement;
+      while (field && field.shadowRoot && field.shadowRoot.activeElement) {
+        field = field.shadowRoot.activeElement;
+      }
+      if (field && (field.tagName === 'IFRAME' || field.tagName === 'FRAME')) {
+        try {
+          return selectionOf(field.contentWindow);
+        } catch (e) {}
+      }
+      if (field && (field.tagName === 'TEXTAREA' || field.tagName === 'INPUT') && field.type !== 'password') {
+        try {
+          if (field.selectionStart !== null && field.selectionStart !== field.selectionEnd) {
+            return field.value.substring(field.selectionStart, field.selectionEnd);
+          }
+        } catch (e) {}
+      }
+      var selection = window.getSelection();
+      return selection ? selection.toString() : '';
+    };
+    return selectionOf(window);
+  }"#
+  };
+}
+
+/// Evaluates to the selected text, see [`WebView::selected_text`](super::WebView::selected_text).
+pub(crate) const SELECTED_TEXT_EXPRESSION: &str = concat!("(", selected_text_function!(), ")()");
+
+/// Reports the selected text once per frame while it changes, and when a page is shown, so the
+/// selection of the previous page isn't kept.
+pub(crate) const SELECTION_SCRIPT: &str = concat!(
+  r#"(function () {
+  if (window !== window.top) return;
+  var pending = false;
+  var last = null;
+  var selectedText = "#,
+  selected_text_function!(),
+  r#";
+  var report = function () {
+    pending = false;
+    var text = selectedText();
+    if (text === last) return;
+    last = text;
+    window.ipc.postMessage('__WRY_SELECTION__:' + text);
+  };
+  document.addEventListener('selectionchange', function () {
+    if (pending) return;
+    pending = true;
+    requestAnimationFrame(report);
+  });
+  window.addEventListener('pageshow', function () {
+    last = null;
+    report();
+  });
+})();"#
+);
+
+/// The selected text of a selection message, `None` when nothing is selected.
+pub(crate) fn parse_selection_message(text: &str) -> Option<String> {
+  (!text.is_empty()).then(|| text.to_string())
+}
+
+/// The selected text from the JSON result of the wrapped [`SELECTED_TEXT_EXPRESSION`], `None` when
+/// nothing is selected.
+pub(crate) fn parse_selected_text(json: &str) -> Result<Option<String>> {
+  let text: String = super::typed_eval::parse_result(json)?;
+  Ok(parse_selection_message(&text))
+}
+
+/// The route of the selection messages, calling `handler` with the selected text.
+pub(crate) fn route(handler: Box<dyn Fn(Option<String>)>) -> Route {
+  (
+    SELECTION_MESSAGE_PREFIX,
+    Box::new(move |_, text| handler(parse_selection_message(text))),
+  )
+}
+
+/// Reports the selection changes of the pages to the selection changed handler of `attributes`.
+pub(crate) fn install(attributes: &mut WebViewAttributes, router: &mut IpcRouter) {
+  if let Some(handler) = attributes.selection_changed_handler.take() {
+    router.add(route(handler));
+    attributes
+      .initialization_scripts
+      .push(router.sign(SELECTION_SCRIPT));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_selection_message() {
+    assert_eq!(
+      parse_selection_message("hello,\nworld"),
+      Some("hello,\nworld".to_string())
+    );
+    assert_eq!(parse_selection_message(""), None);
+  }
+
+  #[test]
+  fn should_parse_selected_text() {
+    assert_eq!(
+      parse_selected_text(r#"{"ok":"hello"}"#).unwrap(),
+      Some("hello".to_string())
+    );
+    assert_eq!(parse_selected_text(r#"{"ok":""}"#).unwrap(), None);
+    assert!(parse_selected_text(r#"{"error":"TypeError"}"#).is_err());
+    assert!(SELECTION_SCRIPT.contains("var selectedText = function () {"));
+  }
+}
@@ -0,0 +1,26 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Scripts controlling the service workers of the page, for engines without a native setting.
+
+/// Makes `navigator.serviceWorker.register` fail and unregisters the existing service workers of
+/// the page origin.
+pub(crate) const DISABLE_SERVICE_WORKERS_SCRIPT: &str = r#"(function () {
+  var container = navigator.serviceWorker;
+  if (!container) return;
+  container.getRegistrations().then(function (registrations) {
+    registrations.forEach(function (registration) { registration.unregister(); });
+  });
+  Object.getPrototypeOf(container).register = function () {
+    return Promise.reject(new DOMException('Service workers are disabled', 'SecurityError'));
+  };
+})();"#;
+
+/// Resolves to the host names of the service worker registrations of the page origin.
+#[allow(dead_code)] // only used on Windows, the other engines list the registrations natively.
+pub(crate) const REGISTRATIONS_SCRIPT: &str = r#"navigator.serviceWorker
+  ? navigator.serviceWorker.getRegistrations().then(function (registrations) {
+      return registrations.map(function (registration) { return new URL(registration.scope).hostname; });
+    })
+  : []"#;
@@ -0,0 +1,107 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+use serde::Deserialize;
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+use crate::{Error, Result};
+
+/// Image format of a webview snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SnapshotFormat {
+  /// Portable Network Graphics.
+  Png,
+  /// JPEG, with the engine's default quality.
+  Jpeg,
+}
+
+/// Bounds of an element, relative to the viewport, in CSS pixels.
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ElementRect {
+  pub x: f64,
+  pub y: f64,
+  pub width: f64,
+  pub height: f64,
+  pub scroll_x: f64,
+  pub scroll_y: f64,
+  pub viewport_width: f64,
+  pub viewport_height: f64,
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+impl ElementRect {
+  /// The part of the element which is inside the viewport.
+  pub fn clip_to_viewport(self) -> Self {
+    let x = self.x.max(0.);
+    let y = self.y.max(0.);
+    Self {
+      x,
+      y,
+      width: ((self.x + self.width).min(self.viewport_width) - x).max(0.),
+      height: ((self.y + self.height).min(self.viewport_height) - y).max(0.),
+      ..self
+    }
+  }
+}
+
+/// Script scrolling the first element matching `selector` into view and returning its
+/// [`ElementRect`], or `null` if no element matches.
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub(crate) fn element_rect_script(selector: &str) -> String {
+  format!(
+    r#"(function () {{
+  var element = document.querySelector({});
+  if (!element) return null;
+  element.scrollIntoView({{ block: 'nearest', inline: 'nearest' }});
+  var rect = element.getBoundingClientRect();
+  return {{
+    x: rect.left, y: rect.top, width: rect.width, height: rect.height,
+    scrollX: window.scrollX, scrollY: window.scrollY,
+    viewportWidth: window.innerWidth, viewportHeight: window.innerHeight
+  }};
+}})()"#,
+    serde_json::Value::String(selector.into())
+  )
+}
+
+/// Parses the JSON result of [`element_rect_script`].
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub(crate) fn parse_element_rect(selector: &str, json: &str) -> Result<ElementRect> {
+  serde_json::from_str::<Option<ElementRect>>(json)?
+    .map(ElementRect::clip_to_viewport)
+    .filter(|rect| rect.width > 0. && rect.height > 0.)
+    .ok_or_else(|| Error::ElementNotFound(selector.into()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_element_rect_script_result() {
+    // The object returned by the script, as serialized by the engines.
+    let json = r#"{"x":-20,"y":580,"width":300,"height":100,"scrollX":0,"scrollY":1200.5,"viewportWidth":800,"viewportHeight":600}"#;
+    assert!(element_rect_script("#chart").contains("viewportWidth: window.innerWidth"));
+    assert_eq!(
+      parse_element_rect("#chart", json).unwrap(),
+      ElementRect {
+        x: 0.,
+        y: 580.,
+        width: 280.,
+        height: 20.,
+        scroll_x: 0.,
+        scroll_y: 1200.5,
+        viewport_width: 800.,
+        viewport_height: 600.,
+      }
+    );
+    assert!(matches!(
+      parse_element_rect("#missing", "null"),
+      Err(Error::ElementNotFound(_))
+    ));
+  }
+}
@@ -0,0 +1,16 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// Sets the `spellcheck` attribute of the root element of the pages, inherited by their editable
+/// elements which don't set their own, once the root element is created.
+pub(crate) fn spellcheck_script(enabled: bool) -> String {
+  format!(
+    r#"(function () {{
+  var apply = function () {{
+    if (!document.documentElement.hasAttribute('spellcheck')) document.documentElement.spellcheck = {enabled};
+  }};
+  if (document.documentElement) apply(); else document.addEventListener('DOMContentLoaded', apply);
+}})();"#
+  )
+}
@@ -0,0 +1,69 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// The automatic changes of the text typed in the editable content of the pages, see
+/// [`WebViewBuilder::with_text_input_settings`](super::WebViewBuilder::with_text_input_settings).
+///
+/// The settings which are `None` keep the default of the system.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextInputSettings {
+  /// Whether the misspelled words are corrected while typing.
+  pub autocorrect: Option<bool>,
+  /// Whether the first letter of the sentences is capitalized.
+  pub autocapitalize: Option<bool>,
+  /// Whether the straight quotes are replaced with the typographic ones.
+  pub smart_quotes: Option<bool>,
+  /// Whether the double hyphens are replaced with dashes.
+  pub smart_dashes: Option<bool>,
+  /// Whether the text replacements of the system, like `(c)` to `©`, are applied.
+  pub text_replacement: Option<bool>,
+}
+
+/// Sets the `autocorrect` and `autocapitalize` attributes on the focused editable elements which
+/// don't set their own, since they aren't inherited from the root element.
+pub(crate) fn text_input_script(settings: &TextInputSettings) -> Option<String> {
+  let mut attributes = serde_json::Map::new();
+  if let Some(autocorrect) = settings.autocorrect {
+    let value = if autocorrect { "on" } else { "off" };
+    attributes.insert("autocorrect".into(), value.into());
+  }
+  if let Some(autocapitalize) = settings.autocapitalize {
+    let value = if autocapitalize { "sentences" } else { "none" };
+    attributes.insert("autocapitalize".into(), value.into());
+  }
+  if attributes.is_empty() {
+    return None;
+  }
+  let attributes = serde_json::Value::Object(attributes);
+  Some(format!(
+    r#"(function () {{
+  var attributes = {attributes};
+  document.addEventListener('focusin', function (event) {{
+    var element = event.target;
+    if (!element || !element.setAttribute) return;
+    for (var name in attributes) {{
+      if (!element.hasAttribute(name)) element.setAttribute(name, attributes[name]);
+    }}
+  }}, true);
+}})();"#
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_build_text_input_script() {
+    assert_eq!(text_input_script(&TextInputSettings::default()), None);
+    let script = text_input_script(&TextInputSettings {
+      autocorrect: Some(false),
+      autocapitalize: Some(false),
+      smart_quotes: Some(false),
+      ..Default::default()
+    })
+    .unwrap();
+    assert!(script.contains(r#"var attributes = {"autocapitalize":"none","autocorrect":"off"};"#));
+  }
+}
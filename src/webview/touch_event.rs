@@ -0,0 +1,178 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::application::{dpi::LogicalPosition, event::TouchPhase};
+
+/// A finger touching the webview, at a position in CSS pixels relative to its top left corner at
+/// the default zoom.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchPoint {
+  /// Identifies the finger across the events of a touch sequence.
+  pub id: u32,
+  pub position: LogicalPosition<f64>,
+}
+
+impl TouchPoint {
+  pub fn new(id: u32, position: LogicalPosition<f64>) -> Self {
+    Self { id, position }
+  }
+}
+
+/// A synthetic touch input sent to the page with
+/// [`WebView::send_touch_event`](super::WebView::send_touch_event).
+///
+/// The `touches` are the fingers which started touching the webview, moved, or were lifted,
+/// depending on the `phase`. The other fingers of the sequence keep touching it where they were.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TouchEvent {
+  pub phase: TouchPhase,
+  pub touches: Vec<TouchPoint>,
+}
+
+impl TouchEvent {
+  pub fn new(phase: TouchPhase, touches: Vec<TouchPoint>) -> Self {
+    Self { phase, touches }
+  }
+
+  /// The sequence of a tap of one finger at `position`.
+  pub fn tap(position: LogicalPosition<f64>) -> Vec<Self> {
+    let touch = vec![TouchPoint::new(0, position)];
+    vec![
+      Self::new(TouchPhase::Started, touch.clone()),
+      Self::new(TouchPhase::Ended, touch),
+    ]
+  }
+
+  /// The sequence of a swipe of one finger from `from` to `to`, moving in `steps` events.
+  pub fn swipe(from: LogicalPosition<f64>, to: LogicalPosition<f64>, steps: u32) -> Vec<Self> {
+    let steps = steps.max(1);
+    let mut events = vec![Self::new(
+      TouchPhase::Started,
+      vec![TouchPoint::new(0, from)],
+    )];
+    for step in 1..=steps {
+      let position = lerp(from, to, step as f64 / steps as f64);
+      events.push(Self::new(
+        TouchPhase::Moved,
+        vec![TouchPoint::new(0, position)],
+      ));
+    }
+    events.push(Self::new(TouchPhase::Ended, vec![TouchPoint::new(0, to)]));
+    events
+  }
+
+  /// The sequence of a pinch of two fingers on each side of `center`, moving horizontally from
+  /// `start_distance` to `end_distance` pixels apart in `steps` events. The page zooms out when
+  /// the end distance is smaller.
+  pub fn pinch(
+    center: LogicalPosition<f64>,
+    start_distance: f64,
+    end_distance: f64,
+    steps: u32,
+  ) -> Vec<Self> {
+    let steps = steps.max(1);
+    let fingers = |distance: f64| {
+      vec![
+        TouchPoint::new(0, LogicalPosition::new(center.x - distance / 2., center.y)),
+        TouchPoint::new(1, LogicalPosition::new(center.x + distance / 2., center.y)),
+      ]
+    };
+    let mut events = vec![Self::new(TouchPhase::Started, fingers(start_distance))];
+    for step in 1..=steps {
+      let progress = step as f64 / steps as f64;
+      let distance = start_distance + (end_distance - start_distance) * progress;
+      events.push(Self::new(TouchPhase::Moved, fingers(distance)));
+    }
+    events.push(Self::new(TouchPhase::Ended, fingers(end_distance)));
+    events
+  }
+}
+
+fn lerp(
+  from: LogicalPosition<f64>,
+  to: LogicalPosition<f64>,
+  progress: f64,
+) -> LogicalPosition<f64> {
+  LogicalPosition::new(
+    from.x + (to.x - from.x) * progress,
+    from.y + (to.y - from.y) * progress,
+  )
+}
+
+/// Updates the fingers touching the webview with `event`, and returns the parameters of the
+/// [`Input.dispatchTouchEvent`](https://chromedevtools.github.io/devtools-protocol/tot/Input/#method-dispatchTouchEvent)
+/// devtools protocol method, which takes all of them.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn devtools_params(
+  active: &mut Vec<TouchPoint>,
+  event: &TouchEvent,
+) -> serde_json::Value {
+  let started = active.is_empty();
+  for touch in &event.touches {
+    active.retain(|active| active.id != touch.id);
+    if matches!(event.phase, TouchPhase::Started | TouchPhase::Moved) {
+      active.push(*touch);
+    }
+  }
+  let points: Vec<_> = active
+    .iter()
+    .map(
+      |touch| serde_json::json!({ "x": touch.position.x, "y": touch.position.y, "id": touch.id }),
+    )
+    .collect();
+  // Only the first and last events of the sequence start and end it, the points missing from the
+  // other ones were lifted.
+  let event_type = match event.phase {
+    TouchPhase::Cancelled if points.is_empty() => "touchCancel",
+    _ if points.is_empty() => "touchEnd",
+    TouchPhase::Started if started => "touchStart",
+    _ => "touchMove",
+  };
+  serde_json::json!({ "type": event_type, "touchPoints": points })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_build_sequences() {
+    let tap = TouchEvent::tap(LogicalPosition::new(5., 5.));
+    assert_eq!(tap.len(), 2);
+    assert_eq!(tap[1].phase, TouchPhase::Ended);
+
+    let swipe = TouchEvent::swipe(
+      LogicalPosition::new(0., 0.),
+      LogicalPosition::new(0., 100.),
+      4,
+    );
+    assert_eq!(swipe.len(), 6);
+    assert_eq!(swipe[2].touches[0].position, LogicalPosition::new(0., 50.));
+
+    let pinch = TouchEvent::pinch(LogicalPosition::new(100., 50.), 100., 20., 2);
+    assert_eq!(pinch[0].touches[0].position, LogicalPosition::new(50., 50.));
+    assert_eq!(
+      pinch[3].touches[1].position,
+      LogicalPosition::new(110., 50.)
+    );
+  }
+
+  #[test]
+  fn should_track_active_touches() {
+    let mut active = Vec::new();
+    let pinch = TouchEvent::pinch(LogicalPosition::new(100., 50.), 100., 20., 1);
+    let params = devtools_params(&mut active, &pinch[0]);
+    assert_eq!(params["type"], "touchStart");
+    assert_eq!(params["touchPoints"].as_array().unwrap().len(), 2);
+    assert_eq!(devtools_params(&mut active, &pinch[1])["type"], "touchMove");
+
+    let lift = TouchEvent::new(TouchPhase::Ended, vec![pinch[2].touches[0]]);
+    let params = devtools_params(&mut active, &lift);
+    assert_eq!(params["type"], "touchMove");
+    assert_eq!(params["touchPoints"][0]["id"], 1);
+    assert_eq!(devtools_params(&mut active, &pinch[2])["type"], "touchEnd");
+    assert!(active.is_empty());
+  }
+}
@@ -0,0 +1,97 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Evaluates an expression and reports its value or exception as JSON, since the engines either
+//! drop the exceptions of evaluated scripts or report them inconsistently, and binds serialized
+//! arguments to functions.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+#[derive(Deserialize)]
+struct Outcome {
+  #[serde(default)]
+  ok: serde_json::Value,
+  error: Option<String>,
+}
+
+/// Wraps the `expression` so it evaluates to `{ ok: value }` or `{ error: message }`.
+pub(crate) fn wrap_expression(expression: &str) -> String {
+  // The new lines keep a trailing `//` comment from commenting out the rest of the wrapper.
+  format!(
+    r#"(function () {{
+  try {{
+    var value = ({}
+    );
+    return {{ ok: value === undefined ? null : value }};
+  }} catch (e) {{
+    return {{ error: String(e) }};
+  }}
+}})()"#,
+    expression.trim().trim_end_matches(';')
+  )
+}
+
+/// Serializes `args` into a JSON array, valid as a javascript array literal.
+pub(crate) fn serialize_arguments<A: Serialize>(args: &[A]) -> Result<String> {
+  Ok(serde_json::to_string(args)?)
+}
+
+/// Calls the `function` expression with the arguments from [`serialize_arguments`], without
+/// string-formatting them into the function itself.
+pub(crate) fn apply_arguments(function: &str, args: &str) -> String {
+  format!(
+    "({}\n).apply(null, {})",
+    function.trim().trim_end_matches(';'),
+    args
+  )
+}
+
+/// Parses the JSON result of a script from [`wrap_expression`].
+pub(crate) fn parse_result<T: DeserializeOwned>(json: &str) -> Result<T> {
+  let outcome: Outcome = serde_json::from_str(json)?;
+  match outcome.error {
+    Some(message) => Err(Error::JavaScriptException(message)),
+    None => Ok(serde_json::from_value(outcome.ok)?),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_wrap_expression() {
+    assert!(wrap_expression("document.title; ").contains("var value = (document.title\n"));
+  }
+
+  #[test]
+  fn should_apply_arguments() {
+    assert_eq!(
+      apply_arguments(
+        "(a, b) => a + b;",
+        &serialize_arguments(&[r#"x"); alert("y"#, "z"]).unwrap()
+      ),
+      r#"((a, b) => a + b
+).apply(null, ["x\"); alert(\"y","z"])"#
+    );
+  }
+
+  #[test]
+  fn should_parse_result() {
+    assert_eq!(parse_result::<Vec<u8>>(r#"{"ok":[1,2]}"#).unwrap(), [1, 2]);
+    assert_eq!(parse_result::<Option<u8>>(r#"{"ok":null}"#).unwrap(), None);
+    assert_eq!(parse_result::<Option<u8>>("{}").unwrap(), None);
+    assert!(matches!(
+      parse_result::<u8>(r#"{"error":"ReferenceError: x is not defined"}"#),
+      Err(Error::JavaScriptException(message)) if message == "ReferenceError: x is not defined"
+    ));
+    assert!(matches!(
+      parse_result::<u8>(r#"{"ok":"text"}"#),
+      Err(Error::Json(_))
+    ));
+    assert!(parse_result::<u8>("").is_err());
+  }
+}
@@ -0,0 +1,195 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Identifies an initialization script added with
+/// [`WebView::add_init_script`](super::WebView::add_init_script).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScriptId(u32);
+
+impl ScriptId {
+  pub(crate) fn next() -> Self {
+    static NEXT: AtomicU32 = AtomicU32::new(0);
+    Self(NEXT.fetch_add(1, Ordering::Relaxed))
+  }
+}
+
+/// When an initialization script runs while a page loads.
+///
+/// See [`InitScript::with_injection_time`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum InjectionTime {
+  /// Before any script of the page, when the document is still empty.
+  #[default]
+  DocumentStart,
+  /// Once the document is parsed, before the subresources like the images are loaded, i.e. when
+  /// the `DOMContentLoaded` event fires.
+  DocumentEnd,
+}
+
+/// An initialization script with its injection options.
+///
+/// See [`WebViewBuilder::with_init_script`](super::WebViewBuilder::with_init_script) and
+/// [`WebView::add_init_script_with`](super::WebView::add_init_script_with).
+///
+/// # Examples
+///
+/// ```
+/// use wry::webview::{InitScript, InjectionTime};
+///
+/// let script = InitScript::new("window.bridge = {}")
+///   .with_injection_time(InjectionTime::DocumentEnd)
+///   .for_main_frame_only(true);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitScript {
+  pub(crate) js: String,
+  pub(crate) injection_time: InjectionTime,
+  pub(crate) for_main_frame_only: bool,
+  pub(crate) url_patterns: Vec<String>,
+}
+
+impl InitScript {
+  /// Create a script running at document start in every frame.
+  pub fn new(js: &str) -> Self {
+    Self {
+      js: js.to_string(),
+      injection_time: InjectionTime::DocumentStart,
+      for_main_frame_only: false,
+      url_patterns: Vec::new(),
+    }
+  }
+
+  /// Set when the script runs while a page loads.
+  pub fn with_injection_time(mut self, time: InjectionTime) -> Self {
+    self.injection_time = time;
+    self
+  }
+
+  /// Run the script in the main frame only, and not in the iframes of the page, e.g. so a bridge
+  /// isn't exposed to untrusted embedded content.
+  pub fn for_main_frame_only(mut self, main_frame_only: bool) -> Self {
+    self.for_main_frame_only = main_frame_only;
+    self
+  }
+
+  /// Only run the script on the documents whose URL matches the glob `pattern`, where `*` matches
+  /// any sequence of characters and `?` a single character, e.g. `https://app.example.com/*`.
+  ///
+  /// The pattern must match the whole URL. When called several times, the script runs if any of
+  /// the patterns matches.
+  pub fn with_url_pattern(mut self, pattern: &str) -> Self {
+    self.url_patterns.push(pattern.to_string());
+    self
+  }
+
+  /// The script guarded by its URL patterns, which every engine checks in javascript so they
+  /// behave the same way.
+  pub(crate) fn source(&self) -> String {
+    if self.url_patterns.is_empty() {
+      return self.js.clone();
+    }
+    let patterns = self
+      .url_patterns
+      .iter()
+      .map(|pattern| glob_to_regex(pattern))
+      .collect::<Vec<_>>()
+      .join("|");
+    format!(
+      "if (new RegExp({}).test(location.href)) {{\n{}\n}}",
+      serde_json::Value::String(format!("^(?:{patterns})$")),
+      self.js
+    )
+  }
+
+  /// The script with its options applied in javascript, for engines injecting every script at
+  /// document start in all frames.
+  pub(crate) fn to_portable_script(&self) -> String {
+    let mut js = match self.injection_time {
+      InjectionTime::DocumentStart => self.source(),
+      InjectionTime::DocumentEnd => at_document_end(&self.source()),
+    };
+    if self.for_main_frame_only {
+      js = format!("if (window === window.top) {{\n{js}\n}}");
+    }
+    js
+  }
+}
+
+/// Translates a glob pattern to the source of a javascript regular expression.
+fn glob_to_regex(glob: &str) -> String {
+  let mut regex = String::with_capacity(glob.len());
+  for c in glob.chars() {
+    match c {
+      '*' => regex.push_str("[\\s\\S]*"),
+      '?' => regex.push_str("[\\s\\S]"),
+      '\\' | '^' | '$' | '.' | '|' | '+' | '(' | ')' | '[' | ']' | '{' | '}' | '/' => {
+        regex.push('\\');
+        regex.push(c);
+      }
+      _ => regex.push(c),
+    }
+  }
+  regex
+}
+
+/// Defers `js` to the `DOMContentLoaded` event, for engines only running scripts at document start.
+fn at_document_end(js: &str) -> String {
+  format!(
+    r#"(function () {{
+  var run = function () {{
+{js}
+  }};
+  if (document.readyState === 'loading') document.addEventListener('DOMContentLoaded', run, {{ once: true }});
+  else run();
+}})();"#
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_defer_to_document_end() {
+    let js = at_document_end("document.body.dataset.ready = 'yes' // done");
+    assert!(js.contains("\ndocument.body.dataset.ready = 'yes' // done\n"));
+    assert!(js.contains("addEventListener('DOMContentLoaded', run"));
+    assert_ne!(ScriptId::next(), ScriptId::next());
+  }
+
+  #[test]
+  fn should_apply_options_in_script() {
+    assert_eq!(InitScript::new("init()").to_portable_script(), "init()");
+    assert_eq!(
+      InitScript::new("init()")
+        .for_main_frame_only(true)
+        .to_portable_script(),
+      "if (window === window.top) {\ninit()\n}"
+    );
+    let js = InitScript::new("init()")
+      .with_injection_time(InjectionTime::DocumentEnd)
+      .for_main_frame_only(true)
+      .to_portable_script();
+    assert!(js.starts_with("if (window === window.top) {\n(function () {"));
+  }
+
+  #[test]
+  fn should_guard_url_patterns() {
+    assert_eq!(
+      glob_to_regex("https://*.example.com/?"),
+      r"https:\/\/[\s\S]*\.example\.com\/[\s\S]"
+    );
+    assert_eq!(
+      InitScript::new("init()")
+        .with_url_pattern("https://a.com/*")
+        .with_url_pattern("wry://(x)")
+        .source(),
+      r#"if (new RegExp("^(?:https:\\/\\/a\\.com\\/[\\s\\S]*|wry:\\/\\/\\(x\\))$").test(location.href)) {
+init()
+}"#
+    );
+  }
+}
@@ -0,0 +1,172 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Serves local folders on virtual hosts, see
+//! [`WebViewBuilder::with_virtual_host_mapping`](super::WebViewBuilder::with_virtual_host_mapping).
+
+use std::{
+  borrow::Cow,
+  path::{Path, PathBuf},
+  sync::Arc,
+};
+
+use http::{header::CONTENT_TYPE, Request, Response, StatusCode};
+
+use super::RequestAsyncResponder;
+
+/// The custom protocol serving the virtual hosts where WebView2 can't.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub(crate) const VIRTUAL_HOST_SCHEME: &str = "wry-host";
+
+/// The origin of the pages served from the folder mapped to `host` with
+/// [`WebViewBuilder::with_virtual_host_mapping`](super::WebViewBuilder::with_virtual_host_mapping),
+/// e.g. `https://app.local` on Windows and `wry-host://app.local` on the other platforms.
+pub fn virtual_host_origin(host: &str) -> String {
+  if cfg!(target_os = "windows") {
+    format!("https://{host}")
+  } else {
+    format!("{VIRTUAL_HOST_SCHEME}://{host}")
+  }
+}
+
+/// The custom protocol handler serving the files of the folders mapped to the hosts of the
+/// requests. The files are read on another thread.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub(crate) fn protocol_handler(
+  mappings: Vec<(String, PathBuf)>,
+) -> impl Fn(Request<Vec<u8>>, RequestAsyncResponder) + 'static {
+  let mappings = Arc::new(mappings);
+  move |request, responder| {
+    let mappings = mappings.clone();
+    std::thread::spawn(move || responder.respond(serve(&mappings, &request)));
+  }
+}
+
+fn response(status: StatusCode) -> Response<Cow<'static, [u8]>> {
+  Response::builder()
+    .status(status)
+    .body(Cow::Borrowed(&[][..]))
+    .unwrap()
+}
+
+fn serve(
+  mappings: &[(String, PathBuf)],
+  request: &Request<Vec<u8>>,
+) -> Response<Cow<'static, [u8]>> {
+  let host = request.uri().host().unwrap_or_default();
+  let Some((_, folder)) = mappings.iter().find(|(h, _)| h.eq_ignore_ascii_case(host)) else {
+    return response(StatusCode::NOT_FOUND);
+  };
+  let Some(path) = resolve(folder, request.uri().path()) else {
+    return response(StatusCode::FORBIDDEN);
+  };
+  match std::fs::read(&path) {
+    Ok(content) => Response::builder()
+      .header(CONTENT_TYPE, mime_type(&path))
+      .body(Cow::Owned(content))
+      .unwrap(),
+    Err(_) => response(StatusCode::NOT_FOUND),
+  }
+}
+
+/// The file of `folder` at the percent-encoded `path` of a URL, `None` if it leaves the folder.
+fn resolve(folder: &Path, path: &str) -> Option<PathBuf> {
+  let mut file = folder.to_path_buf();
+  for segment in path.split('/').filter(|s| !s.is_empty()) {
+    let segment = percent_decode(segment)?;
+    if segment == "." || segment == ".." || segment.contains(['/', '\\']) {
+      return None;
+    }
+    file.push(segment);
+  }
+  Some(file)
+}
+
+pub(crate) fn percent_decode(segment: &str) -> Option<String> {
+  let mut bytes = Vec::with_capacity(segment.len());
+  let mut rest = segment.as_bytes();
+  while let Some((&byte, tail)) = rest.split_first() {
+    if byte == b'%' {
+      let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+      bytes.push(u8::from_str_radix(hex, 16).ok()?);
+      rest = &tail[2..];
+    } else {
+      bytes.push(byte);
+      rest = tail;
+    }
+  }
+  String::from_utf8(bytes).ok()
+}
+
+pub(crate) fn mime_type(path: &Path) -> &'static str {
+  let extension = path
+    .extension()
+    .and_then(|e| e.to_str())
+    .unwrap_or_default()
+    .to_ascii_lowercase();
+  match extension.as_str() {
+    "html" | "htm" => "text/html",
+    "js" | "mjs" => "text/javascript",
+    "css" => "text/css",
+    "json" => "application/json",
+    "wasm" => "application/wasm",
+    "svg" => "image/svg+xml",
+    "png" => "image/png",
+    "jpg" | "jpeg" => "image/jpeg",
+    "gif" => "image/gif",
+    "webp" => "image/webp",
+    "ico" => "image/x-icon",
+    "woff" => "font/woff",
+    "woff2" => "font/woff2",
+    "ttf" => "font/ttf",
+    "txt" => "text/plain",
+    "xml" => "application/xml",
+    "mp4" => "video/mp4",
+    "webm" => "video/webm",
+    "mp3" => "audio/mpeg",
+    "wav" => "audio/wav",
+    _ => "application/octet-stream",
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_resolve_paths_inside_the_folder() {
+    let folder = Path::new("/srv/app");
+    assert_eq!(
+      resolve(folder, "/assets/my%20logo.png"),
+      Some(folder.join("assets").join("my logo.png"))
+    );
+    assert_eq!(resolve(folder, "/"), Some(folder.to_path_buf()));
+    assert_eq!(resolve(folder, "/../secret"), None);
+    assert_eq!(resolve(folder, "/a/%2e%2E/secret"), None);
+    assert_eq!(resolve(folder, "/a%2Fb"), None);
+    assert_eq!(resolve(folder, "/%zz"), None);
+  }
+
+  #[test]
+  fn should_serve_the_mapped_folder() {
+    let folder = std::env::temp_dir().join("wry-virtual-host-test");
+    std::fs::create_dir_all(&folder).unwrap();
+    std::fs::write(folder.join("index.html"), "<p>hi</p>").unwrap();
+    let mappings = [("app.local".to_string(), folder)];
+    let request = |uri: &str| Request::builder().uri(uri).body(Vec::new()).unwrap();
+
+    let response = serve(&mappings, &request("wry-host://App.Local/index.html"));
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "text/html");
+    assert_eq!(&response.body()[..], b"<p>hi</p>");
+    assert_eq!(
+      serve(&mappings, &request("wry-host://app.local/missing.js")).status(),
+      StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+      serve(&mappings, &request("wry-host://other.local/index.html")).status(),
+      StatusCode::NOT_FOUND
+    );
+  }
+}
@@ -11,13 +11,31 @@
 ))]
 use crate::webview::webkitgtk::WebContextImpl;
 
-use std::path::{Path, PathBuf};
+use crate::{
+  application::{event_loop::EventLoopWindowTarget, window::WindowBuilder},
+  webview::{content_blocker, har::HarRecorder, Har, WebView, WebViewBuilder},
+};
+
+use std::{
+  fmt,
+  path::{Path, PathBuf},
+};
+use url::Url;
 
 /// A context that is shared between multiple [`WebView`]s.
 ///
 /// A browser would have a context for all the normal tabs and a different context for all the
 /// private/incognito tabs.
 ///
+/// The webviews created with the same context share the resources of the engine, like their
+/// caches and processes, which makes creating many webviews faster and lighter:
+///
+/// - **Linux**: The webviews share a `WebKitWebContext`.
+/// - **macOS / iOS**: The webviews share a `WKProcessPool`.
+/// - **Windows**: The webviews share the `CoreWebView2Environment` created for the first one,
+///   with its browser arguments and proxy configuration.
+/// - **Android**: Unsupported, all the webviews share the engine of the app anyway.
+///
 /// # Warning
 /// If [`Webview`] is created by a WebContext. Dropping `WebContext` will cause [`WebView`] lose
 /// some actions like custom protocol on Mac. Please keep both instances when you still wish to
@@ -26,8 +44,10 @@ use std::path::{Path, PathBuf};
 /// [`WebView`]: crate::webview::WebView
 #[derive(Debug)]
 pub struct WebContext {
+  // Dropped first, the webviews may need the engine resources of the context.
+  prerendered: Prerendered,
   data: WebContextData,
-  #[allow(dead_code)] // It's not needed on Windows and macOS.
+  #[allow(dead_code)] // It's not needed on Android.
   pub(crate) os: WebContextImpl,
 }
 
@@ -38,9 +58,16 @@ impl WebContext {
   /// * Whether the WebView window should have a custom user data path. This is useful in Windows
   ///   when a bundled application can't have the webview data inside `Program Files`.
   pub fn new(data_directory: Option<PathBuf>) -> Self {
-    let data = WebContextData { data_directory };
+    let data = WebContextData {
+      data_directory,
+      ..Default::default()
+    };
     let os = WebContextImpl::new(&data);
-    Self { data, os }
+    Self {
+      prerendered: Default::default(),
+      data,
+      os,
+    }
   }
 
   #[cfg(any(
@@ -53,7 +80,11 @@ impl WebContext {
   pub(crate) fn new_ephemeral() -> Self {
     let data = WebContextData::default();
     let os = WebContextImpl::new_ephemeral();
-    Self { data, os }
+    Self {
+      prerendered: Default::default(),
+      data,
+      os,
+    }
   }
 
   /// A reference to the data directory the context was created with.
@@ -61,6 +92,33 @@ impl WebContext {
     self.data.data_directory()
   }
 
+  /// The content blocker rule lists added to the context, as `(identifier, rules)` pairs.
+  #[allow(dead_code)] // It's not needed on Linux and Android.
+  pub(crate) fn content_rule_lists(&self) -> &[(String, String)] {
+    &self.data.content_rule_lists
+  }
+
+  /// Set whether the pages of the webviews created afterwards with this context can register
+  /// service workers. Default is `true`.
+  ///
+  /// When disabled, the service workers previously registered by a page are unregistered when it
+  /// loads. Use [`WebView::unregister_service_workers`] to remove all of them at once.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: Enforced by an [initialization script].
+  ///
+  /// [`WebView::unregister_service_workers`]: crate::webview::WebView::unregister_service_workers
+  /// [initialization script]: crate::webview::WebViewBuilder::with_initialization_script
+  pub fn set_service_workers_enabled(&mut self, enabled: bool) {
+    self.data.service_workers_disabled = !enabled;
+  }
+
+  /// Whether the pages of this context can register service workers.
+  pub fn service_workers_enabled(&self) -> bool {
+    !self.data.service_workers_disabled
+  }
+
   /// Set if this context allows automation.
   ///
   /// **Note:** This is currently only enforced on Linux, and has the stipulation that
@@ -68,13 +126,186 @@ impl WebContext {
   pub fn set_allows_automation(&mut self, flag: bool) {
     self.os.set_allows_automation(flag);
   }
+
+  /// Compile a content blocker rule list and attach it to the webviews of this context.
+  ///
+  /// `rules` is a JSON encoded list of rules in the
+  /// [WebKit content rule list format](https://developer.apple.com/documentation/safariservices/creating_a_content_blocker),
+  /// e.g. `[{ "trigger": { "url-filter": "ads\\.example" }, "action": { "type": "block" } }]`.
+  /// Adding a list with an `identifier` already in use replaces the previous list.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: The lists are compiled when a webview is created, so only the webviews
+  ///   created afterwards are affected.
+  /// - **Linux**: The list is compiled asynchronously and applies to all the webviews of the context
+  ///   once ready.
+  /// - **Windows**: WebView2 has no native content blocker: the lists are matched against every
+  ///   resource request of the webviews created afterwards. Only the `url-filter`,
+  ///   `url-filter-is-case-sensitive`, `resource-type`, `if-domain` and `unless-domain` triggers
+  ///   and the `block` and `ignore-previous-rules` actions are supported.
+  /// - **Android**: Unsupported, the lists are ignored.
+  pub fn add_content_rule_list(&mut self, identifier: &str, rules: &str) -> crate::Result<()> {
+    content_blocker::validate(rules)?;
+    self.os.add_content_rule_list(&self.data, identifier, rules);
+    self
+      .data
+      .content_rule_lists
+      .retain(|(id, _)| id != identifier);
+    self
+      .data
+      .content_rule_lists
+      .push((identifier.to_string(), rules.to_string()));
+    Ok(())
+  }
+
+  /// Start capturing the network activity of the webviews of this context, e.g. for the support
+  /// engineers to collect a trace from the users. A capture already running is restarted.
+  ///
+  /// Only the webviews built while a capture is running report their resources, so start it before
+  /// building them, they keep reporting to the next captures afterwards.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **All**: The resources are reported by an
+  ///   [initialization script](crate::webview::WebViewBuilder::with_initialization_script) from
+  ///   the resource timing entries of the top frames, see [`Har`].
+  pub fn start_har_capture(&mut self) {
+    self.data.har_recorder.start();
+  }
+
+  /// Stop the capture started by [`WebContext::start_har_capture`] and return the activity it
+  /// captured, empty if there was no capture running.
+  pub fn stop_har_capture(&mut self) -> Har {
+    self.data.har_recorder.stop()
+  }
+
+  pub(crate) fn har_recorder(&self) -> HarRecorder {
+    self.data.har_recorder.clone()
+  }
+
+  /// Resolve `host` to `target`, an IP address or another hostname, in the webviews created
+  /// afterwards with this context, e.g. to target a staging backend without editing the hosts
+  /// file. `host` can start with a `*.` wildcard matching its subdomains. Mapping a host again
+  /// replaces its previous target.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: Passed to the `--host-resolver-rules` browser argument of the environment, so
+  ///   the mappings must be added before the first webview of the context is created, and are
+  ///   ignored when [`WebViewBuilderExtWindows::with_additional_browser_args`] is used.
+  /// - **Linux / macOS / iOS / Android**: Unsupported, returns
+  ///   [`Error::Unsupported`](crate::Error::Unsupported).
+  ///
+  /// [`WebViewBuilderExtWindows::with_additional_browser_args`]: crate::webview::WebViewBuilderExtWindows::with_additional_browser_args
+  pub fn map_host(&mut self, host: &str, target: &str) -> crate::Result<()> {
+    if !cfg!(target_os = "windows") {
+      return Err(crate::Error::Unsupported);
+    }
+    for value in [host, target] {
+      if value.is_empty() || value.contains(|c: char| c == ',' || c == '"' || c.is_whitespace()) {
+        return Err(crate::Error::InvalidHostMapping(value.to_string()));
+      }
+    }
+    self.data.host_mappings.retain(|(h, _)| h != host);
+    self
+      .data
+      .host_mappings
+      .push((host.to_string(), target.to_string()));
+    Ok(())
+  }
+
+  /// The `--host-resolver-rules` of the hosts mapped with [`WebContext::map_host`].
+  #[allow(dead_code)] // It's only needed on Windows.
+  pub(crate) fn host_resolver_rules(&self) -> Option<String> {
+    host_resolver_rules(&self.data.host_mappings)
+  }
+
+  /// Load `url` in a hidden webview of this context, so [`WebContext::adopt_prerendered`] can
+  /// show it instantly, e.g. for the page the user is likely to open next.
+  ///
+  /// The webview is built with the default attributes in a window built from `window`, which is
+  /// kept hidden until the webview is adopted. The webview keeps running its page until then, or
+  /// until the context is dropped.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android**: Unsupported, returns [`Error::Unsupported`](crate::Error::Unsupported).
+  pub fn prerender<T: 'static>(
+    &mut self,
+    event_loop: &EventLoopWindowTarget<T>,
+    url: &str,
+    window: WindowBuilder,
+  ) -> crate::Result<()> {
+    let url = Url::parse(url)?;
+    let window = window.with_visible(false).build(event_loop)?;
+    let webview = WebViewBuilder::new(window)?
+      .with_url(url.as_str())?
+      .with_web_context(self)
+      .build()?;
+    self.prerendered.0.push((url, webview));
+    Ok(())
+  }
+
+  /// Show the window of the webview prerendered for `url` by [`WebContext::prerender`], and
+  /// return the webview. Returns `None` if `url` wasn't prerendered.
+  ///
+  /// The webview stays in the window it was built in, so [`WebView::window`] and the handlers
+  /// taking a window get the window which is shown.
+  pub fn adopt_prerendered(&mut self, url: &str) -> crate::Result<Option<WebView>> {
+    let Some(webview) = self.prerendered.take(&Url::parse(url)?) else {
+      return Ok(None);
+    };
+    webview.window().set_visible(true);
+    Ok(Some(webview))
+  }
+}
+
+fn host_resolver_rules(mappings: &[(String, String)]) -> Option<String> {
+  if mappings.is_empty() {
+    return None;
+  }
+  let rules: Vec<_> = mappings
+    .iter()
+    .map(|(host, target)| format!("MAP {host} {target}"))
+    .collect();
+  Some(rules.join(","))
+}
+
+/// The webviews loaded by [`WebContext::prerender`], with their URL.
+struct Prerendered<T = WebView>(Vec<(Url, T)>);
+
+impl<T> Prerendered<T> {
+  /// Remove the first webview prerendered for `url`, the URLs being compared once normalized.
+  fn take(&mut self, url: &Url) -> Option<T> {
+    let index = self.0.iter().position(|(u, _)| u == url)?;
+    Some(self.0.remove(index).1)
+  }
+}
+
+impl<T> Default for Prerendered<T> {
+  fn default() -> Self {
+    Self(Vec::new())
+  }
+}
+
+impl<T> fmt::Debug for Prerendered<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_list()
+      .entries(self.0.iter().map(|(url, _)| url.as_str()))
+      .finish()
+  }
 }
 
 impl Default for WebContext {
   fn default() -> Self {
     let data = WebContextData::default();
     let os = WebContextImpl::new(&data);
-    Self { data, os }
+    Self {
+      prerendered: Default::default(),
+      data,
+      os,
+    }
   }
 }
 
@@ -82,6 +313,10 @@ impl Default for WebContext {
 #[derive(Default, Debug)]
 pub struct WebContextData {
   data_directory: Option<PathBuf>,
+  content_rule_lists: Vec<(String, String)>,
+  service_workers_disabled: bool,
+  har_recorder: HarRecorder,
+  host_mappings: Vec<(String, String)>,
 }
 
 impl WebContextData {
@@ -91,15 +326,83 @@ impl WebContextData {
   }
 }
 
-#[cfg(any(
-  target_os = "windows",
-  target_os = "android",
-  target_os = "macos",
-  target_os = "ios"
-))]
+#[cfg(target_os = "android")]
 #[derive(Debug)]
 pub(crate) struct WebContextImpl;
 
+#[cfg(target_os = "windows")]
+#[derive(Debug)]
+pub(crate) struct WebContextImpl {
+  /// The environment of the webviews of the context, created with the first one and released
+  /// with the last one.
+  pub(crate) environment: super::lifecycle::Shared<
+    webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Environment,
+  >,
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[derive(Debug)]
+pub(crate) struct WebContextImpl {
+  process_pool: super::lifecycle::Shared<ProcessPool>,
+}
+
+/// A retained `WKProcessPool`.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[derive(Debug)]
+pub(crate) struct ProcessPool(pub(crate) cocoa::base::id);
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+impl Clone for ProcessPool {
+  fn clone(&self) -> Self {
+    unsafe {
+      let _: cocoa::base::id = msg_send![self.0, retain];
+    }
+    Self(self.0)
+  }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+impl Drop for ProcessPool {
+  fn drop(&mut self) {
+    unsafe {
+      let () = msg_send![self.0, release];
+    }
+  }
+}
+
+#[cfg(target_os = "android")]
+impl WebContextImpl {
+  fn new(_data: &WebContextData) -> Self {
+    Self
+  }
+}
+
+#[cfg(target_os = "windows")]
+impl WebContextImpl {
+  fn new(_data: &WebContextData) -> Self {
+    Self {
+      environment: super::lifecycle::Shared::new(),
+    }
+  }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+impl WebContextImpl {
+  fn new(_data: &WebContextData) -> Self {
+    Self {
+      process_pool: super::lifecycle::Shared::new(),
+    }
+  }
+
+  /// The process pool of the webviews of the context, created with the first one and released
+  /// with the last one, which keeps the guard.
+  pub(crate) fn process_pool(&self) -> (ProcessPool, super::lifecycle::SharedGuard<ProcessPool>) {
+    self
+      .process_pool
+      .acquire(|| ProcessPool(unsafe { msg_send![class!(WKProcessPool), new] }))
+  }
+}
+
 #[cfg(any(
   target_os = "windows",
   target_os = "android",
@@ -107,9 +410,41 @@ pub(crate) struct WebContextImpl;
   target_os = "ios"
 ))]
 impl WebContextImpl {
-  fn new(_data: &WebContextData) -> Self {
-    Self
+  fn set_allows_automation(&mut self, _flag: bool) {}
+
+  fn add_content_rule_list(&mut self, _data: &WebContextData, _identifier: &str, _rules: &str) {}
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_build_host_resolver_rules() {
+    assert_eq!(host_resolver_rules(&[]), None);
+    assert_eq!(
+      host_resolver_rules(&[
+        ("api.example.com".into(), "10.0.0.12".into()),
+        ("*.cdn.example.com".into(), "staging.example.com".into()),
+      ])
+      .as_deref(),
+      Some("MAP api.example.com 10.0.0.12,MAP *.cdn.example.com staging.example.com")
+    );
   }
 
-  fn set_allows_automation(&mut self, _flag: bool) {}
+  #[test]
+  fn should_take_the_prerendered_urls() {
+    let url = |url| Url::parse(url).unwrap();
+    let mut prerendered = Prerendered::default();
+    prerendered.0.push((url("https://example.com"), 1));
+    prerendered.0.push((url("https://example.com/docs/"), 2));
+    prerendered.0.push((url("https://example.com"), 3));
+
+    assert_eq!(prerendered.take(&url("https://example.com/docs")), None);
+    assert_eq!(prerendered.take(&url("https://example.com/docs/")), Some(2));
+    // The empty path of the origins is normalized to `/`.
+    assert_eq!(prerendered.take(&url("https://EXAMPLE.com/")), Some(1));
+    assert_eq!(prerendered.take(&url("https://example.com")), Some(3));
+    assert_eq!(prerendered.take(&url("https://example.com")), None);
+  }
 }
@@ -0,0 +1,39 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{cell::Cell, rc::Rc};
+
+use gtk::prelude::*;
+use webkit2gtk::WebView;
+
+/// Swallows the click which activates an inactive window, so it only focuses the window like
+/// macOS does when `accept_first_mouse` is disabled.
+pub fn setup(webview: &WebView) {
+  let swallowed = Rc::new(Cell::new(false));
+
+  let swallowed_c = swallowed.clone();
+  webview.connect_button_press_event(move |webview, _| {
+    let is_active = webview
+      .toplevel()
+      .and_then(|w| w.downcast::<gtk::Window>().ok())
+      .map(|w| w.is_active())
+      .unwrap_or(true);
+
+    if is_active {
+      swallowed_c.set(false);
+      gtk::glib::Propagation::Proceed
+    } else {
+      swallowed_c.set(true);
+      gtk::glib::Propagation::Stop
+    }
+  });
+
+  webview.connect_button_release_event(move |_, _| {
+    if swallowed.replace(false) {
+      gtk::glib::Propagation::Stop
+    } else {
+      gtk::glib::Propagation::Proceed
+    }
+  });
+}
@@ -3,19 +3,21 @@
 // SPDX-License-Identifier: MIT
 
 use gtk::{gdk::EventMask, gio::Cancellable, prelude::*};
-#[cfg(any(debug_assertions, feature = "devtools"))]
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::{
-  collections::hash_map::DefaultHasher,
+  cell::{Cell, RefCell},
+  collections::{hash_map::DefaultHasher, HashMap},
   hash::{Hash, Hasher},
   rc::Rc,
   sync::{Arc, Mutex},
 };
 use url::Url;
 use webkit2gtk::{
-  AutoplayPolicy, InputMethodContextExt, LoadEvent, NavigationPolicyDecision,
-  NavigationPolicyDecisionExt, NetworkProxyMode, NetworkProxySettings, PolicyDecisionType,
-  SettingsExt, URIRequest, URIRequestExt, UserContentInjectedFrames, UserContentManagerExt,
+  AutoplayPolicy as WebKitAutoplayPolicy, InputMethodContextExt, LoadEvent,
+  NavigationPolicyDecision, NavigationPolicyDecisionExt, NetworkProxyMode, NetworkProxySettings,
+  PolicyDecisionExt, PolicyDecisionType, PrintOperation, PrintOperationExt, ResponsePolicyDecision,
+  ResponsePolicyDecisionExt, SecurityManagerExt, SettingsExt, SnapshotOptions, SnapshotRegion,
+  URIRequest, URIRequestExt, URIResponseExt, UserContentInjectedFrames, UserContentManagerExt,
   UserScript, UserScriptInjectionTime, WebInspectorExt, WebView, WebViewExt, WebsiteDataManagerExt,
   WebsitePolicies,
 };
@@ -28,35 +30,94 @@ use web_context::WebContextExt;
 pub use web_context::WebContextImpl;
 
 use crate::{
-  application::{platform::unix::*, window::Window},
-  webview::{proxy::ProxyConfig, web_context::WebContext, PageLoadEvent, WebViewAttributes, RGBA},
+  application::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::TouchPhase,
+    platform::unix::*,
+    window::Window,
+  },
+  webview::{
+    mouse_event::button_number,
+    proxy::ProxyConfig,
+    snapshot::{element_rect_script, parse_element_rect},
+    typed_eval::apply_arguments,
+    web_context::WebContext,
+    AutoResizePolicy, AutoplayPolicy, CachePolicy, ClickThroughRegion, DevtoolsPlacement,
+    EditCommand, InitScript, InjectionTime, KeyEvent, KeyEventResponse, KeyModifiers,
+    MediaFeatureOverrides, MouseEvent, NewWindowOpener, NewWindowRequest, NewWindowResponse,
+    PageLoadEvent, PrintOptions, ScriptId, SnapshotFormat, TouchEvent, WebArchive,
+    WebViewAttributes, RGBA,
+  },
   Error, Result,
 };
 
 mod file_drop;
+mod first_mouse;
 mod synthetic_mouse_events;
 mod undecorated_resizing;
 mod web_context;
 
 use javascriptcore::ValueExt;
 
+/// The webview requesting a new window, and the webview built for it.
+pub(crate) struct PlatformOpener {
+  webview: WebView,
+  child: RefCell<Option<WebView>>,
+}
+
 pub(crate) struct InnerWebView {
   pub webview: Rc<WebView>,
-  #[cfg(any(debug_assertions, feature = "devtools"))]
   is_inspector_open: Arc<AtomicBool>,
   pending_scripts: Arc<Mutex<Option<Vec<String>>>>,
+  media_features_script: RefCell<Option<UserScript>>,
+  user_scripts: RefCell<HashMap<ScriptId, UserScript>>,
+  auto_resize: Cell<AutoResizePolicy>,
+  discarded: Cell<bool>,
+  // The mute state to restore when the media muted by `pause_all_media` is resumed.
+  muted_before_pause: Cell<Option<bool>>,
 }
 
+/// The features implemented by this backend, see [`super::Capabilities`].
+pub(crate) const CAPABILITIES: super::Capabilities = super::Capabilities {
+  streaming_protocols: false,
+  protocol_request_body: cfg!(feature = "linux-body"),
+  devtools: true,
+  print: true,
+  print_options: true,
+  edit_commands: true,
+  zoom: true,
+  element_capture: true,
+  muting: true,
+  service_worker_management: true,
+  pdf_downloads: true,
+  content_blocking: true,
+  devtools_protocol: false,
+  proxy: true,
+  offscreen: false,
+  find_in_page: false,
+  print_to_pdf: false,
+  selected_text: true,
+  suspend: false,
+};
+
 impl InnerWebView {
   pub fn new(
     window: Rc<Window>,
     mut attributes: WebViewAttributes,
-    _pl_attrs: super::PlatformSpecificWebViewAttributes,
+    pl_attrs: super::PlatformSpecificWebViewAttributes,
     web_context: Option<&mut WebContext>,
+    child: bool,
   ) -> Result<Self> {
     let window_rc = Rc::clone(&window);
     let window = &window.gtk_window();
 
+    // Read by WebKit when the first web context is created.
+    if let Some(port) = attributes.remote_debugging_port {
+      if std::env::var_os("WEBKIT_INSPECTOR_HTTP_SERVER").is_none() {
+        std::env::set_var("WEBKIT_INSPECTOR_HTTP_SERVER", format!("127.0.0.1:{port}"));
+      }
+    }
+
     // default_context allows us to create a scoped context on-demand
     let mut default_context;
     let web_context = if attributes.incognito {
@@ -85,17 +146,42 @@ impl InnerWebView {
           .set_network_proxy_settings(NetworkProxyMode::Custom, Some(&mut settings));
       }
     }
+    {
+      use webkit2gtk::WebContextExt;
+      if let Some(enabled) = attributes.spellcheck {
+        web_context.context().set_spell_checking_enabled(enabled);
+      }
+      if !attributes.spellcheck_languages.is_empty() {
+        let languages: Vec<&str> = attributes
+          .spellcheck_languages
+          .iter()
+          .map(String::as_str)
+          .collect();
+        web_context
+          .context()
+          .set_spell_checking_languages(&languages);
+      }
+    }
     let webview = {
       let mut webview = WebView::builder();
       webview = webview.user_content_manager(web_context.manager());
-      webview = webview.web_context(web_context.context());
       webview = webview.is_controlled_by_automation(web_context.allows_automation());
-      if attributes.autoplay {
-        webview = webview.website_policies(
-          &WebsitePolicies::builder()
-            .autoplay(AutoplayPolicy::Allow)
-            .build(),
-        );
+      match &attributes.new_window_opener {
+        // Shares the web process and the context of the opener, so `window.opener` stays
+        // connected.
+        Some(opener) => webview = webview.related_view(&opener.0.webview),
+        None => webview = webview.web_context(web_context.context()),
+      }
+      let autoplay = match attributes.autoplay_policy {
+        Some(AutoplayPolicy::AllowAll) => Some(WebKitAutoplayPolicy::Allow),
+        Some(AutoplayPolicy::AllowMuted) => Some(WebKitAutoplayPolicy::AllowWithoutSound),
+        Some(AutoplayPolicy::RequireUserGesture | AutoplayPolicy::Allowlist(_)) => {
+          Some(WebKitAutoplayPolicy::Deny)
+        }
+        None => None,
+      };
+      if let Some(autoplay) = autoplay {
+        webview = webview.website_policies(&WebsitePolicies::builder().autoplay(autoplay).build());
       }
       webview.build()
     };
@@ -131,6 +217,10 @@ impl InnerWebView {
 
     // Register the handler we just connected
     manager.register_script_message_handler(&window_hash);
+    let isolated_worlds = super::isolated_worlds(&attributes.isolated_initialization_scripts);
+    for world in &isolated_worlds {
+      manager.register_script_message_handler_in_world(&window_hash, world);
+    }
 
     // Allow the webview to close it's own window
     let close_window = window_rc.clone();
@@ -149,6 +239,17 @@ impl InnerWebView {
       });
     }
 
+    if let Some(close_requested_handler) = attributes.close_requested_handler.take() {
+      let w = window_rc.clone();
+      webview.connect_close(move |_| close_requested_handler(&w));
+    }
+
+    if let Some(audio_state_handler) = attributes.audio_state_handler.take() {
+      webview.connect_is_playing_audio_notify(move |webview| {
+        audio_state_handler(webview.is_playing_audio())
+      });
+    }
+
     let on_page_load_handler = attributes.on_page_load_handler.take();
     if on_page_load_handler.is_some() {
       webview.connect_load_changed(move |webview, load_event| match load_event {
@@ -175,27 +276,113 @@ impl InnerWebView {
 
     synthetic_mouse_events::setup(&webview);
     undecorated_resizing::setup(&webview);
+    if !attributes.accept_first_mouse {
+      first_mouse::setup(&webview);
+    }
+
+    // The allowed origins get their own website policies on navigation.
+    let autoplay_allowlist = attributes
+      .autoplay_policy
+      .take()
+      .filter(|policy| matches!(policy, AutoplayPolicy::Allowlist(_)));
+    if !attributes.pdf_viewer {
+      // Connected before the navigation handler, which stops the emission of all the decisions.
+      webview.connect_decide_policy(|_, policy_decision, policy_type| {
+        if policy_type != PolicyDecisionType::Response {
+          return false;
+        }
+        let is_pdf = policy_decision
+          .dynamic_cast_ref::<ResponsePolicyDecision>()
+          .and_then(|policy| policy.response())
+          .and_then(|response| response.mime_type())
+          .is_some_and(|mime_type| mime_type == "application/pdf");
+        if is_pdf {
+          policy_decision.download();
+        }
+        is_pdf
+      });
+    }
 
-    if attributes.navigation_handler.is_some() || attributes.new_window_req_handler.is_some() {
+    if let Some(new_window_handler) = attributes.new_window_handler.take() {
+      // The webview built by the handler, handed to WebKit when it creates the new window.
+      let created: Rc<RefCell<Option<WebView>>> = Rc::default();
+      let created_ = created.clone();
+      webview.connect_decide_policy(move |webview, policy_decision, policy_type| {
+        if policy_type != PolicyDecisionType::NewWindowAction {
+          return false;
+        }
+        let Some(uri) = policy_decision
+          .dynamic_cast_ref::<NavigationPolicyDecision>()
+          .and_then(|policy| policy.navigation_action())
+          .and_then(|action| action.request())
+          .and_then(|request| request.uri())
+        else {
+          return false;
+        };
+
+        let opener = NewWindowOpener(Rc::new(PlatformOpener {
+          webview: webview.clone(),
+          child: RefCell::new(None),
+        }));
+        let request = NewWindowRequest {
+          url: uri.to_string(),
+          opener: opener.clone(),
+        };
+        match new_window_handler(request) {
+          NewWindowResponse::Allow => policy_decision.use_(),
+          NewWindowResponse::Deny => policy_decision.ignore(),
+          NewWindowResponse::Navigate => {
+            policy_decision.ignore();
+            webview.load_uri(&uri);
+          }
+          NewWindowResponse::Create => match opener.0.child.take() {
+            Some(child) => {
+              created_.replace(Some(child));
+              policy_decision.use_();
+            }
+            None => policy_decision.ignore(),
+          },
+        }
+        true
+      });
+      webview.connect_create(move |_, _| created.take().map(|child| child.upcast()));
+    }
+
+    if attributes.navigation_handler.is_some() || autoplay_allowlist.is_some() {
       webview.connect_decide_policy(move |_webview, policy_decision, policy_type| {
-        let handler = match policy_type {
-          PolicyDecisionType::NavigationAction => &attributes.navigation_handler,
-          PolicyDecisionType::NewWindowAction => &attributes.new_window_req_handler,
-          _ => &None,
+        let (handler, autoplay_allowlist) = match policy_type {
+          PolicyDecisionType::NavigationAction => {
+            (&attributes.navigation_handler, &autoplay_allowlist)
+          }
+          _ => (&None, &None),
         };
 
-        if let Some(handler) = handler {
+        if handler.is_some() || autoplay_allowlist.is_some() {
           if let Some(policy) = policy_decision.dynamic_cast_ref::<NavigationPolicyDecision>() {
             if let Some(nav_action) = policy.navigation_action() {
               if let Some(uri_req) = nav_action.request() {
                 if let Some(uri) = uri_req.uri() {
-                  let allow = handler(uri.to_string());
-                  let pointer = policy_decision.as_ptr();
-                  unsafe {
-                    if allow {
-                      webkit_policy_decision_use(pointer)
-                    } else {
-                      webkit_policy_decision_ignore(pointer)
+                  let allow = match handler {
+                    Some(handler) => handler(uri.to_string()),
+                    None => true,
+                  };
+                  let autoplay = autoplay_allowlist
+                    .as_ref()
+                    .is_some_and(|policy| policy.allowlisted(&uri));
+                  if allow && autoplay {
+                    policy_decision.use_with_policies(
+                      &WebsitePolicies::builder()
+                        .autoplay(WebKitAutoplayPolicy::Allow)
+                        .build(),
+                    );
+                  } else {
+                    let pointer = policy_decision.as_ptr();
+                    unsafe {
+                      if allow {
+                        webkit_policy_decision_use(pointer)
+                      } else {
+                        webkit_policy_decision_ignore(pointer)
+                      }
                     }
                   }
                 }
@@ -216,8 +403,21 @@ impl InnerWebView {
       )
     }
 
-    // tao adds a default vertical box so we check for that first
-    if let Some(vbox) = window_rc.default_vbox() {
+    if let Some(container) = pl_attrs.container {
+      // Fill the container like the window, e.g. a `gtk::Box` of the application.
+      match container.downcast_ref::<gtk::Box>() {
+        Some(container) => container.pack_start(&*webview, true, true, 0),
+        None => container.add(&*webview),
+      }
+      webview.show_all();
+    } else if child {
+      webview_overlay(&window_rc)?.add_overlay(&*webview);
+      if let Some((position, size)) = attributes.bounds {
+        attributes.auto_resize = attributes.auto_resize.placed();
+        set_overlay_bounds(&webview, position, size, attributes.auto_resize);
+      }
+    } else if let Some(vbox) = window_rc.default_vbox() {
+      // tao adds a default vertical box so we check for that first
       vbox.pack_start(&*webview, true, true, 0);
     } else {
       window.add(&*webview);
@@ -227,6 +427,28 @@ impl InnerWebView {
       webview.grab_focus();
     }
 
+    if let Some(focus_changed_handler) = attributes.focus_changed_handler.take() {
+      let focus_changed_handler = Rc::new(focus_changed_handler);
+      let handler = focus_changed_handler.clone();
+      webview.connect_focus_in_event(move |_, _| {
+        handler(true);
+        gtk::glib::Propagation::Proceed
+      });
+      webview.connect_focus_out_event(move |_, _| {
+        focus_changed_handler(false);
+        gtk::glib::Propagation::Proceed
+      });
+    }
+
+    // The handlers connected to the widget run before the one of the webview, dispatching the keys
+    // to the page.
+    if let Some(key_event_handler) = attributes.key_event_handler.take() {
+      webview.connect_key_press_event(move |_, event| match key_event_handler(key_event(event)) {
+        KeyEventResponse::Handled => gtk::glib::Propagation::Stop,
+        KeyEventResponse::Forward => gtk::glib::Propagation::Proceed,
+      });
+    }
+
     if let Some(context) = WebViewExt::context(&*webview) {
       use webkit2gtk::WebContextExt;
       context.set_use_system_appearance_for_scrollbars(false);
@@ -236,8 +458,14 @@ impl InnerWebView {
     if let Some(settings) = WebViewExt::settings(&*webview) {
       settings.set_enable_webgl(true);
       settings.set_enable_webaudio(true);
+      if let Some(policy) = attributes.webrtc_policy {
+        settings.set_enable_webrtc(policy.enabled);
+      }
       settings
         .set_enable_back_forward_navigation_gestures(attributes.back_forward_navigation_gestures);
+      if let Some(smooth) = attributes.smooth_scrolling {
+        settings.set_enable_smooth_scrolling(smooth);
+      }
 
       // Enable clipboard
       if attributes.clipboard {
@@ -250,6 +478,10 @@ impl InnerWebView {
 
       // Set user agent
       settings.set_user_agent(attributes.user_agent.as_deref());
+      if let Some(suffix) = &attributes.user_agent_suffix {
+        let user_agent = settings.user_agent().unwrap_or_default();
+        settings.set_user_agent(Some(&format!("{user_agent} {suffix}")));
+      }
 
       if attributes.devtools {
         settings.set_enable_developer_extras(true);
@@ -280,7 +512,6 @@ impl InnerWebView {
       window.show_all();
     }
 
-    #[cfg(any(debug_assertions, feature = "devtools"))]
     let is_inspector_open = {
       let is_inspector_open = Arc::new(AtomicBool::default());
       if let Some(inspector) = WebViewExt::inspector(&*webview) {
@@ -299,25 +530,51 @@ impl InnerWebView {
 
     let w = Self {
       webview,
-      #[cfg(any(debug_assertions, feature = "devtools"))]
       is_inspector_open,
       pending_scripts: Arc::new(Mutex::new(Some(Vec::new()))),
+      media_features_script: RefCell::new(None),
+      user_scripts: RefCell::default(),
+      auto_resize: Cell::new(attributes.auto_resize),
+      discarded: Cell::new(false),
+      muted_before_pause: Cell::new(None),
     };
 
     // Initialize message handler
-    let mut init = String::with_capacity(115 + 20 + 22);
-    init.push_str("Object.defineProperty(window, 'ipc', {value: Object.freeze({postMessage:function(x){window.webkit.messageHandlers[\"");
-    init.push_str(&window_hash);
-    init.push_str("\"].postMessage(x)}})})");
+    // The native function is bound right away, the pages can't replace it to read the messages
+    // of the injected scripts.
+    let init = format!(
+      "(function(h){{Object.defineProperty(window, 'ipc', {{value: Object.freeze({{postMessage:h.postMessage.bind(h)}})}})}})(window.webkit.messageHandlers[\"{window_hash}\"])"
+    );
     w.init(&init)?;
+    for world in &isolated_worlds {
+      w.init_in_world(Some(world), &init, InjectionTime::DocumentStart, true)?;
+    }
 
     // Initialize scripts
     for js in attributes.initialization_scripts {
       w.init(&js)?;
     }
+    for script in &attributes.init_scripts {
+      w.init_script(script)?;
+    }
+    for (world, js) in attributes.isolated_initialization_scripts {
+      w.init_in_world(Some(&world), &js, InjectionTime::DocumentStart, true)?;
+    }
+
+    if let Some(overrides) = attributes.media_feature_overrides {
+      w.set_media_feature_overrides(overrides)?;
+    }
 
-    for (name, handler) in attributes.custom_protocols {
-      match web_context.register_uri_scheme(&name, handler) {
+    for (name, handler) in attributes.streaming_custom_protocols {
+      if (attributes.relaxed_cors_protocols.iter()).any(|(relaxed, _)| relaxed == &name) {
+        use webkit2gtk::WebContextExt as _;
+        web_context
+          .context()
+          .security_manager()
+          .ok_or(Error::MissingManager)?
+          .register_uri_scheme_as_cors_enabled(&name);
+      }
+      match web_context.register_streaming_uri_scheme(&name, handler) {
         // Swallow duplicate scheme errors to preserve current behavior.
         // FIXME: we should log this error in the future
         Err(Error::DuplicateCustomProtocol(_)) => (),
@@ -326,12 +583,15 @@ impl InnerWebView {
       }
     }
 
-    // Navigation
-    if let Some(url) = attributes.url {
+    // Navigation, WebKit loads the requested page in the webview of a new window.
+    if let Some(opener) = &attributes.new_window_opener {
+      opener.0.child.replace(Some((*w.webview).clone()));
+    } else if let Some(url) = attributes.url {
       web_context.queue_load_uri(Rc::clone(&w.webview), url, attributes.headers);
       web_context.flush_queue_loader();
     } else if let Some(html) = attributes.html {
-      w.webview.load_html(&html, None);
+      w.webview
+        .load_html(&html, attributes.html_origin.as_ref().map(Url::as_str));
     }
 
     let pending_scripts = w.pending_scripts.clone();
@@ -353,11 +613,45 @@ impl InnerWebView {
 
   pub fn print(&self) {
     let _ = self.eval(
-      "window.print()",
+      super::print::NATIVE_PRINT_SCRIPT,
       None::<Box<dyn FnOnce(String) + Send + 'static>>,
     );
   }
 
+  pub fn print_with_options(&self, options: PrintOptions) -> Result<()> {
+    let operation = PrintOperation::new(&*self.webview);
+    if let Some(printer) = &options.printer {
+      let settings = gtk::PrintSettings::new();
+      settings.set_printer(printer);
+      operation.set_print_settings(&settings);
+    }
+    // Unknown printers are only found once the print starts.
+    operation.connect_failed(|_, error| log::warn!("failed to print: {error}"));
+    if options.silent {
+      operation.print();
+    } else {
+      let window = self
+        .webview
+        .toplevel()
+        .and_then(|window| window.downcast::<gtk::Window>().ok());
+      operation.run_dialog(window.as_ref());
+    }
+    Ok(())
+  }
+
+  pub fn execute_edit_command(&self, command: EditCommand) -> Result<()> {
+    // The names of the `WEBKIT_EDITING_COMMAND_*` constants.
+    self.webview.execute_editing_command(match command {
+      EditCommand::Copy => "Copy",
+      EditCommand::Cut => "Cut",
+      EditCommand::Paste => "Paste",
+      EditCommand::SelectAll => "SelectAll",
+      EditCommand::Undo => "Undo",
+      EditCommand::Redo => "Redo",
+    });
+    Ok(())
+  }
+
   pub fn url(&self) -> Url {
     let uri = self.webview.uri().unwrap();
 
@@ -398,25 +692,105 @@ impl InnerWebView {
   }
 
   fn init(&self, js: &str) -> Result<()> {
+    self
+      .init_in_world(None, js, InjectionTime::DocumentStart, true)
+      .map(drop)
+  }
+
+  /// Adds an initialization script to the isolated `world`, or to the page world if `None`.
+  fn init_in_world(
+    &self,
+    world: Option<&str>,
+    js: &str,
+    time: InjectionTime,
+    main_frame_only: bool,
+  ) -> Result<UserScript> {
     if let Some(manager) = self.webview.user_content_manager() {
-      let script = UserScript::new(
-        js,
-        // FIXME: We allow subframe injection because webview2 does and cannot be disabled (currently).
-        // once webview2 allows disabling all-frame script injection, TopFrame should be set
-        // if it does not break anything. (originally added for isolation pattern).
-        UserContentInjectedFrames::TopFrame,
-        UserScriptInjectionTime::Start,
-        &[],
-        &[],
-      );
+      // FIXME: We allow subframe injection because webview2 does and cannot be disabled (currently).
+      // once webview2 allows disabling all-frame script injection, TopFrame should be set
+      // if it does not break anything. (originally added for isolation pattern).
+      let frames = if main_frame_only {
+        UserContentInjectedFrames::TopFrame
+      } else {
+        UserContentInjectedFrames::AllFrames
+      };
+      let time = match time {
+        InjectionTime::DocumentStart => UserScriptInjectionTime::Start,
+        InjectionTime::DocumentEnd => UserScriptInjectionTime::End,
+      };
+      let script = match world {
+        Some(world) => UserScript::for_world(js, frames, time, world, &[], &[]),
+        None => UserScript::new(js, frames, time, &[], &[]),
+      };
       manager.add_script(&script);
+      Ok(script)
     } else {
-      return Err(Error::InitScriptError);
+      Err(Error::InitScriptError)
+    }
+  }
+
+  fn init_script(&self, script: &InitScript) -> Result<UserScript> {
+    self.init_in_world(
+      None,
+      &script.source(),
+      script.injection_time,
+      script.for_main_frame_only,
+    )
+  }
+
+  pub fn add_init_script(&self, script: &InitScript) -> Result<ScriptId> {
+    let script = self.init_script(script)?;
+    let id = ScriptId::next();
+    self.user_scripts.borrow_mut().insert(id, script);
+    Ok(id)
+  }
+
+  pub fn remove_init_script(&self, id: ScriptId) -> Result<()> {
+    if let Some(script) = self.user_scripts.borrow_mut().remove(&id) {
+      let manager = self
+        .webview
+        .user_content_manager()
+        .ok_or(Error::InitScriptError)?;
+      manager.remove_script(&script);
     }
     Ok(())
   }
 
-  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub fn eval_in_world(&self, world: &str, js: &str) -> Result<()> {
+    let cancellable: Option<&Cancellable> = None;
+    self
+      .webview
+      .run_javascript_in_world(js, world, cancellable, |_| ());
+    Ok(())
+  }
+
+  pub fn eval_in_world_with_callback(
+    &self,
+    world: &str,
+    js: &str,
+    callback: impl FnOnce(String) + 'static,
+  ) -> Result<()> {
+    let cancellable: Option<&Cancellable> = None;
+    self
+      .webview
+      .run_javascript_in_world(js, world, cancellable, |result| {
+        let json = result
+          .ok()
+          .and_then(|result| result.js_value())
+          .and_then(|value| value.to_json(0))
+          .map(|json| json.to_string());
+        callback(json.unwrap_or_default());
+      });
+    Ok(())
+  }
+
+  pub fn eval_with_args(&self, function: &str, args: &str) -> Result<()> {
+    self.eval(
+      &apply_arguments(function, args),
+      None::<Box<dyn Fn(String) + Send + 'static>>,
+    )
+  }
+
   pub fn open_devtools(&self) {
     if let Some(inspector) = WebViewExt::inspector(&*self.webview) {
       inspector.show();
@@ -425,18 +799,215 @@ impl InnerWebView {
     }
   }
 
-  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub fn open_devtools_with_placement(&self, placement: DevtoolsPlacement) {
+    self.open_devtools();
+    if let Some(inspector) = WebViewExt::inspector(&*self.webview) {
+      match placement {
+        DevtoolsPlacement::Detached => inspector.detach(),
+        DevtoolsPlacement::Docked => inspector.attach(),
+        DevtoolsPlacement::Default => {}
+      }
+    }
+  }
+
+  /// The window of the inspector, when it's open detached.
+  fn devtools_window(&self) -> Option<gtk::Window> {
+    let inspector = WebViewExt::inspector(&*self.webview)?;
+    if !self.is_devtools_open() || inspector.is_attached() {
+      return None;
+    }
+    inspector.web_view()?.toplevel()?.downcast().ok()
+  }
+
+  pub fn devtools_window_bounds(&self) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    let window = self.devtools_window()?;
+    let scale_factor = window.scale_factor();
+    let (x, y) = window.position();
+    let (width, height) = window.size();
+    Some((
+      PhysicalPosition::new(x * scale_factor, y * scale_factor),
+      PhysicalSize::new(
+        (width * scale_factor) as u32,
+        (height * scale_factor) as u32,
+      ),
+    ))
+  }
+
+  pub fn set_devtools_window_bounds(
+    &self,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+  ) -> Result<()> {
+    let window = self.devtools_window().ok_or(Error::DevtoolsNotDetached)?;
+    let scale_factor = window.scale_factor();
+    window.move_(position.x / scale_factor, position.y / scale_factor);
+    window.resize(
+      size.width as i32 / scale_factor,
+      size.height as i32 / scale_factor,
+    );
+    Ok(())
+  }
+
   pub fn close_devtools(&self) {
     if let Some(inspector) = WebViewExt::inspector(&*self.webview) {
       inspector.close();
     }
   }
 
-  #[cfg(any(debug_assertions, feature = "devtools"))]
   pub fn is_devtools_open(&self) -> bool {
     self.is_inspector_open.load(Ordering::Relaxed)
   }
 
+  pub fn set_bounds(&self, position: PhysicalPosition<i32>, size: PhysicalSize<u32>) -> Result<()> {
+    self.overlay()?;
+    let policy = self.auto_resize.get().placed();
+    self.auto_resize.set(policy);
+    set_overlay_bounds(&self.webview, position, size, policy);
+    Ok(())
+  }
+
+  pub fn bring_to_front(&self) -> Result<()> {
+    self.overlay()?.reorder_overlay(&*self.webview, -1);
+    Ok(())
+  }
+
+  pub fn send_to_back(&self) -> Result<()> {
+    self.overlay()?.reorder_overlay(&*self.webview, 0);
+    Ok(())
+  }
+
+  pub fn set_ignore_cursor_events(&self, ignore: bool) -> Result<()> {
+    // An empty input shape sends the events to the widgets below.
+    let region = ignore.then(gtk::cairo::Region::create);
+    self.webview.input_shape_combine_region(region.as_ref());
+    Ok(())
+  }
+
+  pub fn script_evaluator(&self) -> Box<dyn Fn(&str)> {
+    // Weak, the evaluator is kept by the IPC handler of the webview.
+    let webview = self.webview.downgrade();
+    Box::new(move |js| {
+      if let Some(webview) = webview.upgrade() {
+        let cancellable: Option<&Cancellable> = None;
+        webview.run_javascript(js, cancellable, |_| ());
+      }
+    })
+  }
+
+  pub fn click_through_setter(&self) -> Option<Box<dyn Fn(&[ClickThroughRegion])>> {
+    // Weak, the setter is kept by the IPC handler of the webview.
+    let webview = self.webview.downgrade();
+    Some(Box::new(move |regions| {
+      if let Some(webview) = webview.upgrade() {
+        set_click_through_regions(&webview, regions);
+      }
+    }))
+  }
+
+  pub fn send_mouse_event(&self, event: MouseEvent) -> Result<()> {
+    use gtk::gdk::{ffi, EventType};
+    use gtk::glib::translate::{ToGlibPtr, ToGlibPtrMut};
+
+    let window = self.webview.window().ok_or(Error::Unsupported)?;
+    let (event_type, button) = match event {
+      MouseEvent::Move { .. } => (EventType::MotionNotify, 0),
+      MouseEvent::Down { button, .. } => (EventType::ButtonPress, button_number(button)),
+      MouseEvent::Up { button, .. } => (EventType::ButtonRelease, button_number(button)),
+      MouseEvent::Wheel { .. } => (EventType::Scroll, 0),
+    };
+    let position = event.position();
+    let (x_root, y_root) = window.root_coords(position.x as i32, position.y as i32);
+    let mut gdk_event = gtk::gdk::Event::new(event_type);
+    let pointer = window
+      .display()
+      .default_seat()
+      .and_then(|seat| seat.pointer());
+    gdk_event.set_device(pointer.as_ref());
+    unsafe {
+      let raw: *mut ffi::GdkEvent = gdk_event.to_glib_none_mut().0;
+      // The event unrefs its window when freed.
+      let gdk_window: *mut ffi::GdkWindow = window.to_glib_full();
+      let time = gtk::current_event_time();
+      match event {
+        MouseEvent::Move { .. } => {
+          let motion = &mut (*raw).motion;
+          motion.window = gdk_window;
+          motion.time = time;
+          (motion.x, motion.y) = (position.x, position.y);
+          (motion.x_root, motion.y_root) = (x_root as f64, y_root as f64);
+        }
+        MouseEvent::Wheel {
+          delta_x, delta_y, ..
+        } => {
+          let scroll = &mut (*raw).scroll;
+          scroll.window = gdk_window;
+          scroll.time = time;
+          scroll.direction = ffi::GDK_SCROLL_SMOOTH;
+          (scroll.x, scroll.y) = (position.x, position.y);
+          (scroll.x_root, scroll.y_root) = (x_root as f64, y_root as f64);
+          scroll.delta_x = delta_x / PIXELS_PER_SCROLL_STEP;
+          scroll.delta_y = delta_y / PIXELS_PER_SCROLL_STEP;
+        }
+        MouseEvent::Down { .. } | MouseEvent::Up { .. } => {
+          let button_event = &mut (*raw).button;
+          button_event.window = gdk_window;
+          button_event.time = time;
+          button_event.button = button;
+          (button_event.x, button_event.y) = (position.x, position.y);
+          (button_event.x_root, button_event.y_root) = (x_root as f64, y_root as f64);
+        }
+      }
+    }
+    self.webview.event(&gdk_event);
+    Ok(())
+  }
+
+  pub fn send_touch_event(&self, event: TouchEvent) -> Result<()> {
+    use gtk::gdk::{ffi, EventType};
+    use gtk::glib::translate::{ToGlibPtr, ToGlibPtrMut};
+
+    let window = self.webview.window().ok_or(Error::Unsupported)?;
+    let event_type = match event.phase {
+      TouchPhase::Started => EventType::TouchBegin,
+      TouchPhase::Moved => EventType::TouchUpdate,
+      TouchPhase::Ended => EventType::TouchEnd,
+      _ => EventType::TouchCancel,
+    };
+    let pointer = window
+      .display()
+      .default_seat()
+      .and_then(|seat| seat.pointer());
+    for touch in event.touches {
+      let position = touch.position;
+      let (x_root, y_root) = window.root_coords(position.x as i32, position.y as i32);
+      let mut gdk_event = gtk::gdk::Event::new(event_type);
+      gdk_event.set_device(pointer.as_ref());
+      unsafe {
+        let raw: *mut ffi::GdkEvent = gdk_event.to_glib_none_mut().0;
+        let touch_event = &mut (*raw).touch;
+        // The event unrefs its window when freed.
+        touch_event.window = window.to_glib_full();
+        touch_event.time = gtk::current_event_time();
+        // The sequences are opaque pointers identifying the fingers, never dereferenced.
+        touch_event.sequence = (touch.id as usize + 1) as *mut ffi::GdkEventSequence;
+        (touch_event.x, touch_event.y) = (position.x, position.y);
+        (touch_event.x_root, touch_event.y_root) = (x_root as f64, y_root as f64);
+      }
+      self.webview.event(&gdk_event);
+    }
+    Ok(())
+  }
+
+  /// The overlay of a webview built as a child.
+  fn overlay(&self) -> Result<gtk::Overlay> {
+    self
+      .webview
+      .parent()
+      .and_then(|parent| parent.downcast::<gtk::Overlay>().ok())
+      .filter(|overlay| overlay.child().as_ref() != Some(self.webview.upcast_ref()))
+      .ok_or(crate::Error::Unsupported)
+  }
+
   pub fn zoom(&self, scale_factor: f64) {
     WebViewExt::set_zoom_level(&*self.webview, scale_factor);
   }
@@ -451,10 +1022,224 @@ impl InnerWebView {
     Ok(())
   }
 
+  pub fn set_back_forward_gestures(&self, enabled: bool) -> Result<()> {
+    if let Some(settings) = WebViewExt::settings(&*self.webview) {
+      settings.set_enable_back_forward_navigation_gestures(enabled);
+    }
+    Ok(())
+  }
+
+  pub fn set_under_page_background_color(&self, _color: RGBA) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn set_transparent(&self, transparent: bool) -> Result<()> {
+    let alpha = if transparent { 0. } else { 1. };
+    self
+      .webview
+      .set_background_color(&gtk::gdk::RGBA::new(1., 1., 1., alpha));
+    Ok(())
+  }
+
+  pub fn focus(&self) -> Result<()> {
+    self.webview.grab_focus();
+    Ok(())
+  }
+
+  pub fn blur(&self) -> Result<()> {
+    if self.webview.has_focus() {
+      if let Some(window) = self
+        .webview
+        .toplevel()
+        .and_then(|toplevel| toplevel.downcast::<gtk::Window>().ok())
+      {
+        window.set_focus(None::<&gtk::Widget>);
+      }
+    }
+    Ok(())
+  }
+
+  pub fn reparent(&self, window: &Window) -> Result<()> {
+    let webview = &*self.webview;
+    if let Some(parent) = webview
+      .parent()
+      .and_then(|parent| parent.downcast::<gtk::Container>().ok())
+    {
+      parent.remove(webview);
+    }
+    // tao adds a default vertical box so we check for that first
+    if let Some(vbox) = window.default_vbox() {
+      vbox.pack_start(webview, true, true, 0);
+    } else {
+      window.gtk_window().add(webview);
+    }
+    webview.show_all();
+    Ok(())
+  }
+
+  pub fn set_visible(&self, visible: bool) -> Result<()> {
+    self.webview.set_visible(visible);
+    Ok(())
+  }
+
+  pub fn is_visible(&self) -> bool {
+    self.webview.is_visible()
+  }
+
+  pub fn capture_element(
+    &self,
+    selector: &str,
+    format: SnapshotFormat,
+    handler: impl FnOnce(Result<Vec<u8>>) + 'static,
+  ) -> Result<()> {
+    let webview = (*self.webview).clone();
+    let selector = selector.to_string();
+    let cancellable: Option<&Cancellable> = None;
+    self.webview.run_javascript(
+      &element_rect_script(&selector),
+      cancellable,
+      move |result| {
+        let rect = result.map_err(Error::from).and_then(|result| {
+          let json = result
+            .js_value()
+            .and_then(|value| value.to_json(0))
+            .map(|json| json.to_string())
+            .unwrap_or_default();
+          parse_element_rect(&selector, &json)
+        });
+        let rect = match rect {
+          Ok(rect) => rect,
+          Err(e) => return handler(Err(e)),
+        };
+
+        webview.snapshot(
+          SnapshotRegion::Visible,
+          SnapshotOptions::NONE,
+          cancellable,
+          move |surface| {
+            handler(surface.map_err(Error::from).and_then(|surface| {
+              let pixbuf = gtk::gdk::pixbuf_get_from_surface(
+                &surface,
+                rect.x as i32,
+                rect.y as i32,
+                rect.width.ceil() as i32,
+                rect.height.ceil() as i32,
+              )
+              .ok_or(Error::SnapshotFailed)?;
+              let format = match format {
+                SnapshotFormat::Png => "png",
+                SnapshotFormat::Jpeg => "jpeg",
+              };
+              Ok(pixbuf.save_to_bufferv(format, &[])?)
+            }))
+          },
+        );
+      },
+    );
+    Ok(())
+  }
+
+  pub fn pause_all_media(&self) -> Result<()> {
+    // WebKitGTK has no API to pause the media, the page is muted instead.
+    if self.muted_before_pause.get().is_none() {
+      self.muted_before_pause.set(Some(self.is_muted()));
+      self.webview.set_is_muted(true);
+    }
+    Ok(())
+  }
+
+  pub fn resume_media(&self) -> Result<()> {
+    if let Some(muted) = self.muted_before_pause.take() {
+      self.webview.set_is_muted(muted);
+    }
+    Ok(())
+  }
+
+  pub fn process_resident_size(&self) -> Option<u64> {
+    None
+  }
+
+  pub fn suspend(&self) -> Result<()> {
+    // WebKitGTK has no API to suspend a webview or to pause its media.
+    Err(Error::Unsupported)
+  }
+
+  pub fn resume(&self) -> Result<()> {
+    Err(Error::Unsupported)
+  }
+
+  pub fn discard(&self) -> Result<()> {
+    self.webview.terminate_web_process();
+    self.discarded.set(true);
+    Ok(())
+  }
+
+  pub fn restore(&self) -> Result<()> {
+    // The back-forward list is kept by the UI process, reloading starts a new web process.
+    if self.discarded.replace(false) {
+      self.webview.reload();
+    }
+    Ok(())
+  }
+
+  pub fn set_muted(&self, muted: bool) {
+    // The page stays muted until `resume_media`, which applies it.
+    if self.muted_before_pause.get().is_some() {
+      self.muted_before_pause.set(Some(muted));
+    } else {
+      self.webview.set_is_muted(muted);
+    }
+  }
+
+  pub fn is_muted(&self) -> bool {
+    self
+      .muted_before_pause
+      .get()
+      .unwrap_or_else(|| WebViewExt::is_muted(&*self.webview))
+  }
+
+  pub fn set_media_feature_overrides(&self, overrides: MediaFeatureOverrides) -> Result<()> {
+    let js = overrides.to_script();
+    let manager = self
+      .webview
+      .user_content_manager()
+      .ok_or(Error::InitScriptError)?;
+    // Swap the script so the new values also apply to the next navigations.
+    let script = UserScript::new(
+      &js,
+      UserContentInjectedFrames::TopFrame,
+      UserScriptInjectionTime::Start,
+      &[],
+      &[],
+    );
+    if let Some(previous) = self.media_features_script.replace(Some(script.clone())) {
+      manager.remove_script(&previous);
+    }
+    manager.add_script(&script);
+
+    self.eval(&js, None::<Box<dyn FnOnce(String) + Send + 'static>>)
+  }
+
   pub fn load_url(&self, url: &str) {
     self.webview.load_uri(url)
   }
 
+  pub fn load_archive(&self, archive: WebArchive) -> Result<()> {
+    let bytes = gtk::glib::Bytes::from_owned(archive.into_bytes()?);
+    self
+      .webview
+      .load_bytes(&bytes, Some("multipart/related"), None, None);
+    Ok(())
+  }
+
+  pub fn load_url_with_cache_policy(&self, url: &str, policy: CachePolicy) {
+    self.load_url_with_headers(url, policy.headers())
+  }
+
+  pub fn reload_ignoring_cache(&self) {
+    self.webview.reload_bypass_cache()
+  }
+
   pub fn load_url_with_headers(&self, url: &str, headers: http::HeaderMap) {
     let req = URIRequest::builder().uri(url).build();
 
@@ -486,6 +1271,287 @@ impl InnerWebView {
 
     Ok(())
   }
+
+  fn website_data_manager(&self) -> Option<webkit2gtk::WebsiteDataManager> {
+    use webkit2gtk::WebContextExt;
+    WebViewExt::context(&*self.webview)?.website_data_manager()
+  }
+
+  pub fn fetch_service_worker_registrations(
+    &self,
+    handler: impl FnOnce(Result<Vec<String>>) + 'static,
+  ) -> Result<()> {
+    use webkit2gtk::WebsiteDataManagerExt;
+    let data_manager = self.website_data_manager().ok_or(Error::MissingManager)?;
+    data_manager.fetch(
+      webkit2gtk::WebsiteDataTypes::SERVICE_WORKER_REGISTRATIONS,
+      None::<&Cancellable>,
+      move |records| {
+        handler(
+          records
+            .map(|records| {
+              records
+                .iter()
+                .filter_map(|record| record.name().map(|name| name.to_string()))
+                .collect()
+            })
+            .map_err(Into::into),
+        )
+      },
+    );
+    Ok(())
+  }
+
+  pub fn unregister_service_workers(&self, domain: Option<&str>) -> Result<()> {
+    use webkit2gtk::{WebsiteDataManagerExt, WebsiteDataManagerExtManual};
+    let data_manager = self.website_data_manager().ok_or(Error::MissingManager)?;
+    let types = webkit2gtk::WebsiteDataTypes::SERVICE_WORKER_REGISTRATIONS
+      | webkit2gtk::WebsiteDataTypes::DOM_CACHE;
+
+    match domain {
+      None => data_manager.clear(
+        types,
+        gtk::glib::TimeSpan::from_seconds(0),
+        None::<&Cancellable>,
+        |_| {},
+      ),
+      Some(domain) => {
+        let domain = domain.to_string();
+        let manager = data_manager.clone();
+        data_manager.fetch(types, None::<&Cancellable>, move |records| {
+          if let Ok(records) = records {
+            let records: Vec<_> = records
+              .iter()
+              .filter(|record| record.name().as_deref() == Some(domain.as_str()))
+              .collect();
+            if !records.is_empty() {
+              manager.remove(types, &records, None::<&Cancellable>, |_| {});
+            }
+          }
+        });
+      }
+    }
+
+    Ok(())
+  }
+
+  pub fn call_devtools_protocol_method(
+    &self,
+    _method: &str,
+    _params: &str,
+    _handler: impl FnOnce(Result<String>) + 'static,
+  ) -> Result<()> {
+    Err(Error::Unsupported)
+  }
+
+  pub fn subscribe_devtools_protocol_event(
+    &self,
+    _event: &str,
+    _handler: impl Fn(String) + 'static,
+  ) -> Result<()> {
+    Err(Error::Unsupported)
+  }
+}
+
+const OVERLAY_NAME: &str = "wry-webviews";
+/// The pixels WebKitGTK scrolls by for each unit of the deltas of a smooth scroll event.
+const PIXELS_PER_SCROLL_STEP: f64 = 40.;
+
+/// Restricts the input shape of the webview to everything but `regions`.
+fn set_click_through_regions(webview: &WebView, regions: &[ClickThroughRegion]) {
+  if regions.is_empty() {
+    webview.input_shape_combine_region(None);
+    return;
+  }
+  let max = i16::MAX as i32;
+  let shape = gtk::cairo::Region::create_rectangle(&gtk::cairo::RectangleInt::new(0, 0, max, max));
+  for region in regions {
+    let (x, y, width, height) = region.to_pixels(1.);
+    let _ = shape.subtract_rectangle(&gtk::cairo::RectangleInt::new(x, y, width, height));
+  }
+  webview.input_shape_combine_region(Some(&shape));
+}
+
+/// The [`KeyEvent`] of a GDK key press.
+fn key_event(event: &gtk::gdk::EventKey) -> KeyEvent {
+  use gtk::gdk::ModifierType;
+
+  let keyval = event.keyval();
+  let named = match keyval.name().as_deref() {
+    Some("Return" | "KP_Enter" | "ISO_Enter") => Some("Enter".to_string()),
+    Some("Escape") => Some("Escape".to_string()),
+    Some("Tab" | "ISO_Left_Tab") => Some("Tab".to_string()),
+    Some("BackSpace") => Some("Backspace".to_string()),
+    Some("Delete" | "KP_Delete") => Some("Delete".to_string()),
+    Some("Insert" | "KP_Insert") => Some("Insert".to_string()),
+    Some("Home" | "KP_Home") => Some("Home".to_string()),
+    Some("End" | "KP_End") => Some("End".to_string()),
+    Some("Page_Up" | "KP_Page_Up") => Some("PageUp".to_string()),
+    Some("Page_Down" | "KP_Page_Down") => Some("PageDown".to_string()),
+    Some("Left" | "KP_Left") => Some("ArrowLeft".to_string()),
+    Some("Up" | "KP_Up") => Some("ArrowUp".to_string()),
+    Some("Right" | "KP_Right") => Some("ArrowRight".to_string()),
+    Some("Down" | "KP_Down") => Some("ArrowDown".to_string()),
+    Some(name) if name.starts_with('F') && name[1..].parse::<u8>().is_ok() => {
+      Some(name.to_string())
+    }
+    _ => None,
+  };
+  let key = named.unwrap_or_else(|| match keyval.to_unicode() {
+    Some(c) if !c.is_control() => c.to_string(),
+    _ => "Unidentified".to_string(),
+  });
+
+  let state = event.state();
+  KeyEvent {
+    key,
+    modifiers: KeyModifiers {
+      shift: state.contains(ModifierType::SHIFT_MASK),
+      control: state.contains(ModifierType::CONTROL_MASK),
+      alt: state.contains(ModifierType::MOD1_MASK),
+      meta: state.intersects(ModifierType::SUPER_MASK | ModifierType::META_MASK),
+    },
+  }
+}
+
+/// The overlay stacking the child webviews above the webview filling `window`, which replaces the
+/// webview in the window when the first child is added.
+fn webview_overlay(window: &Window) -> Result<gtk::Overlay> {
+  let container: gtk::Container = match window.default_vbox() {
+    Some(vbox) => vbox.clone().upcast(),
+    None => window.gtk_window().clone().upcast(),
+  };
+  let children = container.children();
+  if let Some(overlay) = children
+    .iter()
+    .filter(|child| child.widget_name() == OVERLAY_NAME)
+    .find_map(|child| child.clone().downcast::<gtk::Overlay>().ok())
+  {
+    return Ok(overlay);
+  }
+
+  let main = children
+    .into_iter()
+    .find(|child| child.is::<WebView>())
+    .ok_or(crate::Error::Unsupported)?;
+  let overlay = gtk::Overlay::new();
+  overlay.set_widget_name(OVERLAY_NAME);
+  container.remove(&main);
+  overlay.add(&main);
+  match container.downcast_ref::<gtk::Box>() {
+    Some(vbox) => vbox.pack_start(&overlay, true, true, 0),
+    None => container.add(&overlay),
+  }
+  overlay.show();
+  Ok(overlay)
+}
+
+/// Places a child webview in its overlay, with a position and size in logical pixels, aligned so
+/// it follows the size of the overlay with `policy`.
+fn set_overlay_bounds(
+  webview: &WebView,
+  position: PhysicalPosition<i32>,
+  size: PhysicalSize<u32>,
+  policy: AutoResizePolicy,
+) {
+  let scale_factor = webview.scale_factor() as f64;
+  let position = position.to_logical::<i32>(scale_factor);
+  let size = size.to_logical::<i32>(scale_factor);
+  // The overlay isn't allocated yet while the window is built.
+  let parent = webview
+    .parent()
+    .map(|parent| (parent.allocated_width(), parent.allocated_height()))
+    .filter(|&(width, height)| width > 1 && height > 1)
+    .or_else(|| {
+      webview
+        .toplevel()
+        .and_then(|toplevel| toplevel.downcast::<gtk::Window>().ok())
+        .map(|window| window.size())
+    })
+    .unwrap_or_default();
+
+  let anchors = policy.anchors();
+  let axis = |start: i32, len: i32, parent: i32, anchor_start: bool, anchor_end: bool| {
+    let end_margin = (parent - start - len).max(0);
+    match (anchor_start, anchor_end) {
+      (true, true) => (gtk::Align::Fill, start, end_margin, -1),
+      (false, true) => (gtk::Align::End, 0, end_margin, len),
+      _ => (gtk::Align::Start, start, 0, len),
+    }
+  };
+  let (halign, margin_start, margin_end, width) = axis(
+    position.x,
+    size.width,
+    parent.0,
+    anchors.left,
+    anchors.right,
+  );
+  let (valign, margin_top, margin_bottom, height) = axis(
+    position.y,
+    size.height,
+    parent.1,
+    anchors.top,
+    anchors.bottom,
+  );
+  webview.set_halign(halign);
+  webview.set_valign(valign);
+  webview.set_margin_start(margin_start);
+  webview.set_margin_end(margin_end);
+  webview.set_margin_top(margin_top);
+  webview.set_margin_bottom(margin_bottom);
+  webview.set_size_request(width, height);
+}
+
+pub fn platform_pump_events() {
+  if gtk::is_initialized_main_thread() {
+    while gtk::events_pending() {
+      gtk::main_iteration_do(false);
+    }
+  }
+}
+
+pub fn open_in_browser(url: &str) -> Result<()> {
+  gtk::gio::AppInfo::launch_default_for_uri(url, None::<&gtk::gio::AppLaunchContext>)?;
+  Ok(())
+}
+
+pub fn platform_printers() -> Result<Vec<String>> {
+  use gtk::glib::{
+    ffi::{gboolean, gpointer, GTRUE},
+    translate::from_glib_none,
+  };
+  use std::os::raw::{c_char, c_void};
+
+  // Part of the unix print API of GTK, which isn't bound by the gtk crate.
+  extern "C" {
+    fn gtk_enumerate_printers(
+      func: unsafe extern "C" fn(*mut c_void, gpointer) -> gboolean,
+      data: gpointer,
+      destroy: Option<unsafe extern "C" fn(gpointer)>,
+      wait: gboolean,
+    );
+    fn gtk_printer_get_name(printer: *mut c_void) -> *const c_char;
+  }
+
+  unsafe extern "C" fn add_printer(printer: *mut c_void, data: gpointer) -> gboolean {
+    let printers = &mut *(data as *mut Vec<String>);
+    let name: String = from_glib_none(gtk_printer_get_name(printer));
+    printers.push(name);
+    // Keep enumerating.
+    0
+  }
+
+  let mut printers = Vec::new();
+  // Waiting, the printers are all added before it returns.
+  unsafe {
+    gtk_enumerate_printers(
+      add_printer,
+      &mut printers as *mut Vec<String> as gpointer,
+      None,
+      GTRUE,
+    )
+  };
+  Ok(printers)
 }
 
 pub fn platform_webview_version() -> Result<String> {
@@ -5,11 +5,19 @@
 //! Unix platform extensions for [`WebContext`](super::WebContext).
 
 use crate::{
-  webview::{web_context::WebContextData, RequestAsyncResponder},
+  webview::{
+    request_body::{self, RequestBody},
+    web_context::WebContextData,
+    RequestAsyncResponder,
+  },
   Error,
 };
+#[cfg(feature = "linux-body")]
+use gtk::glib::thread_guard::ThreadGuard;
 use gtk::glib::FileError;
 use http::{header::CONTENT_TYPE, Request, Response as HttpResponse};
+#[cfg(feature = "linux-body")]
+use std::sync::mpsc;
 use std::{
   borrow::Cow,
   cell::RefCell,
@@ -106,6 +114,56 @@ impl WebContextImpl {
     self.automation = flag;
     self.context.set_automation_allowed(flag);
   }
+
+  pub fn add_content_rule_list(&mut self, data: &WebContextData, identifier: &str, rules: &str) {
+    use gtk::glib::translate::{from_glib_full, ToGlibPtr};
+
+    unsafe extern "C" fn saved(
+      store: *mut gtk::glib::gobject_ffi::GObject,
+      result: *mut gtk::gio::ffi::GAsyncResult,
+      manager: gtk::glib::ffi::gpointer,
+    ) {
+      let manager: UserContentManager =
+        from_glib_full(manager as *mut webkit2gtk::ffi::WebKitUserContentManager);
+      let mut error = std::ptr::null_mut();
+      let filter = webkit2gtk::ffi::webkit_user_content_filter_store_save_finish(
+        store as _, result, &mut error,
+      );
+      if filter.is_null() {
+        let error: gtk::glib::Error = from_glib_full(error);
+        log::warn!("failed to compile content rule list: {error}");
+        return;
+      }
+      webkit2gtk::ffi::webkit_user_content_manager_add_filter(manager.to_glib_none().0, filter);
+      webkit2gtk::ffi::webkit_user_content_filter_unref(filter);
+    }
+
+    let path = data
+      .data_directory()
+      .map(|d| d.join("content-filters"))
+      .unwrap_or_else(|| std::env::temp_dir().join("wry-content-filters"));
+    let rules = gtk::glib::Bytes::from(rules.as_bytes());
+
+    // Released by the callback.
+    let manager: *mut webkit2gtk::ffi::WebKitUserContentManager = self.manager.to_glib_full();
+
+    // Adding a filter with the identifier of a previous one replaces it.
+    unsafe {
+      let store = webkit2gtk::ffi::webkit_user_content_filter_store_new(
+        path.to_string_lossy().to_glib_none().0,
+      );
+      webkit2gtk::ffi::webkit_user_content_filter_store_save(
+        store,
+        identifier.to_glib_none().0,
+        rules.to_glib_none().0,
+        std::ptr::null_mut(),
+        Some(saved),
+        manager as _,
+      );
+      // The pending operation holds its own reference.
+      gtk::glib::gobject_ffi::g_object_unref(store as _);
+    }
+  }
 }
 
 /// [`WebContext`](super::WebContext) items that only matter on unix.
@@ -116,15 +174,6 @@ pub trait WebContextExt {
   /// The GTK [`UserContentManager`] of all webviews in the context.
   fn manager(&self) -> &UserContentManager;
 
-  /// Register a custom protocol to the web context.
-  ///
-  /// When duplicate schemes are registered, the duplicate handler will still be submitted and the
-  /// `Err(Error::DuplicateCustomProtocol)` will be returned. It is safe to ignore if you are
-  /// relying on the platform's implementation to properly handle duplicated scheme handlers.
-  fn register_uri_scheme<F>(&mut self, name: &str, handler: F) -> crate::Result<()>
-  where
-    F: Fn(Request<Vec<u8>>, RequestAsyncResponder) + 'static;
-
   /// Register a custom protocol to the web context, only if it is not a duplicate scheme.
   ///
   /// If a duplicate scheme has been passed, its handler will **NOT** be registered and the
@@ -133,6 +182,15 @@ pub trait WebContextExt {
   where
     F: Fn(Request<Vec<u8>>, RequestAsyncResponder) + 'static;
 
+  /// Register a custom protocol to the web context.
+  ///
+  /// When duplicate schemes are registered, the duplicate handler will still be submitted and the
+  /// `Err(Error::DuplicateCustomProtocol)` will be returned. It is safe to ignore if you are
+  /// relying on the platform's implementation to properly handle duplicated scheme handlers.
+  fn register_streaming_uri_scheme<F>(&mut self, name: &str, handler: F) -> crate::Result<()>
+  where
+    F: Fn(Request<RequestBody>, RequestAsyncResponder) + 'static;
+
   /// Add a [`WebView`] to the queue waiting to be opened.
   ///
   /// See the `WebviewUriLoader` for more information.
@@ -166,24 +224,24 @@ impl WebContextExt for super::WebContext {
     &self.os.manager
   }
 
-  fn register_uri_scheme<F>(&mut self, name: &str, handler: F) -> crate::Result<()>
+  fn try_register_uri_scheme<F>(&mut self, name: &str, handler: F) -> crate::Result<()>
   where
     F: Fn(Request<Vec<u8>>, RequestAsyncResponder) + 'static,
   {
-    actually_register_uri_scheme(self, name, handler)?;
     if self.os.registered_protocols.insert(name.to_string()) {
-      Ok(())
+      actually_register_uri_scheme(self, name, request_body::buffered(Box::new(handler)))
     } else {
       Err(Error::DuplicateCustomProtocol(name.to_string()))
     }
   }
 
-  fn try_register_uri_scheme<F>(&mut self, name: &str, handler: F) -> crate::Result<()>
+  fn register_streaming_uri_scheme<F>(&mut self, name: &str, handler: F) -> crate::Result<()>
   where
-    F: Fn(Request<Vec<u8>>, RequestAsyncResponder) + 'static,
+    F: Fn(Request<RequestBody>, RequestAsyncResponder) + 'static,
   {
+    actually_register_uri_scheme(self, name, handler)?;
     if self.os.registered_protocols.insert(name.to_string()) {
-      actually_register_uri_scheme(self, name, handler)
+      Ok(())
     } else {
       Err(Error::DuplicateCustomProtocol(name.to_string()))
     }
@@ -288,7 +346,7 @@ fn actually_register_uri_scheme<F>(
   handler: F,
 ) -> crate::Result<()>
 where
-  F: Fn(Request<Vec<u8>>, RequestAsyncResponder) + 'static,
+  F: Fn(Request<RequestBody>, RequestAsyncResponder) + 'static,
 {
   use webkit2gtk::{SecurityManagerExt, URISchemeRequestExt, WebContextExt};
   let context = &context.os.context;
@@ -305,7 +363,6 @@ where
       // FIXME: Read the body (forms post)
       #[allow(unused_mut)]
       let mut http_request = Request::builder().uri(uri).method("GET");
-      let body;
       use http::{header::HeaderName, HeaderValue};
 
       // Set request http headers
@@ -326,40 +383,15 @@ where
         http_request = http_request.method(method.as_str());
       }
 
+      // Set request http body
       #[cfg(feature = "linux-body")]
-      {
-        use gtk::{
-          gdk::prelude::{InputStreamExt, InputStreamExtManual},
-          gio::Cancellable,
-          glib::Bytes,
-        };
-
-        // Set request http body
-        let cancellable: Option<&Cancellable> = None;
-        body = request
-          .http_body()
-          .map(|s| {
-            const BUFFER_LEN: usize = 1024;
-            let mut result = Vec::new();
-            let mut buffer = vec![0; BUFFER_LEN];
-            while let Ok(count) = s.read(&mut buffer[..], cancellable) {
-              if count == BUFFER_LEN {
-                result.append(&mut buffer);
-                buffer.resize(BUFFER_LEN, 0);
-              } else {
-                buffer.truncate(count);
-                result.append(&mut buffer);
-                break;
-              }
-            }
-            result
-          })
-          .unwrap_or(Vec::new());
-      }
+      let body = request
+        .http_body()
+        .map_or_else(RequestBody::empty, |stream| {
+          RequestBody::new(HttpBody::new(stream))
+        });
       #[cfg(not(feature = "linux-body"))]
-      {
-        body = Vec::new();
-      }
+      let body = RequestBody::empty();
 
       let http_request = match http_request.body(body) {
         Ok(req) => req,
@@ -412,6 +444,104 @@ where
   Ok(())
 }
 
+#[cfg(feature = "linux-body")]
+type Chunk = (
+  ThreadGuard<gtk::gio::InputStream>,
+  std::io::Result<gtk::glib::Bytes>,
+);
+
+/// The body of a request, read in chunks from the stream of WebKitGTK by the main loop as the
+/// handler reads it, since the stream can't leave the main thread.
+#[cfg(feature = "linux-body")]
+struct HttpBody {
+  /// The stream, while no chunk is being read from it.
+  stream: Option<ThreadGuard<gtk::gio::InputStream>>,
+  sender: mpsc::Sender<Chunk>,
+  chunks: mpsc::Receiver<Chunk>,
+  chunk: std::io::Cursor<gtk::glib::Bytes>,
+  ended: bool,
+}
+
+#[cfg(feature = "linux-body")]
+impl HttpBody {
+  const CHUNK_SIZE: usize = 64 * 1024;
+
+  fn new(stream: gtk::gio::InputStream) -> Self {
+    let (sender, chunks) = mpsc::channel();
+    Self {
+      stream: Some(ThreadGuard::new(stream)),
+      sender,
+      chunks,
+      chunk: std::io::Cursor::new(gtk::glib::Bytes::from_static(b"")),
+      ended: false,
+    }
+  }
+
+  /// Wait for the chunk read by the main loop, running it if the body is read on the main thread.
+  fn receive(&self) -> Chunk {
+    let context = gtk::glib::MainContext::default();
+    if context.is_owner() {
+      loop {
+        if let Ok(chunk) = self.chunks.try_recv() {
+          return chunk;
+        }
+        context.iteration(true);
+      }
+    }
+    // `self` holds a sender, the channel can't be disconnected.
+    self.chunks.recv().unwrap()
+  }
+}
+
+#[cfg(feature = "linux-body")]
+impl std::io::Read for HttpBody {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    use gtk::gio::prelude::InputStreamExt;
+
+    loop {
+      let read = self.chunk.read(buf)?;
+      if read > 0 || buf.is_empty() || self.ended {
+        return Ok(read);
+      }
+      let Some(stream) = self.stream.take() else {
+        return Ok(0);
+      };
+      let sender = self.sender.clone();
+      gtk::glib::idle_add_once(move || {
+        let input = stream.get_ref().clone();
+        input.read_bytes_async(
+          Self::CHUNK_SIZE,
+          gtk::glib::Priority::DEFAULT,
+          None::<&gtk::gio::Cancellable>,
+          move |chunk| {
+            let chunk = chunk.map_err(std::io::Error::other);
+            // The reader is gone if it fails, and the stream is released here.
+            let _ = sender.send((stream, chunk));
+          },
+        );
+      });
+
+      let (stream, chunk) = self.receive();
+      self.stream = Some(stream);
+      let chunk = chunk?;
+      self.ended = chunk.is_empty();
+      self.chunk = std::io::Cursor::new(chunk);
+    }
+  }
+}
+
+#[cfg(feature = "linux-body")]
+impl Drop for HttpBody {
+  fn drop(&mut self) {
+    // The stream must be released on the main thread.
+    if let Some(stream) = self.stream.take() {
+      if !stream.is_owner() {
+        gtk::glib::idle_add_once(move || drop(stream));
+      }
+    }
+  }
+}
+
 /// Prevents an unknown concurrency bug with loading multiple URIs at the same time on webkit2gtk.
 ///
 /// Using the queue prevents data race issues with loading uris for multiple [`WebView`]s in the
@@ -0,0 +1,82 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// WebRTC restrictions of the webview.
+///
+/// See [`WebViewBuilder::with_webrtc_policy`](super::WebViewBuilder::with_webrtc_policy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WebRtcPolicy {
+  /// Whether WebRTC is available to the page. Default is `true`.
+  pub enabled: bool,
+  /// Only gather relay (TURN) ICE candidates, so the local and public IP addresses of the device
+  /// are not leaked to the peers. Default is `false`.
+  pub relay_only: bool,
+  /// Whether `navigator.mediaDevices.enumerateDevices()` exposes the device labels. Default is
+  /// `true`, in which case the engine still hides them until a media permission is granted.
+  pub expose_device_labels: bool,
+}
+
+impl Default for WebRtcPolicy {
+  fn default() -> Self {
+    Self {
+      enabled: true,
+      relay_only: false,
+      expose_device_labels: true,
+    }
+  }
+}
+
+impl WebRtcPolicy {
+  /// Script enforcing the policy in the page, `None` if it doesn't restrict anything.
+  pub(crate) fn to_script(self) -> Option<String> {
+    let mut script = String::new();
+
+    if !self.enabled {
+      script.push_str(
+        r#"['RTCPeerConnection', 'webkitRTCPeerConnection', 'RTCDataChannel', 'RTCIceCandidate', 'RTCSessionDescription'].forEach(function (name) {
+  Object.defineProperty(window, name, { value: undefined, configurable: false, writable: false });
+});
+"#,
+      );
+    } else if self.relay_only {
+      script.push_str(
+        r#"(function () {
+  var Native = window.RTCPeerConnection;
+  if (!Native) return;
+  var relay = function (config) { return Object.assign({}, config, { iceTransportPolicy: 'relay' }); };
+  var setConfiguration = Native.prototype.setConfiguration;
+  Native.prototype.setConfiguration = function (config) { return setConfiguration.call(this, relay(config)); };
+  var RTCPeerConnection = function (config, constraints) { return new Native(relay(config), constraints); };
+  RTCPeerConnection.prototype = Native.prototype;
+  RTCPeerConnection.generateCertificate = Native.generateCertificate;
+  window.RTCPeerConnection = RTCPeerConnection;
+  if (window.webkitRTCPeerConnection) window.webkitRTCPeerConnection = RTCPeerConnection;
+})();
+"#,
+      );
+    }
+
+    if !self.expose_device_labels {
+      script.push_str(
+        r#"(function () {
+  var devices = navigator.mediaDevices;
+  if (!devices || !devices.enumerateDevices) return;
+  var enumerateDevices = devices.enumerateDevices.bind(devices);
+  devices.enumerateDevices = function () {
+    return enumerateDevices().then(function (list) {
+      return list.map(function (d) {
+        var info = { deviceId: d.deviceId, groupId: d.groupId, kind: d.kind, label: '' };
+        info.toJSON = function () { return { deviceId: info.deviceId, groupId: info.groupId, kind: info.kind, label: '' }; };
+        return info;
+      });
+    });
+  };
+})();
+"#,
+      );
+    }
+
+    (!script.is_empty()).then_some(script)
+  }
+}
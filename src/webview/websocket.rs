@@ -0,0 +1,413 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Intercepts the WebSocket connections opened by the pages, see
+//! [`WebViewBuilder::with_websocket_handler`](super::WebViewBuilder::with_websocket_handler).
+
+use super::{
+  ipc_router::{evaluate, IpcRouter, Route, ScriptEvaluator},
+  WebViewAttributes,
+};
+
+use std::{cell::RefCell, collections::HashMap};
+
+/// A WebSocket connection opened by a page.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebSocketRequest {
+  /// Identifies the connection in [`WebView::send_websocket_message`](super::WebView::send_websocket_message)
+  /// and [`WebView::close_websocket`](super::WebView::close_websocket), unique in the page.
+  pub id: u64,
+  pub url: String,
+  /// The subprotocols requested by the page.
+  pub protocols: Vec<String>,
+}
+
+/// What to do with a [`WebSocketRequest`].
+pub enum WebSocketAction {
+  /// Connect to the requested URL.
+  Allow,
+  /// Connect to another URL instead, e.g. with an authentication token in its query.
+  Redirect(String),
+  /// Fail the connection, like an unreachable server.
+  Block,
+  /// Don't connect, the connection is handled by the application: the messages sent by the page
+  /// and its closing are given to the handler, and the application answers with
+  /// [`WebView::send_websocket_message`](super::WebView::send_websocket_message) and
+  /// [`WebView::close_websocket`](super::WebView::close_websocket).
+  Proxy(Box<dyn Fn(WebSocketProxyEvent)>),
+}
+
+/// A message of a WebSocket connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebSocketMessage {
+  Text(String),
+  Binary(Vec<u8>),
+}
+
+/// An event of a connection proxied with [`WebSocketAction::Proxy`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebSocketProxyEvent {
+  /// The page sent a message.
+  Message(WebSocketMessage),
+  /// The connection was closed, by the page or the application.
+  Closed { code: u16, reason: String },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Message {
+  Open {
+    id: u64,
+    url: String,
+    protocols: Vec<String>,
+  },
+  Send {
+    id: u64,
+    text: Option<String>,
+    binary: Option<Vec<u8>>,
+  },
+  Close {
+    id: u64,
+    code: u16,
+    reason: String,
+  },
+}
+
+/// Prefix of the IPC messages carrying the WebSocket connections and their proxied messages.
+pub(crate) const WEBSOCKET_MESSAGE_PREFIX: &str = "__WRY_WEBSOCKET__:";
+
+/// Replaces `WebSocket` with a connection waiting for the decision of the application before
+/// opening the native one, or exchanging messages through the IPC when proxied.
+pub(crate) const WEBSOCKET_SCRIPT: &str = r#"(function () {
+  var Native = window.WebSocket;
+  if (!Native || window.__WRY_WEBSOCKET__) return;
+  var post = function (message) {
+    window.ipc.postMessage('__WRY_WEBSOCKET__:' + JSON.stringify(message));
+  };
+  var bytes = function (data) {
+    if (data instanceof ArrayBuffer) return Array.from(new Uint8Array(data));
+    return Array.from(new Uint8Array(data.buffer, data.byteOffset, data.byteLength));
+  };
+  var sockets = {};
+  var nextId = 0;
+
+  function WebSocket(url, protocols) {
+    var target = new EventTarget();
+    Object.setPrototypeOf(target, WebSocket.prototype);
+    target._id = nextId++;
+    target._url = new URL(url, location.href).href.replace(/^http/, 'ws');
+    target._protocols = protocols === undefined ? [] : [].concat(protocols).map(String);
+    target._readyState = 0;
+    target._binaryType = 'blob';
+    target._protocol = '';
+    sockets[target._id] = target;
+    post({ type: 'open', id: target._id, url: target._url, protocols: target._protocols });
+    return target;
+  }
+  WebSocket.prototype = Object.create(EventTarget.prototype);
+  WebSocket.prototype.constructor = WebSocket;
+  ['CONNECTING', 'OPEN', 'CLOSING', 'CLOSED'].forEach(function (name, value) {
+    WebSocket[name] = value;
+    WebSocket.prototype[name] = value;
+  });
+  ['open', 'message', 'error', 'close'].forEach(function (type) {
+    Object.defineProperty(WebSocket.prototype, 'on' + type, {
+      get: function () { return this['_on' + type] || null; },
+      set: function (handler) { this['_on' + type] = handler; }
+    });
+  });
+  Object.defineProperties(WebSocket.prototype, {
+    url: { get: function () { return this._url; } },
+    readyState: { get: function () { return this._readyState; } },
+    protocol: { get: function () { return this._protocol; } },
+    extensions: { get: function () { return this._native ? this._native.extensions : ''; } },
+    bufferedAmount: { get: function () { return this._native ? this._native.bufferedAmount : 0; } },
+    binaryType: {
+      get: function () { return this._binaryType; },
+      set: function (type) {
+        this._binaryType = type;
+        if (this._native) this._native.binaryType = type;
+      }
+    }
+  });
+  WebSocket.prototype._emit = function (event) {
+    this.dispatchEvent(event);
+    var handler = this['_on' + event.type];
+    if (typeof handler === 'function') handler.call(this, event);
+  };
+  WebSocket.prototype._closed = function (code, reason, wasClean) {
+    if (this._readyState === 3) return;
+    this._readyState = 3;
+    delete sockets[this._id];
+    this._emit(new CloseEvent('close', { code: code, reason: reason, wasClean: wasClean }));
+  };
+  WebSocket.prototype.send = function (data) {
+    if (this._readyState === 0) throw new DOMException('Still in CONNECTING state.', 'InvalidStateError');
+    if (this._readyState !== 1) return;
+    if (this._native) return this._native.send(data);
+    var id = this._id;
+    if (typeof data === 'string') {
+      post({ type: 'send', id: id, text: data });
+    } else if (data instanceof Blob) {
+      data.arrayBuffer().then(function (buffer) {
+        post({ type: 'send', id: id, binary: bytes(buffer) });
+      });
+    } else {
+      post({ type: 'send', id: id, binary: bytes(data) });
+    }
+  };
+  WebSocket.prototype.close = function (code, reason) {
+    if (this._readyState >= 2) return;
+    code = code === undefined ? 1005 : code;
+    reason = reason === undefined ? '' : String(reason);
+    if (this._native) {
+      this._readyState = 2;
+      return this._native.close(code === 1005 ? undefined : code, reason);
+    }
+    post({ type: 'close', id: this._id, code: code, reason: reason });
+    this._closed(code, reason, true);
+  };
+
+  var connect = function (socket, url) {
+    var native = new Native(url, socket._protocols);
+    native.binaryType = socket._binaryType;
+    socket._native = native;
+    native.addEventListener('open', function () {
+      socket._readyState = 1;
+      socket._protocol = native.protocol;
+      socket._emit(new Event('open'));
+    });
+    native.addEventListener('message', function (event) {
+      socket._emit(new MessageEvent('message', { data: event.data, origin: event.origin }));
+    });
+    native.addEventListener('error', function () {
+      socket._emit(new Event('error'));
+    });
+    native.addEventListener('close', function (event) {
+      socket._closed(event.code, event.reason, event.wasClean);
+    });
+  };
+
+  Object.defineProperty(window, '__WRY_WEBSOCKET__', {
+    value: {
+      decide: function (id, action, url) {
+        var socket = sockets[id];
+        if (!socket || socket._readyState !== 0) return;
+        if (action === 'connect') {
+          connect(socket, url);
+        } else if (action === 'proxy') {
+          socket._readyState = 1;
+          socket._emit(new Event('open'));
+        } else {
+          socket._emit(new Event('error'));
+          socket._closed(1006, '', false);
+        }
+      },
+      message: function (id, text, binary) {
+        var socket = sockets[id];
+        if (!socket || socket._readyState !== 1 || socket._native) return;
+        var data = text;
+        if (binary) {
+          var array = new Uint8Array(binary);
+          data = socket._binaryType === 'arraybuffer' ? array.buffer : new Blob([array]);
+        }
+        socket._emit(new MessageEvent('message', { data: data, origin: new URL(socket._url).origin }));
+      },
+      close: function (id, code, reason) {
+        var socket = sockets[id];
+        if (!socket || socket._native) return;
+        post({ type: 'close', id: id, code: code, reason: reason });
+        socket._closed(code, reason, true);
+      }
+    }
+  });
+  window.WebSocket = WebSocket;
+})();"#;
+
+/// Decides what to do with the WebSocket connections of the page, and dispatches the events of
+/// the proxied ones.
+pub(crate) struct WebSocketInterceptor {
+  handler: Box<dyn Fn(WebSocketRequest) -> WebSocketAction>,
+  proxies: RefCell<HashMap<u64, Box<dyn Fn(WebSocketProxyEvent)>>>,
+}
+
+impl WebSocketInterceptor {
+  pub(crate) fn new(handler: Box<dyn Fn(WebSocketRequest) -> WebSocketAction>) -> Self {
+    Self {
+      handler,
+      proxies: Default::default(),
+    }
+  }
+
+  /// Handles the JSON of a WebSocket message, returning the script answering it if any.
+  pub(crate) fn handle(&self, json: &str) -> Option<String> {
+    match serde_json::from_str(json).ok()? {
+      Message::Open { id, url, protocols } => {
+        let request = WebSocketRequest { id, url, protocols };
+        let url = request.url.clone();
+        let (action, url) = match (self.handler)(request) {
+          WebSocketAction::Allow => ("connect", Some(url)),
+          WebSocketAction::Redirect(url) => ("connect", Some(url)),
+          WebSocketAction::Block => ("block", None),
+          WebSocketAction::Proxy(proxy) => {
+            // A new page reuses the ids of the connections of the previous one.
+            self.proxies.borrow_mut().insert(id, proxy);
+            ("proxy", None)
+          }
+        };
+        Some(format!(
+          "window.__WRY_WEBSOCKET__.decide({id}, '{action}', {});",
+          serde_json::Value::from(url)
+        ))
+      }
+      Message::Send { id, text, binary } => {
+        let message = match (text, binary) {
+          (Some(text), _) => WebSocketMessage::Text(text),
+          (None, Some(binary)) => WebSocketMessage::Binary(binary),
+          (None, None) => return None,
+        };
+        if let Some(proxy) = self.proxies.borrow().get(&id) {
+          proxy(WebSocketProxyEvent::Message(message));
+        }
+        None
+      }
+      Message::Close { id, code, reason } => {
+        let proxy = self.proxies.borrow_mut().remove(&id);
+        if let Some(proxy) = proxy {
+          proxy(WebSocketProxyEvent::Closed { code, reason });
+        }
+        None
+      }
+    }
+  }
+}
+
+/// Sends `message` to the page on the proxied connection `id`.
+pub(crate) fn send_message_script(id: u64, message: &WebSocketMessage) -> String {
+  let (text, binary) = match message {
+    WebSocketMessage::Text(text) => (serde_json::Value::from(text.as_str()), "null".into()),
+    WebSocketMessage::Binary(binary) => (
+      serde_json::Value::Null,
+      serde_json::to_string(binary).unwrap_or_default(),
+    ),
+  };
+  format!("window.__WRY_WEBSOCKET__.message({id}, {text}, {binary});")
+}
+
+/// Closes the proxied connection `id`.
+pub(crate) fn close_script(id: u64, code: u16, reason: &str) -> String {
+  format!(
+    "window.__WRY_WEBSOCKET__.close({id}, {code}, {});",
+    serde_json::Value::from(reason)
+  )
+}
+
+/// The route of the WebSocket messages to an interceptor calling `handler`, answering the page
+/// with `evaluator`.
+pub(crate) fn route(
+  handler: Box<dyn Fn(WebSocketRequest) -> WebSocketAction>,
+  evaluator: ScriptEvaluator,
+) -> Route {
+  let interceptor = WebSocketInterceptor::new(handler);
+  (
+    WEBSOCKET_MESSAGE_PREFIX,
+    Box::new(move |_, json| {
+      if let Some(js) = interceptor.handle(json) {
+        evaluate(&evaluator, &js)
+      }
+    }),
+  )
+}
+
+/// Intercepts the WebSocket connections of the pages for the WebSocket handler of `attributes`,
+/// answering the pages with `evaluator`.
+pub(crate) fn install(
+  attributes: &mut WebViewAttributes,
+  router: &mut IpcRouter,
+  evaluator: &ScriptEvaluator,
+) {
+  if let Some(handler) = attributes.websocket_handler.take() {
+    router.add(route(handler, evaluator.clone()));
+    // Run before the user scripts so they only see the intercepting constructor.
+    attributes
+      .initialization_scripts
+      .insert(0, router.sign(WEBSOCKET_SCRIPT));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::rc::Rc;
+
+  #[test]
+  fn should_decide_connections() {
+    let interceptor = WebSocketInterceptor::new(Box::new(|request| {
+      if request.url.starts_with("wss://api.example.com/") {
+        WebSocketAction::Redirect(format!("{}?token=secret", request.url))
+      } else {
+        WebSocketAction::Block
+      }
+    }));
+    assert_eq!(
+      interceptor
+        .handle(r#"{"type":"open","id":0,"url":"wss://api.example.com/live","protocols":[]}"#)
+        .as_deref(),
+      Some(
+        r#"window.__WRY_WEBSOCKET__.decide(0, 'connect', "wss://api.example.com/live?token=secret");"#
+      )
+    );
+    assert_eq!(
+      interceptor
+        .handle(r#"{"type":"open","id":1,"url":"wss://tracker.example.com/","protocols":["v1"]}"#)
+        .as_deref(),
+      Some("window.__WRY_WEBSOCKET__.decide(1, 'block', null);")
+    );
+    assert_eq!(interceptor.handle("{}"), None);
+  }
+
+  #[test]
+  fn should_dispatch_proxied_events() {
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let events_ = events.clone();
+    let interceptor = WebSocketInterceptor::new(Box::new(move |_| {
+      let events = events_.clone();
+      WebSocketAction::Proxy(Box::new(move |event| events.borrow_mut().push(event)))
+    }));
+    interceptor.handle(r#"{"type":"open","id":3,"url":"ws://localhost/","protocols":[]}"#);
+    interceptor.handle(r#"{"type":"send","id":3,"text":"ping"}"#);
+    interceptor.handle(r#"{"type":"send","id":3,"binary":[1,2]}"#);
+    interceptor.handle(r#"{"type":"close","id":3,"code":1000,"reason":"done"}"#);
+    interceptor.handle(r#"{"type":"send","id":3,"text":"late"}"#);
+    assert_eq!(
+      *events.borrow(),
+      vec![
+        WebSocketProxyEvent::Message(WebSocketMessage::Text("ping".into())),
+        WebSocketProxyEvent::Message(WebSocketMessage::Binary(vec![1, 2])),
+        WebSocketProxyEvent::Closed {
+          code: 1000,
+          reason: "done".into()
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn should_build_scripts() {
+    assert_eq!(
+      send_message_script(2, &WebSocketMessage::Text("it's".into())),
+      r#"window.__WRY_WEBSOCKET__.message(2, "it's", null);"#
+    );
+    assert_eq!(
+      send_message_script(2, &WebSocketMessage::Binary(vec![0, 255])),
+      "window.__WRY_WEBSOCKET__.message(2, null, [0,255]);"
+    );
+    assert_eq!(
+      close_script(2, 1000, ""),
+      r#"window.__WRY_WEBSOCKET__.close(2, 1000, "");"#
+    );
+  }
+}
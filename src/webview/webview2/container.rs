@@ -0,0 +1,217 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::cell::Cell;
+
+use windows::{
+  core::{w, PCWSTR},
+  Win32::{
+    Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, RECT, WPARAM},
+    Graphics::Gdi::{CombineRgn, CreateRectRgn, DeleteObject, SetWindowRgn, HRGN, RGN_DIFF},
+    UI::{
+      Shell::{DefSubclassProc, RemoveWindowSubclass, SetWindowSubclass},
+      WindowsAndMessaging as win32wm,
+    },
+  },
+};
+
+use crate::{
+  application::dpi::{PhysicalPosition, PhysicalSize},
+  webview::{AutoResizePolicy, ClickThroughRegion},
+};
+
+type Bounds = (i32, i32, i32, i32);
+
+/// The child window hosting a webview built with `WebViewBuilder::new_as_child`, so it can be laid
+/// out and ordered above the other webviews of the window.
+///
+/// The container follows the size of its parent window with its [`AutoResizePolicy`], by
+/// subclassing the parent.
+pub(crate) struct Container {
+  pub hwnd: HWND,
+  parent: Cell<HWND>,
+  policy: Cell<AutoResizePolicy>,
+  // The bounds of the container when it was placed, and the client size of its parent then.
+  placement: Cell<(Bounds, (i32, i32))>,
+}
+
+impl Container {
+  pub fn new(
+    parent: HWND,
+    bounds: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+    policy: AutoResizePolicy,
+  ) -> webview2_com::Result<Box<Self>> {
+    let parent_size = client_size(parent)?;
+    let (bounds, policy) = match bounds {
+      Some(bounds) => (to_bounds(bounds), policy.placed()),
+      None => ((0, 0, parent_size.0, parent_size.1), policy),
+    };
+    let hwnd = unsafe {
+      win32wm::CreateWindowExW(
+        win32wm::WINDOW_EX_STYLE::default(),
+        w!("STATIC"),
+        PCWSTR::null(),
+        win32wm::WS_CHILD
+          | win32wm::WS_VISIBLE
+          | win32wm::WS_CLIPCHILDREN
+          | win32wm::WS_CLIPSIBLINGS,
+        bounds.0,
+        bounds.1,
+        bounds.2,
+        bounds.3,
+        parent,
+        win32wm::HMENU::default(),
+        HINSTANCE::default(),
+        None,
+      )
+    };
+    if hwnd.0 == 0 {
+      return Err(windows::core::Error::from_win32().into());
+    }
+
+    // Boxed so the parent subclass can keep a pointer to it.
+    let container = Box::new(Self {
+      hwnd,
+      parent: Cell::new(parent),
+      policy: Cell::new(policy),
+      placement: Cell::new((bounds, parent_size)),
+    });
+    container.subclass_parent();
+    Ok(container)
+  }
+
+  /// Moves and resizes the container, which then follows the size of its parent from these bounds.
+  pub fn set_bounds(
+    &self,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+  ) -> webview2_com::Result<()> {
+    self.policy.set(self.policy.get().placed());
+    self
+      .placement
+      .set((to_bounds((position, size)), client_size(self.parent.get())?));
+    self.layout()
+  }
+
+  /// Moves the container into `parent`, keeping its distance to the anchored edges of the window.
+  pub fn reparent(&self, parent: HWND) -> webview2_com::Result<()> {
+    unsafe {
+      self.unsubclass_parent();
+      if win32wm::SetParent(self.hwnd, parent).0 == 0 {
+        let error = windows::core::Error::from_win32();
+        self.subclass_parent();
+        return Err(error.into());
+      }
+    }
+    self.parent.set(parent);
+    self.subclass_parent();
+    self.layout()
+  }
+
+  /// Lays the container out in the current client area of its parent.
+  fn layout(&self) -> webview2_com::Result<()> {
+    let (bounds, parent_size) = self.placement.get();
+    let (x, y, width, height) =
+      self
+        .policy
+        .get()
+        .resize(bounds, parent_size, client_size(self.parent.get())?);
+    unsafe {
+      win32wm::SetWindowPos(
+        self.hwnd,
+        HWND::default(),
+        x,
+        y,
+        width,
+        height,
+        win32wm::SWP_NOZORDER | win32wm::SWP_NOACTIVATE,
+      )?;
+    }
+    Ok(())
+  }
+
+  fn subclass_parent(&self) {
+    unsafe {
+      SetWindowSubclass(
+        self.parent.get(),
+        Some(parent_subclass_proc),
+        self.hwnd.0 as _,
+        self as *const Self as _,
+      );
+    }
+  }
+
+  fn unsubclass_parent(&self) {
+    unsafe {
+      RemoveWindowSubclass(
+        self.parent.get(),
+        Some(parent_subclass_proc),
+        self.hwnd.0 as _,
+      );
+    }
+  }
+}
+
+impl Drop for Container {
+  fn drop(&mut self) {
+    self.unsubclass_parent();
+    let _ = unsafe { win32wm::DestroyWindow(self.hwnd) };
+  }
+}
+
+unsafe extern "system" fn parent_subclass_proc(
+  hwnd: HWND,
+  msg: u32,
+  wparam: WPARAM,
+  lparam: LPARAM,
+  _uidsubclass: usize,
+  dwrefdata: usize,
+) -> LRESULT {
+  if msg == win32wm::WM_SIZE {
+    let container = &*(dwrefdata as *const Container);
+    let _ = container.layout();
+  }
+
+  DefSubclassProc(hwnd, msg, wparam, lparam)
+}
+
+/// Clips the window of a container to everything but `regions`, so the mouse events over them go
+/// to the parent window.
+pub(crate) fn set_click_through_regions(
+  hwnd: HWND,
+  regions: &[ClickThroughRegion],
+  scale_factor: f64,
+) {
+  unsafe {
+    if regions.is_empty() {
+      SetWindowRgn(hwnd, HRGN::default(), true);
+      return;
+    }
+    let max = i16::MAX as i32;
+    let shape = CreateRectRgn(0, 0, max, max);
+    for region in regions {
+      let (x, y, width, height) = region.to_pixels(scale_factor);
+      let region = CreateRectRgn(x, y, x + width, y + height);
+      CombineRgn(shape, shape, region, RGN_DIFF);
+      DeleteObject(region);
+    }
+    // The system owns the region once it is set.
+    SetWindowRgn(hwnd, shape, true);
+  }
+}
+
+fn to_bounds((position, size): (PhysicalPosition<i32>, PhysicalSize<u32>)) -> Bounds {
+  (
+    position.x,
+    position.y,
+    size.width as i32,
+    size.height as i32,
+  )
+}
+
+fn client_size(hwnd: HWND) -> webview2_com::Result<(i32, i32)> {
+  let mut rect = RECT::default();
+  unsafe { win32wm::GetClientRect(hwnd, &mut rect)? };
+  Ok((rect.right - rect.left, rect.bottom - rect.top))
+}
@@ -2,35 +2,52 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+mod container;
 mod file_drop;
 mod resize;
 
 use crate::{
   webview::{
-    proxy::ProxyConfig, MemoryUsageLevel, PageLoadEvent, RequestAsyncResponder, WebContext,
-    WebViewAttributes, RGBA,
+    content_blocker::ContentBlocker,
+    default_headers,
+    key_event::key_from_virtual_key,
+    mouse_event, network_conditions,
+    proxy::ProxyConfig,
+    service_worker::REGISTRATIONS_SCRIPT,
+    snapshot::{element_rect_script, parse_element_rect},
+    touch_event,
+    typed_eval::apply_arguments,
+    AutoplayPolicy, CachePolicy, ClickThroughRegion, DevtoolsPlacement, EditCommand, FrameInfo,
+    InitScript, KeyEvent, KeyEventResponse, KeyModifiers, MediaFeatureOverrides, MemoryUsageLevel,
+    MouseEvent, NewWindowOpener, NewWindowRequest, NewWindowResponse, PageLoadEvent, PrintOptions,
+    RequestAsyncResponder, RequestBody, ScriptId, SnapshotFormat, TouchEvent, TouchPoint,
+    WebArchive, WebContext, WebRtcPolicy, WebViewAttributes, RGBA,
   },
   Error, Result,
 };
 
+use container::Container;
 use file_drop::FileDropController;
 use url::Url;
 
 use std::{
   borrow::Cow,
-  collections::HashSet,
+  cell::{Cell, RefCell},
+  collections::{HashMap, HashSet},
   fmt::Write,
+  io::{self, Read},
   iter::once,
   os::windows::prelude::OsStrExt,
   path::PathBuf,
   rc::Rc,
   sync::{mpsc, Arc},
+  time::Duration,
 };
 
 use once_cell::{sync::Lazy, unsync::OnceCell};
 
 use windows::{
-  core::{s, ComInterface, PCSTR, PCWSTR, PWSTR},
+  core::{s, AgileReference, ComInterface, PCSTR, PCWSTR, PWSTR},
   Win32::{
     Foundation::*,
     Globalization::{self, MAX_LOCALE_NAME},
@@ -42,7 +59,11 @@ use windows::{
       WinRT::EventRegistrationToken,
     },
     UI::{
-      Shell::{DefSubclassProc, SHCreateMemStream, SetWindowSubclass},
+      Input::KeyboardAndMouse::{
+        EnableWindow, GetKeyState, SetFocus, VIRTUAL_KEY, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN,
+        VK_SHIFT,
+      },
+      Shell::{DefSubclassProc, SHCreateMemStream, SetWindowSubclass, ShellExecuteW},
       WindowsAndMessaging::{self as win32wm, PostMessageW, RegisterWindowMessageA},
     },
   },
@@ -50,10 +71,17 @@ use windows::{
 
 use webview2_com::{Microsoft::Web::WebView2::Win32::*, *};
 
-use crate::application::{platform::windows::WindowExtWindows, window::Window};
-use http::{Request, Response as HttpResponse, StatusCode};
+use crate::application::{
+  dpi::{PhysicalPosition, PhysicalSize},
+  platform::windows::WindowExtWindows,
+  window::Window,
+};
+use http::{header::CONTENT_TYPE, Request, Response as HttpResponse, StatusCode};
 
-use super::Theme;
+use super::{
+  lifecycle::{InstanceGuard, SharedGuard},
+  Theme,
+};
 
 impl From<webview2_com::Error> for Error {
   fn from(err: webview2_com::Error) -> Self {
@@ -61,31 +89,136 @@ impl From<webview2_com::Error> for Error {
   }
 }
 
+/// The environment of the webview requesting a new window, and the webview built for it.
+pub(crate) struct PlatformOpener {
+  environment: ICoreWebView2Environment,
+  // WebView2 requires the webview to use the profile of its opener.
+  incognito: bool,
+  child: RefCell<Option<ICoreWebView2>>,
+}
+
 pub(crate) struct InnerWebView {
   pub controller: ICoreWebView2Controller,
   webview: ICoreWebView2,
   env: ICoreWebView2Environment,
+  // The frames of the main document, with the id they are listed with.
+  frames: Rc<RefCell<Vec<(u32, ICoreWebView2Frame)>>>,
+  // The ids WebView2 gave to the scripts added with `add_init_script`.
+  user_scripts: RefCell<HashMap<ScriptId, String>>,
+  // The fingers touching the webview, sent with each touch event.
+  touches: RefCell<Vec<TouchPoint>>,
+  // The child window of the webviews built with `WebViewBuilder::new_as_child`.
+  container: Option<Box<Container>>,
+  // The mute state to restore when the media muted by `pause_all_media` is resumed.
+  muted_before_pause: Cell<Option<bool>>,
+  // Keeps the environment of the context alive while the webview is.
+  _shared_environment: Option<SharedGuard<ICoreWebView2Environment>>,
   // Store FileDropController in here to make sure it gets dropped when
   // the webview gets dropped, otherwise we'll have a memory leak
   #[allow(dead_code)]
   file_drop_controller: Rc<OnceCell<FileDropController>>,
 }
 
+/// The features implemented by this backend, see [`super::Capabilities`].
+pub(crate) const CAPABILITIES: super::Capabilities = super::Capabilities {
+  streaming_protocols: false,
+  protocol_request_body: true,
+  devtools: true,
+  print: true,
+  print_options: true,
+  edit_commands: true,
+  zoom: true,
+  element_capture: true,
+  muting: true,
+  service_worker_management: true,
+  pdf_downloads: false,
+  content_blocking: true,
+  devtools_protocol: true,
+  proxy: true,
+  offscreen: false,
+  find_in_page: false,
+  print_to_pdf: false,
+  selected_text: false,
+  suspend: true,
+};
+
 impl InnerWebView {
   pub fn new(
     window: Rc<Window>,
     mut attributes: WebViewAttributes,
     pl_attrs: super::PlatformSpecificWebViewAttributes,
     web_context: Option<&mut WebContext>,
+    child: bool,
   ) -> Result<Self> {
-    let hwnd = HWND(window.hwnd() as _);
+    // Child webviews are hosted in their own child window, so they can be laid out and ordered
+    // above the other webviews of the window.
+    let container = if child {
+      Some(Container::new(
+        HWND(window.hwnd() as _),
+        attributes.bounds,
+        attributes.auto_resize,
+      )?)
+    } else {
+      None
+    };
+    let hwnd = container
+      .as_ref()
+      .map_or(HWND(window.hwnd() as _), |container| container.hwnd);
     let file_drop_controller: Rc<OnceCell<FileDropController>> = Rc::new(OnceCell::new());
     let file_drop_handler = attributes.file_drop_handler.take();
     let file_drop_window = window.clone();
 
-    let env = Self::create_environment(&web_context, pl_attrs.clone(), &attributes)?;
+    let mut content_blocker = ContentBlocker::default();
+    if let Some(web_context) = &web_context {
+      for (_, rules) in web_context.content_rule_lists() {
+        content_blocker.add(rules)?;
+      }
+    }
+
+    // The webview of a new window shares the environment of its opener, which loads the
+    // requested page in it.
+    let opener = attributes.new_window_opener.take();
+    let mut shared_environment = None;
+    let env = match (&opener, web_context.as_ref()) {
+      (Some(opener), _) => {
+        attributes.url = None;
+        attributes.html = None;
+        attributes.incognito = opener.0.incognito;
+        opener.0.environment.clone()
+      }
+      // The webviews of a context share its environment, released with the last one.
+      (None, Some(context)) => {
+        let (env, guard) = context.os.environment.try_acquire(|| {
+          let env = Self::create_environment(&web_context, pl_attrs.clone(), &attributes)?;
+          Self::count_browser_process(&env);
+          Ok::<_, Error>(env)
+        })?;
+        shared_environment = Some(guard);
+        env
+      }
+      (None, None) => {
+        let env = Self::create_environment(&web_context, pl_attrs.clone(), &attributes)?;
+        Self::count_browser_process(&env);
+        env
+      }
+    };
     let controller = Self::create_controller(hwnd, &env, attributes.incognito)?;
-    let webview = Self::init_webview(window, hwnd, attributes, &env, &controller, pl_attrs)?;
+    let webview = Self::init_webview(
+      window,
+      hwnd,
+      attributes,
+      &env,
+      &controller,
+      pl_attrs,
+      content_blocker,
+    )?;
+
+    if let Some(opener) = opener {
+      opener.0.child.replace(Some(webview.clone()));
+    }
+
+    let frames = Rc::default();
+    Self::track_frames(&webview, &frames);
 
     if let Some(file_drop_handler) = file_drop_handler {
       let mut controller = FileDropController::new();
@@ -97,10 +230,108 @@ impl InnerWebView {
       controller,
       webview,
       env,
+      frames,
+      user_scripts: RefCell::default(),
+      touches: RefCell::default(),
+      container,
+      muted_before_pause: Cell::new(None),
+      _shared_environment: shared_environment,
       file_drop_controller,
     })
   }
 
+  fn track_frames(webview: &ICoreWebView2, frames: &Rc<RefCell<Vec<(u32, ICoreWebView2Frame)>>>) {
+    let Ok(webview) = webview.cast::<ICoreWebView2_4>() else {
+      return;
+    };
+    // The frames reference the webview, which references the handlers.
+    let frames = Rc::downgrade(frames);
+    let next_id = std::cell::Cell::new(0);
+    let mut token = EventRegistrationToken::default();
+    let _ = unsafe {
+      webview.add_FrameCreated(
+        &FrameCreatedEventHandler::create(Box::new(move |_, args| {
+          let (Some(args), Some(list)) = (args, frames.upgrade()) else {
+            return Ok(());
+          };
+          let frame = args.Frame()?;
+          let id = next_id.get();
+          next_id.set(id + 1);
+          list.borrow_mut().push((id, frame.clone()));
+
+          let frames = frames.clone();
+          let mut token = EventRegistrationToken::default();
+          frame.add_Destroyed(
+            &FrameDestroyedEventHandler::create(Box::new(move |_, _| {
+              if let Some(frames) = frames.upgrade() {
+                frames.borrow_mut().retain(|(frame_id, _)| *frame_id != id);
+              }
+              Ok(())
+            })),
+            &mut token,
+          )
+        })),
+        &mut token,
+      )
+    };
+  }
+
+  pub fn frames(&self) -> Vec<FrameInfo> {
+    self
+      .frames
+      .borrow()
+      .iter()
+      .map(|(id, frame)| {
+        let mut name = PWSTR::null();
+        let name = match unsafe { frame.Name(&mut name) } {
+          Ok(()) => take_pwstr(name),
+          Err(_) => String::new(),
+        };
+        FrameInfo { id: *id, name }
+      })
+      .collect()
+  }
+
+  pub fn eval_in_frame(&self, frame_id: u32, js: &str) -> Result<()> {
+    let frame = self
+      .frames
+      .borrow()
+      .iter()
+      .find(|(id, _)| *id == frame_id)
+      .map(|(_, frame)| frame.clone())
+      .ok_or(Error::FrameNotFound(frame_id))?;
+    unsafe {
+      frame
+        .cast::<ICoreWebView2Frame2>()
+        .and_then(|frame| {
+          frame.ExecuteScript(
+            PCWSTR::from_raw(encode_wide(js).as_ptr()),
+            &ExecuteScriptCompletedHandler::create(Box::new(|_, _| Ok(()))),
+          )
+        })
+        .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+    }
+  }
+
+  /// Counts the browser process of `env` in [`super::webview_instance_count`] until it exits.
+  fn count_browser_process(env: &ICoreWebView2Environment) {
+    let Ok(env5) = env.cast::<ICoreWebView2Environment5>() else {
+      return;
+    };
+    // Keep the environment alive until the process exits, the handler is dropped with it.
+    let pending = std::cell::Cell::new(Some((env.clone(), InstanceGuard::process())));
+    let mut token = EventRegistrationToken::default();
+    let _ = unsafe {
+      env5.add_BrowserProcessExited(
+        &BrowserProcessExitedEventHandler::create(Box::new(move |_, _| {
+          pending.take();
+          Ok(())
+        })),
+        &mut token,
+      )
+    };
+  }
+
   fn create_environment(
     web_context: &Option<&mut WebContext>,
     pl_attrs: super::PlatformSpecificWebViewAttributes,
@@ -119,9 +350,22 @@ impl InnerWebView {
         // remove "mini menu" - See https://github.com/tauri-apps/wry/issues/535
         // and "smart screen" - See https://github.com/tauri-apps/tauri/issues/1345
         format!(
-          "--disable-features=msWebOOUI,msPdfOOUI,msSmartScreenProtection{}{}",
-          if attributes.autoplay {
-            " --autoplay-policy=no-user-gesture-required"
+          "--disable-features=msWebOOUI,msPdfOOUI,msSmartScreenProtection{}{}{}{}{}{}",
+          match attributes.autoplay_policy {
+            Some(AutoplayPolicy::AllowAll) => " --autoplay-policy=no-user-gesture-required",
+            Some(AutoplayPolicy::RequireUserGesture | AutoplayPolicy::Allowlist(_)) => {
+              " --autoplay-policy=user-gesture-required"
+            }
+            Some(AutoplayPolicy::AllowMuted) | None => "",
+          },
+          if matches!(
+            attributes.webrtc_policy,
+            Some(WebRtcPolicy {
+              relay_only: true,
+              ..
+            })
+          ) {
+            " --force-webrtc-ip-handling-policy=disable_non_proxied_udp"
           } else {
             ""
           },
@@ -137,6 +381,22 @@ impl InnerWebView {
             }
           } else {
             "".to_string()
+          },
+          match attributes.remote_debugging_port {
+            Some(port) => format!(" --remote-debugging-port={port}"),
+            None => "".to_string(),
+          },
+          match attributes.smooth_scrolling {
+            Some(true) => " --enable-smooth-scrolling",
+            Some(false) => " --disable-smooth-scrolling",
+            None => "",
+          },
+          match web_context
+            .as_deref()
+            .and_then(WebContext::host_resolver_rules)
+          {
+            Some(rules) => format!(" --host-resolver-rules=\"{rules}\""),
+            None => "".to_string(),
           }
         )
       }))
@@ -233,6 +493,7 @@ impl InnerWebView {
     env: &ICoreWebView2Environment,
     controller: &ICoreWebView2Controller,
     pl_attrs: super::PlatformSpecificWebViewAttributes,
+    content_blocker: ContentBlocker,
   ) -> webview2_com::Result<ICoreWebView2> {
     let webview =
       unsafe { controller.CoreWebView2() }.map_err(webview2_com::Error::WindowsError)?;
@@ -242,6 +503,12 @@ impl InnerWebView {
       set_theme(&webview, theme);
     }
 
+    // accessibility media features
+    if let Some(overrides) = attributes.media_feature_overrides {
+      set_media_feature_overrides(&webview, overrides)
+        .map_err(webview2_com::Error::WindowsError)?;
+    }
+
     // background color
     if !attributes.transparent {
       if let Some(background_color) = attributes.background_color {
@@ -303,6 +570,19 @@ impl InnerWebView {
         .SetIsSwipeNavigationEnabled(attributes.back_forward_navigation_gestures)
         .map_err(webview2_com::Error::WindowsError)?;
 
+      if !attributes.virtual_host_mappings.is_empty() {
+        let webview = webview.cast::<ICoreWebView2_3>()?;
+        for (host, folder) in &attributes.virtual_host_mappings {
+          webview
+            .SetVirtualHostNameToFolderMapping(
+              PCWSTR::from_raw(encode_wide(host).as_ptr()),
+              PCWSTR::from_raw(encode_wide(folder).as_ptr()),
+              COREWEBVIEW2_HOST_RESOURCE_ACCESS_KIND_ALLOW,
+            )
+            .map_err(webview2_com::Error::WindowsError)?;
+        }
+      }
+
       let mut rect = RECT::default();
       win32wm::GetClientRect(hwnd, &mut rect)?;
       controller
@@ -310,6 +590,70 @@ impl InnerWebView {
         .map_err(webview2_com::Error::WindowsError)?;
     }
 
+    // focus changed handler
+    if let Some(focus_changed_handler) = attributes.focus_changed_handler.take() {
+      let focus_changed_handler = Rc::new(focus_changed_handler);
+      let handler = focus_changed_handler.clone();
+      unsafe {
+        controller
+          .add_GotFocus(
+            &FocusChangedEventHandler::create(Box::new(move |_, _| {
+              handler(true);
+              Ok(())
+            })),
+            &mut token,
+          )
+          .map_err(webview2_com::Error::WindowsError)?;
+        controller
+          .add_LostFocus(
+            &FocusChangedEventHandler::create(Box::new(move |_, _| {
+              focus_changed_handler(false);
+              Ok(())
+            })),
+            &mut token,
+          )
+          .map_err(webview2_com::Error::WindowsError)?;
+      }
+    }
+
+    // key event handler, WebView2 only reports the accelerator keys before the page
+    if let Some(key_event_handler) = attributes.key_event_handler.take() {
+      unsafe {
+        controller
+          .add_AcceleratorKeyPressed(
+            &AcceleratorKeyPressedEventHandler::create(Box::new(move |_, args| {
+              if let Some(args) = args {
+                let mut kind = COREWEBVIEW2_KEY_EVENT_KIND_KEY_DOWN;
+                args.KeyEventKind(&mut kind)?;
+                if kind == COREWEBVIEW2_KEY_EVENT_KIND_KEY_DOWN
+                  || kind == COREWEBVIEW2_KEY_EVENT_KIND_SYSTEM_KEY_DOWN
+                {
+                  let mut virtual_key = 0;
+                  args.VirtualKey(&mut virtual_key)?;
+                  let pressed = |key: VIRTUAL_KEY| GetKeyState(key.0 as i32) < 0;
+                  let modifiers = KeyModifiers {
+                    shift: pressed(VK_SHIFT),
+                    control: pressed(VK_CONTROL),
+                    alt: pressed(VK_MENU),
+                    meta: pressed(VK_LWIN) || pressed(VK_RWIN),
+                  };
+                  let event = KeyEvent {
+                    key: key_from_virtual_key(virtual_key, modifiers.shift),
+                    modifiers,
+                  };
+                  if key_event_handler(event) == KeyEventResponse::Handled {
+                    args.SetHandled(true)?;
+                  }
+                }
+              }
+              Ok(())
+            })),
+            &mut token,
+          )
+          .map_err(webview2_com::Error::WindowsError)?;
+      }
+    }
+
     // document title changed handler
     if let Some(document_title_changed_handler) = attributes.document_title_changed_handler {
       let window_ = window.clone();
@@ -331,9 +675,46 @@ impl InnerWebView {
       }
     }
 
+    if let Some(close_requested_handler) = attributes.close_requested_handler.take() {
+      let window_ = window.clone();
+      unsafe {
+        webview
+          .add_WindowCloseRequested(
+            &WindowCloseRequestedEventHandler::create(Box::new(move |_, _| {
+              close_requested_handler(&window_);
+              Ok(())
+            })),
+            &mut token,
+          )
+          .map_err(webview2_com::Error::WindowsError)?;
+      }
+    }
+
+    // audio state changed handler
+    if let Some(audio_state_handler) = attributes.audio_state_handler {
+      unsafe {
+        let webview8: ICoreWebView2_8 =
+          webview.cast().map_err(webview2_com::Error::WindowsError)?;
+        webview8
+          .add_IsDocumentPlayingAudioChanged(
+            &IsDocumentPlayingAudioChangedEventHandler::create(Box::new(move |webview, _| {
+              if let Some(webview) = webview {
+                let mut is_playing = BOOL::default();
+                webview.IsDocumentPlayingAudio(&mut is_playing)?;
+                audio_state_handler(is_playing.as_bool());
+              }
+              Ok(())
+            })),
+            &mut token,
+          )
+          .map_err(webview2_com::Error::WindowsError)?;
+      }
+    }
+
     if let Some(on_page_load_handler) = attributes.on_page_load_handler {
       let on_page_load_handler = Arc::new(on_page_load_handler);
       let on_page_load_handler_ = on_page_load_handler.clone();
+      let dom_content_loaded_handler = on_page_load_handler.clone();
 
       unsafe {
         webview
@@ -349,6 +730,25 @@ impl InnerWebView {
           .map_err(webview2_com::Error::WindowsError)?;
       }
 
+      if let Ok(webview) = webview.cast::<ICoreWebView2_2>() {
+        unsafe {
+          webview
+            .add_DOMContentLoaded(
+              &DOMContentLoadedEventHandler::create(Box::new(move |webview, _| {
+                if let Some(webview) = webview {
+                  dom_content_loaded_handler(
+                    PageLoadEvent::DomContentLoaded,
+                    url_from_webview(&webview),
+                  )
+                }
+                Ok(())
+              })),
+              &mut token,
+            )
+            .map_err(webview2_com::Error::WindowsError)?;
+        }
+      }
+
       unsafe {
         webview
           .add_NavigationCompleted(
@@ -369,7 +769,7 @@ impl InnerWebView {
       &webview,
       String::from(
         r#"Object.defineProperty(window, 'ipc', {
-  value: Object.freeze({postMessage:s=>window.chrome.webview.postMessage(s)})
+  value: Object.freeze({postMessage:window.chrome.webview.postMessage.bind(window.chrome.webview)})
 });
 
 window.addEventListener('mousedown', (e) => {
@@ -542,19 +942,44 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
       }
     }
 
-    if let Some(new_window_req_handler) = attributes.new_window_req_handler {
+    if let Some(new_window_handler) = attributes.new_window_handler.take() {
+      let env = env.clone();
+      let incognito = attributes.incognito;
       unsafe {
         webview
           .add_NewWindowRequested(
-            &NewWindowRequestedEventHandler::create(Box::new(move |_, args| {
-              if let Some(args) = args {
+            &NewWindowRequestedEventHandler::create(Box::new(move |sender, args| {
+              if let (Some(webview), Some(args)) = (sender, args) {
                 let mut uri = PWSTR::null();
                 args.Uri(&mut uri)?;
                 let uri = take_pwstr(uri);
 
-                let allow = new_window_req_handler(uri);
-
-                args.SetHandled(!allow)?;
+                // The handler may create a webview, which completes asynchronously.
+                let deferral = args.GetDeferral()?;
+                let opener = NewWindowOpener(Rc::new(PlatformOpener {
+                  environment: env.clone(),
+                  incognito,
+                  child: RefCell::new(None),
+                }));
+                let request = NewWindowRequest {
+                  url: uri.clone(),
+                  opener: opener.clone(),
+                };
+                match new_window_handler(request) {
+                  NewWindowResponse::Allow => (),
+                  NewWindowResponse::Deny => args.SetHandled(true)?,
+                  NewWindowResponse::Navigate => {
+                    args.SetHandled(true)?;
+                    webview.Navigate(PCWSTR::from_raw(encode_wide(uri).as_ptr()))?;
+                  }
+                  NewWindowResponse::Create => {
+                    if let Some(child) = opener.0.child.take() {
+                      args.SetNewWindow(&child)?;
+                    }
+                    args.SetHandled(true)?;
+                  }
+                }
+                deferral.Complete()?;
               }
 
               Ok(())
@@ -565,14 +990,153 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
       }
     }
 
+    // Default headers, added before the other handlers see the requests
+    if let Some(headers) = attributes.default_headers.take().filter(|h| !h.is_empty()) {
+      unsafe {
+        webview.AddWebResourceRequestedFilter(
+          PCWSTR::from_raw(encode_wide("*").as_ptr()),
+          COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL,
+        )
+      }
+      .map_err(webview2_com::Error::WindowsError)?;
+
+      let headers = default_headers::joined(&headers);
+      unsafe {
+        webview
+          .add_WebResourceRequested(
+            &WebResourceRequestedEventHandler::create(Box::new(move |_, args| {
+              if let Some(args) = args {
+                let request_headers = args.Request()?.Headers()?;
+                for (name, value) in &headers {
+                  request_headers.SetHeader(
+                    PCWSTR::from_raw(encode_wide(name).as_ptr()),
+                    PCWSTR::from_raw(encode_wide(value).as_ptr()),
+                  )?;
+                }
+              }
+              Ok(())
+            })),
+            &mut token,
+          )
+          .map_err(webview2_com::Error::WindowsError)?;
+      }
+    }
+
+    // Referrer policy
+    if let Some(policy) = attributes.referrer_policy {
+      unsafe {
+        webview.AddWebResourceRequestedFilter(
+          PCWSTR::from_raw(encode_wide("*").as_ptr()),
+          COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL,
+        )
+      }
+      .map_err(webview2_com::Error::WindowsError)?;
+
+      unsafe {
+        webview
+          .add_WebResourceRequested(
+            &WebResourceRequestedEventHandler::create(Box::new(move |_, args| {
+              let Some(args) = args else {
+                return Ok(());
+              };
+              let request = args.Request()?;
+              let headers = request.Headers()?;
+              let name = encode_wide("Referer");
+              let mut referrer = PWSTR::null();
+              if headers
+                .GetHeader(PCWSTR::from_raw(name.as_ptr()), &mut referrer)
+                .is_err()
+              {
+                return Ok(());
+              }
+              let referrer = take_pwstr(referrer);
+              let mut uri = PWSTR::null();
+              request.Uri(&mut uri)?;
+              let uri = take_pwstr(uri);
+
+              let referrer = match (Url::parse(&referrer), Url::parse(&uri)) {
+                (Ok(referrer), Ok(destination)) => policy.referrer(&referrer, &destination),
+                _ => None,
+              };
+              match referrer {
+                Some(referrer) => headers.SetHeader(
+                  PCWSTR::from_raw(name.as_ptr()),
+                  PCWSTR::from_raw(encode_wide(referrer).as_ptr()),
+                ),
+                None => headers.RemoveHeader(PCWSTR::from_raw(name.as_ptr())),
+              }
+            })),
+            &mut token,
+          )
+          .map_err(webview2_com::Error::WindowsError)?;
+      }
+    }
+
     let scheme = if pl_attrs.https_scheme {
       "https"
     } else {
       "http"
     };
+
+    // `NavigateToString` can't be associated with an origin, so the HTML string is served when
+    // navigating to it instead.
+    let html_origin = attributes.html.as_ref().and(attributes.html_origin.take());
+    if let (Some(html), Some(origin)) = (&attributes.html, &html_origin) {
+      let html = html.clone().into_bytes();
+      let html_response = move || {
+        HttpResponse::builder()
+          .header(CONTENT_TYPE, "text/html")
+          .body(Cow::Owned(html.clone()))
+          .unwrap()
+      };
+      let origin_string = origin.to_string();
+
+      if let Some((_, handler)) = attributes
+        .streaming_custom_protocols
+        .iter_mut()
+        .find(|(name, _)| name == origin.scheme())
+      {
+        let protocol = std::mem::replace(handler, Box::new(|_, _| {}));
+        *handler = Box::new(move |request, responder| {
+          if request.uri().to_string() == origin_string {
+            responder.respond(html_response());
+          } else {
+            protocol(request, responder);
+          }
+        });
+      } else {
+        unsafe {
+          webview.AddWebResourceRequestedFilter(
+            PCWSTR::from_raw(encode_wide(&origin_string).as_ptr()),
+            COREWEBVIEW2_WEB_RESOURCE_CONTEXT_DOCUMENT,
+          )
+        }
+        .map_err(webview2_com::Error::WindowsError)?;
+
+        let env = env.clone();
+        unsafe {
+          webview
+            .add_WebResourceRequested(
+              &WebResourceRequestedEventHandler::create(Box::new(move |_, args| {
+                if let Some(args) = args {
+                  let mut uri = PWSTR::null();
+                  args.Request()?.Uri(&mut uri)?;
+                  if take_pwstr(uri) == origin_string {
+                    args.SetResponse(&prepare_web_request_response(&env, &html_response())?)?;
+                  }
+                }
+                Ok(())
+              })),
+              &mut token,
+            )
+            .map_err(webview2_com::Error::WindowsError)?;
+        }
+      }
+    }
+
     let mut custom_protocol_names = HashSet::new();
-    if !attributes.custom_protocols.is_empty() {
-      for (name, _) in &attributes.custom_protocols {
+    if !attributes.streaming_custom_protocols.is_empty() {
+      for (name, _) in &attributes.streaming_custom_protocols {
         // WebView2 supports non-standard protocols only on Windows 10+, so we have to use this workaround
         // See https://github.com/MicrosoftEdge/WebView2Feedback/issues/73
         custom_protocol_names.insert(name.clone());
@@ -585,7 +1149,7 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
         .map_err(webview2_com::Error::WindowsError)?;
       }
 
-      let custom_protocols = attributes.custom_protocols;
+      let custom_protocols = attributes.streaming_custom_protocols;
       let env = env.clone();
       let main_thread_id = std::thread::current().id();
 
@@ -622,28 +1186,11 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
                   }
                 }
 
-                // get the body content if available
-                let mut body_sent = Vec::new();
-                if let Ok(content) = webview_request.Content() {
-                  let mut buffer: [u8; 1024] = [0; 1024];
-                  loop {
-                    let mut cb_read = 0;
-                    let content: IStream = content.cast()?;
-                    content
-                      .Read(
-                        buffer.as_mut_ptr() as *mut _,
-                        buffer.len() as u32,
-                        Some(&mut cb_read),
-                      )
-                      .ok()?;
-
-                    if cb_read == 0 {
-                      break;
-                    }
-
-                    body_sent.extend_from_slice(&buffer[..(cb_read as usize)]);
-                  }
-                }
+                // get the body content if available, read as the handler reads it
+                let body = match webview_request.Content() {
+                  Ok(content) => RequestBody::new(ContentReader(AgileReference::new(&content)?)),
+                  Err(_) => RequestBody::empty(),
+                };
 
                 // uri
                 let mut uri = PWSTR::null();
@@ -663,7 +1210,7 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
                   let final_request = match request
                     .uri(&path)
                     .method(request_method.as_str())
-                    .body(body_sent)
+                    .body(body)
                   {
                     Ok(req) => req,
                     Err(_) => return Err(E_FAIL.into()),
@@ -719,6 +1266,74 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
       }
     }
 
+    // Content blocking
+    if !content_blocker.is_empty() {
+      unsafe {
+        webview.AddWebResourceRequestedFilter(
+          PCWSTR::from_raw(encode_wide("*").as_ptr()),
+          COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL,
+        )
+      }
+      .map_err(webview2_com::Error::WindowsError)?;
+
+      // Custom protocols serve the application itself, they are never blocked.
+      let custom_protocol_prefixes: Vec<String> = custom_protocol_names
+        .iter()
+        .map(|name| format!("{scheme}://{name}."))
+        .collect();
+      let env = env.clone();
+      unsafe {
+        webview
+          .add_WebResourceRequested(
+            &WebResourceRequestedEventHandler::create(Box::new(move |webview, args| {
+              let (Some(webview), Some(args)) = (webview, args) else {
+                return Ok(());
+              };
+
+              let mut uri = PWSTR::null();
+              args.Request()?.Uri(&mut uri)?;
+              let uri = take_pwstr(uri);
+              if custom_protocol_prefixes.iter().any(|p| uri.starts_with(p)) {
+                return Ok(());
+              }
+
+              let mut context = COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL;
+              args.ResourceContext(&mut context)?;
+              // `if-domain` and `unless-domain` match the page the resource is loaded into.
+              let document = if context == COREWEBVIEW2_WEB_RESOURCE_CONTEXT_DOCUMENT {
+                uri.clone()
+              } else {
+                let mut source = PWSTR::null();
+                webview.Source(&mut source)?;
+                take_pwstr(source)
+              };
+              let document_host = Url::parse(&document)
+                .ok()
+                .and_then(|url| url.host_str().map(ToString::to_string));
+
+              if content_blocker.should_block(
+                &uri,
+                content_rule_resource_types(context),
+                document_host.as_deref(),
+              ) {
+                let status = StatusCode::FORBIDDEN;
+                let response = env.CreateWebResourceResponse(
+                  None,
+                  status.as_u16() as i32,
+                  PCWSTR::from_raw(encode_wide(status.canonical_reason().unwrap_or("")).as_ptr()),
+                  PCWSTR::from_raw(encode_wide(String::new()).as_ptr()),
+                )?;
+                args.SetResponse(&response)?;
+              }
+
+              Ok(())
+            })),
+            &mut token,
+          )
+          .map_err(webview2_com::Error::WindowsError)?;
+      }
+    }
+
     // Enable clipboard
     if attributes.clipboard {
       unsafe {
@@ -741,12 +1356,23 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
     }
 
     // Set user agent
-    if let Some(user_agent) = attributes.user_agent {
+    if attributes.user_agent.is_some() || attributes.user_agent_suffix.is_some() {
       unsafe {
         let settings: ICoreWebView2Settings2 = webview
           .Settings()?
           .cast()
           .map_err(webview2_com::Error::WindowsError)?;
+        let mut user_agent = match attributes.user_agent {
+          Some(user_agent) => user_agent,
+          None => {
+            let mut user_agent = PWSTR::null();
+            settings.UserAgent(&mut user_agent)?;
+            take_pwstr(user_agent)
+          }
+        };
+        if let Some(suffix) = attributes.user_agent_suffix {
+          user_agent = format!("{user_agent} {suffix}");
+        }
         settings.SetUserAgent(PCWSTR::from_raw(encode_wide(user_agent).as_ptr()))?;
       }
     }
@@ -784,6 +1410,17 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
           }
         }
       }
+    } else if let Some(origin) = html_origin {
+      let mut url_string = origin.to_string();
+      let name = origin.scheme();
+      if custom_protocol_names.contains(name) {
+        url_string = url_string.replace(&format!("{}://", name), &format!("{scheme}://{name}."))
+      }
+      unsafe {
+        webview
+          .Navigate(PCWSTR::from_raw(encode_wide(url_string).as_ptr()))
+          .map_err(webview2_com::Error::WindowsError)?;
+      }
     } else if let Some(html) = attributes.html {
       unsafe {
         webview
@@ -850,6 +1487,30 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
       );
     }
 
+    if !attributes.accept_first_mouse {
+      unsafe extern "system" fn first_mouse_subclass_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+        _uidsubclass: usize,
+        _dwrefdata: usize,
+      ) -> LRESULT {
+        // WebView2 forwards `WM_MOUSEACTIVATE` to its parent, eat the click which activates
+        // the window so it only focuses it, like macOS does.
+        if msg == win32wm::WM_MOUSEACTIVATE
+          && win32wm::GetForegroundWindow() != win32wm::GetAncestor(hwnd, win32wm::GA_ROOT)
+        {
+          return LRESULT(win32wm::MA_ACTIVATEANDEAT as _);
+        }
+
+        DefSubclassProc(hwnd, msg, wparam, lparam)
+      }
+      unsafe {
+        SetWindowSubclass(hwnd, Some(first_mouse_subclass_proc), 8081, 0);
+      }
+    }
+
     unsafe {
       controller
         .SetIsVisible(true)
@@ -864,10 +1525,12 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
     Ok(webview)
   }
 
+  /// Returns the id of the script, to remove it with `RemoveScriptToExecuteOnDocumentCreated`.
   fn add_script_to_execute_on_document_created(
     webview: &ICoreWebView2,
     js: String,
-  ) -> webview2_com::Result<()> {
+  ) -> webview2_com::Result<String> {
+    let (tx, rx) = mpsc::channel();
     let handler_webview = webview.clone();
     AddScriptToExecuteOnDocumentCreatedCompletedHandler::wait_for_async_operation(
       Box::new(move |handler| unsafe {
@@ -875,8 +1538,35 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
           .AddScriptToExecuteOnDocumentCreated(PCWSTR::from_raw(encode_wide(js).as_ptr()), &handler)
           .map_err(webview2_com::Error::WindowsError)
       }),
-      Box::new(|_, _| Ok(())),
-    )
+      Box::new(move |error_code, id| {
+        error_code?;
+        tx.send(id).expect("send over mpsc channel");
+        Ok(())
+      }),
+    )?;
+
+    rx.recv().map_err(|_| webview2_com::Error::SendError)
+  }
+
+  pub fn add_init_script(&self, script: &InitScript) -> Result<ScriptId> {
+    let js = script.to_portable_script();
+    let script = Self::add_script_to_execute_on_document_created(&self.webview, js)
+      .map_err(Error::WebView2Error)?;
+    let id = ScriptId::next();
+    self.user_scripts.borrow_mut().insert(id, script);
+    Ok(id)
+  }
+
+  pub fn remove_init_script(&self, id: ScriptId) -> Result<()> {
+    if let Some(script) = self.user_scripts.borrow_mut().remove(&id) {
+      unsafe {
+        self
+          .webview
+          .RemoveScriptToExecuteOnDocumentCreated(PCWSTR::from_raw(encode_wide(script).as_ptr()))
+          .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))?;
+      }
+    }
+    Ok(())
   }
 
   fn execute_script(
@@ -897,15 +1587,73 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
 
   pub fn print(&self) {
     let _ = self.eval(
-      "window.print()",
+      super::print::NATIVE_PRINT_SCRIPT,
       None::<Box<dyn FnOnce(String) + Send + 'static>>,
     );
   }
 
-  pub fn url(&self) -> Url {
-    Url::parse(&url_from_webview(&self.webview)).unwrap()
-  }
-
+  pub fn print_with_options(&self, options: PrintOptions) -> Result<()> {
+    if !options.silent {
+      self.print();
+      return Ok(());
+    }
+    let (Ok(webview), Ok(env)) = (
+      self.webview.cast::<ICoreWebView2_16>(),
+      self.env.cast::<ICoreWebView2Environment6>(),
+    ) else {
+      return Err(Error::Unsupported);
+    };
+    unsafe {
+      let settings = env
+        .CreatePrintSettings()
+        .map_err(webview2_com::Error::WindowsError)?;
+      if let Some(printer) = &options.printer {
+        settings
+          .cast::<ICoreWebView2PrintSettings2>()
+          .and_then(|settings| {
+            settings.SetPrinterName(PCWSTR::from_raw(encode_wide(printer).as_ptr()))
+          })
+          .map_err(webview2_com::Error::WindowsError)?;
+      }
+      webview
+        .Print(
+          &settings,
+          &PrintCompletedHandler::create(Box::new(|_, status| {
+            // Unknown printers are only found once the print starts.
+            if status == COREWEBVIEW2_PRINT_STATUS_PRINTER_UNAVAILABLE {
+              log::warn!("failed to print: the printer is unavailable");
+            }
+            Ok(())
+          })),
+        )
+        .map_err(webview2_com::Error::WindowsError)?;
+    }
+    Ok(())
+  }
+
+  pub fn execute_edit_command(&self, command: EditCommand) -> Result<()> {
+    // WebView2 has no editing API, the editing commands of the key events of the DevTools protocol
+    // are run by the engine like the ones of the keyboard shortcuts.
+    let command = match command {
+      EditCommand::Copy => "copy",
+      EditCommand::Cut => "cut",
+      EditCommand::Paste => "paste",
+      EditCommand::SelectAll => "selectAll",
+      EditCommand::Undo => "undo",
+      EditCommand::Redo => "redo",
+    };
+    self.call_devtools_protocol_method(
+      "Input.dispatchKeyEvent",
+      &format!(r#"{{"type":"rawKeyDown","commands":["{command}"]}}"#),
+      |_| {},
+    )?;
+    self.call_devtools_protocol_method("Input.dispatchKeyEvent", r#"{"type":"keyUp"}"#, |_| {})
+  }
+
+  pub fn url(&self) -> Url {
+    Url::parse(&url_from_webview(&self.webview)).unwrap()
+  }
+
   pub fn eval(
     &self,
     js: &str,
@@ -919,19 +1667,170 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
     }
   }
 
-  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub fn eval_in_world(&self, _world: &str, _js: &str) -> Result<()> {
+    Err(Error::Unsupported)
+  }
+
+  pub fn eval_in_world_with_callback(
+    &self,
+    _world: &str,
+    _js: &str,
+    _callback: impl FnOnce(String) + 'static,
+  ) -> Result<()> {
+    Err(Error::Unsupported)
+  }
+
+  pub fn eval_with_args(&self, function: &str, args: &str) -> Result<()> {
+    self.eval(
+      &apply_arguments(function, args),
+      None::<Box<dyn Fn(String) + Send + 'static>>,
+    )
+  }
+
   pub fn open_devtools(&self) {
     let _ = unsafe { self.webview.OpenDevToolsWindow() };
   }
 
-  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub fn open_devtools_with_placement(&self, _placement: DevtoolsPlacement) {
+    self.open_devtools();
+  }
+
   pub fn close_devtools(&self) {}
 
-  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub fn devtools_window_bounds(&self) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    None
+  }
+
+  pub fn set_devtools_window_bounds(
+    &self,
+    _position: PhysicalPosition<i32>,
+    _size: PhysicalSize<u32>,
+  ) -> Result<()> {
+    Err(Error::Unsupported)
+  }
+
   pub fn is_devtools_open(&self) -> bool {
     false
   }
 
+  pub fn set_bounds(&self, position: PhysicalPosition<i32>, size: PhysicalSize<u32>) -> Result<()> {
+    let Some(container) = &self.container else {
+      return Err(crate::Error::Unsupported);
+    };
+    // The container resizes the webview when its size changes.
+    container.set_bounds(position, size)?;
+    Ok(())
+  }
+
+  pub fn bring_to_front(&self) -> Result<()> {
+    self.reorder(win32wm::HWND_TOP)
+  }
+
+  pub fn send_to_back(&self) -> Result<()> {
+    self.reorder(win32wm::HWND_BOTTOM)
+  }
+
+  pub fn set_ignore_cursor_events(&self, ignore: bool) -> Result<()> {
+    let Some(container) = &self.container else {
+      return Err(crate::Error::Unsupported);
+    };
+    // The mouse input over a disabled child window goes to its parent.
+    unsafe {
+      let _ = EnableWindow(container.hwnd, !ignore);
+    }
+    Ok(())
+  }
+
+  pub fn script_evaluator(&self) -> Box<dyn Fn(&str)> {
+    let webview = self.webview.clone();
+    Box::new(move |js| {
+      let _ = Self::execute_script(&webview, js.to_string(), |_| ());
+    })
+  }
+
+  pub fn click_through_setter(&self) -> Option<Box<dyn Fn(&[ClickThroughRegion])>> {
+    let hwnd = self.container.as_ref()?.hwnd;
+    Some(Box::new(move |regions| {
+      let scale_factor = unsafe { resize::dpi_to_scale_factor(resize::hwnd_dpi(hwnd)) };
+      container::set_click_through_regions(hwnd, regions, scale_factor);
+    }))
+  }
+
+  pub fn send_mouse_event(&self, event: MouseEvent) -> Result<()> {
+    // `SendMouseInput` is only available to the composition controllers, the devtools protocol
+    // dispatches the input to the windowed ones too.
+    let params = mouse_event::devtools_params(&event).to_string();
+    unsafe {
+      self
+        .webview
+        .CallDevToolsProtocolMethod(
+          PCWSTR::from_raw(encode_wide("Input.dispatchMouseEvent").as_ptr()),
+          PCWSTR::from_raw(encode_wide(params).as_ptr()),
+          &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_, _| Ok(()))),
+        )
+        .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+    }
+  }
+
+  pub fn send_touch_event(&self, event: TouchEvent) -> Result<()> {
+    let params = touch_event::devtools_params(&mut self.touches.borrow_mut(), &event).to_string();
+    unsafe {
+      self
+        .webview
+        .CallDevToolsProtocolMethod(
+          PCWSTR::from_raw(encode_wide("Input.dispatchTouchEvent").as_ptr()),
+          PCWSTR::from_raw(encode_wide(params).as_ptr()),
+          &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_, _| Ok(()))),
+        )
+        .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+    }
+  }
+
+  pub fn set_network_conditions(
+    &self,
+    latency: Duration,
+    throughput: Option<u64>,
+    offline: bool,
+  ) -> Result<()> {
+    let params = network_conditions::devtools_params(latency, throughput, offline).to_string();
+    // The network domain must be enabled for the conditions to apply, enabling it twice is fine.
+    for (method, params) in [
+      ("Network.enable", "{}"),
+      ("Network.emulateNetworkConditions", params.as_str()),
+    ] {
+      unsafe {
+        self
+          .webview
+          .CallDevToolsProtocolMethod(
+            PCWSTR::from_raw(encode_wide(method).as_ptr()),
+            PCWSTR::from_raw(encode_wide(params).as_ptr()),
+            &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_, _| Ok(()))),
+          )
+          .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))?;
+      }
+    }
+    Ok(())
+  }
+
+  fn reorder(&self, insert_after: HWND) -> Result<()> {
+    let Some(container) = &self.container else {
+      return Err(crate::Error::Unsupported);
+    };
+    unsafe {
+      win32wm::SetWindowPos(
+        container.hwnd,
+        insert_after,
+        0,
+        0,
+        0,
+        0,
+        win32wm::SWP_NOMOVE | win32wm::SWP_NOSIZE | win32wm::SWP_NOACTIVATE,
+      )
+      .map_err(webview2_com::Error::WindowsError)?;
+    }
+    Ok(())
+  }
+
   pub fn zoom(&self, scale_factor: f64) {
     let _ = unsafe { self.controller.SetZoomFactor(scale_factor) };
   }
@@ -940,6 +1839,84 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
     set_background_color(&self.controller, background_color).map_err(Into::into)
   }
 
+  pub fn set_back_forward_gestures(&self, enabled: bool) -> Result<()> {
+    unsafe {
+      self
+        .webview
+        .Settings()
+        .and_then(|settings| settings.cast::<ICoreWebView2Settings6>())
+        .and_then(|settings| settings.SetIsSwipeNavigationEnabled(enabled))
+        .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+    }
+  }
+
+  pub fn set_under_page_background_color(&self, _color: RGBA) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn set_transparent(&self, transparent: bool) -> Result<()> {
+    if is_windows_7() {
+      return Ok(());
+    }
+    let alpha = if transparent { 0 } else { 255 };
+    set_background_color(&self.controller, (255, 255, 255, alpha)).map_err(Into::into)
+  }
+
+  pub fn focus(&self) -> Result<()> {
+    unsafe {
+      self
+        .controller
+        .MoveFocus(COREWEBVIEW2_MOVE_FOCUS_REASON_PROGRAMMATIC)
+        .map_err(webview2_com::Error::WindowsError)?;
+    }
+    Ok(())
+  }
+
+  pub fn blur(&self) -> Result<()> {
+    // Focusing the parent window would move the focus back to the webview.
+    unsafe {
+      let _ = SetFocus(HWND::default());
+    }
+    Ok(())
+  }
+
+  pub fn reparent(&self, window: &Window) -> Result<()> {
+    // The webview built with `WebViewBuilder::new` is bound to the messages of its window.
+    let Some(container) = &self.container else {
+      return Err(crate::Error::Unsupported);
+    };
+    container.reparent(HWND(window.hwnd() as _))?;
+    unsafe {
+      let _ = self.controller.NotifyParentWindowPositionChanged();
+    }
+    Ok(())
+  }
+
+  pub fn set_visible(&self, visible: bool) -> Result<()> {
+    unsafe {
+      // Hide the container too, which paints its background.
+      if let Some(container) = &self.container {
+        let command = if visible {
+          win32wm::SW_SHOW
+        } else {
+          win32wm::SW_HIDE
+        };
+        let _ = win32wm::ShowWindow(container.hwnd, command);
+      }
+      self
+        .controller
+        .SetIsVisible(visible)
+        .map_err(webview2_com::Error::WindowsError)?;
+    }
+    Ok(())
+  }
+
+  pub fn is_visible(&self) -> bool {
+    let mut visible = BOOL::default();
+    let _ = unsafe { self.controller.IsVisible(&mut visible) };
+    visible.as_bool()
+  }
+
   pub fn load_url(&self, url: &str) {
     let url = encode_wide(url);
     let _ = unsafe { self.webview.Navigate(PCWSTR::from_raw(url.as_ptr())) };
@@ -949,6 +1926,28 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
     load_url_with_headers(&self.webview, &self.env, url, headers);
   }
 
+  pub fn load_archive(&self, archive: WebArchive) -> Result<()> {
+    let path = dunce::canonicalize(archive.into_file()?)?;
+    let url = Url::from_file_path(path).map_err(|_| Error::Unsupported)?;
+    self.load_url(url.as_str());
+    Ok(())
+  }
+
+  pub fn load_url_with_cache_policy(&self, url: &str, policy: CachePolicy) {
+    self.load_url_with_headers(url, policy.headers())
+  }
+
+  pub fn reload_ignoring_cache(&self) {
+    // https://chromedevtools.github.io/devtools-protocol/tot/Page/#method-reload
+    let _ = unsafe {
+      self.webview.CallDevToolsProtocolMethod(
+        PCWSTR::from_raw(encode_wide("Page.reload").as_ptr()),
+        PCWSTR::from_raw(encode_wide(r#"{"ignoreCache":true}"#).as_ptr()),
+        &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_, _| Ok(()))),
+      )
+    };
+  }
+
   pub fn clear_all_browsing_data(&self) -> Result<()> {
     let handler = ClearBrowsingDataCompletedHandler::create(Box::new(move |_| Ok(())));
     unsafe {
@@ -969,6 +1968,279 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
     set_theme(&self.webview, theme);
   }
 
+  pub fn fetch_service_worker_registrations(
+    &self,
+    handler: impl FnOnce(Result<Vec<String>>) + 'static,
+  ) -> Result<()> {
+    // `ExecuteScript` doesn't wait for promises, the devtools protocol does.
+    // https://chromedevtools.github.io/devtools-protocol/tot/Runtime/#method-evaluate
+    let params = serde_json::json!({
+      "expression": REGISTRATIONS_SCRIPT,
+      "awaitPromise": true,
+      "returnByValue": true,
+    })
+    .to_string();
+    unsafe {
+      self
+        .webview
+        .CallDevToolsProtocolMethod(
+          PCWSTR::from_raw(encode_wide("Runtime.evaluate").as_ptr()),
+          PCWSTR::from_raw(encode_wide(params).as_ptr()),
+          &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(move |error_code, json| {
+            handler(
+              error_code
+                .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+                .and_then(|_| {
+                  let result: serde_json::Value = serde_json::from_str(&json)?;
+                  Ok(serde_json::from_value(result["result"]["value"].clone())?)
+                }),
+            );
+            Ok(())
+          })),
+        )
+        .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+    }
+  }
+
+  pub fn unregister_service_workers(&self, domain: Option<&str>) -> Result<()> {
+    unsafe {
+      match domain {
+        None => {
+          let handler = ClearBrowsingDataCompletedHandler::create(Box::new(move |_| Ok(())));
+          self
+            .webview
+            .cast::<ICoreWebView2_13>()
+            .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))?
+            .Profile()
+            .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))?
+            .cast::<ICoreWebView2Profile2>()
+            .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))?
+            .ClearBrowsingData(
+              COREWEBVIEW2_BROWSING_DATA_KINDS(
+                COREWEBVIEW2_BROWSING_DATA_KINDS_SERVICE_WORKERS.0
+                  | COREWEBVIEW2_BROWSING_DATA_KINDS_CACHE_STORAGE.0,
+              ),
+              &handler,
+            )
+            .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+        }
+        Some(domain) => {
+          // https://chromedevtools.github.io/devtools-protocol/tot/Storage/#method-clearDataForOrigin
+          for scheme in ["https", "http"] {
+            let params = serde_json::json!({
+              "origin": format!("{scheme}://{domain}"),
+              "storageTypes": "service_workers,cache_storage",
+            })
+            .to_string();
+            self
+              .webview
+              .CallDevToolsProtocolMethod(
+                PCWSTR::from_raw(encode_wide("Storage.clearDataForOrigin").as_ptr()),
+                PCWSTR::from_raw(encode_wide(params).as_ptr()),
+                &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_, _| Ok(()))),
+              )
+              .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))?;
+          }
+          Ok(())
+        }
+      }
+    }
+  }
+
+  pub fn call_devtools_protocol_method(
+    &self,
+    method: &str,
+    params: &str,
+    handler: impl FnOnce(Result<String>) + 'static,
+  ) -> Result<()> {
+    unsafe {
+      self
+        .webview
+        .CallDevToolsProtocolMethod(
+          PCWSTR::from_raw(encode_wide(method).as_ptr()),
+          PCWSTR::from_raw(encode_wide(params).as_ptr()),
+          &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(move |error_code, json| {
+            // On failure, `json` holds the protocol error, e.g. `{"code":-32601,"message":"..."}`.
+            handler(match error_code {
+              Ok(()) => Ok(json),
+              Err(_) => Err(Error::DevtoolsProtocolError(json)),
+            });
+            Ok(())
+          })),
+        )
+        .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+    }
+  }
+
+  pub fn subscribe_devtools_protocol_event(
+    &self,
+    event: &str,
+    handler: impl Fn(String) + 'static,
+  ) -> Result<()> {
+    let mut token = EventRegistrationToken::default();
+    unsafe {
+      self
+        .webview
+        .GetDevToolsProtocolEventReceiver(PCWSTR::from_raw(encode_wide(event).as_ptr()))
+        .and_then(|receiver| {
+          receiver.add_DevToolsProtocolEventReceived(
+            &DevToolsProtocolEventReceivedEventHandler::create(Box::new(move |_, args| {
+              if let Some(args) = args {
+                let mut json = PWSTR::null();
+                args.ParameterObjectAsJson(&mut json)?;
+                handler(take_pwstr(json));
+              }
+              Ok(())
+            })),
+            &mut token,
+          )
+        })
+        .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+    }
+  }
+
+  pub fn set_media_feature_overrides(&self, overrides: MediaFeatureOverrides) -> Result<()> {
+    set_media_feature_overrides(&self.webview, overrides)
+      .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+  }
+
+  pub fn capture_element(
+    &self,
+    selector: &str,
+    format: SnapshotFormat,
+    handler: impl FnOnce(Result<Vec<u8>>) + 'static,
+  ) -> Result<()> {
+    let webview = self.webview.clone();
+    let selector = selector.to_string();
+    unsafe {
+      self
+        .webview
+        .ExecuteScript(
+          PCWSTR::from_raw(encode_wide(element_rect_script(&selector)).as_ptr()),
+          &ExecuteScriptCompletedHandler::create(Box::new(move |error_code, json| {
+            let rect = error_code
+              .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+              .and_then(|_| parse_element_rect(&selector, &json));
+            let rect = match rect {
+              Ok(rect) => rect,
+              Err(e) => {
+                handler(Err(e));
+                return Ok(());
+              }
+            };
+
+            // https://chromedevtools.github.io/devtools-protocol/tot/Page/#method-captureScreenshot
+            let params = serde_json::json!({
+              "format": match format {
+                SnapshotFormat::Png => "png",
+                SnapshotFormat::Jpeg => "jpeg",
+              },
+              "clip": {
+                "x": rect.x + rect.scroll_x,
+                "y": rect.y + rect.scroll_y,
+                "width": rect.width,
+                "height": rect.height,
+                "scale": 1,
+              },
+            })
+            .to_string();
+            webview.CallDevToolsProtocolMethod(
+              PCWSTR::from_raw(encode_wide("Page.captureScreenshot").as_ptr()),
+              PCWSTR::from_raw(encode_wide(params).as_ptr()),
+              &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(
+                move |error_code, json| {
+                  handler(
+                    error_code
+                      .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+                      .and_then(|_| decode_screenshot(&json)),
+                  );
+                  Ok(())
+                },
+              )),
+            )
+          })),
+        )
+        .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+    }
+  }
+
+  pub fn pause_all_media(&self) -> Result<()> {
+    // WebView2 has no API to pause the media, the page is muted instead.
+    let webview = self
+      .webview
+      .cast::<ICoreWebView2_8>()
+      .map_err(|_| Error::Unsupported)?;
+    if self.muted_before_pause.get().is_none() {
+      self.muted_before_pause.set(Some(self.is_muted()));
+      unsafe { webview.SetIsMuted(true) }
+        .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))?;
+    }
+    Ok(())
+  }
+
+  pub fn resume_media(&self) -> Result<()> {
+    if let Some(muted) = self.muted_before_pause.take() {
+      self.set_muted(muted);
+    }
+    Ok(())
+  }
+
+  pub fn process_resident_size(&self) -> Option<u64> {
+    None
+  }
+
+  pub fn discard(&self) -> Result<()> {
+    // WebView2 can't terminate the renderer of a single webview, suspending it would keep the
+    // memory of the page.
+    Err(Error::Unsupported)
+  }
+
+  pub fn restore(&self) -> Result<()> {
+    Err(Error::Unsupported)
+  }
+
+  pub fn suspend(&self) -> Result<()> {
+    unsafe {
+      self
+        .webview
+        .cast::<ICoreWebView2_3>()
+        .and_then(|webview| {
+          webview.TrySuspend(&TrySuspendCompletedHandler::create(Box::new(|_, _| Ok(()))))
+        })
+        .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+    }
+  }
+
+  pub fn resume(&self) -> Result<()> {
+    unsafe {
+      self
+        .webview
+        .cast::<ICoreWebView2_3>()
+        .and_then(|webview| webview.Resume())
+        .map_err(|e| Error::WebView2Error(webview2_com::Error::WindowsError(e)))
+    }
+  }
+
+  pub fn set_muted(&self, muted: bool) {
+    // The page stays muted until `resume_media`, which applies it.
+    if self.muted_before_pause.get().is_some() {
+      self.muted_before_pause.set(Some(muted));
+    } else if let Ok(webview) = self.webview.cast::<ICoreWebView2_8>() {
+      let _ = unsafe { webview.SetIsMuted(muted) };
+    }
+  }
+
+  pub fn is_muted(&self) -> bool {
+    if let Some(muted) = self.muted_before_pause.get() {
+      return muted;
+    }
+    let mut muted = BOOL::default();
+    if let Ok(webview) = self.webview.cast::<ICoreWebView2_8>() {
+      let _ = unsafe { webview.IsMuted(&mut muted) };
+    }
+    muted.as_bool()
+  }
+
   pub fn set_memory_usage_level(&self, level: MemoryUsageLevel) {
     let Ok(webview) = self.webview.cast::<ICoreWebView2_19>() else {
       return;
@@ -983,6 +2255,28 @@ window.addEventListener('mousemove', (e) => window.chrome.webview.postMessage('_
   }
 }
 
+/// The body of a custom protocol request, read from the content stream of WebView2 by the thread
+/// reading it.
+struct ContentReader(AgileReference<IStream>);
+
+impl Read for ContentReader {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    // Resolved for the apartment of the current thread, the body can be moved to another thread.
+    let content = self.0.resolve().map_err(io::Error::other)?;
+    let mut read = 0;
+    unsafe {
+      content.Read(
+        buf.as_mut_ptr() as *mut _,
+        buf.len().min(u32::MAX as usize) as u32,
+        Some(&mut read),
+      )
+    }
+    .ok()
+    .map_err(io::Error::other)?;
+    Ok(read as usize)
+  }
+}
+
 unsafe fn prepare_web_request_response(
   env: &ICoreWebView2Environment,
   sent_response: &HttpResponse<Cow<'static, [u8]>>,
@@ -1015,6 +2309,27 @@ unsafe fn prepare_web_request_response(
   )
 }
 
+/// The resource types of the content rule lists matching a WebView2 resource context.
+fn content_rule_resource_types(
+  context: COREWEBVIEW2_WEB_RESOURCE_CONTEXT,
+) -> &'static [&'static str] {
+  match context {
+    COREWEBVIEW2_WEB_RESOURCE_CONTEXT_DOCUMENT => &["document"],
+    COREWEBVIEW2_WEB_RESOURCE_CONTEXT_STYLESHEET => &["style-sheet"],
+    COREWEBVIEW2_WEB_RESOURCE_CONTEXT_IMAGE => &["image"],
+    COREWEBVIEW2_WEB_RESOURCE_CONTEXT_MEDIA => &["media"],
+    COREWEBVIEW2_WEB_RESOURCE_CONTEXT_FONT => &["font"],
+    COREWEBVIEW2_WEB_RESOURCE_CONTEXT_SCRIPT => &["script"],
+    COREWEBVIEW2_WEB_RESOURCE_CONTEXT_XML_HTTP_REQUEST
+    | COREWEBVIEW2_WEB_RESOURCE_CONTEXT_FETCH
+    | COREWEBVIEW2_WEB_RESOURCE_CONTEXT_EVENT_SOURCE => &["raw", "fetch"],
+    COREWEBVIEW2_WEB_RESOURCE_CONTEXT_WEBSOCKET => &["raw", "websocket"],
+    COREWEBVIEW2_WEB_RESOURCE_CONTEXT_PING
+    | COREWEBVIEW2_WEB_RESOURCE_CONTEXT_CSP_VIOLATION_REPORT => &["ping"],
+    _ => &["other"],
+  }
+}
+
 fn encode_wide(string: impl AsRef<std::ffi::OsStr>) -> Vec<u16> {
   string.as_ref().encode_wide().chain(once(0)).collect()
 }
@@ -1092,6 +2407,107 @@ fn set_theme(webview: &ICoreWebView2, theme: Theme) {
   }
 }
 
+fn set_media_feature_overrides(
+  webview: &ICoreWebView2,
+  overrides: MediaFeatureOverrides,
+) -> windows::core::Result<()> {
+  // An empty value resets the feature to the system setting.
+  let features: Vec<serde_json::Value> = overrides
+    .features()
+    .iter()
+    .map(|(name, value)| serde_json::json!({ "name": name, "value": value.unwrap_or_default() }))
+    .collect();
+  let params = serde_json::json!({ "features": features }).to_string();
+
+  unsafe {
+    webview.CallDevToolsProtocolMethod(
+      PCWSTR::from_raw(encode_wide("Emulation.setEmulatedMedia").as_ptr()),
+      PCWSTR::from_raw(encode_wide(params).as_ptr()),
+      &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_, _| Ok(()))),
+    )
+  }
+}
+
+/// Decodes the result of the `Page.captureScreenshot` devtools protocol method.
+fn decode_screenshot(json: &str) -> Result<Vec<u8>> {
+  use base64::{engine::general_purpose, Engine};
+
+  let result: serde_json::Value = serde_json::from_str(json)?;
+  let data = result["data"].as_str().ok_or(Error::SnapshotFailed)?;
+  general_purpose::STANDARD
+    .decode(data)
+    .map_err(|_| Error::SnapshotFailed)
+}
+
+impl Drop for InnerWebView {
+  fn drop(&mut self) {
+    // Release the browser process now instead of waiting for the parent window messages.
+    let _ = unsafe { self.controller.Close() };
+  }
+}
+
+pub fn platform_pump_events() {
+  let mut msg = win32wm::MSG::default();
+  unsafe {
+    while win32wm::PeekMessageW(&mut msg, HWND::default(), 0, 0, win32wm::PM_REMOVE).as_bool() {
+      win32wm::TranslateMessage(&msg);
+      win32wm::DispatchMessageW(&msg);
+    }
+  }
+}
+
+pub fn open_in_browser(url: &str) -> Result<()> {
+  let result = unsafe {
+    ShellExecuteW(
+      HWND::default(),
+      PCWSTR::from_raw(encode_wide("open").as_ptr()),
+      PCWSTR::from_raw(encode_wide(url).as_ptr()),
+      PCWSTR::null(),
+      PCWSTR::null(),
+      win32wm::SW_SHOWNORMAL,
+    )
+  };
+  // Values up to 32 are error codes.
+  if result.0 <= 32 {
+    return Err(std::io::Error::last_os_error().into());
+  }
+  Ok(())
+}
+
+pub fn platform_printers() -> Result<Vec<String>> {
+  use windows::Win32::Graphics::Printing::{
+    EnumPrintersW, PRINTER_ENUM_CONNECTIONS, PRINTER_ENUM_LOCAL, PRINTER_INFO_4W,
+  };
+
+  let flags = PRINTER_ENUM_LOCAL | PRINTER_ENUM_CONNECTIONS;
+  let (mut needed, mut returned) = (0, 0);
+  unsafe {
+    // The first call only measures the buffer, it fails as it is missing.
+    let _ = EnumPrintersW(flags, PCWSTR::null(), 4, None, &mut needed, &mut returned);
+    // `u64`s keep the printer infos aligned.
+    let mut buffer = vec![0u64; (needed as usize + 7) / 8];
+    let bytes = std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, needed as usize);
+    returned = 0;
+    // Nothing is returned if it fails.
+    let _ = EnumPrintersW(
+      flags,
+      PCWSTR::null(),
+      4,
+      Some(bytes),
+      &mut needed,
+      &mut returned,
+    );
+    let infos =
+      std::slice::from_raw_parts(buffer.as_ptr() as *const PRINTER_INFO_4W, returned as usize);
+    Ok(
+      infos
+        .iter()
+        .filter_map(|info| info.pPrinterName.to_string().ok())
+        .collect(),
+    )
+  }
+}
+
 pub fn platform_webview_version() -> Result<String> {
   let mut versioninfo = PWSTR::null();
   unsafe { GetAvailableCoreWebView2BrowserVersionString(PCWSTR::null(), &mut versioninfo) }
@@ -57,11 +57,11 @@ static GET_DPI_FOR_MONITOR: Lazy<Option<GetDpiForMonitor>> =
   Lazy::new(|| get_function!("shcore.dll", GetDpiForMonitor));
 
 const BASE_DPI: u32 = 96;
-fn dpi_to_scale_factor(dpi: u32) -> f64 {
+pub fn dpi_to_scale_factor(dpi: u32) -> f64 {
   dpi as f64 / BASE_DPI as f64
 }
 
-unsafe fn hwnd_dpi(hwnd: HWND) -> u32 {
+pub unsafe fn hwnd_dpi(hwnd: HWND) -> u32 {
   let hdc = GetDC(hwnd);
   if hdc.is_invalid() {
     panic!("[tao] `GetDC` returned null!");
@@ -14,7 +14,9 @@ mod synthetic_mouse_events;
 use url::Url;
 
 #[cfg(target_os = "macos")]
-use cocoa::appkit::{NSView, NSViewHeightSizable, NSViewWidthSizable};
+use cocoa::appkit::{
+  NSEvent, NSEventModifierFlags, NSView, NSViewHeightSizable, NSViewWidthSizable,
+};
 use cocoa::{
   base::{id, nil, NO, YES},
   foundation::{NSDictionary, NSFastEnumeration, NSInteger},
@@ -22,6 +24,8 @@ use cocoa::{
 
 use std::{
   borrow::Cow,
+  cell::{Cell, RefCell},
+  collections::HashMap,
   ffi::{c_void, CStr},
   os::raw::c_char,
   ptr::{null, null_mut},
@@ -55,7 +59,7 @@ use crate::webview::{
 
 use crate::{
   application::{
-    dpi::{LogicalSize, PhysicalSize},
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
     window::Window,
   },
   webview::{
@@ -66,11 +70,22 @@ use crate::{
       },
       navigation::{add_navigation_mathods, drop_navigation_methods, set_navigation_methods},
     },
-    FileDropEvent, PageLoadEvent, RequestAsyncResponder, WebContext, WebViewAttributes, RGBA,
+    AutoResizePolicy, AutoplayPolicy, CachePolicy, ClickThroughRegion, DevtoolsPlacement,
+    EditCommand, FileDropEvent, InitScript, InjectionTime, MediaFeatureOverrides, MouseEvent,
+    NewWindowOpener, NewWindowRequest, NewWindowResponse, PageLoadEvent, PrintOptions,
+    RequestAsyncResponder, RequestBody, ScriptId, SnapshotFormat, TouchEvent, WebArchive,
+    WebContext, WebViewAttributes, RGBA,
   },
   Result,
 };
 
+#[cfg(target_os = "macos")]
+use crate::webview::snapshot::{element_rect_script, parse_element_rect};
+use crate::webview::typed_eval::apply_arguments;
+use crate::webview::{lifecycle::SharedGuard, web_context::ProcessPool};
+#[cfg(target_os = "macos")]
+use crate::webview::{KeyEvent, KeyEventResponse, KeyModifiers};
+
 use http::{
   header::{CONTENT_LENGTH, CONTENT_TYPE},
   status::StatusCode,
@@ -80,9 +95,28 @@ use http::{
 
 const IPC_MESSAGE_HANDLER_NAME: &str = "ipc";
 const ACCEPT_FIRST_MOUSE: &str = "accept_first_mouse";
+const FOCUS_CHANGED_FUNCTION: &str = "focus_changed_function";
+const KEY_EVENT_FUNCTION: &str = "key_event_function";
+const IGNORE_CURSOR_EVENTS: &str = "ignore_cursor_events";
+const CLICK_THROUGH_REGIONS: &str = "click_through_regions";
 
 const NS_JSON_WRITING_FRAGMENTS_ALLOWED: u64 = 4;
 
+/// The configuration WebKit requires for the webview of a new window, and the webview built with it.
+pub(crate) struct PlatformOpener {
+  configuration: id,
+  child: RefCell<Option<id>>,
+}
+
+impl Drop for PlatformOpener {
+  fn drop(&mut self) {
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+      let _: () = msg_send![self.configuration, release];
+    }
+  }
+}
+
 pub(crate) struct InnerWebView {
   pub webview: id,
   #[cfg(target_os = "macos")]
@@ -93,21 +127,72 @@ pub(crate) struct InnerWebView {
   // all functions pointer declarations in objc callbacks below all need to get updated.
   ipc_handler_ptr: *mut (Box<dyn Fn(&Window, String)>, Rc<Window>),
   document_title_changed_handler: *mut (Box<dyn Fn(&Window, String)>, Rc<Window>),
+  audio_state_handler: *mut Box<dyn Fn(bool)>,
+  audio_state_observer: id,
   navigation_decide_policy_ptr: *mut Box<dyn Fn(String, bool) -> bool>,
   page_load_handler: *mut Box<dyn Fn(PageLoadEvent)>,
+  new_window_handler_ptr: *mut Box<dyn Fn(NewWindowRequest) -> NewWindowResponse>,
+  close_requested_handler_ptr: *mut (Box<dyn Fn(&Window)>, Rc<Window>),
+  #[cfg(target_os = "macos")]
+  focus_changed_handler_ptr: *mut Box<dyn Fn(bool)>,
+  #[cfg(target_os = "macos")]
+  key_event_handler_ptr: *mut Box<dyn Fn(KeyEvent) -> KeyEventResponse>,
+  #[cfg(target_os = "macos")]
+  click_through_regions: Rc<RefCell<Vec<ClickThroughRegion>>>,
   #[cfg(target_os = "macos")]
   file_drop_ptr: *mut (Box<dyn Fn(&Window, FileDropEvent) -> bool>, Rc<Window>),
   download_delegate: id,
-  protocol_ptrs: Vec<*mut Box<dyn Fn(Request<Vec<u8>>, RequestAsyncResponder)>>,
+  protocol_ptrs: Vec<*mut Box<dyn Fn(Request<RequestBody>, RequestAsyncResponder)>>,
+  isolated_worlds: Vec<String>,
+  user_scripts: RefCell<HashMap<ScriptId, id>>,
+  auto_resize: Cell<AutoResizePolicy>,
+  discarded: Cell<bool>,
+  // Whether the media is paused by `pause_all_media`, so `resume` keeps it paused.
+  media_paused: Cell<bool>,
+  // Keeps the process pool of the context alive while the webview is.
+  _process_pool: Option<SharedGuard<ProcessPool>>,
 }
 
+/// The features implemented by this backend, see [`super::Capabilities`]. The flags use the
+/// `cfg` of the methods they describe, most of which are macOS only.
+pub(crate) const CAPABILITIES: super::Capabilities = super::Capabilities {
+  streaming_protocols: false,
+  protocol_request_body: true,
+  devtools: cfg!(all(
+    target_os = "macos",
+    any(debug_assertions, feature = "devtools")
+  )),
+  print: cfg!(target_os = "macos"),
+  print_options: cfg!(target_os = "macos"),
+  edit_commands: true,
+  zoom: true,
+  element_capture: cfg!(target_os = "macos"),
+  muting: true,
+  service_worker_management: true,
+  pdf_downloads: true,
+  content_blocking: true,
+  devtools_protocol: false,
+  proxy: cfg!(all(target_os = "macos", feature = "mac-proxy")),
+  offscreen: false,
+  find_in_page: false,
+  print_to_pdf: false,
+  selected_text: true,
+  suspend: true,
+};
+
 impl InnerWebView {
   pub fn new(
     window: Rc<Window>,
     attributes: WebViewAttributes,
     _pl_attrs: super::PlatformSpecificWebViewAttributes,
-    _web_context: Option<&mut WebContext>,
+    web_context: Option<&mut WebContext>,
+    child: bool,
   ) -> Result<Self> {
+    let isolated_worlds = super::isolated_worlds(&attributes.isolated_initialization_scripts);
+    if !isolated_worlds.is_empty() && Class::get("WKContentWorld").is_none() {
+      return Err(crate::Error::Unsupported);
+    }
+
     // Function for ipc handler
     extern "C" fn did_receive(this: &Object, _: Sel, _: id, msg: id) {
       // Safety: objc runtime calls are unsafe
@@ -133,7 +218,7 @@ impl InnerWebView {
         let function = this.get_ivar::<*mut c_void>("function");
         if !function.is_null() {
           let function =
-            &mut *(*function as *mut Box<dyn Fn(Request<Vec<u8>>, RequestAsyncResponder)>);
+            &mut *(*function as *mut Box<dyn Fn(Request<RequestBody>, RequestAsyncResponder)>);
 
           // Get url request
           let request: id = msg_send![task, request];
@@ -155,27 +240,18 @@ impl InnerWebView {
             .uri(nsstring.to_str())
             .method(method.to_str());
 
-          // Get body
-          let mut sent_form_body = Vec::new();
+          // Get body, the stream is read by the handler
           let body: id = msg_send![request, HTTPBody];
           let body_stream: id = msg_send![request, HTTPBodyStream];
-          if !body.is_null() {
+          let sent_form_body = if !body.is_null() {
             let length = msg_send![body, length];
             let data_bytes: id = msg_send![body, bytes];
-            sent_form_body = slice::from_raw_parts(data_bytes as *const u8, length).to_vec();
+            RequestBody::from_vec(slice::from_raw_parts(data_bytes as *const u8, length).to_vec())
           } else if !body_stream.is_null() {
-            let _: () = msg_send![body_stream, open];
-
-            while msg_send![body_stream, hasBytesAvailable] {
-              sent_form_body.reserve(128);
-              let p = sent_form_body.as_mut_ptr().add(sent_form_body.len());
-              let read_length = sent_form_body.capacity() - sent_form_body.len();
-              let count: usize = msg_send![body_stream, read: p maxLength: read_length];
-              sent_form_body.set_len(sent_form_body.len() + count);
-            }
-
-            let _: () = msg_send![body_stream, close];
-          }
+            RequestBody::new(HttpBodyStream::new(body_stream))
+          } else {
+            RequestBody::empty()
+          };
 
           // Extract all headers fields
           let all_headers: id = msg_send![request, allHTTPHeaderFields];
@@ -254,18 +330,64 @@ impl InnerWebView {
 
     // Safety: objc runtime calls are unsafe
     unsafe {
-      // Config and custom protocol
-      let config: id = msg_send![class!(WKWebViewConfiguration), new];
+      // Config and custom protocol. The webview of a new window must use the configuration of its
+      // opener, with its custom protocols and data store.
+      let opener = attributes.new_window_opener;
+      let (config, custom_protocols): (id, _) = match &opener {
+        Some(opener) => (opener.0.configuration, Vec::new()),
+        None => (
+          msg_send![class!(WKWebViewConfiguration), new],
+          attributes.streaming_custom_protocols,
+        ),
+      };
       let mut protocol_ptrs = Vec::new();
 
       // Incognito mode
-      let data_store: id = if attributes.incognito {
+      let data_store: id = if opener.is_some() {
+        msg_send![config, websiteDataStore]
+      } else if attributes.incognito {
         msg_send![class!(WKWebsiteDataStore), nonPersistentDataStore]
       } else {
         msg_send![class!(WKWebsiteDataStore), defaultDataStore]
       };
 
-      for (name, function) in attributes.custom_protocols {
+      // The items of the Edit menu, like "Check Spelling While Typing", read by WebKit from the
+      // user defaults.
+      #[cfg(target_os = "macos")]
+      {
+        let settings = &attributes.text_input_settings;
+        let defaults: id = msg_send![class!(NSUserDefaults), standardUserDefaults];
+        for (key, enabled) in [
+          ("WebContinuousSpellCheckingEnabled", attributes.spellcheck),
+          (
+            "WebAutomaticSpellingCorrectionEnabled",
+            settings.autocorrect,
+          ),
+          (
+            "WebAutomaticQuoteSubstitutionEnabled",
+            settings.smart_quotes,
+          ),
+          ("WebAutomaticDashSubstitutionEnabled", settings.smart_dashes),
+          (
+            "WebAutomaticTextReplacementEnabled",
+            settings.text_replacement,
+          ),
+        ] {
+          if let Some(enabled) = enabled {
+            let () = msg_send![defaults, setBool: if enabled { YES } else { NO } forKey: NSString::new(key)];
+          }
+        }
+      }
+
+      // The webviews of a context share its process pool.
+      let mut process_pool = None;
+      if let (None, Some(web_context)) = (&opener, web_context.as_ref()) {
+        let (pool, guard) = web_context.os.process_pool();
+        let () = msg_send![config, setProcessPool: pool.0];
+        process_pool = Some(guard);
+      }
+
+      for (name, function) in custom_protocols {
         let scheme_name = format!("{}URLSchemeHandler", name);
         let cls = ClassDecl::new(&scheme_name, class!(NSObject));
         let cls = match cls {
@@ -292,7 +414,37 @@ impl InnerWebView {
       }
 
       // Webview and manager
-      let manager: id = msg_send![config, userContentController];
+      let manager: id = if opener.is_some() {
+        // Don't share the scripts and message handlers of the opener.
+        let manager: id = msg_send![class!(WKUserContentController), new];
+        let () = msg_send![config, setUserContentController: manager];
+        manager
+      } else {
+        msg_send![config, userContentController]
+      };
+
+      // Content blockers
+      if let Some(web_context) = &web_context {
+        let store: id = msg_send![class!(WKContentRuleListStore), defaultStore];
+        for (identifier, rules) in web_context.content_rule_lists() {
+          // Keep the manager alive until the list is compiled
+          let _: id = msg_send![manager, retain];
+          let handler = block::ConcreteBlock::new(move |list: id, error: id| {
+            if list != nil {
+              let _: () = msg_send![manager, addContentRuleList: list];
+            } else {
+              let description: id = msg_send![error, localizedDescription];
+              log::warn!(
+                "failed to compile content rule list: {}",
+                NSString(description).to_str()
+              );
+            }
+            let _: () = msg_send![manager, release];
+          })
+          .copy();
+          let _: () = msg_send![store, compileContentRuleListForIdentifier:NSString::new(identifier) encodedContentRuleList:NSString::new(rules) completionHandler:&*handler];
+        }
+      }
       let cls = match ClassDecl::new("WryWebView", class!(WKWebView)) {
         #[allow(unused_mut)]
         Some(mut decl) => {
@@ -316,6 +468,173 @@ impl InnerWebView {
                 }
               }
             }
+
+            decl.add_ivar::<bool>(IGNORE_CURSOR_EVENTS);
+            decl.add_ivar::<*const c_void>(CLICK_THROUGH_REGIONS);
+            decl.add_method(
+              sel!(hitTest:),
+              hit_test as extern "C" fn(&Object, Sel, cocoa::foundation::NSPoint) -> id,
+            );
+
+            // Return no view so the mouse events go to the views below the webview.
+            extern "C" fn hit_test(
+              this: &Object,
+              _sel: Sel,
+              point: cocoa::foundation::NSPoint,
+            ) -> id {
+              unsafe {
+                let ignore: bool = *this.get_ivar(IGNORE_CURSOR_EVENTS);
+                if ignore || in_click_through_region(this, point) {
+                  nil
+                } else {
+                  msg_send![super(this, class!(WKWebView)), hitTest: point]
+                }
+              }
+            }
+
+            // The point is in the coordinates of the superview, and the webview is flipped.
+            unsafe fn in_click_through_region(
+              this: &Object,
+              point: cocoa::foundation::NSPoint,
+            ) -> bool {
+              let regions = *this.get_ivar::<*const c_void>(CLICK_THROUGH_REGIONS);
+              if regions.is_null() {
+                return false;
+              }
+              let regions = &*(regions as *const RefCell<Vec<ClickThroughRegion>>);
+              let regions = regions.borrow();
+              if regions.is_empty() {
+                return false;
+              }
+              let superview: id = msg_send![this, superview];
+              let point: cocoa::foundation::NSPoint =
+                msg_send![this, convertPoint: point fromView: superview];
+              regions
+                .iter()
+                .any(|region| region.contains(point.x, point.y))
+            }
+
+            decl.add_ivar::<*mut c_void>(FOCUS_CHANGED_FUNCTION);
+            decl.add_method(
+              sel!(becomeFirstResponder),
+              become_first_responder as extern "C" fn(&Object, Sel) -> BOOL,
+            );
+            decl.add_method(
+              sel!(resignFirstResponder),
+              resign_first_responder as extern "C" fn(&Object, Sel) -> BOOL,
+            );
+
+            extern "C" fn become_first_responder(this: &Object, _sel: Sel) -> BOOL {
+              unsafe {
+                let accepted: BOOL =
+                  msg_send![super(this, class!(WKWebView)), becomeFirstResponder];
+                if accepted == YES {
+                  focus_changed(this, true);
+                }
+                accepted
+              }
+            }
+
+            extern "C" fn resign_first_responder(this: &Object, _sel: Sel) -> BOOL {
+              unsafe {
+                let accepted: BOOL =
+                  msg_send![super(this, class!(WKWebView)), resignFirstResponder];
+                if accepted == YES {
+                  focus_changed(this, false);
+                }
+                accepted
+              }
+            }
+
+            unsafe fn focus_changed(this: &Object, focused: bool) {
+              let function = this.get_ivar::<*mut c_void>(FOCUS_CHANGED_FUNCTION);
+              if !function.is_null() {
+                let function = &mut *(*function as *mut Box<dyn Fn(bool)>);
+                function(focused);
+              }
+            }
+
+            decl.add_ivar::<*mut c_void>(KEY_EVENT_FUNCTION);
+            decl.add_method(sel!(keyDown:), key_down as extern "C" fn(&Object, Sel, id));
+            decl.add_method(
+              sel!(performKeyEquivalent:),
+              perform_key_equivalent as extern "C" fn(&Object, Sel, id) -> BOOL,
+            );
+
+            extern "C" fn key_down(this: &Object, _sel: Sel, event: id) {
+              unsafe {
+                if key_event(this, event) != KeyEventResponse::Handled {
+                  let () = msg_send![super(this, class!(WKWebView)), keyDown: event];
+                }
+              }
+            }
+
+            // The keys pressed with Command are sent as key equivalents instead of `keyDown:`.
+            extern "C" fn perform_key_equivalent(this: &Object, _sel: Sel, event: id) -> BOOL {
+              unsafe {
+                if event
+                  .modifierFlags()
+                  .contains(NSEventModifierFlags::NSCommandKeyMask)
+                  && key_event(this, event) == KeyEventResponse::Handled
+                {
+                  YES
+                } else {
+                  msg_send![super(this, class!(WKWebView)), performKeyEquivalent: event]
+                }
+              }
+            }
+
+            unsafe fn key_event(this: &Object, event: id) -> KeyEventResponse {
+              let function = this.get_ivar::<*mut c_void>(KEY_EVENT_FUNCTION);
+              if function.is_null() {
+                return KeyEventResponse::Forward;
+              }
+              let function = &*(*function as *mut Box<dyn Fn(KeyEvent) -> KeyEventResponse>);
+              let flags = event.modifierFlags();
+              let characters = NSString(event.charactersIgnoringModifiers());
+              // Dead keys have no characters.
+              let key = if characters.0.is_null() || characters.to_str().is_empty() {
+                "Unidentified".to_string()
+              } else {
+                key_name(characters.to_str())
+              };
+              function(KeyEvent {
+                key,
+                modifiers: KeyModifiers {
+                  shift: flags.contains(NSEventModifierFlags::NSShiftKeyMask),
+                  control: flags.contains(NSEventModifierFlags::NSControlKeyMask),
+                  alt: flags.contains(NSEventModifierFlags::NSAlternateKeyMask),
+                  meta: flags.contains(NSEventModifierFlags::NSCommandKeyMask),
+                },
+              })
+            }
+
+            // The function keys are reported in the Unicode private use area, see `NSUpArrowFunctionKey`.
+            fn key_name(characters: &str) -> String {
+              let mut chars = characters.chars();
+              let (Some(c), None) = (chars.next(), chars.next()) else {
+                return characters.to_string();
+              };
+              let name = match c as u32 {
+                0x0d | 0x03 => "Enter",
+                0x09 | 0x19 => "Tab",
+                0x1b => "Escape",
+                0x7f => "Backspace",
+                0x20 => " ",
+                0xf700 => "ArrowUp",
+                0xf701 => "ArrowDown",
+                0xf702 => "ArrowLeft",
+                0xf703 => "ArrowRight",
+                f @ 0xf704..=0xf71b => return format!("F{}", f - 0xf704 + 1),
+                0xf728 => "Delete",
+                0xf729 => "Home",
+                0xf72b => "End",
+                0xf72c => "PageUp",
+                0xf72d => "PageDown",
+                _ => return c.to_string(),
+              };
+              name.to_string()
+            }
           }
           decl.register()
         }
@@ -347,10 +666,50 @@ impl InnerWebView {
       #[cfg(target_os = "macos")]
       (*webview).set_ivar(ACCEPT_FIRST_MOUSE, attributes.accept_first_mouse);
 
+      #[cfg(target_os = "macos")]
+      let focus_changed_handler_ptr = match attributes.focus_changed_handler {
+        Some(handler) => {
+          let handler = Box::into_raw(Box::new(handler));
+          (*webview).set_ivar(FOCUS_CHANGED_FUNCTION, handler as *mut c_void);
+          handler
+        }
+        None => null_mut(),
+      };
+
+      #[cfg(target_os = "macos")]
+      let click_through_regions = Rc::new(RefCell::new(Vec::new()));
+      #[cfg(target_os = "macos")]
+      (*webview).set_ivar(
+        CLICK_THROUGH_REGIONS,
+        Rc::as_ptr(&click_through_regions) as *const c_void,
+      );
+
+      #[cfg(target_os = "macos")]
+      let key_event_handler_ptr = match attributes.key_event_handler {
+        Some(handler) => {
+          let handler = Box::into_raw(Box::new(handler));
+          (*webview).set_ivar(KEY_EVENT_FUNCTION, handler as *mut c_void);
+          handler
+        }
+        None => null_mut(),
+      };
+
       let _: id = msg_send![_preference, setValue:_yes forKey:NSString::new("allowsPictureInPictureMediaPlayback")];
 
-      if attributes.autoplay {
-        let _: id = msg_send![config, setMediaTypesRequiringUserActionForPlayback:0];
+      // https://developer.apple.com/documentation/webkit/wkaudiovisualmediatypes
+      let media_types_requiring_user_action: Option<usize> = match attributes.autoplay_policy {
+        Some(AutoplayPolicy::AllowAll) => Some(0),
+        Some(AutoplayPolicy::AllowMuted) => Some(1),
+        Some(AutoplayPolicy::RequireUserGesture | AutoplayPolicy::Allowlist(_)) => Some(usize::MAX),
+        None => None,
+      };
+      if let Some(media_types) = media_types_requiring_user_action {
+        let _: id = msg_send![config, setMediaTypesRequiringUserActionForPlayback: media_types];
+      }
+
+      // A custom user-agent replaces the application name, so the suffix is appended to it below.
+      if let (None, Some(suffix)) = (&attributes.user_agent, &attributes.user_agent_suffix) {
+        let _: () = msg_send![config, setApplicationNameForUserAgent: NSString::new(suffix)];
       }
 
       #[cfg(target_os = "macos")]
@@ -391,7 +750,6 @@ impl InnerWebView {
         let _: () = msg_send![scroll, setBounces: NO];
       }
 
-      #[cfg(any(debug_assertions, feature = "devtools"))]
       if attributes.devtools {
         let has_inspectable_property: BOOL =
           msg_send![webview, respondsToSelector: sel!(setInspectable:)];
@@ -399,8 +757,11 @@ impl InnerWebView {
           let _: () = msg_send![webview, setInspectable: YES];
         }
         // this cannot be on an `else` statement, it does not work on macOS :(
-        let dev = NSString::new("developerExtrasEnabled");
-        let _: id = msg_send![_preference, setValue:_yes forKey:dev];
+        #[cfg(any(debug_assertions, feature = "devtools"))]
+        {
+          let dev = NSString::new("developerExtrasEnabled");
+          let _: id = msg_send![_preference, setValue:_yes forKey:dev];
+        }
       }
 
       // allowsBackForwardNavigation
@@ -410,6 +771,24 @@ impl InnerWebView {
         let _: () = msg_send![webview, setAllowsBackForwardNavigationGestures: value];
       }
 
+      // allowsLinkPreview
+      let value = if attributes.allows_link_preview {
+        YES
+      } else {
+        NO
+      };
+      let _: () = msg_send![webview, setAllowsLinkPreview: value];
+
+      #[cfg(target_os = "ios")]
+      if !attributes.elastic_overscroll {
+        let scroll_view: id = msg_send![webview, scrollView];
+        let () = msg_send![scroll_view, setBounces: NO];
+      }
+
+      if let Some(color) = attributes.under_page_background_color {
+        set_under_page_background_color(webview, color);
+      }
+
       // Message handler
       let ipc_handler_ptr = if let Some(ipc_handler) = attributes.ipc_handler {
         let cls = ClassDecl::new("WebViewDelegate", class!(NSObject));
@@ -430,6 +809,9 @@ impl InnerWebView {
         (*handler).set_ivar("function", ipc_handler_ptr as *mut _ as *mut c_void);
         let ipc = NSString::new(IPC_MESSAGE_HANDLER_NAME);
         let _: () = msg_send![manager, addScriptMessageHandler:handler name:ipc];
+        for world in &isolated_worlds {
+          let _: () = msg_send![manager, addScriptMessageHandler:handler contentWorld:content_world(world) name:ipc];
+        }
         ipc_handler_ptr
       } else {
         null_mut()
@@ -489,6 +871,58 @@ impl InnerWebView {
         null_mut()
       };
 
+      // Audio state changed handler
+      let (audio_state_handler, audio_state_observer) = if let Some(audio_state_handler) =
+        attributes.audio_state_handler
+      {
+        let cls = ClassDecl::new("AudioStateChangedDelegate", class!(NSObject));
+        let cls = match cls {
+          Some(mut cls) => {
+            cls.add_ivar::<*mut c_void>("function");
+            cls.add_method(
+              sel!(observeValueForKeyPath:ofObject:change:context:),
+              observe_value_for_key_path as extern "C" fn(&Object, Sel, id, id, id, id),
+            );
+            extern "C" fn observe_value_for_key_path(
+              this: &Object,
+              _sel: Sel,
+              key_path: id,
+              of_object: id,
+              _change: id,
+              _context: id,
+            ) {
+              let key = NSString(key_path);
+              if key.to_str() == "_isPlayingAudio" {
+                unsafe {
+                  let function = this.get_ivar::<*mut c_void>("function");
+                  if !function.is_null() {
+                    let function = &mut *(*function as *mut Box<dyn Fn(bool)>);
+                    let is_playing: BOOL = msg_send![of_object, _isPlayingAudio];
+                    (function)(is_playing == YES);
+                  }
+                }
+              }
+            }
+            cls.register()
+          }
+          None => class!(AudioStateChangedDelegate),
+        };
+
+        let observer: id = msg_send![cls, new];
+        let audio_state_handler = Box::into_raw(Box::new(audio_state_handler));
+        (*observer).set_ivar("function", audio_state_handler as *mut _ as *mut c_void);
+
+        // `_isPlayingAudio` is a private KVO compliant property of WKWebView.
+        let can_observe: BOOL = msg_send![webview, respondsToSelector: sel!(_isPlayingAudio)];
+        if can_observe == YES {
+          let _: () = msg_send![webview, addObserver:observer forKeyPath:NSString::new("_isPlayingAudio") options:0x01 context:nil];
+        }
+
+        (audio_state_handler, observer)
+      } else {
+        (null_mut(), nil)
+      };
+
       // Navigation handler
       extern "C" fn navigation_policy(this: &Object, _: Sel, _: id, action: id, handler: id) {
         unsafe {
@@ -548,6 +982,17 @@ impl InnerWebView {
           let handler = handler as *mut block::Block<(NSInteger,), c_void>;
           let can_show_mime_type: bool = msg_send![response, canShowMIMEType];
 
+          // WKNavigationResponsePolicyDownload is only available on macOS 11.3+
+          let downloads_pdf = *this.get_ivar::<BOOL>("DownloadsPdf") == YES;
+          if downloads_pdf && can_show_mime_type {
+            let url_response: id = msg_send![response, response];
+            let mime_type: id = msg_send![url_response, MIMEType];
+            if !mime_type.is_null() && NSString(mime_type).to_str() == "application/pdf" {
+              (*handler).call((2,));
+              return;
+            }
+          }
+
           if !can_show_mime_type {
             let has_download_handler = this.get_ivar::<*mut c_void>("HasDownloadHandler");
             if !has_download_handler.is_null() {
@@ -570,6 +1015,7 @@ impl InnerWebView {
         Some(mut cls) => {
           cls.add_ivar::<*mut c_void>("pending_scripts");
           cls.add_ivar::<*mut c_void>("HasDownloadHandler");
+          cls.add_ivar::<BOOL>("DownloadsPdf");
           cls.add_method(
             sel!(webView:decidePolicyForNavigationAction:decisionHandler:),
             navigation_policy as extern "C" fn(&Object, Sel, id, id, id),
@@ -586,96 +1032,91 @@ impl InnerWebView {
       };
 
       let navigation_policy_handler: id = msg_send![navigation_delegate_cls, new];
+      (*navigation_policy_handler)
+        .set_ivar::<BOOL>("DownloadsPdf", if attributes.pdf_viewer { NO } else { YES });
 
       (*navigation_policy_handler).set_ivar(
         "pending_scripts",
         Box::into_raw(Box::new(pending_scripts.clone())) as *mut c_void,
       );
 
-      let (navigation_decide_policy_ptr, download_delegate) = if attributes
-        .navigation_handler
-        .is_some()
-        || attributes.new_window_req_handler.is_some()
-        || attributes.download_started_handler.is_some()
-      {
-        let function_ptr = {
-          let navigation_handler = attributes.navigation_handler;
-          let new_window_req_handler = attributes.new_window_req_handler;
-          Box::into_raw(Box::new(
-            Box::new(move |url: String, is_main_frame: bool| -> bool {
-              if is_main_frame {
-                navigation_handler
-                  .as_ref()
-                  .map_or(true, |navigation_handler| (navigation_handler)(url))
-              } else {
-                new_window_req_handler
-                  .as_ref()
-                  .map_or(true, |new_window_req_handler| (new_window_req_handler)(url))
-              }
-            }) as Box<dyn Fn(String, bool) -> bool>,
-          ))
-        };
-        (*navigation_policy_handler).set_ivar(
-          "navigation_policy_function",
-          function_ptr as *mut _ as *mut c_void,
-        );
+      let (navigation_decide_policy_ptr, download_delegate) =
+        if attributes.navigation_handler.is_some() || attributes.download_started_handler.is_some()
+        {
+          let function_ptr = {
+            let navigation_handler = attributes.navigation_handler;
+            // New windows are decided when the UI delegate creates their webview.
+            Box::into_raw(Box::new(
+              Box::new(move |url: String, is_main_frame: bool| -> bool {
+                match &navigation_handler {
+                  Some(navigation_handler) if is_main_frame => (navigation_handler)(url),
+                  _ => true,
+                }
+              }) as Box<dyn Fn(String, bool) -> bool>,
+            ))
+          };
+          (*navigation_policy_handler).set_ivar(
+            "navigation_policy_function",
+            function_ptr as *mut _ as *mut c_void,
+          );
 
-        let has_download_handler = Box::into_raw(Box::new(Box::new(
-          attributes.download_started_handler.is_some(),
-        )));
-        (*navigation_policy_handler).set_ivar(
-          "HasDownloadHandler",
-          has_download_handler as *mut _ as *mut c_void,
-        );
+          let has_download_handler = Box::into_raw(Box::new(Box::new(
+            attributes.download_started_handler.is_some(),
+          )));
+          (*navigation_policy_handler).set_ivar(
+            "HasDownloadHandler",
+            has_download_handler as *mut _ as *mut c_void,
+          );
 
-        // Download handler
-        let download_delegate = if attributes.download_started_handler.is_some()
-          || attributes.download_completed_handler.is_some()
-        {
-          let cls = match ClassDecl::new("WryDownloadDelegate", class!(NSObject)) {
-            Some(mut cls) => {
-              cls.add_ivar::<*mut c_void>("started");
-              cls.add_ivar::<*mut c_void>("completed");
-              cls.add_method(
+          // Download handler
+          let download_delegate = if attributes.download_started_handler.is_some()
+            || attributes.download_completed_handler.is_some()
+          {
+            let cls = match ClassDecl::new("WryDownloadDelegate", class!(NSObject)) {
+              Some(mut cls) => {
+                cls.add_ivar::<*mut c_void>("started");
+                cls.add_ivar::<*mut c_void>("completed");
+                cls.add_method(
                 sel!(download:decideDestinationUsingResponse:suggestedFilename:completionHandler:),
                 download_policy as extern "C" fn(&Object, Sel, id, id, id, id),
               );
-              cls.add_method(
-                sel!(downloadDidFinish:),
-                download_did_finish as extern "C" fn(&Object, Sel, id),
-              );
-              cls.add_method(
-                sel!(download:didFailWithError:resumeData:),
-                download_did_fail as extern "C" fn(&Object, Sel, id, id, id),
-              );
-              cls.register()
+                cls.add_method(
+                  sel!(downloadDidFinish:),
+                  download_did_finish as extern "C" fn(&Object, Sel, id),
+                );
+                cls.add_method(
+                  sel!(download:didFailWithError:resumeData:),
+                  download_did_fail as extern "C" fn(&Object, Sel, id, id, id),
+                );
+                cls.register()
+              }
+              None => class!(WryDownloadDelegate),
+            };
+
+            let download_delegate: id = msg_send![cls, new];
+            if let Some(download_started_handler) = attributes.download_started_handler {
+              let download_started_ptr = Box::into_raw(Box::new(download_started_handler));
+              (*download_delegate)
+                .set_ivar("started", download_started_ptr as *mut _ as *mut c_void);
+            }
+            if let Some(download_completed_handler) = attributes.download_completed_handler {
+              let download_completed_ptr = Box::into_raw(Box::new(download_completed_handler));
+              (*download_delegate)
+                .set_ivar("completed", download_completed_ptr as *mut _ as *mut c_void);
             }
-            None => class!(WryDownloadDelegate),
-          };
 
-          let download_delegate: id = msg_send![cls, new];
-          if let Some(download_started_handler) = attributes.download_started_handler {
-            let download_started_ptr = Box::into_raw(Box::new(download_started_handler));
-            (*download_delegate).set_ivar("started", download_started_ptr as *mut _ as *mut c_void);
-          }
-          if let Some(download_completed_handler) = attributes.download_completed_handler {
-            let download_completed_ptr = Box::into_raw(Box::new(download_completed_handler));
-            (*download_delegate)
-              .set_ivar("completed", download_completed_ptr as *mut _ as *mut c_void);
-          }
+            set_download_delegate(navigation_policy_handler, download_delegate);
 
-          set_download_delegate(navigation_policy_handler, download_delegate);
+            navigation_policy_handler
+          } else {
+            null_mut()
+          };
 
-          navigation_policy_handler
+          (function_ptr, download_delegate)
         } else {
-          null_mut()
+          (null_mut(), null_mut())
         };
 
-        (function_ptr, download_delegate)
-      } else {
-        (null_mut(), null_mut())
-      };
-
       let page_load_handler = set_navigation_methods(
         navigation_policy_handler,
         webview,
@@ -728,8 +1169,68 @@ impl InnerWebView {
         }
       }
 
+      // New window handler
+      extern "C" fn create_webview(
+        this: &Object,
+        _: Sel,
+        webview: id,
+        configuration: id,
+        action: id,
+        _features: id,
+      ) -> id {
+        unsafe {
+          let function = this.get_ivar::<*mut c_void>("new_window_function");
+          if function.is_null() {
+            return nil;
+          }
+          let function = &*(*function as *mut Box<dyn Fn(NewWindowRequest) -> NewWindowResponse>);
+          let request: id = msg_send![action, request];
+          let url: id = msg_send![request, URL];
+          let url: id = msg_send![url, absoluteString];
+
+          let _: id = msg_send![configuration, retain];
+          let opener = NewWindowOpener(Rc::new(PlatformOpener {
+            configuration,
+            child: RefCell::new(None),
+          }));
+          let response = function(NewWindowRequest {
+            url: NSString(url).to_str().to_string(),
+            opener: opener.clone(),
+          });
+          match response {
+            NewWindowResponse::Allow | NewWindowResponse::Deny => nil,
+            NewWindowResponse::Navigate => {
+              let _: id = msg_send![webview, loadRequest: request];
+              nil
+            }
+            NewWindowResponse::Create => opener.0.child.take().unwrap_or(nil),
+          }
+        }
+      }
+
+      // Close requested handler
+      extern "C" fn webview_did_close(this: &Object, _: Sel, _webview: id) {
+        unsafe {
+          let function = this.get_ivar::<*mut c_void>("close_function");
+          if !function.is_null() {
+            let function = &*(*function as *mut (Box<dyn Fn(&Window)>, Rc<Window>));
+            (function.0)(&function.1);
+          }
+        }
+      }
+
       let ui_delegate = match ClassDecl::new("WebViewUIDelegate", class!(NSObject)) {
         Some(mut ctl) => {
+          ctl.add_ivar::<*mut c_void>("new_window_function");
+          ctl.add_ivar::<*mut c_void>("close_function");
+          ctl.add_method(
+            sel!(webViewDidClose:),
+            webview_did_close as extern "C" fn(&Object, Sel, id),
+          );
+          ctl.add_method(
+            sel!(webView:createWebViewWithConfiguration:forNavigationAction:windowFeatures:),
+            create_webview as extern "C" fn(&Object, Sel, id, id, id, id) -> id,
+          );
           ctl.add_method(
             sel!(webView:runOpenPanelWithParameters:initiatedByFrame:completionHandler:),
             run_file_upload_panel as extern "C" fn(&Object, Sel, id, id, id, id),
@@ -746,6 +1247,22 @@ impl InnerWebView {
         None => class!(WebViewUIDelegate),
       };
       let ui_delegate: id = msg_send![ui_delegate, new];
+      let new_window_handler_ptr = match attributes.new_window_handler {
+        Some(handler) => {
+          let handler = Box::into_raw(Box::new(handler));
+          (*ui_delegate).set_ivar("new_window_function", handler as *mut _ as *mut c_void);
+          handler
+        }
+        None => null_mut(),
+      };
+      let close_requested_handler_ptr = match attributes.close_requested_handler {
+        Some(handler) => {
+          let handler = Box::into_raw(Box::new((handler, window.clone())));
+          (*ui_delegate).set_ivar("close_function", handler as *mut _ as *mut c_void);
+          handler
+        }
+        None => null_mut(),
+      };
       let _: () = msg_send![webview, setUIDelegate: ui_delegate];
 
       // File drop handling
@@ -784,47 +1301,88 @@ impl InnerWebView {
         pending_scripts,
         ipc_handler_ptr,
         document_title_changed_handler,
+        audio_state_handler,
+        audio_state_observer,
         navigation_decide_policy_ptr,
         #[cfg(target_os = "macos")]
         file_drop_ptr,
         page_load_handler,
+        new_window_handler_ptr,
+        close_requested_handler_ptr,
+        #[cfg(target_os = "macos")]
+        focus_changed_handler_ptr,
+        #[cfg(target_os = "macos")]
+        key_event_handler_ptr,
+        #[cfg(target_os = "macos")]
+        click_through_regions,
         download_delegate,
         protocol_ptrs,
+        isolated_worlds,
+        user_scripts: RefCell::default(),
+        auto_resize: Cell::new(attributes.auto_resize),
+        discarded: Cell::new(false),
+        media_paused: Cell::new(false),
+        _process_pool: process_pool,
       };
 
       // Initialize scripts
-      w.init(
-r#"Object.defineProperty(window, 'ipc', {
-  value: Object.freeze({postMessage: function(s) {window.webkit.messageHandlers.ipc.postMessage(s);}})
-});"#,
-      );
+      let ipc_script = r#"Object.defineProperty(window, 'ipc', {
+  value: Object.freeze({postMessage: window.webkit.messageHandlers.ipc.postMessage.bind(window.webkit.messageHandlers.ipc)})
+});"#;
+      w.init(ipc_script);
+      for world in &w.isolated_worlds {
+        w.init_in_world(world, ipc_script);
+      }
       for js in attributes.initialization_scripts {
         w.init(&js);
       }
+      for script in &attributes.init_scripts {
+        w.init_script(script);
+      }
+      for (world, js) in &attributes.isolated_initialization_scripts {
+        w.init_in_world(world, js);
+      }
+      if let Some(overrides) = attributes.media_feature_overrides {
+        w.init(&overrides.to_script());
+      }
 
       // Set user agent
       if let Some(user_agent) = attributes.user_agent {
-        w.set_user_agent(user_agent.as_str())
+        match attributes.user_agent_suffix {
+          Some(suffix) => w.set_user_agent(&format!("{user_agent} {suffix}")),
+          None => w.set_user_agent(user_agent.as_str()),
+        }
       }
 
-      // Navigation
-      if let Some(url) = attributes.url {
+      // Navigation, WebKit loads the requested page in the webview of a new window.
+      if let Some(opener) = &opener {
+        opener.0.child.replace(Some(webview));
+      } else if let Some(url) = attributes.url {
         if url.cannot_be_a_base() {
           let s = url.as_str();
           if let Some(pos) = s.find(',') {
             let (_, path) = s.split_at(pos + 1);
-            w.navigate_to_string(path);
+            w.navigate_to_string(path, None);
           }
         } else {
-          w.navigate_to_url(url.as_str(), attributes.headers);
+          w.navigate_to_url(url.as_str(), attributes.headers, None);
         }
       } else if let Some(html) = attributes.html {
-        w.navigate_to_string(&html);
+        w.navigate_to_string(&html, attributes.html_origin.as_ref().map(Url::as_str));
       }
 
       // Inject the web view into the window as main content
       #[cfg(target_os = "macos")]
-      {
+      if child {
+        // Add the webview above the other webviews of the window.
+        let content_view: id = msg_send![window.ns_window() as id, contentView];
+        let bounds: CGRect = msg_send![content_view, bounds];
+        let () = msg_send![webview, setFrame: bounds];
+        let flipped: BOOL = msg_send![content_view, isFlipped];
+        let mask = autoresizing_mask(attributes.auto_resize, flipped == YES);
+        let () = msg_send![webview, setAutoresizingMask: mask];
+        let _: () = msg_send![content_view, addSubview: webview];
+      } else {
         let parent_view_cls = match ClassDecl::new("WryWebViewParent", class!(NSView)) {
           Some(mut decl) => {
             decl.add_method(
@@ -855,7 +1413,9 @@ r#"Object.defineProperty(window, 'ipc', {
         // Tell the webview receive keyboard events in the window.
         // See https://github.com/tauri-apps/wry/issues/739
         let _: () = msg_send![ns_window, setContentView: parent_view];
-        let _: () = msg_send![ns_window, makeFirstResponder: webview];
+        if attributes.focused {
+          let _: () = msg_send![ns_window, makeFirstResponder: webview];
+        }
 
         // make sure the window is always on top when we create a new webview
         let app_class = class!(NSApplication);
@@ -866,13 +1426,52 @@ r#"Object.defineProperty(window, 'ipc', {
       #[cfg(target_os = "ios")]
       {
         let ui_view = window.ui_view() as id;
+        if child {
+          // Like on macOS, fill the view until the webview is placed with its bounds.
+          let bounds: CGRect = msg_send![ui_view, bounds];
+          let () = msg_send![webview, setFrame: bounds];
+          let mask = autoresizing_mask(attributes.auto_resize, true);
+          let () = msg_send![webview, setAutoresizingMask: mask];
+        }
         let _: () = msg_send![ui_view, addSubview: webview];
       }
 
+      if let (true, Some((position, size))) = (child, attributes.bounds) {
+        w.set_bounds(position, size, window.scale_factor())?;
+      }
+
       Ok(w)
     }
   }
 
+  pub fn execute_edit_command(&self, command: EditCommand) -> Result<()> {
+    unsafe {
+      match command {
+        EditCommand::Copy => {
+          let () = msg_send![self.webview, copy: nil];
+        }
+        EditCommand::Cut => {
+          let () = msg_send![self.webview, cut: nil];
+        }
+        EditCommand::Paste => {
+          let () = msg_send![self.webview, paste: nil];
+        }
+        EditCommand::SelectAll => {
+          let () = msg_send![self.webview, selectAll: nil];
+        }
+        EditCommand::Undo | EditCommand::Redo => {
+          let undo_manager: id = msg_send![self.webview, undoManager];
+          if command == EditCommand::Undo {
+            let () = msg_send![undo_manager, undo];
+          } else {
+            let () = msg_send![undo_manager, redo];
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+
   pub fn url(&self) -> Url {
     Url::parse(&url_from_webview(self.webview)).unwrap()
   }
@@ -911,30 +1510,342 @@ r#"Object.defineProperty(window, 'ipc', {
     Ok(())
   }
 
-  fn init(&self, js: &str) {
+  fn init(&self, js: &str) -> id {
+    self.init_script(&InitScript::new(js))
+  }
+
+  fn init_script(&self, script: &InitScript) -> id {
+    let injection_time: NSInteger = match script.injection_time {
+      InjectionTime::DocumentStart => 0,
+      InjectionTime::DocumentEnd => 1,
+    };
+    let main_frame_only = if script.for_main_frame_only { YES } else { NO };
     // Safety: objc runtime calls are unsafe
     // Equivalent Obj-C:
     // [manager addUserScript:[[WKUserScript alloc] initWithSource:[NSString stringWithUTF8String:js.c_str()] injectionTime:WKUserScriptInjectionTimeAtDocumentStart forMainFrameOnly:YES]]
     unsafe {
       let userscript: id = msg_send![class!(WKUserScript), alloc];
-      let script: id =
-      // FIXME: We allow subframe injection because webview2 does and cannot be disabled (currently).
-      // once webview2 allows disabling all-frame script injection, forMainFrameOnly should be enabled
-      // if it does not break anything. (originally added for isolation pattern).
-        msg_send![userscript, initWithSource:NSString::new(js) injectionTime:0 forMainFrameOnly:0];
-      let _: () = msg_send![self.manager, addUserScript: script];
+      let user_script: id =
+      // We allow subframe injection by default because webview2 does and cannot be disabled,
+      // `InitScript::for_main_frame_only` opts out of it (originally added for isolation pattern).
+        msg_send![userscript, initWithSource:NSString::new(&script.source()) injectionTime:injection_time forMainFrameOnly:main_frame_only];
+      let _: () = msg_send![self.manager, addUserScript: user_script];
+      user_script
     }
   }
 
-  pub fn load_url(&self, url: &str) {
-    self.navigate_to_url(url, None)
+  pub fn add_init_script(&self, script: &InitScript) -> Result<ScriptId> {
+    let id = ScriptId::next();
+    let script = self.init_script(script);
+    self.user_scripts.borrow_mut().insert(id, script);
+    Ok(id)
   }
 
-  pub fn load_url_with_headers(&self, url: &str, headers: http::HeaderMap) {
-    self.navigate_to_url(url, Some(headers))
+  pub fn remove_init_script(&self, id: ScriptId) -> Result<()> {
+    let Some(removed) = self.user_scripts.borrow_mut().remove(&id) else {
+      return Ok(());
+    };
+    // WKUserContentController can only remove all the scripts, add back the other ones.
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+      let scripts: id = msg_send![self.manager, userScripts];
+      let scripts: id = msg_send![scripts, retain];
+      let _: () = msg_send![self.manager, removeAllUserScripts];
+      let count: usize = msg_send![scripts, count];
+      for i in 0..count {
+        let script: id = msg_send![scripts, objectAtIndex: i];
+        if script != removed {
+          let _: () = msg_send![self.manager, addUserScript: script];
+        }
+      }
+      let _: () = msg_send![scripts, release];
+      let _: () = msg_send![removed, release];
+    }
+    Ok(())
   }
 
-  pub fn clear_all_browsing_data(&self) -> Result<()> {
+  fn init_in_world(&self, world: &str, js: &str) {
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+      let userscript: id = msg_send![class!(WKUserScript), alloc];
+      let script: id = msg_send![userscript, initWithSource:NSString::new(js) injectionTime:0 forMainFrameOnly:0 inContentWorld:content_world(world)];
+      let _: () = msg_send![self.manager, addUserScript: script];
+    }
+  }
+
+  pub fn eval_in_world(&self, world: &str, js: &str) -> Result<()> {
+    if Class::get("WKContentWorld").is_none() {
+      return Err(crate::Error::Unsupported);
+    }
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+      let _: () = msg_send![self.webview, evaluateJavaScript:NSString::new(js) inFrame:nil inContentWorld:content_world(world) completionHandler:null::<*const c_void>()];
+    }
+    Ok(())
+  }
+
+  pub fn eval_in_world_with_callback(
+    &self,
+    world: &str,
+    js: &str,
+    callback: impl FnOnce(String) + 'static,
+  ) -> Result<()> {
+    if Class::get("WKContentWorld").is_none() {
+      return Err(crate::Error::Unsupported);
+    }
+    // Blocks can be called more than once as far as Rust knows
+    let callback = RefCell::new(Some(callback));
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+      let handler = block::ConcreteBlock::new(move |val: id, _err: id| {
+        let mut result = String::new();
+        if val != nil {
+          let serializer = class!(NSJSONSerialization);
+          let json_ns_data: NSData = msg_send![serializer, dataWithJSONObject:val options:NS_JSON_WRITING_FRAGMENTS_ALLOWED error:nil];
+          result = NSString::from(json_ns_data).to_str().to_string();
+        }
+        if let Some(callback) = callback.borrow_mut().take() {
+          callback(result);
+        }
+      })
+      .copy();
+      let _: () = msg_send![self.webview, evaluateJavaScript:NSString::new(js) inFrame:nil inContentWorld:content_world(world) completionHandler:&*handler];
+    }
+    Ok(())
+  }
+
+  #[cfg(target_os = "macos")]
+  pub fn capture_element(
+    &self,
+    selector: &str,
+    format: SnapshotFormat,
+    handler: impl FnOnce(Result<Vec<u8>>) + 'static,
+  ) -> Result<()> {
+    use core_graphics::geometry::{CGPoint, CGSize};
+    use std::cell::RefCell;
+
+    // Blocks can be called more than once as far as Rust knows
+    let handler = Rc::new(RefCell::new(Some(handler)));
+    let respond = move |result: Result<Vec<u8>>| {
+      if let Some(handler) = handler.borrow_mut().take() {
+        handler(result);
+      }
+    };
+
+    unsafe {
+      let webview = self.webview;
+      // Keep the webview alive until the snapshot is taken
+      let _: id = msg_send![webview, retain];
+      let selector = selector.to_string();
+      let script = NSString::new(&element_rect_script(&selector));
+
+      let rect_handler = block::ConcreteBlock::new(move |val: id, _err: id| {
+        let mut json = String::new();
+        if val != nil {
+          let serializer = class!(NSJSONSerialization);
+          let json_ns_data: NSData = msg_send![serializer, dataWithJSONObject:val options:NS_JSON_WRITING_FRAGMENTS_ALLOWED error:nil];
+          json = NSString::from(json_ns_data).to_str().to_string();
+        }
+        let rect = match parse_element_rect(&selector, &json) {
+          Ok(rect) => rect,
+          Err(e) => {
+            let _: () = msg_send![webview, release];
+            return respond(Err(e));
+          }
+        };
+
+        let config: id = msg_send![class!(WKSnapshotConfiguration), new];
+        let rect = CGRect::new(
+          &CGPoint::new(rect.x, rect.y),
+          &CGSize::new(rect.width, rect.height),
+        );
+        let _: () = msg_send![config, setRect: rect];
+
+        let respond = respond.clone();
+        let snapshot_handler = block::ConcreteBlock::new(move |image: id, _err: id| {
+          let _: () = msg_send![webview, release];
+          if image == nil {
+            return respond(Err(crate::Error::SnapshotFailed));
+          }
+          // https://developer.apple.com/documentation/appkit/nsbitmapimagerep/filetype
+          let file_type: usize = match format {
+            SnapshotFormat::Png => 4,
+            SnapshotFormat::Jpeg => 3,
+          };
+          let tiff: id = msg_send![image, TIFFRepresentation];
+          let rep: id = msg_send![class!(NSBitmapImageRep), imageRepWithData: tiff];
+          let properties: id = msg_send![class!(NSDictionary), dictionary];
+          let data: id = msg_send![rep, representationUsingType:file_type properties:properties];
+          if data == nil {
+            return respond(Err(crate::Error::SnapshotFailed));
+          }
+          let bytes: *const u8 = msg_send![data, bytes];
+          let length: usize = msg_send![data, length];
+          respond(Ok(slice::from_raw_parts(bytes, length).to_vec()));
+        });
+        let _: () = msg_send![webview, takeSnapshotWithConfiguration:config completionHandler:snapshot_handler];
+        let _: () = msg_send![config, release];
+      });
+
+      let _: () = msg_send![webview, evaluateJavaScript:script completionHandler:rect_handler];
+    }
+    Ok(())
+  }
+
+  #[cfg(target_os = "ios")]
+  pub fn capture_element(
+    &self,
+    _selector: &str,
+    _format: SnapshotFormat,
+    _handler: impl FnOnce(Result<Vec<u8>>) + 'static,
+  ) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn pause_all_media(&self) -> Result<()> {
+    self.set_all_media_playback_suspended(true)?;
+    self.media_paused.set(true);
+    Ok(())
+  }
+
+  pub fn resume_media(&self) -> Result<()> {
+    self.set_all_media_playback_suspended(false)?;
+    self.media_paused.set(false);
+    Ok(())
+  }
+
+  fn set_all_media_playback_suspended(&self, suspended: bool) -> Result<()> {
+    unsafe {
+      // setAllMediaPlaybackSuspended is only available on macOS 12+ / iOS 15+
+      let can_suspend: BOOL = msg_send![
+        self.webview,
+        respondsToSelector: sel!(setAllMediaPlaybackSuspended:completionHandler:)
+      ];
+      if can_suspend == NO {
+        return Err(crate::Error::Unsupported);
+      }
+      let suspended = if suspended { YES } else { NO };
+      let _: () =
+        msg_send![self.webview, setAllMediaPlaybackSuspended:suspended completionHandler:nil];
+    }
+    Ok(())
+  }
+
+  pub fn set_muted(&self, muted: bool) {
+    unsafe {
+      // `_setPageMuted:` takes a `_WKMediaMutedState` bitmask, `1` being `_WKMediaAudioMuted`.
+      let can_mute: BOOL = msg_send![self.webview, respondsToSelector: sel!(_setPageMuted:)];
+      if can_mute == YES {
+        let state: usize = if muted { 1 } else { 0 };
+        let () = msg_send![self.webview, _setPageMuted: state];
+      }
+    }
+  }
+
+  #[cfg(target_os = "macos")]
+  pub fn process_resident_size(&self) -> Option<u64> {
+    unsafe {
+      // `_webProcessIdentifier` is a private property of WKWebView.
+      let can_query: BOOL =
+        msg_send![self.webview, respondsToSelector: sel!(_webProcessIdentifier)];
+      if can_query == YES {
+        let pid: i32 = msg_send![self.webview, _webProcessIdentifier];
+        resident_size(pid)
+      } else {
+        None
+      }
+    }
+  }
+
+  #[cfg(target_os = "ios")]
+  pub fn process_resident_size(&self) -> Option<u64> {
+    None
+  }
+
+  pub fn suspend(&self) -> Result<()> {
+    // WebKit has no API to stop the timers, it throttles them while the webview is hidden.
+    self.set_all_media_playback_suspended(true)
+  }
+
+  pub fn resume(&self) -> Result<()> {
+    if self.media_paused.get() {
+      return Ok(());
+    }
+    self.set_all_media_playback_suspended(false)
+  }
+
+  pub fn discard(&self) -> Result<()> {
+    unsafe {
+      // `_killWebContentProcess` is a private method of WKWebView.
+      let can_kill: BOOL =
+        msg_send![self.webview, respondsToSelector: sel!(_killWebContentProcess)];
+      if can_kill == NO {
+        return Err(crate::Error::Unsupported);
+      }
+      let () = msg_send![self.webview, _killWebContentProcess];
+    }
+    self.discarded.set(true);
+    Ok(())
+  }
+
+  pub fn restore(&self) -> Result<()> {
+    // The back-forward list is kept by the UI process, reloading starts a new web process.
+    if self.discarded.replace(false) {
+      unsafe {
+        let _: id = msg_send![self.webview, reload];
+      }
+    }
+    Ok(())
+  }
+
+  pub fn is_muted(&self) -> bool {
+    unsafe {
+      let can_query: BOOL = msg_send![self.webview, respondsToSelector: sel!(_mediaMutedState)];
+      if can_query == YES {
+        let state: usize = msg_send![self.webview, _mediaMutedState];
+        state & 1 != 0
+      } else {
+        false
+      }
+    }
+  }
+
+  pub fn set_media_feature_overrides(&self, overrides: MediaFeatureOverrides) -> Result<()> {
+    self.eval(
+      &overrides.to_script(),
+      None::<Box<dyn Fn(String) + Send + 'static>>,
+    )
+  }
+
+  pub fn load_url(&self, url: &str) {
+    self.navigate_to_url(url, None, None)
+  }
+
+  pub fn load_url_with_headers(&self, url: &str, headers: http::HeaderMap) {
+    self.navigate_to_url(url, Some(headers), None)
+  }
+
+  pub fn load_archive(&self, archive: WebArchive) -> Result<()> {
+    let bytes = archive.into_bytes()?;
+    unsafe {
+      let data: id = msg_send![class!(NSData), dataWithBytes:bytes.as_ptr() length:bytes.len()];
+      let base_url: id = msg_send![class!(NSURL), URLWithString: NSString::new("about:blank")];
+      let _: id = msg_send![self.webview, loadData:data MIMEType:NSString::new("application/x-webarchive") characterEncodingName:NSString::new("utf-8") baseURL:base_url];
+    }
+    Ok(())
+  }
+
+  pub fn load_url_with_cache_policy(&self, url: &str, policy: CachePolicy) {
+    self.navigate_to_url(url, Some(policy.headers()), Some(policy))
+  }
+
+  pub fn reload_ignoring_cache(&self) {
+    unsafe {
+      let _: id = msg_send![self.webview, reloadFromOrigin];
+    }
+  }
+
+  pub fn clear_all_browsing_data(&self) -> Result<()> {
     unsafe {
       let config: id = msg_send![self.webview, configuration];
       let store: id = msg_send![config, websiteDataStore];
@@ -946,11 +1857,99 @@ r#"Object.defineProperty(window, 'ipc', {
     Ok(())
   }
 
-  fn navigate_to_url(&self, url: &str, headers: Option<http::HeaderMap>) {
+  pub fn fetch_service_worker_registrations(
+    &self,
+    handler: impl FnOnce(Result<Vec<String>>) + 'static,
+  ) -> Result<()> {
+    let handler = std::cell::Cell::new(Some(handler));
+    unsafe {
+      let config: id = msg_send![self.webview, configuration];
+      let store: id = msg_send![config, websiteDataStore];
+      let completion = block::ConcreteBlock::new(move |records: id| {
+        let domains = service_worker_records(records, None)
+          .into_iter()
+          .map(|record| {
+            let name: id = msg_send![record, displayName];
+            NSString(name).to_str().to_string()
+          })
+          .collect();
+        if let Some(handler) = handler.take() {
+          handler(Ok(domains));
+        }
+      })
+      .copy();
+      let _: () = msg_send![store, fetchDataRecordsOfTypes:service_worker_data_types() completionHandler:&*completion];
+    }
+    Ok(())
+  }
+
+  pub fn unregister_service_workers(&self, domain: Option<&str>) -> Result<()> {
+    unsafe {
+      let config: id = msg_send![self.webview, configuration];
+      let store: id = msg_send![config, websiteDataStore];
+      match domain {
+        None => {
+          let date: id = msg_send![class!(NSDate), dateWithTimeIntervalSince1970: 0.0];
+          let handler = block::ConcreteBlock::new(|| {}).copy();
+          let _: () = msg_send![store, removeDataOfTypes:service_worker_data_types() modifiedSince:date completionHandler:&*handler];
+        }
+        Some(domain) => {
+          let domain = domain.to_string();
+          // Keep the store alive until the records are fetched
+          let _: id = msg_send![store, retain];
+          let completion = block::ConcreteBlock::new(move |records: id| {
+            let records = service_worker_records(records, Some(&domain));
+            if !records.is_empty() {
+              let array: id = msg_send![class!(NSArray), arrayWithObjects:records.as_ptr() count:records.len()];
+              let handler = block::ConcreteBlock::new(|| {}).copy();
+              let _: () = msg_send![store, removeDataOfTypes:service_worker_data_types() forDataRecords:array completionHandler:&*handler];
+            }
+            let _: () = msg_send![store, release];
+          })
+          .copy();
+          let _: () = msg_send![store, fetchDataRecordsOfTypes:service_worker_data_types() completionHandler:&*completion];
+        }
+      }
+    }
+    Ok(())
+  }
+
+  pub fn call_devtools_protocol_method(
+    &self,
+    _method: &str,
+    _params: &str,
+    _handler: impl FnOnce(Result<String>) + 'static,
+  ) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn subscribe_devtools_protocol_event(
+    &self,
+    _event: &str,
+    _handler: impl Fn(String) + 'static,
+  ) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  fn navigate_to_url(
+    &self,
+    url: &str,
+    headers: Option<http::HeaderMap>,
+    cache_policy: Option<CachePolicy>,
+  ) {
     // Safety: objc runtime calls are unsafe
     unsafe {
       let url: id = msg_send![class!(NSURL), URLWithString: NSString::new(url)];
       let request: id = msg_send![class!(NSMutableURLRequest), requestWithURL: url];
+      if let Some(cache_policy) = cache_policy {
+        // https://developer.apple.com/documentation/foundation/nsurlrequestcachepolicy
+        let cache_policy: usize = match cache_policy {
+          CachePolicy::UseCache => 2,
+          CachePolicy::Validate => 0,
+          CachePolicy::Bypass => 1,
+        };
+        let _: () = msg_send![request, setCachePolicy: cache_policy];
+      }
       if let Some(headers) = headers {
         for (name, value) in headers.iter() {
           let key = NSString::new(name.as_str());
@@ -962,10 +1961,14 @@ r#"Object.defineProperty(window, 'ipc', {
     }
   }
 
-  fn navigate_to_string(&self, html: &str) {
+  fn navigate_to_string(&self, html: &str, base_url: Option<&str>) {
     // Safety: objc runtime calls are unsafe
     unsafe {
-      let () = msg_send![self.webview, loadHTMLString:NSString::new(html) baseURL:nil];
+      let base_url: id = match base_url {
+        Some(url) => msg_send![class!(NSURL), URLWithString: NSString::new(url)],
+        None => nil,
+      };
+      let () = msg_send![self.webview, loadHTMLString:NSString::new(html) baseURL:base_url];
     }
   }
 
@@ -997,9 +2000,64 @@ r#"Object.defineProperty(window, 'ipc', {
     }
   }
 
-  #[cfg(any(debug_assertions, feature = "devtools"))]
-  pub fn open_devtools(&self) {
+  pub fn print_with_options(&self, options: PrintOptions) -> Result<()> {
     #[cfg(target_os = "macos")]
+    unsafe {
+      let print_info: id = msg_send![class!(NSPrintInfo), sharedPrintInfo];
+      let print_info: id = msg_send![print_info, copy];
+      let _: id = msg_send![print_info, autorelease];
+      if let Some(printer) = &options.printer {
+        let name = printer;
+        let printer: id = msg_send![class!(NSPrinter), printerWithName: NSString::new(name)];
+        if printer == nil {
+          return Err(crate::Error::PrinterNotFound(name.clone()));
+        }
+        let () = msg_send![print_info, setPrinter: printer];
+      }
+      let print_operation: id = msg_send![self.webview, printOperationWithPrintInfo: print_info];
+      let shows_panels = if options.silent { NO } else { YES };
+      let () = msg_send![print_operation, setShowsPrintPanel: shows_panels];
+      let () = msg_send![print_operation, setShowsProgressPanel: shows_panels];
+      let () = msg_send![print_operation, setCanSpawnSeparateThread: YES];
+      let () = msg_send![print_operation, runOperationModalForWindow: self.ns_window delegate: null::<*const c_void>() didRunSelector: null::<*const c_void>() contextInfo: null::<*const c_void>()];
+      Ok(())
+    }
+    #[cfg(target_os = "ios")]
+    {
+      let _ = options;
+      Err(crate::Error::Unsupported)
+    }
+  }
+
+  pub fn eval_with_args(&self, function: &str, args: &str) -> Result<()> {
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+      let can_call_async: BOOL = msg_send![
+        self.webview,
+        respondsToSelector: sel!(callAsyncJavaScript:arguments:inFrame:inContentWorld:completionHandler:)
+      ];
+      // The scripts queued until the page is loaded are run with `eval`.
+      if can_call_async == NO || self.pending_scripts.lock().unwrap().is_some() {
+        return self.eval(
+          &apply_arguments(function, args),
+          None::<Box<dyn Fn(String) + Send + 'static>>,
+        );
+      }
+
+      let json = format!(r#"{{"args":{}}}"#, args);
+      let data: id =
+        msg_send![class!(NSData), dataWithBytes:json.as_ptr() as *const c_void length:json.len()];
+      let arguments: id =
+        msg_send![class!(NSJSONSerialization), JSONObjectWithData:data options:0usize error:nil];
+      let body = NSString::new(&format!("return {};", apply_arguments(function, "args")));
+      let world: id = msg_send![class!(WKContentWorld), pageWorld];
+      let _: () = msg_send![self.webview, callAsyncJavaScript:body arguments:arguments inFrame:nil inContentWorld:world completionHandler:null::<*const c_void>()];
+    }
+    Ok(())
+  }
+
+  pub fn open_devtools(&self) {
+    #[cfg(all(target_os = "macos", any(debug_assertions, feature = "devtools")))]
     unsafe {
       // taken from <https://github.com/WebKit/WebKit/blob/784f93cb80a386c29186c510bba910b67ce3adc1/Source/WebKit/UIProcess/API/Cocoa/WKWebView.mm#L1939>
       let tool: id = msg_send![self.webview, _inspector];
@@ -1007,9 +2065,39 @@ r#"Object.defineProperty(window, 'ipc', {
     }
   }
 
-  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub fn open_devtools_with_placement(&self, placement: DevtoolsPlacement) {
+    self.open_devtools();
+    #[cfg(all(target_os = "macos", any(debug_assertions, feature = "devtools")))]
+    unsafe {
+      let tool: id = msg_send![self.webview, _inspector];
+      match placement {
+        DevtoolsPlacement::Detached => {
+          let _: () = msg_send![tool, detach];
+        }
+        DevtoolsPlacement::Docked => {
+          let _: () = msg_send![tool, attach];
+        }
+        DevtoolsPlacement::Default => {}
+      }
+    }
+    #[cfg(not(all(target_os = "macos", any(debug_assertions, feature = "devtools"))))]
+    let _ = placement;
+  }
+
+  pub fn devtools_window_bounds(&self) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    None
+  }
+
+  pub fn set_devtools_window_bounds(
+    &self,
+    _position: PhysicalPosition<i32>,
+    _size: PhysicalSize<u32>,
+  ) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
   pub fn close_devtools(&self) {
-    #[cfg(target_os = "macos")]
+    #[cfg(all(target_os = "macos", any(debug_assertions, feature = "devtools")))]
     unsafe {
       // taken from <https://github.com/WebKit/WebKit/blob/784f93cb80a386c29186c510bba910b67ce3adc1/Source/WebKit/UIProcess/API/Cocoa/WKWebView.mm#L1939>
       let tool: id = msg_send![self.webview, _inspector];
@@ -1017,16 +2105,15 @@ r#"Object.defineProperty(window, 'ipc', {
     }
   }
 
-  #[cfg(any(debug_assertions, feature = "devtools"))]
   pub fn is_devtools_open(&self) -> bool {
-    #[cfg(target_os = "macos")]
+    #[cfg(all(target_os = "macos", any(debug_assertions, feature = "devtools")))]
     unsafe {
       // taken from <https://github.com/WebKit/WebKit/blob/784f93cb80a386c29186c510bba910b67ce3adc1/Source/WebKit/UIProcess/API/Cocoa/WKWebView.mm#L1939>
       let tool: id = msg_send![self.webview, _inspector];
       let is_visible: objc::runtime::BOOL = msg_send![tool, isVisible];
       is_visible == objc::runtime::YES
     }
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(not(all(target_os = "macos", any(debug_assertions, feature = "devtools"))))]
     false
   }
 
@@ -1037,6 +2124,256 @@ r#"Object.defineProperty(window, 'ipc', {
     logical.to_physical(scale_factor)
   }
 
+  pub fn set_bounds(
+    &self,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    scale_factor: f64,
+  ) -> Result<()> {
+    use core_graphics::geometry::{CGPoint, CGSize};
+    let position = position.to_logical::<f64>(scale_factor);
+    let size = size.to_logical::<f64>(scale_factor);
+    unsafe {
+      let parent: id = msg_send![self.webview, superview];
+      if parent == nil {
+        return Err(crate::Error::Unsupported);
+      }
+      #[allow(unused_mut)]
+      let mut y = position.y;
+      #[cfg(target_os = "macos")]
+      let flipped: BOOL = msg_send![parent, isFlipped];
+      #[cfg(target_os = "ios")]
+      let flipped = YES;
+      // The origin of AppKit views is at their bottom left corner, unless they are flipped.
+      #[cfg(target_os = "macos")]
+      if flipped == NO {
+        let parent_bounds: CGRect = msg_send![parent, bounds];
+        y = parent_bounds.size.height - position.y - size.height;
+      }
+      let frame = CGRect::new(
+        &CGPoint::new(position.x, y),
+        &CGSize::new(size.width, size.height),
+      );
+      let policy = self.auto_resize.get().placed();
+      self.auto_resize.set(policy);
+      let () =
+        msg_send![self.webview, setAutoresizingMask: autoresizing_mask(policy, flipped == YES)];
+      let () = msg_send![self.webview, setFrame: frame];
+    }
+    Ok(())
+  }
+
+  pub fn focus(&self) -> Result<()> {
+    unsafe {
+      #[cfg(target_os = "macos")]
+      {
+        let window: id = msg_send![self.webview, window];
+        if window != nil {
+          let _: BOOL = msg_send![window, makeFirstResponder: self.webview];
+        }
+      }
+      #[cfg(target_os = "ios")]
+      let _: BOOL = msg_send![self.webview, becomeFirstResponder];
+    }
+    Ok(())
+  }
+
+  pub fn blur(&self) -> Result<()> {
+    unsafe {
+      #[cfg(target_os = "macos")]
+      {
+        let window: id = msg_send![self.webview, window];
+        if window != nil {
+          let responder: id = msg_send![window, firstResponder];
+          if responder == self.webview {
+            let _: BOOL = msg_send![window, makeFirstResponder: nil];
+          }
+        }
+      }
+      // The first responder is a subview of the webview on iOS.
+      #[cfg(target_os = "ios")]
+      let _: BOOL = msg_send![self.webview, endEditing: YES];
+    }
+    Ok(())
+  }
+
+  pub fn reparent(&self, window: &Window) -> Result<()> {
+    unsafe {
+      #[cfg(target_os = "macos")]
+      let parent: id = msg_send![window.ns_window() as id, contentView];
+      #[cfg(target_os = "ios")]
+      let parent = window.ui_view() as id;
+      // Retain the webview while it has no superview.
+      let _: id = msg_send![self.webview, retain];
+      let () = msg_send![self.webview, removeFromSuperview];
+      let _: () = msg_send![parent, addSubview: self.webview];
+      let _: () = msg_send![self.webview, release];
+      // Fill the new window, unless the webview was laid out with `set_bounds`.
+      if self.auto_resize.get() == AutoResizePolicy::FillParent {
+        let bounds: CGRect = msg_send![parent, bounds];
+        let () = msg_send![self.webview, setFrame: bounds];
+      }
+    }
+    Ok(())
+  }
+
+  pub fn set_visible(&self, visible: bool) -> Result<()> {
+    unsafe {
+      let _: () = msg_send![self.webview, setHidden: if visible { NO } else { YES }];
+    }
+    Ok(())
+  }
+
+  pub fn is_visible(&self) -> bool {
+    let hidden: BOOL = unsafe { msg_send![self.webview, isHidden] };
+    hidden == NO
+  }
+
+  pub fn bring_to_front(&self) -> Result<()> {
+    self.reorder(true)
+  }
+
+  pub fn send_to_back(&self) -> Result<()> {
+    self.reorder(false)
+  }
+
+  pub fn set_ignore_cursor_events(&self, ignore: bool) -> Result<()> {
+    unsafe {
+      #[cfg(target_os = "macos")]
+      (*self.webview).set_ivar(IGNORE_CURSOR_EVENTS, ignore);
+      #[cfg(target_os = "ios")]
+      let () = msg_send![self.webview, setUserInteractionEnabled: if ignore { NO } else { YES }];
+    }
+    Ok(())
+  }
+
+  pub fn script_evaluator(&self) -> Box<dyn Fn(&str)> {
+    // Retained until the evaluator is dropped with the IPC handler of the webview.
+    let webview: Id<Object> = unsafe { Id::from_ptr(self.webview) };
+    Box::new(move |js| unsafe {
+      let _: id = msg_send![&*webview, evaluateJavaScript:NSString::new(js) completionHandler:null::<*const c_void>()];
+    })
+  }
+
+  pub fn click_through_setter(&self) -> Option<Box<dyn Fn(&[ClickThroughRegion])>> {
+    #[cfg(target_os = "macos")]
+    {
+      let click_through_regions = self.click_through_regions.clone();
+      Some(Box::new(move |regions| {
+        *click_through_regions.borrow_mut() = regions.to_vec()
+      }))
+    }
+    #[cfg(target_os = "ios")]
+    None
+  }
+
+  pub fn send_mouse_event(&self, event: MouseEvent) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    unsafe {
+      use crate::application::event::MouseButton;
+      use cocoa::{
+        appkit::NSEventType,
+        foundation::{NSPoint, NSUInteger},
+      };
+
+      let window: id = msg_send![self.webview, window];
+      if window.is_null() {
+        return Err(crate::Error::Unsupported);
+      }
+      let position = event.position();
+      // The webview is flipped, the window isn't.
+      let location: NSPoint =
+        msg_send![self.webview, convertPoint: NSPoint::new(position.x, position.y) toView: nil];
+      let window_number: NSInteger = msg_send![window, windowNumber];
+      let process_info: id = msg_send![class!(NSProcessInfo), processInfo];
+      let timestamp: f64 = msg_send![process_info, systemUptime];
+      let mouse_event = |event_type: NSEventType| -> id {
+        msg_send![class!(NSEvent), mouseEventWithType: event_type as NSUInteger
+                                           location: location
+                                      modifierFlags: 0 as NSUInteger
+                                          timestamp: timestamp
+                                       windowNumber: window_number
+                                            context: nil
+                                        eventNumber: 0 as NSInteger
+                                         clickCount: 1 as NSInteger
+                                           pressure: 1.0f32]
+      };
+      match event {
+        MouseEvent::Move { .. } => {
+          let () = msg_send![self.webview, mouseMoved: mouse_event(NSEventType::NSMouseMoved)];
+        }
+        MouseEvent::Down {
+          button: MouseButton::Left,
+          ..
+        } => {
+          let () = msg_send![self.webview, mouseDown: mouse_event(NSEventType::NSLeftMouseDown)];
+        }
+        MouseEvent::Down {
+          button: MouseButton::Right,
+          ..
+        } => {
+          let event = mouse_event(NSEventType::NSRightMouseDown);
+          let () = msg_send![self.webview, rightMouseDown: event];
+        }
+        MouseEvent::Up {
+          button: MouseButton::Left,
+          ..
+        } => {
+          let () = msg_send![self.webview, mouseUp: mouse_event(NSEventType::NSLeftMouseUp)];
+        }
+        MouseEvent::Up {
+          button: MouseButton::Right,
+          ..
+        } => {
+          let event = mouse_event(NSEventType::NSRightMouseUp);
+          let () = msg_send![self.webview, rightMouseUp: event];
+        }
+        MouseEvent::Wheel {
+          delta_x, delta_y, ..
+        } => {
+          let event = scroll_wheel_event(window, location, delta_x, delta_y);
+          if event.is_null() {
+            return Err(crate::Error::Unsupported);
+          }
+          let () = msg_send![self.webview, scrollWheel: event];
+        }
+        _ => return Err(crate::Error::Unsupported),
+      }
+      Ok(())
+    }
+    #[cfg(target_os = "ios")]
+    {
+      let _ = event;
+      Err(crate::Error::Unsupported)
+    }
+  }
+
+  pub fn send_touch_event(&self, _event: TouchEvent) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  fn reorder(&self, front: bool) -> Result<()> {
+    unsafe {
+      let parent: id = msg_send![self.webview, superview];
+      if parent == nil {
+        return Err(crate::Error::Unsupported);
+      }
+      #[cfg(target_os = "macos")]
+      {
+        // NSWindowAbove and NSWindowBelow, relative to all the subviews.
+        let mode: NSInteger = if front { 1 } else { -1 };
+        let _: () = msg_send![parent, addSubview:self.webview positioned:mode relativeTo:nil];
+      }
+      #[cfg(target_os = "ios")]
+      if front {
+        let _: () = msg_send![parent, bringSubviewToFront: self.webview];
+      } else {
+        let _: () = msg_send![parent, sendSubviewToBack: self.webview];
+      }
+    }
+    Ok(())
+  }
+
   pub fn zoom(&self, scale_factor: f64) {
     unsafe {
       let _: () = msg_send![self.webview, setPageZoom: scale_factor];
@@ -1046,6 +2383,212 @@ r#"Object.defineProperty(window, 'ipc', {
   pub fn set_background_color(&self, _background_color: RGBA) -> Result<()> {
     Ok(())
   }
+
+  #[cfg(target_os = "macos")]
+  pub fn set_back_forward_gestures(&self, enabled: bool) -> Result<()> {
+    unsafe {
+      let value = if enabled { YES } else { NO };
+      let _: () = msg_send![self.webview, setAllowsBackForwardNavigationGestures: value];
+    }
+    Ok(())
+  }
+
+  #[cfg(target_os = "ios")]
+  pub fn set_back_forward_gestures(&self, _enabled: bool) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  pub fn set_under_page_background_color(&self, color: RGBA) -> Result<()> {
+    unsafe { set_under_page_background_color(self.webview, color) };
+    Ok(())
+  }
+
+  #[cfg(all(target_os = "macos", feature = "transparent"))]
+  pub fn set_transparent(&self, transparent: bool) -> Result<()> {
+    unsafe {
+      let draws_background: id =
+        msg_send![class!(NSNumber), numberWithBool: if transparent { NO } else { YES }];
+      let () =
+        msg_send![self.webview, setValue:draws_background forKey:NSString::new("drawsBackground")];
+    }
+    Ok(())
+  }
+
+  #[cfg(all(target_os = "macos", not(feature = "transparent")))]
+  pub fn set_transparent(&self, _transparent: bool) -> Result<()> {
+    Err(crate::Error::Unsupported)
+  }
+
+  #[cfg(target_os = "ios")]
+  pub fn set_transparent(&self, transparent: bool) -> Result<()> {
+    unsafe {
+      let () = msg_send![self.webview, setOpaque: if transparent { NO } else { YES }];
+      let color: id = if transparent {
+        msg_send![class!(UIColor), clearColor]
+      } else {
+        msg_send![class!(UIColor), whiteColor]
+      };
+      let () = msg_send![self.webview, setBackgroundColor: color];
+    }
+    Ok(())
+  }
+}
+
+/// The autoresizing mask of a webview following its superview with `policy`, where the flags of
+/// `NSAutoresizingMaskOptions` and `UIViewAutoresizing` share their values: 1 and 4 for flexible
+/// left and right margins, 8 and 32 for flexible bottom and top margins of a non-flipped view, 2
+/// and 16 for a flexible width and height.
+fn autoresizing_mask(policy: AutoResizePolicy, flipped: bool) -> cocoa::foundation::NSUInteger {
+  if policy == AutoResizePolicy::FillParent {
+    return 2 | 16;
+  }
+  let anchors = policy.anchors();
+  let horizontal = match (anchors.left, anchors.right) {
+    (true, true) => 2,
+    (false, true) => 1,
+    _ => 4,
+  };
+  let (bottom_margin, top_margin) = if flipped { (32, 8) } else { (8, 32) };
+  let vertical = match (anchors.top, anchors.bottom) {
+    (true, true) => 16,
+    (false, true) => top_margin,
+    _ => bottom_margin,
+  };
+  horizontal | vertical
+}
+
+/// The isolated `WKContentWorld` named `name`, requires macOS 11+ / iOS 14+.
+/// The `proc_taskinfo` of `<sys/proc_info.h>`.
+#[cfg(target_os = "macos")]
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)] // Filled by `proc_pidinfo`.
+struct ProcTaskInfo {
+  virtual_size: u64,
+  resident_size: u64,
+  total_user: u64,
+  total_system: u64,
+  threads_user: u64,
+  threads_system: u64,
+  policy: i32,
+  faults: i32,
+  pageins: i32,
+  cow_faults: i32,
+  messages_sent: i32,
+  messages_received: i32,
+  syscalls_mach: i32,
+  syscalls_unix: i32,
+  csw: i32,
+  threadnum: i32,
+  numrunning: i32,
+  priority: i32,
+}
+
+#[cfg(target_os = "macos")]
+extern "C" {
+  fn proc_pidinfo(
+    pid: i32,
+    flavor: i32,
+    arg: u64,
+    buffer: *mut std::ffi::c_void,
+    buffersize: i32,
+  ) -> i32;
+}
+
+#[cfg(target_os = "macos")]
+const PROC_PIDTASKINFO: i32 = 4;
+
+#[cfg(target_os = "macos")]
+extern "C" {
+  fn CGEventCreateScrollWheelEvent(
+    source: *const c_void,
+    units: u32,
+    wheel_count: u32,
+    wheel1: i32,
+    ...
+  ) -> *mut c_void;
+  fn CGEventSetLocation(event: *mut c_void, location: core_graphics::geometry::CGPoint);
+  fn CFRelease(cf: *const c_void);
+}
+
+#[cfg(target_os = "macos")]
+const CG_SCROLL_EVENT_UNIT_PIXEL: u32 = 0;
+
+/// A scroll wheel event at `location` in the coordinates of `window`, since `NSEvent` can't create
+/// them.
+#[cfg(target_os = "macos")]
+unsafe fn scroll_wheel_event(
+  window: id,
+  location: cocoa::foundation::NSPoint,
+  delta_x: f64,
+  delta_y: f64,
+) -> id {
+  // The positive wheel deltas scroll up and to the left.
+  let cg_event = CGEventCreateScrollWheelEvent(
+    null(),
+    CG_SCROLL_EVENT_UNIT_PIXEL,
+    2,
+    -delta_y as i32,
+    -delta_x as i32,
+  );
+  if cg_event.is_null() {
+    return nil;
+  }
+  // The origin of the global display coordinates is the top left corner of the main screen.
+  let screen_location: cocoa::foundation::NSPoint =
+    msg_send![window, convertPointToScreen: location];
+  let screens: id = msg_send![class!(NSScreen), screens];
+  let main_screen: id = msg_send![screens, objectAtIndex: 0usize];
+  let frame: cocoa::foundation::NSRect = msg_send![main_screen, frame];
+  CGEventSetLocation(
+    cg_event,
+    core_graphics::geometry::CGPoint::new(screen_location.x, frame.size.height - screen_location.y),
+  );
+  let event: id = msg_send![class!(NSEvent), eventWithCGEvent: cg_event];
+  CFRelease(cg_event);
+  event
+}
+
+#[cfg(target_os = "macos")]
+fn resident_size(pid: i32) -> Option<u64> {
+  if pid <= 0 {
+    return None;
+  }
+  let mut info = ProcTaskInfo::default();
+  let size = std::mem::size_of::<ProcTaskInfo>() as i32;
+  let written = unsafe {
+    proc_pidinfo(
+      pid,
+      PROC_PIDTASKINFO,
+      0,
+      &mut info as *mut ProcTaskInfo as *mut _,
+      size,
+    )
+  };
+  (written == size).then_some(info.resident_size)
+}
+
+/// Sets the `underPageBackgroundColor` of `webview`, available on macOS 12+ / iOS 15+.
+unsafe fn set_under_page_background_color(webview: id, color: RGBA) {
+  let can_set: BOOL = msg_send![webview, respondsToSelector: sel!(setUnderPageBackgroundColor:)];
+  if can_set == NO {
+    return;
+  }
+  let (r, g, b, a) = (
+    color.0 as f64 / 255.,
+    color.1 as f64 / 255.,
+    color.2 as f64 / 255.,
+    color.3 as f64 / 255.,
+  );
+  #[cfg(target_os = "macos")]
+  let color: id = msg_send![class!(NSColor), colorWithSRGBRed:r green:g blue:b alpha:a];
+  #[cfg(target_os = "ios")]
+  let color: id = msg_send![class!(UIColor), colorWithRed:r green:g blue:b alpha:a];
+  let () = msg_send![webview, setUnderPageBackgroundColor: color];
+}
+
+unsafe fn content_world(name: &str) -> id {
+  msg_send![class!(WKContentWorld), worldWithName: NSString::new(name)]
 }
 
 pub fn url_from_webview(webview: id) -> String {
@@ -1064,6 +2607,62 @@ pub fn url_from_webview(webview: id) -> String {
   std::str::from_utf8(bytes).unwrap().into()
 }
 
+pub fn platform_pump_events() {
+  unsafe {
+    let is_main_thread: BOOL = msg_send![class!(NSThread), isMainThread];
+    if is_main_thread == YES {
+      let date: id = msg_send![class!(NSDate), dateWithTimeIntervalSinceNow: 0.01f64];
+      let run_loop: id = msg_send![class!(NSRunLoop), currentRunLoop];
+      let _: BOOL =
+        msg_send![run_loop, runMode: NSString::new("kCFRunLoopDefaultMode") beforeDate: date];
+    }
+  }
+}
+
+pub fn open_in_browser(url: &str) -> Result<()> {
+  unsafe {
+    let url: id = msg_send![class!(NSURL), URLWithString: NSString::new(url)];
+    if url == nil {
+      return Err(crate::Error::Unsupported);
+    }
+    #[cfg(target_os = "macos")]
+    {
+      let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+      let opened: BOOL = msg_send![workspace, openURL: url];
+      if opened == NO {
+        return Err(
+          std::io::Error::new(std::io::ErrorKind::Other, "no application opens the URL").into(),
+        );
+      }
+    }
+    #[cfg(target_os = "ios")]
+    {
+      let application: id = msg_send![class!(UIApplication), sharedApplication];
+      let options: id = msg_send![class!(NSDictionary), dictionary];
+      let _: () = msg_send![application, openURL:url options:options completionHandler:nil];
+    }
+  }
+  Ok(())
+}
+
+pub fn platform_printers() -> Result<Vec<String>> {
+  #[cfg(target_os = "macos")]
+  unsafe {
+    let names: id = msg_send![class!(NSPrinter), printerNames];
+    let count: usize = msg_send![names, count];
+    Ok(
+      (0..count)
+        .map(|i| {
+          let name: id = msg_send![names, objectAtIndex: i];
+          NSString(name).to_str().to_string()
+        })
+        .collect(),
+    )
+  }
+  #[cfg(target_os = "ios")]
+  Err(crate::Error::Unsupported)
+}
+
 pub fn platform_webview_version() -> Result<String> {
   unsafe {
     let bundle: id =
@@ -1087,16 +2686,56 @@ impl Drop for InnerWebView {
 
         let ipc = NSString::new(IPC_MESSAGE_HANDLER_NAME);
         let _: () = msg_send![self.manager, removeScriptMessageHandlerForName: ipc];
+        for world in &self.isolated_worlds {
+          let _: () = msg_send![self.manager, removeScriptMessageHandlerForName:ipc contentWorld:content_world(world)];
+        }
       }
 
       if !self.document_title_changed_handler.is_null() {
         drop(Box::from_raw(self.document_title_changed_handler));
       }
 
+      for script in self.user_scripts.get_mut().values() {
+        let _: () = msg_send![*script, release];
+      }
+
+      if !self.audio_state_handler.is_null() {
+        let can_observe: BOOL = msg_send![self.webview, respondsToSelector: sel!(_isPlayingAudio)];
+        if can_observe == YES {
+          let _: () = msg_send![self.webview, removeObserver:self.audio_state_observer forKeyPath:NSString::new("_isPlayingAudio")];
+        }
+        let _: () = msg_send![self.audio_state_observer, release];
+        drop(Box::from_raw(self.audio_state_handler));
+      }
+
       if !self.navigation_decide_policy_ptr.is_null() {
         drop(Box::from_raw(self.navigation_decide_policy_ptr));
       }
 
+      if !self.new_window_handler_ptr.is_null() {
+        drop(Box::from_raw(self.new_window_handler_ptr));
+      }
+
+      if !self.close_requested_handler_ptr.is_null() {
+        drop(Box::from_raw(self.close_requested_handler_ptr));
+      }
+
+      // The webview resigns first responder when it's removed from its window below.
+      #[cfg(target_os = "macos")]
+      if !self.focus_changed_handler_ptr.is_null() {
+        (*self.webview).set_ivar(FOCUS_CHANGED_FUNCTION, null_mut::<c_void>());
+        drop(Box::from_raw(self.focus_changed_handler_ptr));
+      }
+
+      #[cfg(target_os = "macos")]
+      (*self.webview).set_ivar(CLICK_THROUGH_REGIONS, null::<c_void>());
+
+      #[cfg(target_os = "macos")]
+      if !self.key_event_handler_ptr.is_null() {
+        (*self.webview).set_ivar(KEY_EVENT_FUNCTION, null_mut::<c_void>());
+        drop(Box::from_raw(self.key_event_handler_ptr));
+      }
+
       drop_navigation_methods(self);
 
       #[cfg(target_os = "macos")]
@@ -1122,8 +2761,83 @@ impl Drop for InnerWebView {
   }
 }
 
+/// The website data types of the service workers and their caches.
+unsafe fn service_worker_data_types() -> id {
+  let types = [
+    NSString::new("WKWebsiteDataTypeServiceWorkerRegistrations").0,
+    NSString::new("WKWebsiteDataTypeFetchCache").0,
+  ];
+  msg_send![class!(NSSet), setWithObjects:types.as_ptr() count:types.len()]
+}
+
+/// The `WKWebsiteDataRecord`s of `records`, only keeping the ones of `domain` if set.
+unsafe fn service_worker_records(records: id, domain: Option<&str>) -> Vec<id> {
+  let count: usize = msg_send![records, count];
+  (0..count)
+    .map(|i| msg_send![records, objectAtIndex: i])
+    .filter(|&record: &id| {
+      let name: id = msg_send![record, displayName];
+      match domain {
+        Some(domain) => NSString(name).to_str() == domain,
+        None => true,
+      }
+    })
+    .collect()
+}
+
 const UTF8_ENCODING: usize = 4;
 
+/// The `NSInputStream` body of a custom protocol request, opened on the first read.
+struct HttpBodyStream {
+  stream: id,
+  opened: bool,
+}
+
+// SAFETY: `NSInputStream` can be read from any thread, one at a time, which `Read` ensures by
+// borrowing it mutably.
+unsafe impl Send for HttpBodyStream {}
+
+impl HttpBodyStream {
+  unsafe fn new(stream: id) -> Self {
+    let _: id = msg_send![stream, retain];
+    Self {
+      stream,
+      opened: false,
+    }
+  }
+}
+
+impl std::io::Read for HttpBodyStream {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    unsafe {
+      if !self.opened {
+        let _: () = msg_send![self.stream, open];
+        self.opened = true;
+      }
+      let count: isize = msg_send![self.stream, read: buf.as_mut_ptr() maxLength: buf.len()];
+      if count < 0 {
+        Err(std::io::Error::new(
+          std::io::ErrorKind::Other,
+          "failed to read the request body",
+        ))
+      } else {
+        Ok(count as usize)
+      }
+    }
+  }
+}
+
+impl Drop for HttpBodyStream {
+  fn drop(&mut self) {
+    unsafe {
+      if self.opened {
+        let _: () = msg_send![self.stream, close];
+      }
+      let _: () = msg_send![self.stream, release];
+    }
+  }
+}
+
 struct NSString(id);
 
 impl NSString {